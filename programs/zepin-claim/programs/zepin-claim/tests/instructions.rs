@@ -0,0 +1,473 @@
+// Drives the program's real entrypoint: Anchor instruction data, account
+// validation (discriminators, PDA seeds, init / init_if_needed), the handler,
+// and the token-transfer CPI. Syscall stubs stand in for the runtime —
+// `invoke_signed` runs the actual spl-token processor or the handful of
+// system-program instructions `init` uses, checks PDA signers against the
+// caller's seeds, and a failed instruction rolls every account back, as the
+// runtime would.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Once};
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        entrypoint::ProgramResult,
+        hash::hashv,
+        instruction::Instruction,
+        program_pack::Pack,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        program_utils::limited_deserialize,
+        system_instruction::SystemInstruction,
+        system_program,
+    },
+    InstructionData, ToAccountMetas,
+};
+use anchor_spl::token::spl_token;
+use zepin_claim::{ClaimError, ClaimStatus, CumulativeDistributor};
+
+const PAYOUT_PER_POINT: u64 = 10;
+
+// ---- runtime stand-in -----------------------------------------------------
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock { unix_timestamp: 1_700_000_000, ..Clock::default() };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        ix: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let pdas: Vec<Pubkey> = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &zepin_claim::ID).unwrap())
+            .collect();
+        let mut infos = Vec::with_capacity(ix.accounts.len());
+        for meta in &ix.accounts {
+            let mut info = account_infos
+                .iter()
+                .find(|a| *a.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?
+                .clone();
+            if meta.is_signer && !info.is_signer && !pdas.contains(info.key) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            info.is_signer = meta.is_signer;
+            info.is_writable = meta.is_writable;
+            infos.push(info);
+        }
+        if ix.program_id == spl_token::ID {
+            spl_token::processor::Processor::process(&ix.program_id, &infos, &ix.data)
+        } else if ix.program_id == system_program::ID {
+            match limited_deserialize(&ix.data, 1024).map_err(|_| ProgramError::InvalidInstructionData)? {
+                SystemInstruction::CreateAccount { lamports, space, owner } => {
+                    let (from, to) = (&infos[0], &infos[1]);
+                    if to.lamports() != 0 || *to.owner != system_program::ID {
+                        return Err(ProgramError::AccountAlreadyInitialized);
+                    }
+                    **from.try_borrow_mut_lamports()? -= lamports;
+                    **to.try_borrow_mut_lamports()? += lamports;
+                    *to.try_borrow_mut_data()? = Box::leak(vec![0u8; space as usize].into_boxed_slice());
+                    to.assign(&owner);
+                    Ok(())
+                }
+                SystemInstruction::Transfer { lamports } => {
+                    let (from, to) = (&infos[0], &infos[1]);
+                    **from.try_borrow_mut_lamports()? -= lamports;
+                    **to.try_borrow_mut_lamports()? += lamports;
+                    Ok(())
+                }
+                // Like the runtime, only a system-owned, empty account can be
+                // allocated or reassigned — this is what turns a second
+                // `init` of the same PDA into an error.
+                SystemInstruction::Allocate { space } => {
+                    let to = &infos[0];
+                    if *to.owner != system_program::ID || !to.data_is_empty() {
+                        return Err(ProgramError::AccountAlreadyInitialized);
+                    }
+                    *to.try_borrow_mut_data()? = Box::leak(vec![0u8; space as usize].into_boxed_slice());
+                    Ok(())
+                }
+                SystemInstruction::Assign { owner } => {
+                    let to = &infos[0];
+                    if *to.owner != system_program::ID {
+                        return Err(ProgramError::AccountAlreadyInitialized);
+                    }
+                    to.assign(&owner);
+                    Ok(())
+                }
+                other => panic!("system instruction not stubbed: {other:?}"),
+            }
+        } else {
+            panic!("CPI into unexpected program {}", ix.program_id)
+        }
+    }
+}
+
+// Accounts live for the whole test, shared between instructions the way a
+// bank's accounts would be.
+struct Bank {
+    accounts: HashMap<Pubkey, AccountInfo<'static>>,
+}
+
+fn leak<T>(v: T) -> &'static mut T {
+    Box::leak(Box::new(v))
+}
+
+impl Bank {
+    fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(Stubs));
+        });
+        let mut bank = Bank { accounts: HashMap::new() };
+        bank.put(zepin_claim::ID, Pubkey::default(), 1, vec![], true);
+        bank.put(spl_token::ID, Pubkey::default(), 1, vec![], true);
+        bank.put(system_program::ID, Pubkey::default(), 1, vec![], true);
+        bank
+    }
+
+    fn put(&mut self, key: Pubkey, owner: Pubkey, lamports: u64, data: Vec<u8>, executable: bool) {
+        let info = AccountInfo {
+            key: leak(key),
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(leak(lamports))),
+            data: Rc::new(RefCell::new(Box::leak(data.into_boxed_slice()))),
+            owner: leak(owner),
+            executable,
+            rent_epoch: 0,
+        };
+        self.accounts.insert(key, info);
+    }
+
+    fn wallet(&mut self) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.put(key, system_program::ID, 10_000_000_000, vec![], false);
+        key
+    }
+
+    fn mint(&mut self) -> Pubkey {
+        let key = Pubkey::new_unique();
+        let mint = spl_token::state::Mint {
+            mint_authority: Some(Pubkey::new_unique()).into(),
+            supply: 1_000_000_000,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        };
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        mint.pack_into_slice(&mut data);
+        self.put(key, spl_token::ID, 1_000_000, data, false);
+        key
+    }
+
+    fn token_account(&mut self, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        let account = spl_token::state::Account {
+            mint,
+            owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        account.pack_into_slice(&mut data);
+        self.put(key, spl_token::ID, 2_000_000, data, false);
+        key
+    }
+
+    // PDA accounts start out empty and system-owned, as on chain.
+    fn pda(&mut self, seeds: &[&[u8]]) -> Pubkey {
+        let (key, _) = Pubkey::find_program_address(seeds, &zepin_claim::ID);
+        if !self.accounts.contains_key(&key) {
+            self.put(key, system_program::ID, 0, vec![], false);
+        }
+        key
+    }
+
+    fn balance(&self, token_account: Pubkey) -> u64 {
+        let data = self.accounts[&token_account].data.borrow();
+        spl_token::state::Account::unpack(&data).unwrap().amount
+    }
+
+    fn read<T: AccountDeserialize>(&self, key: Pubkey) -> T {
+        let data = self.accounts[&key].data.borrow();
+        T::try_deserialize(&mut &data[..]).unwrap()
+    }
+
+    fn process(&self, data: Vec<u8>, metas: Vec<AccountMeta>) -> ProgramResult {
+        // Snapshot for rollback on failure.
+        let before: Vec<(Pubkey, u64, Vec<u8>, Pubkey)> = self
+            .accounts
+            .values()
+            .map(|a| (*a.key, a.lamports(), a.data.borrow().to_vec(), *a.owner))
+            .collect();
+        let mut infos = Vec::new();
+        for meta in &metas {
+            let mut info = self.accounts[&meta.pubkey].clone();
+            info.is_signer = meta.is_signer;
+            info.is_writable = meta.is_writable;
+            infos.push(info);
+        }
+        let infos: &'static [AccountInfo<'static>] = Box::leak(infos.into_boxed_slice());
+        let result = zepin_claim::entry(&zepin_claim::ID, infos, &data);
+        if result.is_err() {
+            for (key, lamports, data, owner) in before {
+                let a = &self.accounts[&key];
+                **a.lamports.borrow_mut() = lamports;
+                *a.data.borrow_mut() = Box::leak(data.into_boxed_slice());
+                a.assign(&owner);
+            }
+        }
+        result
+    }
+}
+
+fn anchor_error(e: ClaimError) -> ProgramError {
+    ProgramError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + e as u32)
+}
+
+fn framework_error(e: ErrorCode) -> ProgramError {
+    ProgramError::Custom(e as u32)
+}
+
+// ---- merkle (same sorted-pair shape as server/src/merkle.rs) --------------
+
+fn leaf(wallet: &Pubkey, points: u64) -> [u8; 32] {
+    hashv(&[wallet.to_string().as_bytes(), &points.to_le_bytes()]).to_bytes()
+}
+
+fn pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[lo, hi]).to_bytes()
+}
+
+// Two-leaf tree: (root, proof for leaf 0, proof for leaf 1).
+fn tree(a: [u8; 32], b: [u8; 32]) -> ([u8; 32], Vec<[u8; 32]>, Vec<[u8; 32]>) {
+    (pair(&a, &b), vec![b], vec![a])
+}
+
+// ---- fixtures -------------------------------------------------------------
+
+struct Setup {
+    bank: Bank,
+    authority: Pubkey,
+    mint: Pubkey,
+    alice: Pubkey,
+    alice_ata: Pubkey,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let mut bank = Bank::new();
+        let authority = bank.wallet();
+        let mint = bank.mint();
+        let alice = bank.wallet();
+        let alice_ata = bank.token_account(mint, alice, 0);
+        Setup { bank, authority, mint, alice, alice_ata }
+    }
+
+    // Cumulative distributor for `cycle` with a funded vault. Returns
+    // (distributor, vault).
+    fn cumulative(&mut self, cycle: u64, root: [u8; 32]) -> (Pubkey, Pubkey) {
+        let distributor = self.bank.pda(&[CumulativeDistributor::SEED, &cycle.to_le_bytes()]);
+        let vault = self.bank.token_account(self.mint, distributor, 1_000_000);
+        let metas = zepin_claim::accounts::InitializeCumulativeDistributor {
+            authority: self.authority,
+            distributor,
+            mint: self.mint,
+            vault,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        let data = zepin_claim::instruction::InitializeCumulativeDistributor {
+            cycle,
+            merkle_root: root,
+            payout_per_point: PAYOUT_PER_POINT,
+        }
+        .data();
+        self.bank.process(data, metas).unwrap();
+        (distributor, vault)
+    }
+
+    fn delta(&mut self, cycle: u64, root: [u8; 32]) -> (Pubkey, Pubkey) {
+        let distributor = self.bank.pda(&[b"distributor", &cycle.to_le_bytes()]);
+        let vault = self.bank.token_account(self.mint, distributor, 1_000_000);
+        let metas = zepin_claim::accounts::InitializeDistributor {
+            authority: self.authority,
+            distributor,
+            mint: self.mint,
+            vault,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        let data = zepin_claim::instruction::InitializeDistributor {
+            cycle,
+            merkle_root: root,
+            payout_per_point: PAYOUT_PER_POINT,
+        }
+        .data();
+        self.bank.process(data, metas).unwrap();
+        (distributor, vault)
+    }
+
+    fn claim_status(&mut self, claimer: Pubkey) -> Pubkey {
+        let mint = self.mint;
+        self.bank.pda(&[b"claim_status", mint.as_ref(), claimer.as_ref()])
+    }
+
+    fn claim_cumulative(
+        &mut self,
+        (distributor, vault): (Pubkey, Pubkey),
+        claim_status: Pubkey,
+        points: u64,
+        proof: &[[u8; 32]],
+    ) -> ProgramResult {
+        let metas = zepin_claim::accounts::ClaimCumulative {
+            claimer: self.alice,
+            distributor,
+            vault,
+            claimer_ata: self.alice_ata,
+            claim_status,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        let data = zepin_claim::instruction::ClaimCumulative {
+            wallet_str: self.alice.to_string(),
+            cumulative_points: points,
+            merkle_proof: proof.to_vec(),
+        }
+        .data();
+        self.bank.process(data, metas)
+    }
+
+    fn claim(&mut self, (distributor, vault): (Pubkey, Pubkey), points: u64, proof: &[[u8; 32]]) -> ProgramResult {
+        let receipt = self
+            .bank
+            .pda(&[b"receipt", distributor.as_ref(), self.alice.as_ref()]);
+        let metas = zepin_claim::accounts::ClaimRewards {
+            claimer: self.alice,
+            distributor,
+            vault,
+            claimer_ata: self.alice_ata,
+            receipt,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        let data = zepin_claim::instruction::Claim {
+            wallet_str: self.alice.to_string(),
+            points,
+            merkle_proof: proof.to_vec(),
+        }
+        .data();
+        self.bank.process(data, metas)
+    }
+}
+
+// ---- tests ----------------------------------------------------------------
+
+#[test]
+fn cumulative_claims_pay_only_the_increase_across_cycles() {
+    let mut s = Setup::new();
+    let bob = Pubkey::new_unique();
+    let status = s.claim_status(s.alice);
+
+    let (r1, p1, _) = tree(leaf(&s.alice, 100), leaf(&bob, 50));
+    let cycle1 = s.cumulative(1, r1);
+    s.claim_cumulative(cycle1, status, 100, &p1).unwrap();
+    assert_eq!(s.bank.balance(s.alice_ata), 100 * PAYOUT_PER_POINT);
+    assert_eq!(s.bank.balance(cycle1.1), 1_000_000 - 100 * PAYOUT_PER_POINT);
+
+    // Cycles 2 and 3 skipped; the cycle-4 root carries the running total and
+    // the same ClaimStatus (created by init_if_needed above) is reused.
+    let (r4, p4, _) = tree(leaf(&s.alice, 400), leaf(&bob, 90));
+    let cycle4 = s.cumulative(4, r4);
+    s.claim_cumulative(cycle4, status, 400, &p4).unwrap();
+    assert_eq!(s.bank.balance(s.alice_ata), 400 * PAYOUT_PER_POINT);
+
+    let st: ClaimStatus = s.bank.read(status);
+    assert_eq!(st.claimer, s.alice);
+    assert_eq!(st.mint, s.mint);
+    assert_eq!((st.claimed_points, st.claimed_amount, st.last_cycle), (400, 400 * PAYOUT_PER_POINT, 4));
+    assert_eq!(st.last_claimed_at, 1_700_000_000);
+}
+
+#[test]
+fn same_or_older_root_pays_nothing() {
+    let mut s = Setup::new();
+    let bob = Pubkey::new_unique();
+    let status = s.claim_status(s.alice);
+    let (r1, p1, _) = tree(leaf(&s.alice, 100), leaf(&bob, 50));
+    let (r2, p2, _) = tree(leaf(&s.alice, 130), leaf(&bob, 80));
+    let cycle1 = s.cumulative(1, r1);
+    let cycle2 = s.cumulative(2, r2);
+
+    s.claim_cumulative(cycle2, status, 130, &p2).unwrap();
+    assert_eq!(s.claim_cumulative(cycle2, status, 130, &p2), Err(anchor_error(ClaimError::NothingToClaim)));
+    assert_eq!(s.claim_cumulative(cycle1, status, 100, &p1), Err(anchor_error(ClaimError::NothingToClaim)));
+    assert_eq!(s.bank.balance(s.alice_ata), 130 * PAYOUT_PER_POINT);
+    assert_eq!(s.bank.read::<ClaimStatus>(status).claimed_points, 130);
+}
+
+#[test]
+fn inflated_points_fail_the_proof_and_create_nothing() {
+    let mut s = Setup::new();
+    let status = s.claim_status(s.alice);
+    let (r1, p1, _) = tree(leaf(&s.alice, 100), leaf(&Pubkey::new_unique(), 50));
+    let cycle1 = s.cumulative(1, r1);
+
+    assert_eq!(s.claim_cumulative(cycle1, status, 1_000, &p1), Err(anchor_error(ClaimError::InvalidProof)));
+    assert_eq!(s.bank.balance(s.alice_ata), 0);
+    assert_eq!(*s.bank.accounts[&status].owner, system_program::ID, "status creation rolled back");
+}
+
+#[test]
+fn claim_status_must_sit_at_the_claimers_pda() {
+    let mut s = Setup::new();
+    let bob = Pubkey::new_unique();
+    let bobs_status = s.claim_status(bob);
+    let (r1, p1, _) = tree(leaf(&s.alice, 100), leaf(&bob, 50));
+    let cycle1 = s.cumulative(1, r1);
+
+    assert_eq!(s.claim_cumulative(cycle1, bobs_status, 100, &p1), Err(framework_error(ErrorCode::ConstraintSeeds)));
+    assert_eq!(s.bank.balance(s.alice_ata), 0);
+}
+
+#[test]
+fn claim_modes_cannot_be_crossed() {
+    let mut s = Setup::new();
+    let status = s.claim_status(s.alice);
+    let (root, proof, _) = tree(leaf(&s.alice, 100), leaf(&Pubkey::new_unique(), 50));
+    let cumulative = s.cumulative(1, root);
+    let delta = s.delta(1, root);
+
+    // Each instruction only deserializes its own distributor type.
+    assert_eq!(s.claim(cumulative, 100, &proof), Err(framework_error(ErrorCode::AccountDiscriminatorMismatch)));
+    assert_eq!(
+        s.claim_cumulative(delta, status, 100, &proof),
+        Err(framework_error(ErrorCode::AccountDiscriminatorMismatch))
+    );
+    assert_eq!(s.bank.balance(s.alice_ata), 0);
+
+    // The delta path itself still pays once per distributor.
+    s.claim(delta, 100, &proof).unwrap();
+    assert_eq!(s.bank.balance(s.alice_ata), 100 * PAYOUT_PER_POINT);
+    assert!(s.claim(delta, 100, &proof).is_err(), "receipt already exists");
+    assert_eq!(s.bank.balance(s.alice_ata), 100 * PAYOUT_PER_POINT);
+}
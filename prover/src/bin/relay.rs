@@ -1,7 +1,11 @@
 // depinzcash-relay — operator-side CLI that
 //   1. generates a Solana keypair (or loads an existing one),
 //   2. registers a Zebra node with the DePINZcash server,
-//   3. submits proofs of node state on a fixed interval.
+//   3. submits proofs of node state on a fixed interval,
+//   4. manages the registered node (update, pause/resume, rotate-token,
+//      deregister) with the auth token saved at registration,
+//   5. delegates proof / challenge signing to a hot key, so the wallet's
+//      secret key can stay off the node machine.
 //
 // This is the "fully working prototype" submission path. The Halo 2 proof generator
 // (the `depinzcash-prover` binary) is the privacy-preserving variant — once the
@@ -31,6 +35,19 @@ enum Cmd {
     Register(RegisterArgs),
     Submit(SubmitArgs),
     Watch(WatchArgs),
+    // Change the node's label and/or rpc endpoint.
+    Update(UpdateArgs),
+    // Stop polls, probes and rewards until `resume` (e.g. for maintenance).
+    Pause(ManageArgs),
+    Resume(ManageArgs),
+    // Replace the saved auth token; the old one stops working.
+    RotateToken(ManageArgs),
+    // Delete the node from the server, points and history included.
+    Deregister(DeregisterArgs),
+    // Authorise a hot key (see `keygen`) to sign for the node. Run where the
+    // wallet keypair lives; the node machine only needs the hot key.
+    Delegate(DelegateArgs),
+    RevokeDelegate(RevokeDelegateArgs),
 }
 
 #[derive(Parser, Debug)]
@@ -51,11 +68,42 @@ struct RegisterArgs {
     label: String,
     #[arg(long)]
     rpc_endpoint: Option<String>,
+    // host:port your node accepts inbound Zcash peers on (zebra-full only).
+    #[arg(long)]
+    p2p_address: Option<String>,
+    #[command(flatten)]
+    rpc_auth: RpcAuthArgs,
     // Where to persist the credentials returned by /api/nodes/register.
     #[arg(long, default_value = "config/relay-state.json")]
     state: PathBuf,
 }
 
+// Credentials the server should present to --rpc-endpoint: basic auth
+// (--rpc-user + --rpc-password) or a bearer token. Stored encrypted
+// server-side, never echoed back.
+#[derive(Parser, Debug)]
+struct RpcAuthArgs {
+    #[arg(long, env = "NODE_RPC_USER", requires = "rpc_password", conflicts_with = "rpc_bearer")]
+    rpc_user: Option<String>,
+    #[arg(long, env = "NODE_RPC_PASSWORD", hide_env_values = true, requires = "rpc_user")]
+    rpc_password: Option<String>,
+    #[arg(long, env = "NODE_RPC_BEARER", hide_env_values = true)]
+    rpc_bearer: Option<String>,
+}
+
+impl RpcAuthArgs {
+    fn to_rpc_auth(&self) -> Option<RpcAuth> {
+        match (&self.rpc_user, &self.rpc_password, &self.rpc_bearer) {
+            (Some(username), Some(password), None) => Some(RpcAuth::Basic {
+                username: username.clone(),
+                password: password.clone(),
+            }),
+            (None, None, Some(token)) => Some(RpcAuth::Bearer { token: token.clone() }),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 struct SubmitArgs {
     #[arg(long, env = "DEPINZCASH_API", default_value = "http://localhost:3000")]
@@ -64,6 +112,10 @@ struct SubmitArgs {
     keypair: PathBuf,
     #[arg(long, default_value = "config/relay-state.json")]
     state: PathBuf,
+    // Sign with this delegated hot key instead of --keypair, which is then
+    // not read at all.
+    #[arg(long, env = "RELAY_DELEGATE_KEYPAIR")]
+    delegate_keypair: Option<PathBuf>,
     // Source of node metrics, in priority order:
     //   1. --node-rpc <url>         — query Zebra each tick (recommended for `watch`)
     //   2. --proof-file <path>      — read from a depinzcash-prover JSON
@@ -92,6 +144,66 @@ struct WatchArgs {
     interval_secs: u64,
 }
 
+#[derive(Parser, Debug)]
+struct ManageArgs {
+    // Defaults to the API the node was registered with.
+    #[arg(long, env = "DEPINZCASH_API")]
+    api: Option<String>,
+    #[arg(long, default_value = "config/relay-state.json")]
+    state: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+struct UpdateArgs {
+    #[command(flatten)]
+    manage: ManageArgs,
+    // "" clears it.
+    #[arg(long)]
+    label: Option<String>,
+    // "" clears it. Moving the endpoint drops stored credentials unless new
+    // ones are given.
+    #[arg(long)]
+    rpc_endpoint: Option<String>,
+    #[command(flatten)]
+    rpc_auth: RpcAuthArgs,
+}
+
+#[derive(Parser, Debug)]
+struct DeregisterArgs {
+    #[command(flatten)]
+    manage: ManageArgs,
+    // Deregistering can't be undone.
+    #[arg(long)]
+    yes: bool,
+}
+
+#[derive(Parser, Debug)]
+struct DelegateArgs {
+    #[command(flatten)]
+    manage: ManageArgs,
+    // The wallet keypair the node is registered to.
+    #[arg(long, env = "SOLANA_KEYPAIR", default_value = "config/solana-keypair.json")]
+    keypair: PathBuf,
+    // base58 public key of the hot key.
+    #[arg(long)]
+    delegate_pubkey: String,
+    #[arg(long, default_value = "proofs,challenges")]
+    scopes: String,
+    // The server caps delegations at 365 days.
+    #[arg(long, default_value_t = 90)]
+    expires_in_days: i64,
+}
+
+#[derive(Parser, Debug)]
+struct RevokeDelegateArgs {
+    #[command(flatten)]
+    manage: ManageArgs,
+    #[arg(long, env = "SOLANA_KEYPAIR", default_value = "config/solana-keypair.json")]
+    keypair: PathBuf,
+    #[arg(long)]
+    delegate_pubkey: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct KeypairFile {
     // Stored format: 64-byte concatenation of secret-key (32 bytes) + public-key (32 bytes),
@@ -127,6 +239,13 @@ async fn main() -> Result<()> {
             Ok(())
         }
         Cmd::Watch(a) => watch(a).await,
+        Cmd::Update(a) => update(a).await,
+        Cmd::Pause(a) => set_paused(a, true).await,
+        Cmd::Resume(a) => set_paused(a, false).await,
+        Cmd::RotateToken(a) => rotate_token(a).await,
+        Cmd::Deregister(a) => deregister(a).await,
+        Cmd::Delegate(a) => delegate(a).await,
+        Cmd::RevokeDelegate(a) => revoke_delegate(a).await,
     }
 }
 
@@ -172,9 +291,21 @@ async fn register(args: RegisterArgs) -> Result<()> {
     let nonce = random_nonce();
     let ts = Utc::now();
 
-    // Server's registration_message must match exactly.
+    let rpc_auth = args.rpc_auth.to_rpc_auth();
+
+    // Server's registration_message_v2 must match exactly.
     let label = args.label.clone();
-    let msg = registration_message(&wallet, &nonce, &ts.to_rfc3339(), &args.kind, "mainnet", &label);
+    let msg = registration_message_v2(
+        &wallet,
+        &nonce,
+        &ts.to_rfc3339(),
+        &args.kind,
+        "mainnet",
+        &label,
+        args.rpc_endpoint.as_deref(),
+        args.p2p_address.as_deref(),
+        rpc_auth.as_ref(),
+    );
     let sig = sign_b58(&sk, &msg);
 
     let body = json!({
@@ -185,6 +316,9 @@ async fn register(args: RegisterArgs) -> Result<()> {
         "kind": args.kind,
         "label": if args.label.is_empty() { None } else { Some(args.label.clone()) },
         "rpc_endpoint": args.rpc_endpoint,
+        "p2p_address": args.p2p_address,
+        "rpc_auth": rpc_auth,
+        "message_version": 2,
     });
 
     let client = reqwest::Client::builder()
@@ -220,32 +354,42 @@ async fn register(args: RegisterArgs) -> Result<()> {
 }
 
 async fn submit_once(args: &SubmitArgs) -> Result<serde_json::Value> {
-    let (wallet, sk) = load_keypair(&args.keypair)?;
-    let state: RelayState = serde_json::from_slice(
-        &fs::read(&args.state).with_context(|| format!("reading relay state {:?}", args.state))?,
-    )
-    .context("parsing relay state")?;
-
-    if state.wallet != wallet {
-        return Err(anyhow!(
-            "keypair wallet {} does not match registered state wallet {}",
-            wallet,
-            state.wallet
-        ));
-    }
+    let state = load_state(&args.state)?;
+    // A delegate signs for the registered wallet and names itself.
+    let (wallet, sk, delegate) = match &args.delegate_keypair {
+        Some(path) => {
+            let (delegate, sk) = load_keypair(path)?;
+            (state.wallet.clone(), sk, Some(delegate))
+        }
+        None => {
+            let (wallet, sk) = load_keypair(&args.keypair)?;
+            if state.wallet != wallet {
+                return Err(anyhow!(
+                    "keypair wallet {} does not match registered state wallet {}",
+                    wallet,
+                    state.wallet
+                ));
+            }
+            (wallet, sk, None)
+        }
+    };
 
-    let (height, block_hash, uptime, peers, binary_hash) =
+    let Metrics { height, block_hash, uptime, peers, binary_hash, chain_info } =
         gather_metrics(args).await.context("gathering metrics")?;
 
     let nonce = random_nonce();
     let proof_ts = Utc::now();
-    let msg = proof_message(
+    let msg = proof_message_v3(
         &wallet,
         &state.node_id,
         height,
         &block_hash,
         &proof_ts.to_rfc3339(),
         &nonce,
+        uptime,
+        peers,
+        binary_hash.as_deref(),
+        chain_info.as_ref(),
     );
     let sig = sign_b58(&sk, &msg);
 
@@ -260,6 +404,9 @@ async fn submit_once(args: &SubmitArgs) -> Result<serde_json::Value> {
         "uptime_seconds": uptime,
         "peers": peers,
         "binary_hash": binary_hash,
+        "chain_info": chain_info,
+        "message_version": 3,
+        "delegate": delegate,
     });
 
     let url = format!("{}/api/proofs/submit", args.api.trim_end_matches('/'));
@@ -279,6 +426,12 @@ async fn submit_once(args: &SubmitArgs) -> Result<serde_json::Value> {
         v.get("verdict").and_then(|x| x.as_str()).unwrap_or("?"),
         v.get("points_awarded").and_then(|x| x.as_u64()).unwrap_or(0)
     );
+    if let Some(bonus) = v.get("readiness_bonus").and_then(|x| x.as_u64()).filter(|b| *b > 0) {
+        println!("upgrade readiness bonus: {bonus} points");
+    }
+    if let Some(why) = v.get("chain_info_mismatch").and_then(|x| x.as_str()) {
+        tracing::warn!(mismatch = why, "node's chain info disagrees with the trusted quorum");
+    }
     Ok(v)
 }
 
@@ -294,11 +447,203 @@ async fn watch(args: WatchArgs) -> Result<()> {
     }
 }
 
-async fn gather_metrics(args: &SubmitArgs) -> Result<(u64, String, u64, u32, Option<String>)> {
+fn load_state(path: &PathBuf) -> Result<RelayState> {
+    serde_json::from_slice(&fs::read(path).with_context(|| format!("reading relay state {:?}", path))?)
+        .context("parsing relay state")
+}
+
+fn save_state(path: &PathBuf, state: &RelayState) -> Result<()> {
+    fs::write(path, serde_json::to_vec_pretty(state)?).with_context(|| format!("writing relay state {:?}", path))
+}
+
+// POST /api/nodes/{id}/{action} with the saved auth token.
+async fn manage_call(
+    args: &ManageArgs,
+    state: &RelayState,
+    action: &str,
+    body: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let api = args.api.as_deref().unwrap_or(&state.api);
+    let url = format!("{}/api/nodes/{}/{}", api.trim_end_matches('/'), state.node_id, action);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()?;
+    let resp = client
+        .post(&url)
+        .bearer_auth(&state.auth_token)
+        .json(&body)
+        .send()
+        .await?;
+    let status = resp.status();
+    let text = resp.text().await?;
+    if !status.is_success() {
+        return Err(anyhow!("{} failed ({}): {}", action, status, text));
+    }
+    Ok(serde_json::from_str(&text)?)
+}
+
+async fn update(args: UpdateArgs) -> Result<()> {
+    let mut state = load_state(&args.manage.state)?;
+    if args.label.is_none() && args.rpc_endpoint.is_none() && args.rpc_auth.to_rpc_auth().is_none() {
+        return Err(anyhow!("nothing to update — pass --label, --rpc-endpoint or rpc credentials"));
+    }
+    let body = json!({
+        "label": args.label,
+        "rpc_endpoint": args.rpc_endpoint,
+        "rpc_auth": args.rpc_auth.to_rpc_auth(),
+    });
+    let v = manage_call(&args.manage, &state, "update", body).await?;
+    if let Some(label) = &args.label {
+        state.label = label.clone();
+        save_state(&args.manage.state, &state)?;
+    }
+    println!(
+        "updated node {} label={} rpc_endpoint={}",
+        state.node_id,
+        v["label"].as_str().unwrap_or("-"),
+        v["rpc_endpoint"].as_str().unwrap_or("-")
+    );
+    Ok(())
+}
+
+async fn set_paused(args: ManageArgs, paused: bool) -> Result<()> {
+    let state = load_state(&args.state)?;
+    let action = if paused { "pause" } else { "resume" };
+    let v = manage_call(&args, &state, action, json!({})).await?;
+    println!("node {} is {}", state.node_id, v["status"].as_str().unwrap_or("?"));
+    Ok(())
+}
+
+async fn rotate_token(args: ManageArgs) -> Result<()> {
+    let mut state = load_state(&args.state)?;
+    let v = manage_call(&args, &state, "rotate-token", json!({})).await?;
+    state.auth_token = v["auth_token"].as_str().ok_or_else(|| anyhow!("missing auth_token"))?.to_string();
+    // The old token is already dead; losing the new one would lock the node out.
+    save_state(&args.state, &state)?;
+    println!("rotated auth token for node {}", state.node_id);
+    println!("state saved to {:?}", args.state);
+    Ok(())
+}
+
+async fn deregister(args: DeregisterArgs) -> Result<()> {
+    let state = load_state(&args.manage.state)?;
+    if !args.yes {
+        return Err(anyhow!(
+            "deregistering node {} retires it for good (its points and history are kept) — rerun with --yes",
+            state.node_id
+        ));
+    }
+    manage_call(&args.manage, &state, "deregister", json!({})).await?;
+    fs::remove_file(&args.manage.state).with_context(|| format!("removing relay state {:?}", args.manage.state))?;
+    println!("deregistered node {}", state.node_id);
+    Ok(())
+}
+
+async fn delegate(args: DelegateArgs) -> Result<()> {
+    let state = load_state(&args.manage.state)?;
+    let (wallet, sk) = load_wallet(&args.keypair, &state)?;
+    let expires_at = (Utc::now() + chrono::Duration::days(args.expires_in_days)).to_rfc3339();
+    let nonce = random_nonce();
+    let ts = Utc::now().to_rfc3339();
+    let msg = delegation_message(
+        &wallet,
+        &state.node_id,
+        &args.delegate_pubkey,
+        &args.scopes,
+        &expires_at,
+        &nonce,
+        &ts,
+    );
+    let body = json!({
+        "wallet": wallet,
+        "delegate": args.delegate_pubkey,
+        "scopes": args.scopes,
+        "expires_at": expires_at,
+        "nonce": nonce,
+        "timestamp": ts,
+        "signature": sign_b58(&sk, &msg),
+    });
+    post_signed(&args.manage, &state, "delegates", body).await?;
+    println!(
+        "delegated {} to {} for node {} until {}",
+        args.scopes, args.delegate_pubkey, state.node_id, expires_at
+    );
+    Ok(())
+}
+
+async fn revoke_delegate(args: RevokeDelegateArgs) -> Result<()> {
+    let state = load_state(&args.manage.state)?;
+    let (wallet, sk) = load_wallet(&args.keypair, &state)?;
+    let nonce = random_nonce();
+    let ts = Utc::now().to_rfc3339();
+    let msg = revocation_message(&wallet, &state.node_id, &args.delegate_pubkey, &nonce, &ts);
+    let body = json!({
+        "wallet": wallet,
+        "delegate": args.delegate_pubkey,
+        "nonce": nonce,
+        "timestamp": ts,
+        "signature": sign_b58(&sk, &msg),
+    });
+    post_signed(&args.manage, &state, "delegates/revoke", body).await?;
+    println!("revoked {} for node {}", args.delegate_pubkey, state.node_id);
+    Ok(())
+}
+
+fn load_wallet(path: &PathBuf, state: &RelayState) -> Result<(String, SigningKey)> {
+    let (wallet, sk) = load_keypair(path)?;
+    if wallet != state.wallet {
+        return Err(anyhow!(
+            "keypair wallet {} does not match registered state wallet {}",
+            wallet,
+            state.wallet
+        ));
+    }
+    Ok((wallet, sk))
+}
+
+// POST /api/nodes/{id}/{action} with a wallet-signed body (no auth token).
+async fn post_signed(
+    args: &ManageArgs,
+    state: &RelayState,
+    action: &str,
+    body: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let api = args.api.as_deref().unwrap_or(&state.api);
+    let url = format!("{}/api/nodes/{}/{}", api.trim_end_matches('/'), state.node_id, action);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()?;
+    let resp = client.post(&url).json(&body).send().await?;
+    let status = resp.status();
+    let text = resp.text().await?;
+    if !status.is_success() {
+        return Err(anyhow!("{} failed ({}): {}", action, status, text));
+    }
+    Ok(serde_json::from_str(&text)?)
+}
+
+// What one submission reports about the node.
+struct Metrics {
+    height: u64,
+    block_hash: String,
+    uptime: u64,
+    peers: u32,
+    binary_hash: Option<String>,
+    chain_info: Option<ChainInfo>,
+}
+
+async fn gather_metrics(args: &SubmitArgs) -> Result<Metrics> {
     // 1. live Zebra RPC has highest precedence — every tick reflects the current tip.
     if let Some(rpc_url) = &args.node_rpc {
-        let (height, block_hash) = query_zebra_tip(rpc_url).await?;
-        return Ok((height, block_hash, args.uptime_seconds, args.peers, args.binary_hash.clone()));
+        let (height, block_hash, chain_info) = query_zebra_tip(rpc_url).await?;
+        return Ok(Metrics {
+            height,
+            block_hash,
+            uptime: args.uptime_seconds,
+            peers: args.peers,
+            binary_hash: args.binary_hash.clone(),
+            chain_info,
+        });
     }
     if let Some(path) = &args.proof_file {
         let bytes = fs::read(path).with_context(|| format!("reading proof file {:?}", path))?;
@@ -316,7 +661,7 @@ async fn gather_metrics(args: &SubmitArgs) -> Result<(u64, String, u64, u32, Opt
         let uptime_seconds = (uptime_hours * 3600.0) as u64;
         let peers = v["metrics"]["peer_count"].as_u64().unwrap_or(0) as u32;
         let binary_hash = v["node_info"]["zebra_binary_hash"].as_str().map(String::from);
-        Ok((height, block_hash, uptime_seconds, peers, binary_hash))
+        Ok(Metrics { height, block_hash, uptime: uptime_seconds, peers, binary_hash, chain_info: None })
     } else {
         let height = args
             .height
@@ -325,12 +670,21 @@ async fn gather_metrics(args: &SubmitArgs) -> Result<(u64, String, u64, u32, Opt
             .block_hash
             .clone()
             .ok_or_else(|| anyhow!("--block-hash required when --proof-file is absent"))?;
-        Ok((height, block_hash, args.uptime_seconds, args.peers, args.binary_hash.clone()))
+        Ok(Metrics {
+            height,
+            block_hash,
+            uptime: args.uptime_seconds,
+            peers: args.peers,
+            binary_hash: args.binary_hash.clone(),
+            chain_info: None,
+        })
     }
 }
 
-// Hits Zebra's JSON-RPC for the current tip. Returns (height, best_block_hash).
-async fn query_zebra_tip(rpc_url: &str) -> Result<(u64, String)> {
+// Hits Zebra's JSON-RPC for the current tip and chain info. Returns
+// (height, best_block_hash, chain info). A node that can't answer
+// getblockchaininfo still submits, just without readiness data.
+async fn query_zebra_tip(rpc_url: &str) -> Result<(u64, String, Option<ChainInfo>)> {
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(5))
         .build()?;
@@ -342,8 +696,68 @@ async fn query_zebra_tip(rpc_url: &str) -> Result<(u64, String)> {
         .as_str()
         .map(String::from)
         .ok_or_else(|| anyhow!("getbestblockhash: result is not a string"))?;
+    let chain_info = match rpc_call(&client, rpc_url, "getblockchaininfo", json!([])).await {
+        Ok(v) => ChainInfo::from_blockchain_info(&v),
+        Err(e) => {
+            tracing::warn!(error = ?e, "getblockchaininfo failed; submitting without chain info");
+            None
+        }
+    };
+
+    Ok((height, hash, chain_info))
+}
 
-    Ok((height, hash))
+// Same shape as the server's types::ChainInfo.
+#[derive(Serialize)]
+struct ChainInfo {
+    chain: String,
+    consensus_branch_id: String,
+    next_branch_id: Option<String>,
+    upgrades: Vec<NetworkUpgrade>,
+}
+
+#[derive(Serialize)]
+struct NetworkUpgrade {
+    branch_id: String,
+    name: String,
+    activation_height: u64,
+    status: String,
+}
+
+impl ChainInfo {
+    fn from_blockchain_info(v: &serde_json::Value) -> Option<Self> {
+        let consensus = &v["consensus"];
+        let mut upgrades = Vec::new();
+        for (branch_id, u) in v["upgrades"].as_object().into_iter().flatten() {
+            upgrades.push(NetworkUpgrade {
+                branch_id: branch_id.to_ascii_lowercase(),
+                name: u["name"].as_str().unwrap_or_default().to_string(),
+                activation_height: u["activationheight"].as_u64()?,
+                status: u["status"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+        upgrades.sort_by(|a, b| (a.activation_height, &a.branch_id).cmp(&(b.activation_height, &b.branch_id)));
+        Some(Self {
+            chain: v["chain"].as_str()?.to_string(),
+            consensus_branch_id: consensus["chaintip"].as_str()?.to_ascii_lowercase(),
+            next_branch_id: consensus["nextblock"].as_str().map(str::to_ascii_lowercase),
+            upgrades,
+        })
+    }
+
+    // Server's auth::chain_info_digest. Upgrades are already in its order.
+    fn digest(&self) -> String {
+        let mut canonical = format!(
+            "{}\n{}\n{}\n",
+            self.chain,
+            self.consensus_branch_id,
+            self.next_branch_id.as_deref().unwrap_or_default()
+        );
+        for u in &self.upgrades {
+            canonical.push_str(&format!("{}:{}:{}:{}\n", u.branch_id, u.name, u.activation_height, u.status));
+        }
+        hex::encode(Sha256::digest(canonical.as_bytes()))
+    }
 }
 
 async fn rpc_call(
@@ -374,32 +788,89 @@ async fn rpc_call(
         .ok_or_else(|| anyhow!("zebra rpc {method} missing result"))
 }
 
-fn registration_message(
+// Same shape as the server's types::RpcAuth.
+#[derive(Serialize)]
+#[serde(tag = "scheme", rename_all = "lowercase")]
+enum RpcAuth {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+}
+
+// Server's auth::registration_message_v2 must match exactly. The endpoints
+// are signed, and the credentials as a digest so the secret itself isn't.
+#[allow(clippy::too_many_arguments)]
+fn registration_message_v2(
     wallet: &str,
     nonce: &str,
     timestamp: &str,
     kind: &str,
     network: &str,
     label: &str,
+    rpc_endpoint: Option<&str>,
+    p2p_address: Option<&str>,
+    rpc_auth: Option<&RpcAuth>,
 ) -> Vec<u8> {
-    format!("depinzcash:register:v1\n{wallet}\n{nonce}\n{timestamp}\n{kind}\n{network}\n{label}\n")
-        .into_bytes()
+    let rpc_endpoint = rpc_endpoint.unwrap_or_default();
+    let p2p_address = p2p_address.unwrap_or_default();
+    let rpc_auth = rpc_auth.map(rpc_auth_digest).unwrap_or_default();
+    format!(
+        "depinzcash:register:v2\n{wallet}\n{nonce}\n{timestamp}\n{kind}\n{network}\n{label}\n{rpc_endpoint}\n{p2p_address}\n{rpc_auth}\n"
+    )
+    .into_bytes()
 }
 
-fn proof_message(
+// Server's auth::rpc_auth_digest.
+fn rpc_auth_digest(auth: &RpcAuth) -> String {
+    let canonical = match auth {
+        RpcAuth::Basic { username, password } => format!("basic\n{username}\n{password}"),
+        RpcAuth::Bearer { token } => format!("bearer\n{token}"),
+    };
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+// Server's auth::proof_message_v3 must match exactly. uptime / peers /
+// binary_hash / chain info are signed so nothing between us and the server
+// can inflate them.
+#[allow(clippy::too_many_arguments)]
+fn proof_message_v3(
     wallet: &str,
     node_id: &str,
     height: u64,
     block_hash: &str,
     proof_timestamp: &str,
     nonce: &str,
+    uptime_seconds: u64,
+    peers: u32,
+    binary_hash: Option<&str>,
+    chain_info: Option<&ChainInfo>,
 ) -> Vec<u8> {
+    let binary_hash = binary_hash.unwrap_or_default();
+    let chain_info = chain_info.map(ChainInfo::digest).unwrap_or_default();
     format!(
-        "depinzcash:proof:v1\n{wallet}\n{node_id}\n{height}\n{block_hash}\n{proof_timestamp}\n{nonce}\n"
+        "depinzcash:proof:v3\n{wallet}\n{node_id}\n{height}\n{block_hash}\n{proof_timestamp}\n{nonce}\n{uptime_seconds}\n{peers}\n{binary_hash}\n{chain_info}\n"
     )
     .into_bytes()
 }
 
+// Server's auth::delegation_message must match exactly.
+fn delegation_message(
+    wallet: &str,
+    node_id: &str,
+    delegate: &str,
+    scopes: &str,
+    expires_at: &str,
+    nonce: &str,
+    timestamp: &str,
+) -> Vec<u8> {
+    format!("depinzcash:delegate:v1\n{wallet}\n{node_id}\n{delegate}\n{scopes}\n{expires_at}\n{nonce}\n{timestamp}\n")
+        .into_bytes()
+}
+
+// Server's auth::revocation_message.
+fn revocation_message(wallet: &str, node_id: &str, delegate: &str, nonce: &str, timestamp: &str) -> Vec<u8> {
+    format!("depinzcash:delegate:revoke:v1\n{wallet}\n{node_id}\n{delegate}\n{nonce}\n{timestamp}\n").into_bytes()
+}
+
 fn sign_b58(sk: &SigningKey, msg: &[u8]) -> String {
     bs58::encode(sk.sign(msg).to_bytes()).into_string()
 }
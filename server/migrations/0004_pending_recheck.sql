@@ -0,0 +1,9 @@
+-- Pending proofs are re-checked by the scheduler once the trusted quorum
+-- recovers. Keep the tip the server had cached when the proof arrived so the
+-- late verdict uses the same drift window (and the same freshness bonus) the
+-- submit handler would have applied.
+ALTER TABLE proofs ADD COLUMN trusted_tip_height INTEGER;
+
+-- The re-check worker pages through verdict = 'pending' oldest-first.
+CREATE INDEX IF NOT EXISTS idx_proofs_verdict_received
+    ON proofs(verdict, received_at);
@@ -0,0 +1,10 @@
+-- Reorg-aware finality. An accepted proof stays provisional until the
+-- confirmation job re-checks its block hash against the trusted quorum once
+-- it is FINALITY_DEPTH blocks deep. Match = finalized_at stamped; mismatch =
+-- verdict flips to 'reorged' and the node's points are clawed back.
+ALTER TABLE proofs ADD COLUMN finalized_at TEXT;
+
+-- Confirmation job scans accepted-but-unfinalized proofs lowest height first;
+-- the snapshot query subtracts the same set per node.
+CREATE INDEX IF NOT EXISTS idx_proofs_unfinalized
+    ON proofs(verdict, finalized_at, claimed_height);
@@ -0,0 +1,28 @@
+-- Delta snapshots. Each cycle's leaf now pays the points a wallet earned
+-- since its previous snapshot, not its all-time total. `points_from` /
+-- `points_to` record the cumulative range a leaf covers; the per-wallet
+-- baseline is the cumulative total already distributed.
+ALTER TABLE snapshot_leaves ADD COLUMN points_from INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE snapshot_leaves ADD COLUMN points_to INTEGER NOT NULL DEFAULT 0;
+
+-- Leaves published before this migration were cumulative totals from 0.
+UPDATE snapshot_leaves SET points_to = points WHERE points_to = 0;
+
+CREATE TABLE IF NOT EXISTS snapshot_baselines (
+    wallet TEXT PRIMARY KEY,
+    points INTEGER NOT NULL,
+    cycle INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Seed baselines from the newest cumulative leaf per wallet so the first
+-- delta cycle doesn't pay out history a second time.
+INSERT OR IGNORE INTO snapshot_baselines (wallet, points, cycle, updated_at)
+SELECT l.wallet, l.points_to, s.cycle, s.published_at
+FROM snapshot_leaves l
+JOIN snapshots s ON s.id = l.snapshot_id
+WHERE s.cycle = (
+    SELECT MAX(s2.cycle) FROM snapshot_leaves l2
+    JOIN snapshots s2 ON s2.id = l2.snapshot_id
+    WHERE l2.wallet = l.wallet
+);
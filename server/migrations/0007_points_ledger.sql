@@ -0,0 +1,23 @@
+-- Append-only points ledger. Every change to nodes.points writes one row here
+-- in the same transaction, so a node's balance can be explained line by line
+-- and nodes.points (kept as a cache for the hot stats queries) can be
+-- reconciled against SUM(delta).
+CREATE TABLE IF NOT EXISTS points_ledger (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    node_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    -- Signed: credits > 0, debits (revocations, admin corrections) < 0.
+    delta INTEGER NOT NULL,
+    -- proof | uptime | challenge_bonus | admin | revocation | opening_balance
+    source TEXT NOT NULL,
+    -- proof id / challenge id / admin note; NULL for uptime ticks.
+    ref_id TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_points_ledger_node ON points_ledger(node_id, id);
+
+-- Balances that predate the ledger can't be itemised — carry them in as one
+-- opening row per node so reconciliation starts clean.
+INSERT INTO points_ledger (node_id, delta, source, ref_id, created_at)
+SELECT id, points, 'opening_balance', NULL, strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+FROM nodes WHERE points != 0;
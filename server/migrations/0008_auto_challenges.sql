@@ -0,0 +1,11 @@
+-- Server-issued challenges. The scheduler picks a random past height, asks
+-- an exposed-RPC node for its hash directly and records the result in one
+-- go — no operator round-trip. `origin` tells those apart from challenges an
+-- operator requested; `answer_hash` / `fail_reason` make the history
+-- auditable after the fact.
+ALTER TABLE challenges ADD COLUMN origin TEXT NOT NULL DEFAULT 'operator';
+ALTER TABLE challenges ADD COLUMN answer_hash TEXT;
+ALTER TABLE challenges ADD COLUMN fail_reason TEXT;
+
+-- Per-node history (newest first) and the consecutive-failure count.
+CREATE INDEX IF NOT EXISTS idx_challenges_node_issued ON challenges(node_id, issued_at);
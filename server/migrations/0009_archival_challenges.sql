@@ -0,0 +1,6 @@
+-- Archival challenge kinds (block_header, raw_transaction, tree_state).
+-- `subject` holds whatever the challenge asked about beyond target_height —
+-- the txid for raw_transaction. For archival kinds `expected_hash` /
+-- `answer_hash` hold a SHA-256 fingerprint of the answer rather than the raw
+-- response (a raw transaction can be megabytes).
+ALTER TABLE challenges ADD COLUMN subject TEXT;
@@ -0,0 +1,20 @@
+-- P2P reachability. Full nodes can advertise the host:port they accept
+-- inbound peers on; the scheduler connects there, runs the version/verack
+-- handshake and records what the peer said about itself. Reachable nodes
+-- earn a bonus per probe, written to the points ledger with the probe id.
+ALTER TABLE nodes ADD COLUMN p2p_address TEXT;
+
+CREATE TABLE IF NOT EXISTS p2p_probes (
+    id TEXT PRIMARY KEY,
+    node_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    address TEXT NOT NULL,
+    reachable INTEGER NOT NULL,
+    user_agent TEXT,
+    protocol_version INTEGER,
+    start_height INTEGER,
+    error TEXT,
+    points_awarded INTEGER NOT NULL DEFAULT 0,
+    probed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_p2p_probes_node_probed ON p2p_probes(node_id, probed_at);
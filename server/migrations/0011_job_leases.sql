@@ -0,0 +1,13 @@
+-- Scheduler job leases. With several server instances on one database, each
+-- job that writes shared state (uptime credit, exposed-RPC polling,
+-- snapshots, ...) only runs on the instance holding its lease row. The holder
+-- renews before `expires_at`; once a lease lapses any instance may take it.
+CREATE TABLE IF NOT EXISTS job_leases (
+    job TEXT PRIMARY KEY,
+    -- Instance id of the current (or last) holder.
+    holder TEXT NOT NULL,
+    -- When this holder first took the lease; renewals keep it.
+    acquired_at TEXT NOT NULL,
+    renewed_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL
+);
@@ -0,0 +1,14 @@
+-- Scheduler run history: one row per job with when it last ran and when it's
+-- next due. Loops read this on startup, so a restart resumes each job's
+-- cadence (and runs anything overdue) instead of starting its clock over.
+CREATE TABLE IF NOT EXISTS job_runs (
+    job TEXT PRIMARY KEY,
+    next_run_at TEXT NOT NULL,
+    -- When the last run started / finished. NULL until the first run.
+    last_run_at TEXT,
+    last_finished_at TEXT,
+    -- Most recent failure. Kept after later successes; compare last_error_at
+    -- with last_run_at to tell whether the latest run failed.
+    last_error TEXT,
+    last_error_at TEXT
+);
@@ -0,0 +1,15 @@
+-- Exposed-RPC poll state, one row per polled node. Tells an operator why the
+-- node isn't being credited (last outcome + error) and lets the scheduler back
+-- off endpoints that keep failing (`next_poll_at`).
+CREATE TABLE IF NOT EXISTS rpc_poll_stats (
+    node_id TEXT PRIMARY KEY REFERENCES nodes(id) ON DELETE CASCADE,
+    last_polled_at TEXT NOT NULL,
+    -- RpcPollOutcome::as_str
+    last_outcome TEXT NOT NULL,
+    last_error TEXT,
+    last_latency_ms INTEGER,
+    -- Last poll the endpoint answered, whatever the verdict.
+    last_success_at TEXT,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    next_poll_at TEXT NOT NULL
+);
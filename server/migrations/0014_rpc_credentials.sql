@@ -0,0 +1,7 @@
+-- Operator RPC credentials, sealed under RPC_CREDENTIALS_KEY (see
+-- src/credentials.rs). Kept out of `nodes` so no node query can return them.
+CREATE TABLE IF NOT EXISTS node_rpc_credentials (
+    node_id TEXT PRIMARY KEY REFERENCES nodes(id) ON DELETE CASCADE,
+    sealed TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
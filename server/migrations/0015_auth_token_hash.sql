@@ -0,0 +1,6 @@
+-- Node auth tokens are kept as sha256 hex (see auth::hash_auth_token), never
+-- in plain text. SQLite has no sha256(), so rows registered before this
+-- migration are hashed by SqliteStore::migrate, which also blanks the old
+-- column; `auth_token` is otherwise always ''.
+ALTER TABLE nodes ADD COLUMN auth_token_hash TEXT;
+CREATE INDEX IF NOT EXISTS idx_nodes_auth_token_hash ON nodes(auth_token_hash);
@@ -0,0 +1,13 @@
+-- Relay hot keys a wallet has authorised for one of its nodes (see
+-- api/delegates.rs). `scopes` is a comma-separated DelegateScope list.
+-- Revoked rows are kept for the audit trail; delegating the same key again
+-- replaces the row.
+CREATE TABLE IF NOT EXISTS node_delegates (
+    node_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    delegate TEXT NOT NULL,
+    scopes TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    revoked_at TEXT,
+    PRIMARY KEY (node_id, delegate)
+);
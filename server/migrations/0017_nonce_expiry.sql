@@ -0,0 +1,18 @@
+-- Nonces are single-use per wallet (the signature already binds one to its
+-- wallet) and carry the timestamp they were signed with. A request signed
+-- outside MAX_CLOCK_SKEW is refused before its nonce is looked at, so rows
+-- older than that are dead weight; the `nonce_prune` job deletes them by
+-- `signed_at`. SQLite can't change a primary key in place, so rebuild.
+-- Existing rows have no signed timestamp: `used_at` is within the skew of it.
+CREATE TABLE used_nonces_v2 (
+    wallet TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    signed_at TEXT NOT NULL,
+    used_at TEXT NOT NULL,
+    PRIMARY KEY (wallet, nonce)
+);
+INSERT OR IGNORE INTO used_nonces_v2 (wallet, nonce, signed_at, used_at)
+    SELECT wallet, nonce, used_at, used_at FROM used_nonces;
+DROP TABLE used_nonces;
+ALTER TABLE used_nonces_v2 RENAME TO used_nonces;
+CREATE INDEX IF NOT EXISTS idx_used_nonces_signed_at ON used_nonces(signed_at);
@@ -0,0 +1,15 @@
+-- Quorum rounds the trusted rpcs didn't agree on (see rpc.rs), for the
+-- admin forensics endpoint and the `rpc_dissent` alert job. `params`,
+-- `agreed`, `answers` and `dissenters` are JSON text; `agreed` is NULL for a
+-- round that ended without a quorum.
+CREATE TABLE IF NOT EXISTS rpc_disagreements (
+    id TEXT PRIMARY KEY,
+    method TEXT NOT NULL,
+    params TEXT NOT NULL,
+    agreed TEXT,
+    answers TEXT NOT NULL,
+    dissenters TEXT NOT NULL,
+    observed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rpc_disagreements_observed ON rpc_disagreements(observed_at);
@@ -0,0 +1,28 @@
+-- Network-upgrade readiness (see upgrades.rs). One row per node: the last
+-- getblockchaininfo it reported, by exposed poll or relay proof, and how it
+-- compared with the trusted quorum. `upgrades` is JSON text; `quorum_match`
+-- is NULL when the quorum had no answer.
+CREATE TABLE IF NOT EXISTS node_chain_info (
+    node_id TEXT PRIMARY KEY REFERENCES nodes(id) ON DELETE CASCADE,
+    -- ChainInfoSource::as_str
+    source TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    consensus_branch_id TEXT NOT NULL,
+    next_branch_id TEXT,
+    upgrades TEXT NOT NULL,
+    quorum_match INTEGER,
+    mismatch TEXT,
+    observed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_chain_info_observed ON node_chain_info(observed_at);
+
+-- A node's readiness bonus for one announced upgrade, keyed by its branch
+-- id so it's paid once. The ledger row carries the branch id as ref_id.
+CREATE TABLE IF NOT EXISTS upgrade_readiness (
+    node_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    branch_id TEXT NOT NULL,
+    points_awarded INTEGER NOT NULL,
+    ready_at TEXT NOT NULL,
+    PRIMARY KEY (node_id, branch_id)
+);
@@ -0,0 +1,16 @@
+-- One node per advertised P2P address. Hosts are case-insensitive, so
+-- addresses are stored lower-cased; where several nodes already share one,
+-- the earliest registration keeps it and the rest are cleared (they stop
+-- being probed until the operator re-registers with their own address).
+UPDATE nodes SET p2p_address = LOWER(p2p_address) WHERE p2p_address IS NOT NULL;
+
+UPDATE nodes SET p2p_address = NULL
+ WHERE p2p_address IS NOT NULL
+   AND EXISTS (
+     SELECT 1 FROM nodes earlier
+      WHERE earlier.p2p_address = nodes.p2p_address
+        AND (earlier.registered_at < nodes.registered_at
+             OR (earlier.registered_at = nodes.registered_at AND earlier.id < nodes.id))
+   );
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_nodes_p2p_address ON nodes(p2p_address);
@@ -0,0 +1,144 @@
+-- DePINZcash Postgres schema. Same tables and columns as the SQLite
+-- migrations 0001-0010 rolled into one: ids and timestamps stay TEXT (uuid /
+-- RFC3339, so both backends share row mapping and ordering), integers are
+-- BIGINT so the store binds i64 everywhere, and flags that SQLite keeps as
+-- INTEGER 0/1 stay integers here too. Later schema changes land as a
+-- numbered file in this directory alongside the SQLite one.
+
+CREATE TABLE IF NOT EXISTS nodes (
+    id TEXT PRIMARY KEY,
+    wallet TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    label TEXT,
+    rpc_endpoint TEXT,
+    p2p_address TEXT,
+    network TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'registered',
+    last_height BIGINT,
+    last_block_hash TEXT,
+    last_proof_at TEXT,
+    registered_at TEXT NOT NULL,
+    points BIGINT NOT NULL DEFAULT 0,
+    uptime_seconds BIGINT NOT NULL DEFAULT 0,
+    auth_token TEXT NOT NULL,
+    UNIQUE (wallet, kind, label)
+);
+
+CREATE INDEX IF NOT EXISTS idx_nodes_wallet ON nodes(wallet);
+CREATE INDEX IF NOT EXISTS idx_nodes_status ON nodes(status);
+CREATE INDEX IF NOT EXISTS idx_nodes_points ON nodes(points DESC);
+CREATE INDEX IF NOT EXISTS idx_nodes_network_last_proof ON nodes(network, last_proof_at);
+CREATE INDEX IF NOT EXISTS idx_nodes_network_status_last_proof
+    ON nodes(network, status, last_proof_at);
+CREATE INDEX IF NOT EXISTS idx_nodes_network_last_height ON nodes(network, last_height);
+CREATE INDEX IF NOT EXISTS idx_nodes_wallet_filter
+    ON nodes(network, last_proof_at, last_height, wallet);
+
+CREATE TABLE IF NOT EXISTS proofs (
+    id TEXT PRIMARY KEY,
+    node_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    wallet TEXT NOT NULL,
+    claimed_height BIGINT NOT NULL,
+    claimed_block_hash TEXT NOT NULL,
+    proof_timestamp TEXT NOT NULL,
+    binary_hash TEXT,
+    uptime_seconds BIGINT,
+    peers BIGINT,
+    verdict TEXT NOT NULL DEFAULT 'pending',
+    reject_reason TEXT,
+    points_awarded BIGINT NOT NULL DEFAULT 0,
+    received_at TEXT NOT NULL,
+    trusted_tip_height BIGINT,
+    finalized_at TEXT,
+    UNIQUE (node_id, claimed_height, claimed_block_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_proofs_node ON proofs(node_id);
+CREATE INDEX IF NOT EXISTS idx_proofs_wallet ON proofs(wallet);
+CREATE INDEX IF NOT EXISTS idx_proofs_received ON proofs(received_at DESC);
+CREATE INDEX IF NOT EXISTS idx_proofs_verdict ON proofs(verdict);
+CREATE INDEX IF NOT EXISTS idx_proofs_verdict_received ON proofs(verdict, received_at);
+CREATE INDEX IF NOT EXISTS idx_proofs_unfinalized
+    ON proofs(verdict, finalized_at, claimed_height);
+
+CREATE TABLE IF NOT EXISTS challenges (
+    id TEXT PRIMARY KEY,
+    node_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    target_height BIGINT NOT NULL,
+    expected_hash TEXT NOT NULL,
+    issued_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open',
+    answered_at TEXT,
+    passed BIGINT,
+    origin TEXT NOT NULL DEFAULT 'operator',
+    answer_hash TEXT,
+    fail_reason TEXT,
+    subject TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_challenges_node ON challenges(node_id);
+CREATE INDEX IF NOT EXISTS idx_challenges_status ON challenges(status);
+CREATE INDEX IF NOT EXISTS idx_challenges_node_issued ON challenges(node_id, issued_at);
+
+CREATE TABLE IF NOT EXISTS snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    cycle BIGINT NOT NULL UNIQUE,
+    merkle_root TEXT NOT NULL,
+    total_points BIGINT NOT NULL,
+    spl_mint TEXT,
+    published_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS snapshot_leaves (
+    snapshot_id BIGINT NOT NULL REFERENCES snapshots(id) ON DELETE CASCADE,
+    wallet TEXT NOT NULL,
+    points BIGINT NOT NULL,
+    leaf_hash TEXT NOT NULL,
+    proof_json TEXT NOT NULL,
+    points_from BIGINT NOT NULL DEFAULT 0,
+    points_to BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (snapshot_id, wallet)
+);
+
+CREATE INDEX IF NOT EXISTS idx_snapshot_leaves_wallet ON snapshot_leaves(wallet);
+
+CREATE TABLE IF NOT EXISTS snapshot_baselines (
+    wallet TEXT PRIMARY KEY,
+    points BIGINT NOT NULL,
+    cycle BIGINT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS used_nonces (
+    nonce TEXT PRIMARY KEY,
+    wallet TEXT NOT NULL,
+    used_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS points_ledger (
+    id BIGSERIAL PRIMARY KEY,
+    node_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    delta BIGINT NOT NULL,
+    source TEXT NOT NULL,
+    ref_id TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_points_ledger_node ON points_ledger(node_id, id);
+
+CREATE TABLE IF NOT EXISTS p2p_probes (
+    id TEXT PRIMARY KEY,
+    node_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    address TEXT NOT NULL,
+    reachable BIGINT NOT NULL,
+    user_agent TEXT,
+    protocol_version BIGINT,
+    start_height BIGINT,
+    error TEXT,
+    points_awarded BIGINT NOT NULL DEFAULT 0,
+    probed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_p2p_probes_node_probed ON p2p_probes(node_id, probed_at);
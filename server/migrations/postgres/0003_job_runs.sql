@@ -0,0 +1,9 @@
+-- Scheduler run history; see migrations/0012_job_runs.sql.
+CREATE TABLE IF NOT EXISTS job_runs (
+    job TEXT PRIMARY KEY,
+    next_run_at TEXT NOT NULL,
+    last_run_at TEXT,
+    last_finished_at TEXT,
+    last_error TEXT,
+    last_error_at TEXT
+);
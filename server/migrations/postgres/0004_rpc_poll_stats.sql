@@ -0,0 +1,11 @@
+-- Exposed-RPC poll state; see migrations/0013_rpc_poll_stats.sql.
+CREATE TABLE IF NOT EXISTS rpc_poll_stats (
+    node_id TEXT PRIMARY KEY REFERENCES nodes(id) ON DELETE CASCADE,
+    last_polled_at TEXT NOT NULL,
+    last_outcome TEXT NOT NULL,
+    last_error TEXT,
+    last_latency_ms BIGINT,
+    last_success_at TEXT,
+    consecutive_failures BIGINT NOT NULL DEFAULT 0,
+    next_poll_at TEXT NOT NULL
+);
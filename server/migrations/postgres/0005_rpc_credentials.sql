@@ -0,0 +1,6 @@
+-- Sealed operator RPC credentials; see migrations/0014_rpc_credentials.sql.
+CREATE TABLE IF NOT EXISTS node_rpc_credentials (
+    node_id TEXT PRIMARY KEY REFERENCES nodes(id) ON DELETE CASCADE,
+    sealed TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
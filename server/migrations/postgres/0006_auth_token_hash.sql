@@ -0,0 +1,8 @@
+-- Hashed node auth tokens; see migrations/0015_auth_token_hash.sql. Postgres
+-- can hash the existing plain-text tokens itself.
+ALTER TABLE nodes ADD COLUMN auth_token_hash TEXT;
+UPDATE nodes
+   SET auth_token_hash = encode(sha256(convert_to(auth_token, 'UTF8')), 'hex'),
+       auth_token = ''
+ WHERE auth_token <> '';
+CREATE INDEX IF NOT EXISTS idx_nodes_auth_token_hash ON nodes(auth_token_hash);
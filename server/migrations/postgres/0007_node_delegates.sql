@@ -0,0 +1,10 @@
+-- Delegated relay hot keys; see migrations/0016_node_delegates.sql.
+CREATE TABLE IF NOT EXISTS node_delegates (
+    node_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    delegate TEXT NOT NULL,
+    scopes TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    revoked_at TEXT,
+    PRIMARY KEY (node_id, delegate)
+);
@@ -0,0 +1,8 @@
+-- Nonces keyed by (wallet, nonce) with their signed timestamp, pruned by the
+-- `nonce_prune` job; see migrations/0017_nonce_expiry.sql.
+ALTER TABLE used_nonces ADD COLUMN signed_at TEXT;
+UPDATE used_nonces SET signed_at = used_at;
+ALTER TABLE used_nonces ALTER COLUMN signed_at SET NOT NULL;
+ALTER TABLE used_nonces DROP CONSTRAINT used_nonces_pkey;
+ALTER TABLE used_nonces ADD PRIMARY KEY (wallet, nonce);
+CREATE INDEX IF NOT EXISTS idx_used_nonces_signed_at ON used_nonces(signed_at);
@@ -0,0 +1,12 @@
+-- Trusted-rpc disagreements; see migrations/0018_rpc_disagreements.sql.
+CREATE TABLE IF NOT EXISTS rpc_disagreements (
+    id TEXT PRIMARY KEY,
+    method TEXT NOT NULL,
+    params TEXT NOT NULL,
+    agreed TEXT,
+    answers TEXT NOT NULL,
+    dissenters TEXT NOT NULL,
+    observed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rpc_disagreements_observed ON rpc_disagreements(observed_at);
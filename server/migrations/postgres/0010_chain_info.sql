@@ -0,0 +1,22 @@
+-- Network-upgrade readiness; see migrations/0019_chain_info.sql.
+CREATE TABLE IF NOT EXISTS node_chain_info (
+    node_id TEXT PRIMARY KEY REFERENCES nodes(id) ON DELETE CASCADE,
+    source TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    consensus_branch_id TEXT NOT NULL,
+    next_branch_id TEXT,
+    upgrades TEXT NOT NULL,
+    quorum_match BIGINT,
+    mismatch TEXT,
+    observed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_chain_info_observed ON node_chain_info(observed_at);
+
+CREATE TABLE IF NOT EXISTS upgrade_readiness (
+    node_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    branch_id TEXT NOT NULL,
+    points_awarded BIGINT NOT NULL,
+    ready_at TEXT NOT NULL,
+    PRIMARY KEY (node_id, branch_id)
+);
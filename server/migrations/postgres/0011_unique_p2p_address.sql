@@ -0,0 +1,13 @@
+-- One node per advertised P2P address; see migrations/0020_unique_p2p_address.sql.
+UPDATE nodes SET p2p_address = LOWER(p2p_address) WHERE p2p_address IS NOT NULL;
+
+UPDATE nodes SET p2p_address = NULL
+ WHERE p2p_address IS NOT NULL
+   AND EXISTS (
+     SELECT 1 FROM nodes earlier
+      WHERE earlier.p2p_address = nodes.p2p_address
+        AND (earlier.registered_at < nodes.registered_at
+             OR (earlier.registered_at = nodes.registered_at AND earlier.id < nodes.id))
+   );
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_nodes_p2p_address ON nodes(p2p_address);
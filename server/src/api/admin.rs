@@ -3,11 +3,17 @@ use axum::{
     http::HeaderMap,
     Json,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use uuid::Uuid;
 
-use crate::{error::AppError, merkle, state::AppState, types::NodeStatus};
+use crate::{
+    error::AppError,
+    merkle, scheduler,
+    state::AppState,
+    types::{EndpointDissent, JobRun, LedgerEntry, LedgerSource, NodeStatus, PointsMismatch, RpcDisagreement},
+};
 
 #[derive(Debug, Serialize)]
 pub struct PublishSnapshotResponse {
@@ -40,13 +46,12 @@ pub async fn purge_node(
     headers: HeaderMap,
 ) -> Result<Json<Value>, AppError> {
     require_admin(&state, &headers)?;
-    let pool = state.store().pool();
-    let res = sqlx::query("DELETE FROM nodes WHERE id = ?1")
-        .bind(id.to_string())
-        .execute(pool)
+    let deleted = state
+        .store()
+        .delete_node(id)
         .await
         .map_err(|e| AppError::Internal(anyhow::anyhow!("purge_node: {e}")))?;
-    if res.rows_affected() == 0 {
+    if !deleted {
         return Err(AppError::NotFound);
     }
     tracing::warn!(node_id = %id, "node purged by admin");
@@ -69,6 +74,173 @@ pub async fn suspend_node(
     Ok(Json(json!({ "suspended": id.to_string(), "ok": true })))
 }
 
+// ---- points ledger ----------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+pub struct ReconcileResponse {
+    pub balanced: bool,
+    pub mismatches: Vec<PointsMismatch>,
+}
+
+// Every node whose cached nodes.points disagrees with its ledger sum. Read-only:
+// a drift means something wrote nodes.points outside the store, so work out
+// which side is wrong before touching either.
+pub async fn reconcile_points(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ReconcileResponse>, AppError> {
+    require_admin(&state, &headers)?;
+    let mismatches = state.store().points_mismatches().await?;
+    if !mismatches.is_empty() {
+        tracing::warn!(count = mismatches.len(), "points ledger out of balance");
+    }
+    Ok(Json(ReconcileResponse {
+        balanced: mismatches.is_empty(),
+        mismatches,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobsResponse {
+    // The instance answering, to compare against each job's lease holder.
+    pub instance_id: String,
+    pub jobs: Vec<JobStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobStatus {
+    #[serde(flatten)]
+    pub run: JobRun,
+    pub last_run_failed: bool,
+}
+
+// Scheduler jobs as recorded in the database: last run, next run, last error
+// and which instance holds the lease. A job that has never been scheduled on
+// any instance (disabled in config) isn't listed.
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<JobsResponse>, AppError> {
+    require_admin(&state, &headers)?;
+    let jobs = state
+        .store()
+        .list_job_runs()
+        .await?
+        .into_iter()
+        .map(|run| JobStatus {
+            last_run_failed: matches!((run.last_error_at, run.last_run_at), (Some(err), Some(ran)) if err >= ran),
+            run,
+        })
+        .collect();
+    Ok(Json(JobsResponse {
+        instance_id: state.instance_id().to_string(),
+        jobs,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LedgerQuery {
+    #[serde(default = "default_ledger_limit")]
+    pub limit: i64,
+}
+
+fn default_ledger_limit() -> i64 {
+    200
+}
+
+pub async fn node_ledger(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Query(q): Query<LedgerQuery>,
+) -> Result<Json<Vec<LedgerEntry>>, AppError> {
+    require_admin(&state, &headers)?;
+    if state.store().get_node(id).await?.is_none() {
+        return Err(AppError::NotFound);
+    }
+    let entries = state
+        .store()
+        .ledger_for_node(id, q.limit.clamp(1, 1000))
+        .await?;
+    Ok(Json(entries))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DisagreementsQuery {
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default = "default_ledger_limit")]
+    pub limit: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisagreementsResponse {
+    pub window_secs: u64,
+    pub alert_threshold: u32,
+    // Dissent per trusted rpc within the window, most first.
+    pub endpoints: Vec<EndpointDissent>,
+    // Newest first.
+    pub disagreements: Vec<RpcDisagreement>,
+}
+
+// Rounds the trusted rpcs disagreed on, each with every rpc's answer or
+// error, plus how often each rpc was the odd one out lately. This
+// instance's not-yet-stored records are stored first.
+pub async fn rpc_disagreements(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<DisagreementsQuery>,
+) -> Result<Json<DisagreementsResponse>, AppError> {
+    require_admin(&state, &headers)?;
+    scheduler::flush_rpc_disagreements(&state).await?;
+    let endpoints = scheduler::recent_dissent(&state, Utc::now()).await?;
+    let disagreements = state
+        .store()
+        .list_rpc_disagreements(q.since.unwrap_or(DateTime::UNIX_EPOCH), q.limit.clamp(1, 1000))
+        .await?;
+    Ok(Json(DisagreementsResponse {
+        window_secs: state.config().rpc_dissent_window.as_secs(),
+        alert_threshold: state.config().rpc_dissent_alert_threshold,
+        endpoints,
+        disagreements,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdjustPointsRequest {
+    pub delta: i64,
+    pub reason: String,
+}
+
+// Manual credit/debit. The reason is stored as the ledger row's ref_id so the
+// audit trail says why.
+pub async fn adjust_points(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(req): Json<AdjustPointsRequest>,
+) -> Result<Json<Value>, AppError> {
+    require_admin(&state, &headers)?;
+    let reason = req.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::bad_request("reason is required"));
+    }
+    if state.store().get_node(id).await?.is_none() {
+        return Err(AppError::NotFound);
+    }
+    let applied = state
+        .store()
+        .adjust_points(id, req.delta, LedgerSource::Admin, Some(reason))
+        .await?;
+    tracing::warn!(
+        node_id = %id,
+        requested = req.delta,
+        applied,
+        reason,
+        "points adjusted by admin"
+    );
+    Ok(Json(json!({ "node_id": id.to_string(), "applied": applied, "ok": true })))
+}
+
 // Mass cleanup: delete fake / spam nodes in two passes:
 //   1. Delete every node with last_height < min_real_height (fake block heights)
 //   2. For each wallet with > max_nodes_per_wallet, keep the oldest N and delete the rest
@@ -94,35 +266,23 @@ pub async fn cleanup(
 ) -> Result<Json<Value>, AppError> {
     require_admin(&state, &headers)?;
 
-    let pool = state.store().pool();
-    let min_height = state.config().min_real_height as i64;
+    let store = state.store();
+    let min_height = state.config().min_real_height;
     let max_per_wallet = state.config().max_nodes_per_wallet as i64;
 
-    // Pass 1: count nodes with fake heights (last_height < min_real_height OR
-    // last_height IS NULL but they have a last_proof_at, meaning they submitted
-    // something but at a garbage height).
-    let fake_height_count: i64 = sqlx::query_scalar(
-        r#"SELECT COUNT(1) FROM nodes
-           WHERE last_height IS NOT NULL AND last_height < ?1 AND last_height > 0"#,
-    )
-    .bind(min_height)
-    .fetch_one(pool)
-    .await
-    .map_err(|e| AppError::Internal(anyhow::anyhow!("cleanup count fake: {e}")))?;
-
-    // Pass 2: count excess nodes per wallet (beyond max_per_wallet).
-    // ROW_NUMBER over (PARTITION BY wallet ORDER BY registered_at ASC) — keep
-    // the first N, mark the rest for deletion.
-    let excess_count: i64 = sqlx::query_scalar(
-        r#"SELECT COUNT(1) FROM (
-             SELECT id, ROW_NUMBER() OVER (PARTITION BY wallet ORDER BY registered_at ASC) AS rn
-             FROM nodes
-           ) WHERE rn > ?1"#,
-    )
-    .bind(max_per_wallet)
-    .fetch_one(pool)
-    .await
-    .map_err(|e| AppError::Internal(anyhow::anyhow!("cleanup count excess: {e}")))?;
+    // Pass 1: count nodes with fake heights (0 < last_height < min_real_height:
+    // they submitted something, but at a garbage height).
+    let fake_height_count = store
+        .count_fake_height_nodes(min_height)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("cleanup count fake: {e}")))?;
+
+    // Pass 2: count excess nodes per wallet (beyond max_per_wallet, oldest
+    // registrations kept).
+    let excess_count = store
+        .count_excess_wallet_nodes(max_per_wallet)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("cleanup count excess: {e}")))?;
 
     if !q.confirm {
         return Ok(Json(json!({
@@ -141,36 +301,16 @@ pub async fn cleanup(
     let batch = q.batch.clamp(50, 2000);
 
     // Pass 1: delete a batch of fake-height nodes.
-    let deleted_fake = sqlx::query(
-        r#"DELETE FROM nodes WHERE id IN (
-             SELECT id FROM nodes
-             WHERE last_height IS NOT NULL AND last_height < ?1 AND last_height > 0
-             LIMIT ?2
-           )"#,
-    )
-    .bind(min_height)
-    .bind(batch)
-    .execute(pool)
-    .await
-    .map_err(|e| AppError::Internal(anyhow::anyhow!("cleanup delete fake: {e}")))?
-    .rows_affected();
+    let deleted_fake = store
+        .delete_fake_height_nodes(min_height, batch)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("cleanup delete fake: {e}")))?;
 
     // Pass 2: delete a batch of excess-per-wallet nodes.
-    let deleted_excess = sqlx::query(
-        r#"DELETE FROM nodes WHERE id IN (
-             SELECT id FROM (
-               SELECT id, ROW_NUMBER() OVER (PARTITION BY wallet ORDER BY registered_at ASC) AS rn
-               FROM nodes
-             ) WHERE rn > ?1
-             LIMIT ?2
-           )"#,
-    )
-    .bind(max_per_wallet)
-    .bind(batch)
-    .execute(pool)
-    .await
-    .map_err(|e| AppError::Internal(anyhow::anyhow!("cleanup delete excess: {e}")))?
-    .rows_affected();
+    let deleted_excess = store
+        .delete_excess_wallet_nodes(max_per_wallet, batch)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("cleanup delete excess: {e}")))?;
 
     let total = deleted_fake + deleted_excess;
     tracing::warn!(
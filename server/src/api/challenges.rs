@@ -1,13 +1,17 @@
 use axum::{extract::State, Json};
 use chrono::{Duration as ChronoDuration, Utc};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::{
+    api::{delegates::verify_node_signer, proofs::normalize_hash},
     auth,
     error::{AppError, AppResult},
+    rpc::{RpcError, ZcashRpcQuorum},
     state::AppState,
-    types::{Challenge, ChallengeKind, ChallengeStatus},
+    types::{Challenge, ChallengeKind, ChallengeOrigin, ChallengeStatus, DelegateScope, LedgerSource, NodeStatus},
 };
 
 #[derive(Debug, Deserialize)]
@@ -17,6 +21,9 @@ pub struct RequestChallengeRequest {
     pub signature: String,
     pub nonce: String,
     pub timestamp: chrono::DateTime<Utc>,
+    // Set when a delegated hot key signed instead of the wallet.
+    #[serde(default)]
+    pub delegate: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -37,6 +44,9 @@ pub async fn request(
     if node.wallet != req.wallet {
         return Err(AppError::bad_request("wallet does not match node"));
     }
+    if node.status == NodeStatus::Deregistered {
+        return Err(AppError::conflict("node is deregistered"));
+    }
 
     let msg = format!(
         "depinzcash:challenge:request:v1\n{}\n{}\n{}\n{}\n",
@@ -45,9 +55,17 @@ pub async fn request(
         req.nonce,
         req.timestamp.to_rfc3339()
     );
-    auth::verify_solana_signature(&req.wallet, msg.as_bytes(), &req.signature).map_err(AppError::from)?;
+    verify_node_signer(
+        &state,
+        &node,
+        DelegateScope::Challenges,
+        req.delegate.as_deref(),
+        msg.as_bytes(),
+        &req.signature,
+    )
+    .await?;
 
-    if !state.store().try_use_nonce(&req.nonce, &req.wallet).await? {
+    if !state.store().try_use_nonce(&req.nonce, &req.wallet, req.timestamp).await? {
         return Err(AppError::conflict("nonce already used"));
     }
 
@@ -56,10 +74,7 @@ pub async fn request(
         return Err(AppError::Upstream("no trusted rpcs configured".into()));
     }
 
-    let tip = rpc.get_block_count().await.map_err(|e| AppError::Upstream(e.to_string()))?;
-    let depth = ChallengeDepth::pick(tip);
-    let target = tip.saturating_sub(depth);
-    let expected = rpc.get_block_hash(target).await.map_err(|e| AppError::Upstream(e.to_string()))?;
+    let (target, expected) = challenge_target(rpc).await.map_err(|e| AppError::Upstream(e.to_string()))?;
 
     let issued = Utc::now();
     let challenge = Challenge {
@@ -73,6 +88,10 @@ pub async fn request(
         status: ChallengeStatus::Open,
         answered_at: None,
         passed: None,
+        origin: ChallengeOrigin::Operator,
+        subject: None,
+        answer_hash: None,
+        fail_reason: None,
     };
     state.store().insert_challenge(&challenge).await?;
 
@@ -83,6 +102,32 @@ pub async fn request(
     }))
 }
 
+// The height to challenge and its quorum hash. Once the quorum has seen a
+// tip, the fresh tip and the hash at that tip's challenge height go out in
+// one batch. The target stays good while the fresh tip hasn't moved back or
+// more than ChallengeDepth::MAX past it; otherwise it's picked again from the fresh tip
+// as a second round.
+async fn challenge_target(rpc: &ZcashRpcQuorum) -> Result<(u64, String), RpcError> {
+    if let Some(known) = rpc.known_tip() {
+        let target = known.saturating_sub(ChallengeDepth::pick(known));
+        let answers = rpc
+            .quorum_batch(&[("getblockcount", json!([])), ("getblockhash", json!([target]))])
+            .await?;
+        let tip = answers.first().and_then(Value::as_u64);
+        let hash = answers.get(1).and_then(Value::as_str);
+        match (tip, hash) {
+            (Some(tip), Some(hash)) if (known..=target + ChallengeDepth::MAX).contains(&tip) => {
+                return Ok((target, hash.to_string()))
+            }
+            (Some(_), Some(_)) => {}
+            _ => return Err(RpcError::Other(format!("unexpected batch answers: {answers:?}"))),
+        }
+    }
+    let tip = rpc.get_block_count().await?;
+    let target = tip.saturating_sub(ChallengeDepth::pick(tip));
+    Ok((target, rpc.get_block_hash(target).await?))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SubmitChallengeRequest {
     pub challenge_id: Uuid,
@@ -91,6 +136,9 @@ pub struct SubmitChallengeRequest {
     pub answer_block_hash: String,
     pub nonce: String,
     pub timestamp: chrono::DateTime<Utc>,
+    // Set when a delegated hot key signed instead of the wallet.
+    #[serde(default)]
+    pub delegate: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -118,7 +166,7 @@ pub async fn submit(
     if challenge.expires_at < Utc::now() {
         state
             .store()
-            .mark_challenge_answered(challenge.id, false, Utc::now())
+            .mark_challenge_answered(challenge.id, false, None, Utc::now())
             .await?;
         return Err(AppError::conflict("challenge expired"));
     }
@@ -131,6 +179,9 @@ pub async fn submit(
     if node.wallet != req.wallet {
         return Err(AppError::bad_request("wallet does not match challenge node"));
     }
+    if node.status == NodeStatus::Deregistered {
+        return Err(AppError::conflict("node is deregistered"));
+    }
 
     let msg = format!(
         "depinzcash:challenge:answer:v1\n{}\n{}\n{}\n{}\n{}\n",
@@ -140,26 +191,38 @@ pub async fn submit(
         req.nonce,
         req.timestamp.to_rfc3339()
     );
-    auth::verify_solana_signature(&req.wallet, msg.as_bytes(), &req.signature).map_err(AppError::from)?;
+    verify_node_signer(
+        &state,
+        &node,
+        DelegateScope::Challenges,
+        req.delegate.as_deref(),
+        msg.as_bytes(),
+        &req.signature,
+    )
+    .await?;
 
-    if !state.store().try_use_nonce(&req.nonce, &req.wallet).await? {
+    if !state.store().try_use_nonce(&req.nonce, &req.wallet, req.timestamp).await? {
         return Err(AppError::conflict("nonce already used"));
     }
 
-    let expected = challenge.expected_hash.trim().to_lowercase();
-    let answer = req.answer_block_hash.trim().trim_start_matches("0x").to_lowercase();
-    let passed = expected.trim_start_matches("0x") == answer;
+    let answer = normalize_hash(&req.answer_block_hash);
+    let passed = normalize_hash(&challenge.expected_hash) == answer;
 
     state
         .store()
-        .mark_challenge_answered(challenge.id, passed, Utc::now())
+        .mark_challenge_answered(challenge.id, passed, Some(&answer), Utc::now())
         .await?;
 
     if passed {
         // Small bonus for surviving an audit.
         state
             .store()
-            .add_uptime_and_points(node.id, 0, node.kind.reward_tier() as u64)
+            .adjust_points(
+                node.id,
+                (node.kind.reward_tier() * challenge.kind.reward_multiplier()) as i64,
+                LedgerSource::ChallengeBonus,
+                Some(&challenge.id.to_string()),
+            )
             .await?;
     }
 
@@ -174,6 +237,9 @@ pub async fn submit(
 struct ChallengeDepth;
 
 impl ChallengeDepth {
+    // Deepest a challenge is meant to reach.
+    const MAX: u64 = 256;
+
     fn pick(tip: u64) -> u64 {
         if tip < 256 {
             return tip / 2;
@@ -182,11 +248,19 @@ impl ChallengeDepth {
         // a single tip exposes the same challenge surface, but rotation across blocks
         // makes precomputed answers expensive.
         let span = 256 - 32;
-        let pick = (tip % span as u64) + 32;
-        pick
+        (tip % span as u64) + 32
     }
 }
 
+// Scheduler-issued challenges have no operator in the loop to precompute
+// against, so the depth is drawn fresh each time rather than derived from tip.
+pub(crate) fn random_depth(tip: u64) -> u64 {
+    if tip < 256 {
+        return tip / 2;
+    }
+    rand::thread_rng().gen_range(32..=256)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,7 +270,16 @@ mod tests {
         assert!(ChallengeDepth::pick(50) <= 50);
         for tip in [300u64, 1_000, 100_000, 2_000_000] {
             let d = ChallengeDepth::pick(tip);
-            assert!(d >= 32 && d <= 256, "depth out of range: {d}");
+            assert!((32..=256).contains(&d), "depth out of range: {d}");
+        }
+    }
+
+    #[test]
+    fn random_depth_in_range() {
+        assert!(random_depth(50) <= 50);
+        for _ in 0..200 {
+            let d = random_depth(2_000_000);
+            assert!((32..=256).contains(&d), "depth out of range: {d}");
         }
     }
 }
@@ -0,0 +1,204 @@
+// Delegated relay hot keys. The relay signs every proof, so without this the
+// reward wallet's secret key has to live on the node machine. Instead the
+// wallet signs a delegation (`auth::delegation_message`) for a separate
+// ed25519 key, scoped to proofs and/or challenges and bounded by an expiry,
+// and the relay signs with that. Requests signed by a delegate name it in a
+// `delegate` field; `verify_node_signer` is the check every such call site
+// uses. Registering, managing and delegating still need the wallet.
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth,
+    error::{AppError, AppResult},
+    state::AppState,
+    types::{DelegateScope, Delegation, Node, NodeStatus},
+};
+
+// Long enough for a relay left alone for a season, short enough that a
+// forgotten key dies on its own.
+const MAX_DELEGATION_DAYS: i64 = 365;
+// Active (unrevoked, unexpired) delegates per node.
+const MAX_ACTIVE_DELEGATES: usize = 8;
+
+#[derive(Debug, Deserialize)]
+pub struct DelegateRequest {
+    pub wallet: String,
+    // base58 ed25519 public key of the hot key.
+    pub delegate: String,
+    // Comma-separated: "proofs", "challenges" or both. Signed as sent.
+    pub scopes: String,
+    // RFC3339, signed as sent.
+    pub expires_at: String,
+    pub nonce: String,
+    pub timestamp: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevokeDelegateRequest {
+    pub wallet: String,
+    pub delegate: String,
+    pub nonce: String,
+    pub timestamp: String,
+    pub signature: String,
+}
+
+pub async fn delegate(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<DelegateRequest>,
+) -> AppResult<Json<Delegation>> {
+    let now = Utc::now();
+    let (node, signed_at) = wallet_request(&state, id, &req.wallet, &req.nonce, &req.timestamp).await?;
+
+    auth::decode_solana_pubkey(&req.delegate)
+        .map_err(|e| AppError::bad_request(format!("invalid delegate: {e}")))?;
+    if req.delegate == node.wallet {
+        return Err(AppError::bad_request("the wallet can't be its own delegate"));
+    }
+    let scopes = DelegateScope::parse_list(&req.scopes).ok_or_else(|| {
+        AppError::bad_request("scopes must be a comma-separated list of proofs, challenges")
+    })?;
+    let expires_at = parse_rfc3339(&req.expires_at, "expires_at")?;
+    if expires_at <= now {
+        return Err(AppError::bad_request("expires_at is in the past"));
+    }
+    if expires_at > now + ChronoDuration::days(MAX_DELEGATION_DAYS) {
+        return Err(AppError::bad_request(format!(
+            "delegations can last at most {MAX_DELEGATION_DAYS} days"
+        )));
+    }
+
+    let msg = auth::delegation_message(
+        &req.wallet,
+        &id.to_string(),
+        &req.delegate,
+        &req.scopes,
+        &req.expires_at,
+        &req.nonce,
+        &req.timestamp,
+    );
+    auth::verify_solana_signature(&req.wallet, &msg, &req.signature).map_err(AppError::from)?;
+
+    let store = state.store();
+    if !store.try_use_nonce(&req.nonce, &req.wallet, signed_at).await? {
+        return Err(AppError::conflict("nonce already used"));
+    }
+    let active = store
+        .list_delegations(id)
+        .await?
+        .iter()
+        .filter(|d| d.is_active(now) && d.delegate != req.delegate)
+        .count();
+    if active >= MAX_ACTIVE_DELEGATES {
+        return Err(AppError::conflict(format!(
+            "node already has {MAX_ACTIVE_DELEGATES} active delegates — revoke one first"
+        )));
+    }
+
+    let delegation = Delegation {
+        node_id: id,
+        delegate: req.delegate.clone(),
+        scopes,
+        expires_at,
+        created_at: now,
+        revoked_at: None,
+    };
+    store.upsert_delegation(&delegation).await?;
+    tracing::info!(node_id = %id, delegate = %delegation.delegate, %expires_at, "delegate authorised");
+    Ok(Json(delegation))
+}
+
+pub async fn revoke(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<RevokeDelegateRequest>,
+) -> AppResult<Json<Delegation>> {
+    let (_, signed_at) = wallet_request(&state, id, &req.wallet, &req.nonce, &req.timestamp).await?;
+    let msg = auth::revocation_message(&req.wallet, &id.to_string(), &req.delegate, &req.nonce, &req.timestamp);
+    auth::verify_solana_signature(&req.wallet, &msg, &req.signature).map_err(AppError::from)?;
+
+    let store = state.store();
+    if !store.try_use_nonce(&req.nonce, &req.wallet, signed_at).await? {
+        return Err(AppError::conflict("nonce already used"));
+    }
+    if !store.revoke_delegation(id, &req.delegate, Utc::now()).await? {
+        return Err(AppError::NotFound);
+    }
+    tracing::info!(node_id = %id, delegate = %req.delegate, "delegate revoked");
+    let delegation = store.get_delegation(id, &req.delegate).await?.ok_or(AppError::NotFound)?;
+    Ok(Json(delegation))
+}
+
+// Every delegation the node has had, revoked and expired included.
+pub async fn list(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<Vec<Delegation>>> {
+    state.store().get_node(id).await?.ok_or(AppError::NotFound)?;
+    Ok(Json(state.store().list_delegations(id).await?))
+}
+
+// Checks `signature` over `message` for a request acting on `node`: by the
+// node's wallet when `delegate` is None, otherwise by that delegate, which
+// must hold an active delegation covering `scope`.
+pub(crate) async fn verify_node_signer(
+    state: &AppState,
+    node: &Node,
+    scope: DelegateScope,
+    delegate: Option<&str>,
+    message: &[u8],
+    signature: &str,
+) -> AppResult<()> {
+    let Some(delegate) = delegate else {
+        return auth::verify_solana_signature(&node.wallet, message, signature).map_err(AppError::from);
+    };
+    let delegation = state
+        .store()
+        .get_delegation(node.id, delegate)
+        .await?
+        .ok_or_else(|| AppError::bad_request("delegate is not authorised for this node"))?;
+    if !delegation.allows(scope, Utc::now()) {
+        return Err(AppError::bad_request(format!(
+            "delegate is revoked, expired or not authorised for {}",
+            scope.as_str()
+        )));
+    }
+    auth::verify_solana_signature(delegate, message, signature).map_err(AppError::from)
+}
+
+// Shared preamble for the wallet-signed endpoints: fresh nonce and
+// timestamp, and the node belongs to the wallet. Returns the node and the
+// parsed timestamp.
+async fn wallet_request(
+    state: &AppState,
+    id: Uuid,
+    wallet: &str,
+    nonce: &str,
+    timestamp: &str,
+) -> AppResult<(Node, DateTime<Utc>)> {
+    auth::check_nonce(nonce).map_err(AppError::from)?;
+    let ts = parse_rfc3339(timestamp, "timestamp")?;
+    auth::check_timestamp(ts, state.config().max_clock_skew).map_err(AppError::from)?;
+    let node = state.store().get_node(id).await?.ok_or(AppError::NotFound)?;
+    if node.wallet != wallet {
+        return Err(AppError::bad_request("wallet does not match node owner"));
+    }
+    if node.status == NodeStatus::Deregistered {
+        return Err(AppError::conflict("node is deregistered"));
+    }
+    Ok((node, ts))
+}
+
+fn parse_rfc3339(s: &str, field: &str) -> AppResult<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(s)
+        .map_err(|e| AppError::bad_request(format!("invalid {field}: {e}")))?
+        .with_timezone(&Utc))
+}
@@ -7,12 +7,17 @@ pub async fn healthz() -> Json<Value> {
     Json(json!({ "status": "ok" }))
 }
 
+// "degraded" when the database is down, or when trusted rpcs are configured
+// but open circuit breakers leave too little weight for a quorum.
 pub async fn readyz(State(state): State<AppState>) -> Json<Value> {
-    let db_ok = sqlx::query("SELECT 1").execute(state.store().pool()).await.is_ok();
+    let db_ok = state.store().ping().await.is_ok();
+    let rpc = state.rpc().health();
+    let rpc_ok = !state.rpc().is_configured() || rpc.ready;
     Json(json!({
-        "status": if db_ok { "ok" } else { "degraded" },
+        "status": if db_ok && rpc_ok { "ok" } else { "degraded" },
         "db": db_ok,
         "rpc_endpoints": state.rpc().endpoints().len(),
+        "rpc_quorum": rpc,
     }))
 }
 
@@ -24,14 +29,20 @@ pub async fn info(State(state): State<AppState>) -> Json<Value> {
         "version": env!("CARGO_PKG_VERSION"),
         "network": cfg.network.as_str(),
         "rpc_endpoints": state.rpc().endpoints().len(),
+        "rpc_quorum": state.rpc().health(),
         "trusted_tip_height": tip,
         "spl_mint": cfg.spl_mint,
         "solana_cluster": cfg.solana_cluster,
         "scheduler_enabled": cfg.scheduler_enabled,
         "exposed_rpc_enabled": cfg.exposed_rpc_poll_interval.is_some(),
         "exposed_rpc_poll_seconds": cfg.exposed_rpc_poll_interval.map(|d| d.as_secs()),
+        "finality_depth": cfg.finality_depth,
         // Operators care about this — what message do they need to sign?
         "registration_message_v1": "depinzcash:register:v1\\n<wallet>\\n<nonce>\\n<rfc3339-ts>\\n<kind>\\n<network>\\n<label>\\n",
+        "registration_message_v2": "depinzcash:register:v2\\n<wallet>\\n<nonce>\\n<rfc3339-ts>\\n<kind>\\n<network>\\n<label>\\n<rpc_endpoint>\\n<p2p_address>\\n<sha256-hex(rpc_auth)>\\n",
+        "rpc_credentials_enabled": cfg.rpc_credentials_key.is_some(),
+        "proof_message_v2": "depinzcash:proof:v2\\n<wallet>\\n<node_id>\\n<height>\\n<block_hash>\\n<rfc3339-ts>\\n<nonce>\\n<uptime_seconds>\\n<peers>\\n<binary_hash>\\n",
+        "proof_v1_cutoff": cfg.proof_v1_cutoff,
         // Until NU7 + ZIP-227 ship Zcash custom assets, rewards are paid in $ZePIN on Solana.
         "rewards_note": "$ZePIN rewards settle on Solana mainnet — migrating to native Zcash when NU7 / ZIP-227 ships"
     }))
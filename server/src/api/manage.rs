@@ -0,0 +1,246 @@
+// Operator-side node management, authenticated by the `auth_token` issued at
+// registration (`Authorization: Bearer <token>`). A token is only good for
+// its own node: any other id in the path answers 401, same as a bad token,
+// so the endpoints don't reveal which node ids exist.
+
+use axum::{
+    extract::{Path, State},
+    http::{header::AUTHORIZATION, HeaderMap},
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    api::nodes::{split_url_credentials, validate_rpc_auth, validate_rpc_endpoint, PublicNode},
+    auth,
+    error::{AppError, AppResult},
+    state::AppState,
+    types::{Node, NodeStatus, RpcAuth},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateNodeRequest {
+    // Absent leaves the field as it is; "" clears it.
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub rpc_endpoint: Option<String>,
+    // Replaces the stored credentials. Moving rpc_endpoint without sending
+    // new ones drops the old: they are never presented to a different URL.
+    #[serde(default)]
+    pub rpc_auth: Option<RpcAuth>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateTokenResponse {
+    pub node_id: Uuid,
+    pub auth_token: String,
+}
+
+pub async fn update_node(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateNodeRequest>,
+) -> AppResult<Json<PublicNode>> {
+    let node = authenticate(&state, &headers, id).await?;
+    let store = state.store();
+
+    let mut label = node.label.clone();
+    if let Some(new) = &req.label {
+        // Same uniqueness rule as registration: (wallet, kind, label).
+        let taken = store
+            .list_nodes_by_wallet(&node.wallet)
+            .await?
+            .iter()
+            .any(|n| {
+                n.id != node.id
+                    && n.status != NodeStatus::Deregistered
+                    && n.kind == node.kind
+                    && n.label.as_deref().unwrap_or_default() == new
+            });
+        if taken {
+            return Err(AppError::conflict(
+                "another node of this wallet and kind already has that label",
+            ));
+        }
+        label = (!new.is_empty()).then(|| new.clone());
+    }
+
+    let mut rpc_endpoint = node.rpc_endpoint.clone();
+    let mut rpc_auth = req.rpc_auth.clone();
+    if let Some(new) = &req.rpc_endpoint {
+        rpc_endpoint = if new.is_empty() {
+            None
+        } else {
+            validate_rpc_endpoint(new)?;
+            match split_url_credentials(new) {
+                Some(_) if rpc_auth.is_some() => {
+                    return Err(AppError::bad_request(
+                        "send rpc credentials in rpc_auth or in the URL, not both",
+                    ))
+                }
+                Some((bare, embedded)) => {
+                    rpc_auth = Some(embedded);
+                    Some(bare)
+                }
+                None => Some(new.clone()),
+            }
+        };
+    }
+    let sealed = match &rpc_auth {
+        None => None,
+        Some(auth) => {
+            if rpc_endpoint.is_none() {
+                return Err(AppError::bad_request("rpc_auth needs an rpc_endpoint"));
+            }
+            validate_rpc_auth(auth)?;
+            let key = state
+                .config()
+                .rpc_credentials_key
+                .as_ref()
+                .ok_or_else(|| AppError::bad_request("this server does not store rpc credentials"))?;
+            Some(key.seal(node.id, auth)?)
+        }
+    };
+
+    let endpoint_changed = rpc_endpoint != node.rpc_endpoint;
+    if endpoint_changed {
+        store.delete_rpc_credential(node.id).await?;
+    }
+    if !store
+        .update_node_details(node.id, label.as_deref(), rpc_endpoint.as_deref())
+        .await?
+    {
+        return Err(AppError::NotFound);
+    }
+    if let Some(sealed) = &sealed {
+        store.put_rpc_credential(node.id, sealed, Utc::now()).await?;
+    }
+    // The old endpoint's failure streak says nothing about the new one; poll
+    // it on the next tick instead of waiting out the backoff.
+    if endpoint_changed {
+        if let Some(mut stats) = store.get_rpc_poll_stats(node.id).await? {
+            stats.consecutive_failures = 0;
+            stats.next_poll_at = Utc::now();
+            store.upsert_rpc_poll_stats(&stats).await?;
+        }
+    }
+
+    tracing::info!(node_id = %node.id, endpoint_changed, "node updated by operator");
+    current(&state, node.id).await
+}
+
+// Stops polls, probes, auto challenges and uptime credit, and refuses proofs,
+// until resumed. A suspended node stays suspended. Idempotent.
+pub async fn pause_node(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> AppResult<Json<PublicNode>> {
+    let node = authenticate(&state, &headers, id).await?;
+    match node.status {
+        NodeStatus::Suspended => return Err(AppError::Forbidden),
+        NodeStatus::Paused => {}
+        from => transition(&state, node.id, from, NodeStatus::Paused).await?,
+    }
+    tracing::info!(node_id = %node.id, "node paused by operator");
+    current(&state, node.id).await
+}
+
+// Back to `stale` if the node ever proved (its next accepted proof makes it
+// active again), else `registered`. Idempotent.
+pub async fn resume_node(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> AppResult<Json<PublicNode>> {
+    let node = authenticate(&state, &headers, id).await?;
+    match node.status {
+        NodeStatus::Suspended => return Err(AppError::Forbidden),
+        NodeStatus::Paused => {
+            let to = if node.last_proof_at.is_some() {
+                NodeStatus::Stale
+            } else {
+                NodeStatus::Registered
+            };
+            transition(&state, node.id, NodeStatus::Paused, to).await?;
+            tracing::info!(node_id = %node.id, "node resumed by operator");
+        }
+        _ => {}
+    }
+    current(&state, node.id).await
+}
+
+// Issues a new token; the presented one stops working immediately.
+pub async fn rotate_token(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> AppResult<Json<RotateTokenResponse>> {
+    let node = authenticate(&state, &headers, id).await?;
+    let presented = bearer(&headers)?;
+    let auth_token = auth::generate_auth_token();
+    if !state
+        .store()
+        .replace_auth_token_hash(node.id, &auth::hash_auth_token(presented), &auth::hash_auth_token(&auth_token))
+        .await?
+    {
+        // Rotated (or deregistered) by a concurrent request.
+        return Err(AppError::Unauthorized);
+    }
+    tracing::info!(node_id = %node.id, "node auth token rotated");
+    Ok(Json(RotateTokenResponse {
+        node_id: node.id,
+        auth_token,
+    }))
+}
+
+// Retires the node for good: the token stops working and it is no longer
+// polled, probed, challenged or credited. Unlike an admin purge the row stays,
+// so its proofs and ledger are kept and the wallet keeps what it earned —
+// dropping those would leave its total below what snapshots already paid.
+pub async fn deregister_node(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> AppResult<Json<Value>> {
+    let node = authenticate(&state, &headers, id).await?;
+    if !state.store().deregister_node(node.id).await? {
+        return Err(AppError::NotFound);
+    }
+    tracing::warn!(node_id = %node.id, wallet = %node.wallet, "node deregistered by operator");
+    Ok(Json(json!({ "deregistered": node.id.to_string(), "ok": true })))
+}
+
+fn bearer(headers: &HeaderMap) -> AppResult<&str> {
+    auth::extract_bearer(headers.get(AUTHORIZATION).and_then(|v| v.to_str().ok()))
+}
+
+async fn authenticate(state: &AppState, headers: &HeaderMap, id: Uuid) -> AppResult<Node> {
+    let token = bearer(headers)?;
+    let node = state
+        .store()
+        .get_node_by_auth_token_hash(&auth::hash_auth_token(token))
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+    if node.id != id {
+        return Err(AppError::Unauthorized);
+    }
+    Ok(node)
+}
+
+async fn transition(state: &AppState, id: Uuid, from: NodeStatus, to: NodeStatus) -> AppResult<()> {
+    if !state.store().transition_node_status(id, from, to).await? {
+        return Err(AppError::conflict("node status changed concurrently — retry"));
+    }
+    Ok(())
+}
+
+async fn current(state: &AppState, id: Uuid) -> AppResult<Json<PublicNode>> {
+    let node = state.store().get_node(id).await?.ok_or(AppError::NotFound)?;
+    Ok(Json(PublicNode::from(&node)))
+}
@@ -1,6 +1,8 @@
 pub mod admin;
 pub mod challenges;
+pub mod delegates;
 pub mod health;
+pub mod manage;
 pub mod nodes;
 pub mod proofs;
 pub mod rewards;
@@ -65,16 +67,29 @@ impl KeyExtractor for FlyClientIpKeyExtractor {
 pub fn router(state: AppState) -> Router {
     let cors = build_cors(&state);
 
-    // Hot mutating endpoints get a per-IP rate limit. Read-only endpoints stay open.
-    let mut posts: Router<AppState> = Router::new()
+    // Hot mutating endpoints and every admin route, reads included (each one
+    // is an admin-key guess), get a per-IP rate limit. Public reads stay open.
+    let mut limited: Router<AppState> = Router::new()
         .route("/api/nodes/register", post(nodes::register))
+        .route("/api/nodes/:id/update", post(manage::update_node))
+        .route("/api/nodes/:id/pause", post(manage::pause_node))
+        .route("/api/nodes/:id/resume", post(manage::resume_node))
+        .route("/api/nodes/:id/rotate-token", post(manage::rotate_token))
+        .route("/api/nodes/:id/deregister", post(manage::deregister_node))
+        .route("/api/nodes/:id/delegates", post(delegates::delegate))
+        .route("/api/nodes/:id/delegates/revoke", post(delegates::revoke))
         .route("/api/proofs/submit", post(proofs::submit))
         .route("/api/challenges/request", post(challenges::request))
         .route("/api/challenges/submit", post(challenges::submit))
         .route("/api/admin/snapshot/publish", post(admin::publish_snapshot))
         .route("/api/admin/nodes/:id/purge", post(admin::purge_node))
         .route("/api/admin/nodes/:id/suspend", post(admin::suspend_node))
-        .route("/api/admin/cleanup", post(admin::cleanup));
+        .route("/api/admin/nodes/:id/points", post(admin::adjust_points))
+        .route("/api/admin/cleanup", post(admin::cleanup))
+        .route("/api/admin/ledger/reconcile", get(admin::reconcile_points))
+        .route("/api/admin/jobs", get(admin::list_jobs))
+        .route("/api/admin/rpc/disagreements", get(admin::rpc_disagreements))
+        .route("/api/admin/nodes/:id/ledger", get(admin::node_ledger));
 
     if state.config().rate_limit_enabled {
         let gov_conf = Arc::new(
@@ -85,7 +100,7 @@ pub fn router(state: AppState) -> Router {
                 .finish()
                 .expect("governor config builds with positive values"),
         );
-        posts = posts.layer(GovernorLayer { config: gov_conf });
+        limited = limited.layer(GovernorLayer { config: gov_conf });
     }
 
     let gets: Router<AppState> = Router::new()
@@ -96,16 +111,23 @@ pub fn router(state: AppState) -> Router {
         .route("/api/nodes/:id", get(nodes::get_by_id))
         .route("/api/nodes/:id/proofs", get(nodes::list_proofs))
         .route("/api/nodes/:id/series", get(nodes::daily_series))
+        .route("/api/nodes/:id/challenges", get(nodes::list_challenges))
+        .route("/api/nodes/:id/p2p-probes", get(nodes::list_p2p_probes))
+        .route("/api/nodes/:id/rpc-poll", get(nodes::rpc_poll_status))
+        .route("/api/nodes/:id/chain-info", get(nodes::chain_info))
+        .route("/api/nodes/:id/delegates", get(delegates::list))
         .route("/api/proofs/recent", get(proofs::list_recent))
         .route("/api/wallet/:wallet/nodes", get(nodes::list_for_wallet))
         .route("/api/wallet/:wallet/stats", get(stats::wallet_stats))
         .route("/api/wallet/:wallet/proofs", get(proofs::list_for_wallet))
         .route("/api/wallet/:wallet/claim/latest", get(rewards::latest_claim))
+        .route("/api/wallet/:wallet/claims", get(rewards::list_claims))
         .route("/api/stats/network", get(stats::network))
         .route("/api/stats/leaderboard", get(stats::leaderboard))
+        .route("/api/stats/upgrades", get(stats::upgrades))
         .route("/api/snapshots/latest", get(rewards::latest_snapshot));
 
-    gets.merge(posts)
+    gets.merge(limited)
         .layer(TraceLayer::new_for_http())
         .layer(cors)
         .with_state(state)
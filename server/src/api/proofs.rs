@@ -7,11 +7,13 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
+    api::delegates::verify_node_signer,
     auth::{self},
     error::{AppError, AppResult},
     rpc::RpcError,
     state::AppState,
-    types::{Node, NodeStatus, Proof, ProofVerdict},
+    types::{ChainInfo, ChainInfoSource, DelegateScope, Node, NodeStatus, Proof, ProofVerdict},
+    upgrades,
 };
 
 #[derive(Debug, Deserialize)]
@@ -31,6 +33,18 @@ pub struct SubmitProofRequest {
     pub uptime_seconds: Option<u64>,
     #[serde(default)]
     pub peers: Option<u32>,
+    // Which canonical proof message the signature covers. Absent = 1 for
+    // relays that predate v2; see auth::proof_message_v2.
+    #[serde(default)]
+    pub message_version: Option<u32>,
+    // Public key of the delegated hot key that signed instead of the wallet;
+    // see api::delegates.
+    #[serde(default)]
+    pub delegate: Option<String>,
+    // The node's getblockchaininfo, for network-upgrade readiness. Only
+    // accepted with message_version 3, which signs it.
+    #[serde(default)]
+    pub chain_info: Option<ChainInfo>,
 }
 
 #[derive(Debug, Serialize)]
@@ -41,6 +55,10 @@ pub struct SubmitProofResponse {
     pub points_awarded: u64,
     pub trusted_tip_height: Option<u64>,
     pub trusted_block_hash: Option<String>,
+    // How the chain_info compared with the trusted quorum, and any upgrade
+    // readiness bonus it earned (on top of points_awarded).
+    pub chain_info_mismatch: Option<String>,
+    pub readiness_bonus: u64,
 }
 
 pub async fn submit(
@@ -71,6 +89,12 @@ pub async fn submit(
     if node.status == NodeStatus::Suspended {
         return Err(AppError::Forbidden);
     }
+    if node.status == NodeStatus::Paused {
+        return Err(AppError::conflict("node is paused — resume it before submitting proofs"));
+    }
+    if node.status == NodeStatus::Deregistered {
+        return Err(AppError::conflict("node is deregistered"));
+    }
     if node.network != state.config().network.as_str() {
         return Err(AppError::bad_request(format!(
             "node registered on network '{}', server is configured for '{}'",
@@ -80,19 +104,69 @@ pub async fn submit(
     }
 
     // ---- signature verification --------------------------------------------
-    let msg = auth::proof_message(
-        &req.wallet,
-        &req.node_id.to_string(),
-        req.claimed_height,
-        &req.claimed_block_hash,
-        &req.proof_timestamp,
-        &req.nonce,
-    );
-    auth::verify_solana_signature(&req.wallet, &msg, &req.signature)
-        .map_err(AppError::from)?;
+    let version = req.message_version.unwrap_or(1);
+    if req.chain_info.is_some() && version < 3 {
+        return Err(AppError::bad_request("chain_info must be signed — use proof message_version 3"));
+    }
+    let msg = match version {
+        1 => {
+            // v1 leaves uptime / peers / binary_hash unsigned. Tolerated until
+            // the configured cutoff so existing relays keep earning.
+            if let Some(cutoff) = state.config().proof_v1_cutoff {
+                if Utc::now() >= cutoff {
+                    return Err(AppError::bad_request(
+                        "proof message v1 is no longer accepted — sign depinzcash:proof:v2",
+                    ));
+                }
+            }
+            auth::proof_message(
+                &req.wallet,
+                &req.node_id.to_string(),
+                req.claimed_height,
+                &req.claimed_block_hash,
+                &req.proof_timestamp,
+                &req.nonce,
+            )
+        }
+        2 => auth::proof_message_v2(
+            &req.wallet,
+            &req.node_id.to_string(),
+            req.claimed_height,
+            &req.claimed_block_hash,
+            &req.proof_timestamp,
+            &req.nonce,
+            req.uptime_seconds,
+            req.peers,
+            req.binary_hash.as_deref(),
+        ),
+        3 => auth::proof_message_v3(
+            &req.wallet,
+            &req.node_id.to_string(),
+            req.claimed_height,
+            &req.claimed_block_hash,
+            &req.proof_timestamp,
+            &req.nonce,
+            req.uptime_seconds,
+            req.peers,
+            req.binary_hash.as_deref(),
+            req.chain_info.as_ref(),
+        ),
+        other => {
+            return Err(AppError::bad_request(format!(
+                "unsupported proof message_version: {other}"
+            )))
+        }
+    };
+    verify_node_signer(&state, &node, DelegateScope::Proofs, req.delegate.as_deref(), &msg, &req.signature).await?;
+    let chain_info = req
+        .chain_info
+        .clone()
+        .map(upgrades::normalize_reported)
+        .transpose()
+        .map_err(|e| AppError::bad_request(format!("invalid chain_info: {e:#}")))?;
 
     // ---- replay prevention -------------------------------------------------
-    if !store.try_use_nonce(&req.nonce, &req.wallet).await? {
+    if !store.try_use_nonce(&req.nonce, &req.wallet, proof_ts).await? {
         return Err(AppError::conflict("nonce already used"));
     }
 
@@ -118,43 +192,14 @@ pub async fn submit(
     } else {
         match rpc.get_block_hash(req.claimed_height).await {
             Ok(hash) => {
-                let expected = normalize_hash(&hash);
-                let claimed = normalize_hash(&req.claimed_block_hash);
-                if expected != claimed {
-                    (
-                        ProofVerdict::Rejected,
-                        Some(format!(
-                            "block hash mismatch at height {}: expected {} got {}",
-                            req.claimed_height, expected, claimed
-                        )),
-                        Some(hash),
-                    )
-                } else if let Some(tip) = trusted_tip {
-                    if req.claimed_height + cfg.max_height_drift < tip {
-                        (
-                            ProofVerdict::Rejected,
-                            Some(format!(
-                                "claimed_height {} too far behind trusted tip {}",
-                                req.claimed_height, tip
-                            )),
-                            Some(hash),
-                        )
-                    } else if req.claimed_height > tip + cfg.max_height_drift {
-                        (
-                            ProofVerdict::Rejected,
-                            Some(format!(
-                                "claimed_height {} ahead of trusted tip {}",
-                                req.claimed_height, tip
-                            )),
-                            Some(hash),
-                        )
-                    } else {
-                        (ProofVerdict::Accepted, None, Some(hash))
-                    }
-                } else {
-                    // Tip not yet cached; height matched hash — accept.
-                    (ProofVerdict::Accepted, None, Some(hash))
-                }
+                let (verdict, reason) = judge_against_quorum(
+                    req.claimed_height,
+                    &req.claimed_block_hash,
+                    &hash,
+                    trusted_tip,
+                    cfg.max_height_drift,
+                );
+                (verdict, reason, Some(hash))
             }
             Err(RpcError::NoQuorum) => (
                 ProofVerdict::Pending,
@@ -192,6 +237,8 @@ pub async fn submit(
         reject_reason: reject_reason.clone(),
         points_awarded,
         received_at: Utc::now(),
+        trusted_tip_height: trusted_tip,
+        finalized_at: None,
     };
     // Race-safe: rely on the UNIQUE (node_id, height, hash) constraint instead of
     // a preflight count. If a concurrent submission already wrote the row, we
@@ -207,6 +254,7 @@ pub async fn submit(
         store
             .apply_proof_acceptance(
                 node.id,
+                proof.id,
                 req.claimed_height,
                 &req.claimed_block_hash,
                 points_awarded,
@@ -215,6 +263,19 @@ pub async fn submit(
             .await?;
     }
 
+    // ---- network-upgrade readiness -----------------------------------------
+    let (mut chain_info_mismatch, mut readiness_bonus) = (None, 0);
+    if let Some(info) = chain_info {
+        let in_sync = verdict == ProofVerdict::Accepted;
+        match upgrades::observe(&state, &node, ChainInfoSource::Relay, info, in_sync).await {
+            Ok((record, paid)) => {
+                chain_info_mismatch = record.mismatch;
+                readiness_bonus = paid;
+            }
+            Err(e) => tracing::warn!(error = ?e, node_id = %node.id, "recording chain info failed"),
+        }
+    }
+
     Ok(Json(SubmitProofResponse {
         proof_id: proof.id,
         verdict: verdict.as_str().to_string(),
@@ -222,6 +283,8 @@ pub async fn submit(
         points_awarded,
         trusted_tip_height: trusted_tip,
         trusted_block_hash: trusted_hash,
+        chain_info_mismatch,
+        readiness_bonus,
     }))
 }
 
@@ -263,7 +326,7 @@ pub async fn list_recent(
     let limit = q.limit.clamp(1, 500);
     let verdict = match q.verdict.as_deref() {
         None | Some("") | Some("all") => None,
-        Some("accepted") | Some("rejected") | Some("pending") => q.verdict.as_deref(),
+        Some("accepted") | Some("rejected") | Some("pending") | Some("reorged") => q.verdict.as_deref(),
         Some(other) => return Err(AppError::bad_request(format!("unknown verdict filter: {other}"))),
     };
     if let Some(ref w) = q.wallet {
@@ -299,13 +362,9 @@ pub async fn list_recent(
 //   peers_bonus = min(peers / 4, 3)                      (0..=3)
 //   points = base * (1 + freshness) + uptime_bonus + peers_bonus
 fn calculate_points(node: &Node, req: &SubmitProofRequest, trusted_tip: Option<u64>) -> u64 {
-    let drift = match trusted_tip {
-        Some(tip) if tip >= req.claimed_height => tip - req.claimed_height,
-        _ => 0,
-    };
     points_from_parts(
         node.kind.reward_tier() as u64,
-        drift,
+        drift_from_tip(trusted_tip, req.claimed_height),
         req.uptime_seconds.unwrap_or(0),
         req.peers.unwrap_or(0),
     )
@@ -321,7 +380,57 @@ pub(crate) fn points_from_parts(tier: u64, drift: u64, uptime_seconds: u64, peer
     tier.saturating_mul(1 + freshness) + uptime_bonus + peers_bonus
 }
 
-fn normalize_hash(s: &str) -> String {
+pub(crate) fn drift_from_tip(trusted_tip: Option<u64>, claimed_height: u64) -> u64 {
+    match trusted_tip {
+        Some(tip) if tip >= claimed_height => tip - claimed_height,
+        _ => 0,
+    }
+}
+
+// Verdict for a proof once the trusted quorum has produced a hash for the
+// claimed height. Shared with the scheduler's pending re-check so a proof
+// resolved late is judged exactly as it would have been on submit.
+pub(crate) fn judge_against_quorum(
+    claimed_height: u64,
+    claimed_block_hash: &str,
+    trusted_hash: &str,
+    trusted_tip: Option<u64>,
+    max_height_drift: u64,
+) -> (ProofVerdict, Option<String>) {
+    let expected = normalize_hash(trusted_hash);
+    let claimed = normalize_hash(claimed_block_hash);
+    if expected != claimed {
+        return (
+            ProofVerdict::Rejected,
+            Some(format!(
+                "block hash mismatch at height {}: expected {} got {}",
+                claimed_height, expected, claimed
+            )),
+        );
+    }
+    match trusted_tip {
+        Some(tip) if claimed_height + max_height_drift < tip => (
+            ProofVerdict::Rejected,
+            Some(format!(
+                "claimed_height {} too far behind trusted tip {}",
+                claimed_height, tip
+            )),
+        ),
+        Some(tip) if claimed_height > tip + max_height_drift => (
+            ProofVerdict::Rejected,
+            Some(format!(
+                "claimed_height {} ahead of trusted tip {}",
+                claimed_height, tip
+            )),
+        ),
+        // In the drift window, or tip not yet cached; height matched hash — accept.
+        _ => (ProofVerdict::Accepted, None),
+    }
+}
+
+// The one normalization every hash / hex comparison uses — proofs, polls,
+// finality, challenges — so they can't disagree on what "the same" means.
+pub(crate) fn normalize_hash(s: &str) -> String {
     // Lowercase first so both "0x" and "0X" prefixes get caught uniformly.
     let lower = s.trim().to_lowercase();
     lower.trim_start_matches("0x").to_string()
@@ -339,6 +448,7 @@ mod tests {
             kind,
             label: None,
             rpc_endpoint: None,
+            p2p_address: None,
             network: "mainnet".into(),
             status: NodeStatus::Active,
             last_height: None,
@@ -362,6 +472,9 @@ mod tests {
             binary_hash: None,
             uptime_seconds: Some(uptime),
             peers: Some(peers),
+            message_version: None,
+            delegate: None,
+            chain_info: None,
         }
     }
 
@@ -427,6 +540,29 @@ mod tests {
         assert_eq!(normalize_hash("   "), "");
     }
 
+    #[test]
+    fn judge_accepts_matching_hash_inside_drift() {
+        let (v, r) = judge_against_quorum(100, "0xAB", "ab", Some(103), 10);
+        assert_eq!(v, ProofVerdict::Accepted);
+        assert!(r.is_none());
+        // No cached tip: hash match alone is enough.
+        let (v, _) = judge_against_quorum(100, "ab", "ab", None, 10);
+        assert_eq!(v, ProofVerdict::Accepted);
+    }
+
+    #[test]
+    fn judge_rejects_mismatch_and_out_of_window() {
+        let (v, r) = judge_against_quorum(100, "aa", "bb", Some(100), 10);
+        assert_eq!(v, ProofVerdict::Rejected);
+        assert!(r.unwrap().contains("mismatch"));
+        let (v, r) = judge_against_quorum(100, "ab", "ab", Some(200), 10);
+        assert_eq!(v, ProofVerdict::Rejected);
+        assert!(r.unwrap().contains("behind"));
+        let (v, r) = judge_against_quorum(200, "ab", "ab", Some(100), 10);
+        assert_eq!(v, ProofVerdict::Rejected);
+        assert!(r.unwrap().contains("ahead"));
+    }
+
     // ---- pure points function: matches the wrapper exactly ----
 
     #[test]
@@ -1,14 +1,15 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::{
     auth,
     error::{AppError, AppResult},
     state::AppState,
+    types::SnapshotLeaf,
 };
 
 #[derive(Debug, Serialize)]
@@ -37,33 +38,77 @@ pub struct ClaimResponse {
     pub cycle: i64,
     pub merkle_root: String,
     pub points: u64,
+    // Cumulative points range this claim pays out: points = points_to - points_from.
+    pub points_from: u64,
+    pub points_to: u64,
     pub leaf_hash: String,
     pub proof: Value,
     pub spl_mint: Option<String>,
     pub solana_cluster: String,
 }
 
+// Snapshots are per-cycle deltas, so the wallet's latest claim is the newest
+// cycle it has a leaf in — not necessarily the newest cycle overall.
 pub async fn latest_claim(
     State(state): State<AppState>,
     Path(wallet): Path<String>,
 ) -> AppResult<Json<ClaimResponse>> {
     auth::decode_solana_pubkey(&wallet).map_err(AppError::from)?;
-    let snap = state.store().latest_snapshot().await?.ok_or(AppError::NotFound)?;
     let leaf = state
         .store()
-        .snapshot_leaf_for_wallet(snap.0, &wallet)
+        .latest_snapshot_leaf_for_wallet(&wallet)
         .await?
         .ok_or(AppError::NotFound)?;
-    let proof: Value = serde_json::from_str(&leaf.2)
+    Ok(Json(claim_response(&state, wallet, leaf)?))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClaimsQuery {
+    #[serde(default)]
+    pub after_cycle: i64,
+    #[serde(default = "default_claims_limit")]
+    pub limit: i64,
+}
+
+fn default_claims_limit() -> i64 {
+    100
+}
+
+// Every cycle's claim for the wallet, oldest first. Each cycle pays its own
+// delta, so one the wallet never claimed stays claimable after newer cycles
+// land. The server doesn't watch the chain: claimed cycles are listed too and
+// the claim program refuses them; `after_cycle` skips the ones a client
+// already knows about.
+pub async fn list_claims(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+    Query(q): Query<ClaimsQuery>,
+) -> AppResult<Json<Vec<ClaimResponse>>> {
+    auth::decode_solana_pubkey(&wallet).map_err(AppError::from)?;
+    let leaves = state
+        .store()
+        .snapshot_leaves_for_wallet(&wallet, q.after_cycle, q.limit.clamp(1, 500))
+        .await?;
+    let claims = leaves
+        .into_iter()
+        .map(|leaf| claim_response(&state, wallet.clone(), leaf))
+        .collect::<AppResult<Vec<_>>>()?;
+    Ok(Json(claims))
+}
+
+fn claim_response(state: &AppState, wallet: String, leaf: SnapshotLeaf) -> AppResult<ClaimResponse> {
+    let proof: Value = serde_json::from_str(&leaf.proof_json)
         .map_err(|e| AppError::Internal(anyhow::Error::new(e)))?;
-    Ok(Json(ClaimResponse {
+    Ok(ClaimResponse {
         wallet,
-        cycle: snap.1,
-        merkle_root: snap.2,
-        points: leaf.0,
-        leaf_hash: leaf.1,
+        cycle: leaf.cycle,
+        merkle_root: leaf.merkle_root,
+        points: leaf.points,
+        points_from: leaf.points_from,
+        points_to: leaf.points_to,
+        leaf_hash: leaf.leaf_hash,
         proof,
         spl_mint: state.config().spl_mint.clone(),
         solana_cluster: state.config().solana_cluster.clone(),
-    }))
+    })
 }
@@ -8,7 +8,8 @@ use crate::{
     auth,
     error::{AppError, AppResult},
     state::AppState,
-    types::{NetworkStats, WalletStats},
+    types::{NetworkStats, UpgradeReadiness, WalletStats},
+    upgrades,
 };
 
 pub async fn network(State(state): State<AppState>) -> AppResult<Json<NetworkStats>> {
@@ -68,3 +69,9 @@ pub async fn wallet_stats(
     let stats = state.store().wallet_stats(&wallet).await?;
     Ok(Json(stats))
 }
+
+// Adoption of the announced network upgrade: how many recently reporting
+// nodes agree with the quorum's consensus view and already list the upgrade.
+pub async fn upgrades(State(state): State<AppState>) -> AppResult<Json<UpgradeReadiness>> {
+    Ok(Json(upgrades::readiness(&state).await?))
+}
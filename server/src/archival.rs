@@ -0,0 +1,197 @@
+// Challenge specs: what to ask a node for each `ChallengeKind`, and how to
+// reduce its answer to something comparable with the trusted quorum's.
+//
+// BlockHash stays shallow (32–256 blocks) and compares the raw hash. The
+// archival kinds go at least `ARCHIVAL_MIN_DEPTH` deep and compare a SHA-256
+// fingerprint of the fields that matter — pruned nodes can't serve old block
+// bodies or transactions, and a node still syncing has no tree state there.
+
+use rand::{seq::SliceRandom, Rng};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    api::{challenges::random_depth, proofs::normalize_hash},
+    config::ZcashNetwork,
+    rpc::{RpcError, ZcashRpcQuorum},
+    types::ChallengeKind,
+};
+
+// Archival targets are drawn from [floor, tip - ARCHIVAL_MIN_DEPTH].
+pub const ARCHIVAL_MIN_DEPTH: u64 = 1_000;
+
+// First height with a Sapling note commitment tree.
+pub fn sapling_activation_height(network: ZcashNetwork) -> u64 {
+    match network {
+        ZcashNetwork::Mainnet => 419_200,
+        ZcashNetwork::Testnet => 280_000,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChallengeSpec {
+    pub kind: ChallengeKind,
+    pub target_height: u64,
+    pub subject: Option<String>,
+    pub method: &'static str,
+    pub params: Value,
+    // `fingerprint(kind, response)` as agreed by the trusted quorum.
+    pub expected: String,
+}
+
+// Ok(None) = the chain isn't tall enough for this kind yet.
+pub async fn build_spec(
+    rpc: &ZcashRpcQuorum,
+    kind: ChallengeKind,
+    tip: u64,
+    network: ZcashNetwork,
+) -> Result<Option<ChallengeSpec>, RpcError> {
+    let (target_height, subject, method, params) = match kind {
+        ChallengeKind::BlockHash => {
+            let h = tip.saturating_sub(random_depth(tip));
+            (h, None, "getblockhash", json!([h]))
+        }
+        ChallengeKind::BlockHeader => {
+            let Some(h) = deep_height(tip, 1) else { return Ok(None) };
+            (h, None, "getblock", json!([h.to_string(), 1]))
+        }
+        ChallengeKind::TreeState => {
+            let Some(h) = deep_height(tip, sapling_activation_height(network)) else {
+                return Ok(None);
+            };
+            (h, None, "z_gettreestate", json!([h.to_string()]))
+        }
+        ChallengeKind::RawTransaction => {
+            let Some(h) = deep_height(tip, 1) else { return Ok(None) };
+            let txids = rpc
+                .quorum_call_projected("getblock", json!([h.to_string(), 1]), |v| {
+                    v.get("tx").filter(|t| t.is_array()).cloned()
+                })
+                .await?;
+            let Some(txid) = txids
+                .as_array()
+                .and_then(|a| a.choose(&mut rand::thread_rng()))
+                .and_then(|t| t.as_str())
+                .map(str::to_string)
+            else {
+                return Err(RpcError::Other(format!("block {h} has no txids")));
+            };
+            (h, Some(txid.clone()), "getrawtransaction", json!([txid, 0]))
+        }
+    };
+    let expected = rpc
+        .quorum_call_projected(method, params.clone(), move |v| fingerprint(kind, v).map(Value::String))
+        .await?;
+    let expected = expected
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| RpcError::Other(format!("expected string fingerprint, got {expected}")))?;
+    Ok(Some(ChallengeSpec {
+        kind,
+        target_height,
+        subject,
+        method,
+        params,
+        expected,
+    }))
+}
+
+// Reduce a node's response to the comparable answer. None = wrong shape.
+pub fn fingerprint(kind: ChallengeKind, v: &Value) -> Option<String> {
+    match kind {
+        ChallengeKind::BlockHash => v.as_str().map(normalize_hash),
+        ChallengeKind::BlockHeader => {
+            // `confirmations` and friends vary per node; only the committed
+            // header fields have to match.
+            let hash = v.get("hash")?.as_str()?;
+            let merkle = v.get("merkleroot")?.as_str()?;
+            let time = v.get("time")?.as_u64()?;
+            Some(sha256_hex(&format!(
+                "{}|{}|{}",
+                normalize_hash(hash),
+                normalize_hash(merkle),
+                time
+            )))
+        }
+        ChallengeKind::RawTransaction => v.as_str().map(|hex| sha256_hex(&normalize_hash(hex))),
+        ChallengeKind::TreeState => {
+            let sapling = final_state(v, "sapling")?;
+            // Pre-NU5 heights have no Orchard tree.
+            let orchard = final_state(v, "orchard").unwrap_or_default();
+            Some(sha256_hex(&format!("{sapling}|{orchard}")))
+        }
+    }
+}
+
+fn final_state(v: &Value, pool: &str) -> Option<String> {
+    v.get(pool)?
+        .get("commitments")?
+        .get("finalState")?
+        .as_str()
+        .map(normalize_hash)
+}
+
+fn deep_height(tip: u64, floor: u64) -> Option<u64> {
+    let ceiling = tip.checked_sub(ARCHIVAL_MIN_DEPTH)?;
+    (ceiling >= floor).then(|| rand::thread_rng().gen_range(floor..=ceiling))
+}
+
+fn sha256_hex(s: &str) -> String {
+    hex::encode(Sha256::digest(s.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deep_height_respects_floor_and_depth() {
+        assert_eq!(deep_height(500, 1), None);
+        assert_eq!(deep_height(1_000 + 419_199, 419_200), None);
+        assert_eq!(deep_height(1_000 + 419_200, 419_200), Some(419_200));
+        for _ in 0..200 {
+            let h = deep_height(3_000_000, 419_200).unwrap();
+            assert!((419_200..=2_999_000).contains(&h));
+        }
+    }
+
+    #[test]
+    fn header_fingerprint_ignores_confirmations() {
+        let a = json!({"hash": "AB", "merkleroot": "cd", "time": 1, "confirmations": 10});
+        let b = json!({"hash": "0xab", "merkleroot": "CD", "time": 1, "confirmations": 99});
+        assert_eq!(
+            fingerprint(ChallengeKind::BlockHeader, &a),
+            fingerprint(ChallengeKind::BlockHeader, &b)
+        );
+        let c = json!({"hash": "ab", "merkleroot": "ce", "time": 1});
+        assert_ne!(
+            fingerprint(ChallengeKind::BlockHeader, &a),
+            fingerprint(ChallengeKind::BlockHeader, &c)
+        );
+        assert_eq!(fingerprint(ChallengeKind::BlockHeader, &json!({"hash": "ab"})), None);
+    }
+
+    #[test]
+    fn tree_state_fingerprint_needs_sapling_and_tolerates_missing_orchard() {
+        let pre_nu5 = json!({"sapling": {"commitments": {"finalState": "01aa"}}});
+        let post_nu5 = json!({
+            "sapling": {"commitments": {"finalState": "01aa"}},
+            "orchard": {"commitments": {"finalState": "02bb"}},
+        });
+        assert!(fingerprint(ChallengeKind::TreeState, &pre_nu5).is_some());
+        assert_ne!(
+            fingerprint(ChallengeKind::TreeState, &pre_nu5),
+            fingerprint(ChallengeKind::TreeState, &post_nu5)
+        );
+        assert_eq!(fingerprint(ChallengeKind::TreeState, &json!({"height": 5})), None);
+    }
+
+    #[test]
+    fn block_hash_fingerprint_is_the_normalized_hash() {
+        assert_eq!(
+            fingerprint(ChallengeKind::BlockHash, &json!("0xABCD")).as_deref(),
+            Some("abcd")
+        );
+        assert_eq!(fingerprint(ChallengeKind::BlockHash, &json!(5)), None);
+    }
+}
@@ -1,12 +1,25 @@
 use chrono::{DateTime, Duration, Utc};
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use rand::RngCore;
+use sha2::{Digest, Sha256};
 
-use crate::error::{AppError, AppResult};
+use crate::{
+    error::{AppError, AppResult},
+    types::{ChainInfo, RpcAuth},
+};
 
 const SOLANA_PUBKEY_LEN: usize = 32;
 const SOLANA_SIG_LEN: usize = 64;
 
+// Solana off-chain message envelope, version 0 (solana-sdk `OffchainMessage`,
+// what `solana sign-offchain-message` and the Ledger app sign):
+//   "\xffsolana offchain" | version u8 | format u8 | length u16 LE | message
+const OFFCHAIN_SIGNING_DOMAIN: &[u8; 16] = b"\xffsolana offchain";
+const OFFCHAIN_HEADER_LEN: usize = OFFCHAIN_SIGNING_DOMAIN.len() + 4;
+// Longest message a Ledger will sign: one packet less the header.
+const OFFCHAIN_MAX_LEN_LEDGER: usize = 1232 - OFFCHAIN_HEADER_LEN;
+const OFFCHAIN_MAX_LEN: usize = u16::MAX as usize - OFFCHAIN_HEADER_LEN;
+
 #[derive(Debug, thiserror::Error)]
 pub enum AuthError {
     #[error("invalid solana wallet: {0}")]
@@ -61,12 +74,49 @@ pub fn decode_signature(s: &str) -> Result<Signature, AuthError> {
     Ok(Signature::from_bytes(&arr))
 }
 
+// Accepts a signature over the raw message, or over the same message wrapped
+// in the off-chain envelope (see `offchain_message`) for wallets that won't
+// sign arbitrary bytes.
 pub fn verify_solana_signature(wallet: &str, message: &[u8], signature_b58: &str) -> Result<(), AuthError> {
     let pubkey = decode_solana_pubkey(wallet)?;
     let sig = decode_signature(signature_b58)?;
-    pubkey
-        .verify(message, &sig)
-        .map_err(|_| AuthError::BadSignature)
+    if pubkey.verify(message, &sig).is_ok() {
+        return Ok(());
+    }
+    match offchain_message(message) {
+        Some(envelope) => pubkey.verify(&envelope, &sig).map_err(|_| AuthError::BadSignature),
+        None => Err(AuthError::BadSignature),
+    }
+}
+
+// `message` wrapped the way solana-sdk's `OffchainMessage::new(0, message)`
+// serializes it. The format byte follows from the content: 0 for printable
+// ASCII, 1 for other UTF-8 up to the Ledger limit, 2 for longer UTF-8. Our
+// messages contain newlines, so they are format 1. None when the message
+// can't be enveloped (empty, not UTF-8, too long).
+pub fn offchain_message(message: &[u8]) -> Option<Vec<u8>> {
+    let utf8 = std::str::from_utf8(message).is_ok();
+    let format: u8 = match message.len() {
+        0 => return None,
+        n if n <= OFFCHAIN_MAX_LEN_LEDGER => {
+            if message.iter().all(|c| (0x20..=0x7e).contains(c)) {
+                0
+            } else if utf8 {
+                1
+            } else {
+                return None;
+            }
+        }
+        n if n <= OFFCHAIN_MAX_LEN && utf8 => 2,
+        _ => return None,
+    };
+    let mut out = Vec::with_capacity(OFFCHAIN_HEADER_LEN + message.len());
+    out.extend_from_slice(OFFCHAIN_SIGNING_DOMAIN);
+    out.push(0);
+    out.push(format);
+    out.extend_from_slice(&(message.len() as u16).to_le_bytes());
+    out.extend_from_slice(message);
+    Some(out)
 }
 
 pub fn check_timestamp(timestamp: DateTime<Utc>, max_skew: std::time::Duration) -> Result<(), AuthError> {
@@ -98,6 +148,13 @@ pub fn generate_auth_token() -> String {
     hex::encode(bytes)
 }
 
+// What the store keeps instead of the token: sha256, hex. The token is 256
+// random bits, so a plain hash (no salt, no stretching) is enough to make a
+// leaked database useless for calling the management API.
+pub fn hash_auth_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
 // Bearer token extraction helper.
 pub fn extract_bearer(header: Option<&str>) -> AppResult<&str> {
     let h = header.ok_or(AppError::Unauthorized)?;
@@ -133,6 +190,47 @@ pub fn registration_message(
     s.into_bytes()
 }
 
+// Canonical message format for node registration, v2. Extends v1 with the
+// fields v1 leaves unsigned — the endpoints the server will dial and the
+// credentials it presents there. Credentials sign as a digest so a wallet's
+// sign-message prompt never shows the secret. Absent fields sign as an empty
+// line.
+//   1: "depinzcash:register:v2"
+//   2-7: same as v1
+//   8: rpc_endpoint
+//   9: p2p_address
+//  10: rpc_auth digest (`rpc_auth_digest`)
+#[allow(clippy::too_many_arguments)]
+pub fn registration_message_v2(
+    wallet: &str,
+    nonce: &str,
+    timestamp: &str,
+    kind: &str,
+    network: &str,
+    label: &str,
+    rpc_endpoint: Option<&str>,
+    p2p_address: Option<&str>,
+    rpc_auth: Option<&RpcAuth>,
+) -> Vec<u8> {
+    let rpc_endpoint = rpc_endpoint.unwrap_or_default();
+    let p2p_address = p2p_address.unwrap_or_default();
+    let rpc_auth = rpc_auth.map(rpc_auth_digest).unwrap_or_default();
+    let s = format!(
+        "depinzcash:register:v2\n{wallet}\n{nonce}\n{timestamp}\n{kind}\n{network}\n{label}\n{rpc_endpoint}\n{p2p_address}\n{rpc_auth}\n"
+    );
+    s.into_bytes()
+}
+
+// Hex SHA-256 of the credential's canonical form: "basic\n<user>\n<pass>" or
+// "bearer\n<token>".
+pub fn rpc_auth_digest(auth: &RpcAuth) -> String {
+    let canonical = match auth {
+        RpcAuth::Basic { username, password } => format!("basic\n{username}\n{password}"),
+        RpcAuth::Bearer { token } => format!("bearer\n{token}"),
+    };
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
 // Canonical message format for proof submissions.
 //   1: "depinzcash:proof:v1"
 //   2: wallet
@@ -155,6 +253,121 @@ pub fn proof_message(
     s.into_bytes()
 }
 
+// Canonical message format for proof submissions, v2. Extends v1 with every
+// operator-reported field that feeds the points formula, so nobody relaying
+// the request can inflate uptime or peers. Absent optional fields sign as an
+// empty line (distinct from an explicit "0").
+//   1: "depinzcash:proof:v2"
+//   2-7: same as v1
+//   8: uptime_seconds
+//   9: peers
+//  10: binary_hash
+#[allow(clippy::too_many_arguments)]
+pub fn proof_message_v2(
+    wallet: &str,
+    node_id: &str,
+    height: u64,
+    block_hash: &str,
+    proof_timestamp: &str,
+    nonce: &str,
+    uptime_seconds: Option<u64>,
+    peers: Option<u32>,
+    binary_hash: Option<&str>,
+) -> Vec<u8> {
+    let uptime = uptime_seconds.map(|u| u.to_string()).unwrap_or_default();
+    let peers = peers.map(|p| p.to_string()).unwrap_or_default();
+    let binary_hash = binary_hash.unwrap_or_default();
+    let s = format!(
+        "depinzcash:proof:v2\n{wallet}\n{node_id}\n{height}\n{block_hash}\n{proof_timestamp}\n{nonce}\n{uptime}\n{peers}\n{binary_hash}\n"
+    );
+    s.into_bytes()
+}
+
+// Canonical message format for proof submissions, v3. Extends v2 with the
+// node's getblockchaininfo, which can earn the upgrade readiness bonus.
+//   1: "depinzcash:proof:v3"
+//   2-10: same as v2
+//  11: chain_info_digest, empty if none was sent
+#[allow(clippy::too_many_arguments)]
+pub fn proof_message_v3(
+    wallet: &str,
+    node_id: &str,
+    height: u64,
+    block_hash: &str,
+    proof_timestamp: &str,
+    nonce: &str,
+    uptime_seconds: Option<u64>,
+    peers: Option<u32>,
+    binary_hash: Option<&str>,
+    chain_info: Option<&ChainInfo>,
+) -> Vec<u8> {
+    let uptime = uptime_seconds.map(|u| u.to_string()).unwrap_or_default();
+    let peers = peers.map(|p| p.to_string()).unwrap_or_default();
+    let binary_hash = binary_hash.unwrap_or_default();
+    let chain_info = chain_info.map(chain_info_digest).unwrap_or_default();
+    let s = format!(
+        "depinzcash:proof:v3\n{wallet}\n{node_id}\n{height}\n{block_hash}\n{proof_timestamp}\n{nonce}\n{uptime}\n{peers}\n{binary_hash}\n{chain_info}\n"
+    );
+    s.into_bytes()
+}
+
+// Hex SHA-256 of "<chain>\n<branch>\n<next branch>\n" followed by one
+// "<branch_id>:<name>:<activation_height>:<status>\n" per upgrade, by
+// activation height then branch id. Fields as sent, no case folding.
+pub fn chain_info_digest(info: &ChainInfo) -> String {
+    let mut upgrades: Vec<_> = info.upgrades.iter().collect();
+    upgrades.sort_by(|a, b| (a.activation_height, &a.branch_id).cmp(&(b.activation_height, &b.branch_id)));
+    let mut canonical = format!(
+        "{}\n{}\n{}\n",
+        info.chain,
+        info.consensus_branch_id,
+        info.next_branch_id.as_deref().unwrap_or_default()
+    );
+    for u in upgrades {
+        canonical.push_str(&format!("{}:{}:{}:{}\n", u.branch_id, u.name, u.activation_height, u.status));
+    }
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+// Canonical message a wallet signs to authorise a relay hot key for one of
+// its nodes. Fields are signed as sent.
+//   1: "depinzcash:delegate:v1"
+//   2: wallet
+//   3: node_id
+//   4: delegate (base58 ed25519 public key)
+//   5: scopes, comma-separated ("proofs,challenges")
+//   6: expires_at (RFC3339)
+//   7: nonce
+//   8: timestamp (RFC3339)
+#[allow(clippy::too_many_arguments)]
+pub fn delegation_message(
+    wallet: &str,
+    node_id: &str,
+    delegate: &str,
+    scopes: &str,
+    expires_at: &str,
+    nonce: &str,
+    timestamp: &str,
+) -> Vec<u8> {
+    let s = format!(
+        "depinzcash:delegate:v1\n{wallet}\n{node_id}\n{delegate}\n{scopes}\n{expires_at}\n{nonce}\n{timestamp}\n"
+    );
+    s.into_bytes()
+}
+
+// Canonical message a wallet signs to revoke a delegate. Only the wallet can;
+// a delegate's signature is never accepted here.
+//   1: "depinzcash:delegate:revoke:v1"
+//   2: wallet
+//   3: node_id
+//   4: delegate
+//   5: nonce
+//   6: timestamp (RFC3339)
+pub fn revocation_message(wallet: &str, node_id: &str, delegate: &str, nonce: &str, timestamp: &str) -> Vec<u8> {
+    let s = format!("depinzcash:delegate:revoke:v1\n{wallet}\n{node_id}\n{delegate}\n{nonce}\n{timestamp}\n");
+    s.into_bytes()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,6 +400,34 @@ mod tests {
         assert!(verify_solana_signature(&wallet, b"hello", &sig_b58).is_err());
     }
 
+    #[test]
+    fn offchain_message_envelope_layout() {
+        let msg = registration_message("w", "n", "ts", "zebra-full", "mainnet", "");
+        let env = offchain_message(&msg).unwrap();
+        assert_eq!(&env[..16], b"\xffsolana offchain");
+        assert_eq!(env[16], 0, "version");
+        assert_eq!(env[17], 1, "newlines make it limited UTF-8");
+        assert_eq!(u16::from_le_bytes([env[18], env[19]]) as usize, msg.len());
+        assert_eq!(&env[20..], &msg[..]);
+
+        assert_eq!(offchain_message(b"hello").unwrap()[17], 0);
+        assert_eq!(offchain_message("x".repeat(2000).as_bytes()).unwrap()[17], 2);
+        assert!(offchain_message(b"").is_none());
+        assert!(offchain_message(&[0xff, 0xfe]).is_none());
+        assert!(offchain_message(&vec![b'x'; 70_000]).is_none());
+    }
+
+    #[test]
+    fn enveloped_signature_verifies_for_the_same_message_only() {
+        let signing = fresh_signing_key();
+        let wallet = bs58::encode(signing.verifying_key().to_bytes()).into_string();
+        let msg = proof_message("w", "node", 1, "abc", "ts", "nonce");
+        let sig = bs58::encode(signing.sign(&offchain_message(&msg).unwrap()).to_bytes()).into_string();
+        assert!(verify_solana_signature(&wallet, &msg, &sig).is_ok());
+        let other = proof_message("w", "node", 2, "abc", "ts", "nonce");
+        assert!(verify_solana_signature(&wallet, &other, &sig).is_err());
+    }
+
     #[test]
     fn nonce_rules() {
         assert!(check_nonce("0123456789abcdef0123").is_ok());
@@ -272,6 +513,15 @@ mod tests {
         assert!(t.chars().all(|c| c.is_ascii_hexdigit()));
     }
 
+    #[test]
+    fn hash_auth_token_is_sha256_hex() {
+        assert_eq!(
+            hash_auth_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_ne!(hash_auth_token("abc"), hash_auth_token("abd"));
+    }
+
     #[test]
     fn generate_auth_token_is_unique() {
         let a = generate_auth_token();
@@ -291,6 +541,53 @@ mod tests {
         assert!(s.ends_with('\n'));
     }
 
+    #[test]
+    fn delegation_and_revocation_messages_are_distinct() {
+        let d = delegation_message("w", "node", "hot", "proofs", "2030-01-01T00:00:00Z", "n", "ts");
+        let lines: Vec<&str> = std::str::from_utf8(&d).unwrap().split('\n').collect();
+        assert_eq!(lines[0], "depinzcash:delegate:v1");
+        assert_eq!(lines[3], "hot");
+        assert_eq!(lines[4], "proofs");
+        assert_ne!(d, delegation_message("w", "node", "hot", "proofs,challenges", "2030-01-01T00:00:00Z", "n", "ts"));
+        let r = revocation_message("w", "node", "hot", "n", "ts");
+        assert!(r.starts_with(b"depinzcash:delegate:revoke:v1\n"));
+        assert_ne!(d, r);
+    }
+
+    #[test]
+    fn registration_message_v2_signs_endpoints_and_credential_digest() {
+        let auth = RpcAuth::Basic {
+            username: "zebra".into(),
+            password: "hunter2".into(),
+        };
+        let msg = registration_message_v2(
+            "w",
+            "n12345678901234567",
+            "ts",
+            "zebra-full",
+            "mainnet",
+            "lbl",
+            Some("https://rpc.example.com"),
+            None,
+            Some(&auth),
+        );
+        let s = std::str::from_utf8(&msg).unwrap();
+        let lines: Vec<&str> = s.split('\n').collect();
+        assert_eq!(lines[0], "depinzcash:register:v2");
+        assert_eq!(lines[7], "https://rpc.example.com");
+        assert_eq!(lines[8], "");
+        assert_eq!(lines[9], rpc_auth_digest(&auth));
+        assert!(!s.contains("hunter2"), "the secret itself is never signed");
+
+        let other = RpcAuth::Basic {
+            username: "zebra".into(),
+            password: "hunter3".into(),
+        };
+        let bearer = RpcAuth::Bearer { token: "zebra\nhunter2".into() };
+        assert_ne!(rpc_auth_digest(&auth), rpc_auth_digest(&other));
+        assert_ne!(rpc_auth_digest(&auth), rpc_auth_digest(&bearer));
+    }
+
     #[test]
     fn proof_message_format_contains_all_fields() {
         let msg = proof_message("wallet", "node-1", 100, "abc123", "2024-01-01T00:00:00Z", "nonce");
@@ -376,6 +673,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn proof_message_v2_format_contains_all_fields() {
+        let msg = proof_message_v2("wallet", "node-1", 100, "abc123", "ts", "nonce", Some(7200), Some(12), Some("bin"));
+        let s = std::str::from_utf8(&msg).unwrap();
+        let lines: Vec<&str> = s.split('\n').collect();
+        assert_eq!(lines[0], "depinzcash:proof:v2");
+        assert_eq!(lines[3], "100");
+        assert_eq!(lines[7], "7200");
+        assert_eq!(lines[8], "12");
+        assert_eq!(lines[9], "bin");
+        assert!(s.ends_with('\n'));
+    }
+
+    #[test]
+    fn proof_message_v2_distinguishes_each_field() {
+        let n = "n12345678901234567";
+        let base = proof_message_v2("w", "node", 100, "h", "ts", n, Some(3600), Some(8), Some("b"));
+        for variant in [
+            proof_message_v2("X", "node", 100, "h", "ts", n, Some(3600), Some(8), Some("b")),
+            proof_message_v2("w", "Y", 100, "h", "ts", n, Some(3600), Some(8), Some("b")),
+            proof_message_v2("w", "node", 101, "h", "ts", n, Some(3600), Some(8), Some("b")),
+            proof_message_v2("w", "node", 100, "H", "ts", n, Some(3600), Some(8), Some("b")),
+            proof_message_v2("w", "node", 100, "h", "Zts", n, Some(3600), Some(8), Some("b")),
+            proof_message_v2("w", "node", 100, "h", "ts", "Z2345678901234567X", Some(3600), Some(8), Some("b")),
+            proof_message_v2("w", "node", 100, "h", "ts", n, Some(86_400), Some(8), Some("b")),
+            proof_message_v2("w", "node", 100, "h", "ts", n, None, Some(8), Some("b")),
+            proof_message_v2("w", "node", 100, "h", "ts", n, Some(3600), Some(32), Some("b")),
+            proof_message_v2("w", "node", 100, "h", "ts", n, Some(3600), None, Some("b")),
+            proof_message_v2("w", "node", 100, "h", "ts", n, Some(3600), Some(8), Some("c")),
+            proof_message_v2("w", "node", 100, "h", "ts", n, Some(3600), Some(8), None),
+        ] {
+            assert_ne!(base, variant);
+        }
+    }
+
+    #[test]
+    fn proof_message_v2_never_equals_v1() {
+        let v1 = proof_message("w", "node", 100, "h", "ts", "n12345678901234567");
+        let v2 = proof_message_v2("w", "node", 100, "h", "ts", "n12345678901234567", None, None, None);
+        assert_ne!(v1, v2);
+    }
+
+    #[test]
+    fn proof_message_v3_signs_the_chain_info() {
+        use crate::types::NetworkUpgrade;
+        let n = "n12345678901234567";
+        let nu5 = NetworkUpgrade {
+            branch_id: "c2d6d0b4".into(),
+            name: "NU5".into(),
+            activation_height: 1_687_104,
+            status: "active".into(),
+        };
+        let nu7 = NetworkUpgrade {
+            branch_id: "77190ad8".into(),
+            name: "NU7".into(),
+            activation_height: 3_500_000,
+            status: "pending".into(),
+        };
+        let info = ChainInfo {
+            chain: "main".into(),
+            consensus_branch_id: "c2d6d0b4".into(),
+            next_branch_id: Some("c2d6d0b4".into()),
+            upgrades: vec![nu5.clone(), nu7.clone()],
+        };
+        let v3 = |info: Option<&ChainInfo>| proof_message_v3("w", "node", 100, "h", "ts", n, None, None, None, info);
+        let base = v3(Some(&info));
+        let reordered = ChainInfo { upgrades: vec![nu7.clone(), nu5.clone()], ..info.clone() };
+        assert_eq!(base, v3(Some(&reordered)), "upgrade order doesn't matter");
+        let lines: Vec<_> = std::str::from_utf8(&base).unwrap().split('\n').collect();
+        assert_eq!(lines[0], "depinzcash:proof:v3");
+        assert_eq!(lines[10], chain_info_digest(&info));
+
+        let without_nu7 = ChainInfo { upgrades: vec![nu5.clone()], ..info.clone() };
+        let moved = ChainInfo {
+            upgrades: vec![nu5, NetworkUpgrade { activation_height: 3_600_000, ..nu7 }],
+            ..info.clone()
+        };
+        let testnet = ChainInfo { chain: "test".into(), ..info.clone() };
+        for variant in [v3(None), v3(Some(&without_nu7)), v3(Some(&moved)), v3(Some(&testnet))] {
+            assert_ne!(base, variant);
+        }
+        assert_ne!(v3(None), proof_message_v2("w", "node", 100, "h", "ts", n, None, None, None));
+    }
+
     #[test]
     fn check_timestamp_zero_skew_accepts_now_only() {
         // With zero skew, only the current instant works — in practice any
@@ -1,6 +1,14 @@
 use std::time::Duration;
 
 use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    credentials::CredentialKey,
+    rpc::{DEFAULT_HASH_CACHE_DEPTH, DEFAULT_HASH_CACHE_SIZE},
+    types::{AnnouncedUpgrade, ChallengeKind},
+    upgrades,
+};
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -8,6 +16,22 @@ pub struct Config {
     pub database_url: String,
     pub trusted_rpcs: Vec<String>,
     pub rpc_timeout: Duration,
+    // Voting weight of each trusted rpc, in TRUSTED_RPCS order. Empty = all 1.
+    pub trusted_rpc_weights: Vec<u32>,
+    // Agreeing weight a quorum answer needs. None = more than half the total.
+    pub rpc_quorum_threshold: Option<u32>,
+    // Consecutive failures that open a trusted rpc's circuit breaker (0 =
+    // never), and how long an open breaker skips it.
+    pub rpc_breaker_failures: u32,
+    pub rpc_breaker_cooldown: Duration,
+    // Quorum-agreed block hashes kept in memory (0 = none), for heights at
+    // least `block_hash_cache_depth` below the trusted tip.
+    pub block_hash_cache_size: usize,
+    pub block_hash_cache_depth: u64,
+    // A trusted rpc that dissents from the others this many times within
+    // `rpc_dissent_window` gets flagged by the `rpc_dissent` job. 0 = never.
+    pub rpc_dissent_alert_threshold: u32,
+    pub rpc_dissent_window: Duration,
     pub admin_api_key: Option<String>,
     pub cors_allowed_origins: Vec<String>,
     pub scheduler_enabled: bool,
@@ -18,6 +42,48 @@ pub struct Config {
     // Exposed RPC poll: server polls each node's public RPC every N seconds and
     // verifies against the trusted quorum. Set to None to disable.
     pub exposed_rpc_poll_interval: Option<Duration>,
+    // At most this many endpoints are polled (or auto-challenged) at once. An
+    // endpoint that keeps failing is skipped for exponentially more ticks, up
+    // to `max_backoff`.
+    pub exposed_rpc_poll_concurrency: usize,
+    pub exposed_rpc_max_backoff: Duration,
+    // Pending re-check: proofs stored as Pending (quorum errored / disagreed)
+    // are re-verified every N seconds in batches. None = disabled. Proofs
+    // still pending after `pending_proof_max_age` are rejected.
+    pub pending_recheck_interval: Option<Duration>,
+    pub pending_recheck_batch: u32,
+    pub pending_proof_max_age: Duration,
+    // Reorg finality: accepted proofs are re-checked against the quorum once
+    // they're this many blocks below the trusted tip. Snapshots only count
+    // confirmed points. 0 = disabled (depth-0 acceptance is final).
+    pub finality_depth: u64,
+    pub finality_check_interval: Duration,
+    // Auto challenges: every N seconds each exposed-RPC node gets a BlockHash
+    // challenge at a random past height, answered by querying its rpc_endpoint.
+    // None = disabled. `challenge_failure_threshold` consecutive failures
+    // suspend the node; 0 = never suspend. Each round draws its kind at
+    // random from `auto_challenge_kinds`.
+    pub auto_challenge_interval: Option<Duration>,
+    pub auto_challenge_kinds: Vec<ChallengeKind>,
+    pub challenge_failure_threshold: u32,
+    // P2P probe: every N seconds each node with a p2p_address gets a
+    // version/verack handshake, at most `p2p_probe_concurrency` at once. A
+    // reachable, in-sync node earns its reward tier per probe if the address
+    // is proven its own, otherwise once. None = disabled.
+    pub p2p_probe_interval: Option<Duration>,
+    pub p2p_probe_concurrency: usize,
+    // Next network upgrade, from ANNOUNCED_UPGRADE ("NU7:77190ad8:3500000").
+    // Until it activates, a node that is in sync and already lists it earns
+    // `upgrade_readiness_bonus` once. None = no readiness tracking.
+    pub announced_upgrade: Option<AnnouncedUpgrade>,
+    pub upgrade_readiness_bonus: u64,
+    // Operator endpoints are resolved before every poll and refused if they
+    // land on a private, loopback, link-local or metadata address. true only
+    // for local dev against nodes on the same machine / LAN.
+    pub allow_private_endpoints: bool,
+    // Seals operator RPC credentials at rest. None = registrations carrying
+    // credentials are refused.
+    pub rpc_credentials_key: Option<CredentialKey>,
     pub max_height_drift: u64,
     pub max_clock_skew: Duration,
     // Rate limiting (per-IP token bucket).
@@ -40,6 +106,10 @@ pub struct Config {
     pub spl_mint: Option<String>,
     pub solana_cluster: String,
     pub network: ZcashNetwork,
+    // Proofs signed with the legacy `depinzcash:proof:v1` message (which
+    // leaves uptime / peers / binary_hash unsigned) are rejected at or after
+    // this instant. None = v1 still accepted.
+    pub proof_v1_cutoff: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -73,6 +143,50 @@ impl Config {
         }
 
         let rpc_timeout = parse_duration("RPC_TIMEOUT", Duration::from_secs(10))?;
+        let trusted_rpc_weights = std::env::var("TRUSTED_RPC_WEIGHTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<u32>().with_context(|| format!("parsing TRUSTED_RPC_WEIGHTS entry: {s}")))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let rpc_quorum_threshold = std::env::var("RPC_QUORUM_THRESHOLD")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<u32>())
+            .transpose()
+            .context("parsing RPC_QUORUM_THRESHOLD")?;
+        check_quorum(&trusted_rpcs, &trusted_rpc_weights, rpc_quorum_threshold)?;
+        let rpc_breaker_failures: u32 = std::env::var("RPC_BREAKER_FAILURES")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse())
+            .transpose()
+            .context("parsing RPC_BREAKER_FAILURES")?
+            .unwrap_or(3);
+        let rpc_breaker_cooldown = parse_duration("RPC_BREAKER_COOLDOWN", Duration::from_secs(60))?;
+        let block_hash_cache_size: usize = std::env::var("BLOCK_HASH_CACHE_SIZE")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse())
+            .transpose()
+            .context("parsing BLOCK_HASH_CACHE_SIZE")?
+            .unwrap_or(DEFAULT_HASH_CACHE_SIZE);
+        let block_hash_cache_depth: u64 = std::env::var("BLOCK_HASH_CACHE_DEPTH")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse())
+            .transpose()
+            .context("parsing BLOCK_HASH_CACHE_DEPTH")?
+            .unwrap_or(DEFAULT_HASH_CACHE_DEPTH);
+        let rpc_dissent_alert_threshold: u32 = std::env::var("RPC_DISSENT_ALERT_THRESHOLD")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse())
+            .transpose()
+            .context("parsing RPC_DISSENT_ALERT_THRESHOLD")?
+            .unwrap_or(5);
+        let rpc_dissent_window = parse_duration("RPC_DISSENT_WINDOW", Duration::from_secs(3600))?;
 
         let admin_api_key = std::env::var("ADMIN_API_KEY").ok().filter(|s| !s.is_empty());
         let cors_allowed_origins = std::env::var("CORS_ALLOWED_ORIGINS")
@@ -105,6 +219,94 @@ impl Config {
             None | Some("") | Some("0" | "off" | "false" | "no" | "disabled") => None,
             Some(other) => Some(parse_duration_str(other)?),
         };
+        let exposed_rpc_poll_concurrency = std::env::var("EXPOSED_RPC_POLL_CONCURRENCY")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .context("parsing EXPOSED_RPC_POLL_CONCURRENCY")?
+            .unwrap_or(16)
+            .max(1);
+        let exposed_rpc_max_backoff = parse_duration("EXPOSED_RPC_MAX_BACKOFF", Duration::from_secs(6 * 60 * 60))?;
+
+        let pending_recheck_interval = match std::env::var("PENDING_RECHECK_INTERVAL").ok().as_deref() {
+            None | Some("") => Some(Duration::from_secs(120)),
+            Some("0" | "off" | "false" | "no" | "disabled") => None,
+            Some(other) => Some(parse_duration_str(other)?),
+        };
+        let pending_recheck_batch: u32 = std::env::var("PENDING_RECHECK_BATCH")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(100);
+        let pending_proof_max_age = parse_duration("PENDING_PROOF_MAX_AGE", Duration::from_secs(24 * 60 * 60))?;
+
+        let finality_depth = std::env::var("FINALITY_DEPTH")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<u64>())
+            .transpose()
+            .context("parsing FINALITY_DEPTH")?
+            .unwrap_or(24);
+        let finality_check_interval = parse_duration("FINALITY_CHECK_INTERVAL", Duration::from_secs(300))?;
+
+        // Off by default, same as EXPOSED_RPC_POLL_INTERVAL — it calls out to
+        // operator endpoints.
+        let auto_challenge_interval = match std::env::var("AUTO_CHALLENGE_INTERVAL").ok().as_deref() {
+            None | Some("") | Some("0" | "off" | "false" | "no" | "disabled") => None,
+            Some(other) => Some(parse_duration_str(other)?),
+        };
+        let auto_challenge_kinds = match std::env::var("AUTO_CHALLENGE_KINDS").ok().as_deref() {
+            None | Some("") => ChallengeKind::ALL.to_vec(),
+            Some(list) => list
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| ChallengeKind::parse(s).with_context(|| format!("unknown AUTO_CHALLENGE_KINDS entry: {s}")))
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        };
+        if auto_challenge_kinds.is_empty() {
+            bail!("AUTO_CHALLENGE_KINDS must list at least one kind");
+        }
+        let challenge_failure_threshold: u32 = std::env::var("CHALLENGE_FAILURE_THRESHOLD")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(3);
+        let p2p_probe_interval = match std::env::var("P2P_PROBE_INTERVAL").ok().as_deref() {
+            None | Some("") | Some("0" | "off" | "false" | "no" | "disabled") => None,
+            Some(other) => Some(parse_duration_str(other)?),
+        };
+        let p2p_probe_concurrency = std::env::var("P2P_PROBE_CONCURRENCY")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<usize>())
+            .transpose()
+            .context("parsing P2P_PROBE_CONCURRENCY")?
+            .unwrap_or(16)
+            .max(1);
+        let announced_upgrade = std::env::var("ANNOUNCED_UPGRADE")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| upgrades::parse_announced(&s))
+            .transpose()
+            .context("parsing ANNOUNCED_UPGRADE")?;
+        let upgrade_readiness_bonus: u64 = std::env::var("UPGRADE_READINESS_BONUS")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse())
+            .transpose()
+            .context("parsing UPGRADE_READINESS_BONUS")?
+            .unwrap_or(100);
+
+        let allow_private_endpoints = matches!(
+            std::env::var("ALLOW_PRIVATE_ENDPOINTS").unwrap_or_default().to_lowercase().as_str(),
+            "true" | "1" | "yes" | "on"
+        );
+        let rpc_credentials_key = std::env::var("RPC_CREDENTIALS_KEY")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| CredentialKey::from_hex(&s))
+            .transpose()
+            .context("parsing RPC_CREDENTIALS_KEY")?;
 
         let max_height_drift = std::env::var("MAX_HEIGHT_DRIFT")
             .ok()
@@ -153,11 +355,28 @@ impl Config {
             other => bail!("unknown ZCASH_NETWORK: {}", other),
         };
 
+        let proof_v1_cutoff = match std::env::var("PROOF_V1_CUTOFF").ok().as_deref() {
+            None | Some("") | Some("0" | "off" | "false" | "no" | "disabled") => None,
+            Some(other) => Some(
+                DateTime::parse_from_rfc3339(other)
+                    .context("parsing PROOF_V1_CUTOFF (expected RFC3339)")?
+                    .with_timezone(&Utc),
+            ),
+        };
+
         Ok(Self {
             bind_addr,
             database_url,
             trusted_rpcs,
             rpc_timeout,
+            trusted_rpc_weights,
+            rpc_quorum_threshold,
+            rpc_breaker_failures,
+            rpc_breaker_cooldown,
+            block_hash_cache_size,
+            block_hash_cache_depth,
+            rpc_dissent_alert_threshold,
+            rpc_dissent_window,
             admin_api_key,
             cors_allowed_origins,
             scheduler_enabled,
@@ -166,6 +385,22 @@ impl Config {
             uptime_reward_interval,
             snapshot_interval,
             exposed_rpc_poll_interval,
+            exposed_rpc_poll_concurrency,
+            exposed_rpc_max_backoff,
+            pending_recheck_interval,
+            pending_recheck_batch,
+            pending_proof_max_age,
+            finality_depth,
+            finality_check_interval,
+            auto_challenge_interval,
+            auto_challenge_kinds,
+            challenge_failure_threshold,
+            p2p_probe_interval,
+            p2p_probe_concurrency,
+            announced_upgrade,
+            upgrade_readiness_bonus,
+            allow_private_endpoints,
+            rpc_credentials_key,
             max_height_drift,
             max_clock_skew,
             rate_limit_enabled,
@@ -178,10 +413,34 @@ impl Config {
             spl_mint,
             solana_cluster,
             network,
+            proof_v1_cutoff,
         })
     }
 }
 
+// Weights must line up with the endpoints, and the threshold must be more
+// than half the total weight (or two disagreeing values could both pass)
+// and reachable.
+fn check_quorum(rpcs: &[String], weights: &[u32], threshold: Option<u32>) -> anyhow::Result<()> {
+    if !weights.is_empty() && weights.len() != rpcs.len() {
+        bail!(
+            "TRUSTED_RPC_WEIGHTS has {} entries for {} TRUSTED_RPCS",
+            weights.len(),
+            rpcs.len()
+        );
+    }
+    if weights.contains(&0) {
+        bail!("TRUSTED_RPC_WEIGHTS entries must be at least 1");
+    }
+    let total: u32 = if weights.is_empty() { rpcs.len() as u32 } else { weights.iter().sum() };
+    if let Some(t) = threshold {
+        if t * 2 <= total || t > total {
+            bail!("RPC_QUORUM_THRESHOLD must be more than half the total weight ({total}) and at most all of it");
+        }
+    }
+    Ok(())
+}
+
 fn parse_duration(var: &str, default: Duration) -> anyhow::Result<Duration> {
     match std::env::var(var) {
         Ok(s) if !s.is_empty() => parse_duration_str(&s).with_context(|| format!("parsing {}", var)),
@@ -214,6 +473,19 @@ fn parse_duration_str(s: &str) -> anyhow::Result<Duration> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn quorum_threshold_must_be_a_reachable_majority() {
+        let rpcs = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        assert!(check_quorum(&rpcs, &[], None).is_ok());
+        assert!(check_quorum(&rpcs, &[], Some(2)).is_ok());
+        assert!(check_quorum(&rpcs, &[], Some(1)).is_err(), "1 of 3 can split");
+        assert!(check_quorum(&rpcs, &[], Some(4)).is_err(), "unreachable");
+        assert!(check_quorum(&rpcs, &[2, 1, 1], Some(3)).is_ok());
+        assert!(check_quorum(&rpcs, &[2, 1, 1], Some(2)).is_err());
+        assert!(check_quorum(&rpcs, &[2, 1], None).is_err(), "one weight per rpc");
+        assert!(check_quorum(&rpcs, &[2, 0, 1], None).is_err());
+    }
+
     #[test]
     fn duration_parsing() {
         assert_eq!(parse_duration_str("30s").unwrap(), Duration::from_secs(30));
@@ -0,0 +1,142 @@
+// Operator RPC credentials at rest.
+//
+// A node registered with `rpc_auth` gets its credentials sealed with
+// AES-256-GCM under RPC_CREDENTIALS_KEY, the node id as associated data — a
+// ciphertext copied onto another node's row won't open. Only the scheduler
+// opens them, right before it dials the endpoint. Losing or changing the key
+// makes every stored credential unreadable; those nodes fail their polls
+// until they re-register.
+//
+// Sealed form: "v1:" + hex(12-byte nonce || ciphertext || tag).
+
+use anyhow::{anyhow, bail, Context};
+use rand::RngCore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use uuid::Uuid;
+
+use crate::{state::AppState, types::RpcAuth};
+
+const SEALED_PREFIX: &str = "v1:";
+
+#[derive(Clone)]
+pub struct CredentialKey([u8; 32]);
+
+// Keeps the key out of `Config`'s Debug output.
+impl std::fmt::Debug for CredentialKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CredentialKey(..)")
+    }
+}
+
+impl CredentialKey {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    // 64 hex characters (`openssl rand -hex 32`).
+    pub fn from_hex(s: &str) -> anyhow::Result<Self> {
+        let bytes = hex::decode(s.trim()).context("key is not hex")?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|b: Vec<u8>| anyhow!("key must be 32 bytes, got {}", b.len()))?;
+        Ok(Self(bytes))
+    }
+
+    pub fn seal(&self, node_id: Uuid, auth: &RpcAuth) -> anyhow::Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let mut buf = serde_json::to_vec(auth)?;
+        self.key()
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(node_id.as_bytes()), &mut buf)
+            .map_err(|_| anyhow!("sealing rpc credentials"))?;
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&buf);
+        Ok(format!("{SEALED_PREFIX}{}", hex::encode(out)))
+    }
+
+    pub fn open(&self, node_id: Uuid, sealed: &str) -> anyhow::Result<RpcAuth> {
+        let hex_part = sealed
+            .strip_prefix(SEALED_PREFIX)
+            .ok_or_else(|| anyhow!("unknown rpc credential format"))?;
+        let bytes = hex::decode(hex_part).context("rpc credential is not hex")?;
+        if bytes.len() < NONCE_LEN {
+            bail!("rpc credential too short");
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("bad nonce"))?;
+        let mut buf = ciphertext.to_vec();
+        let plain = self
+            .key()
+            .open_in_place(nonce, Aad::from(node_id.as_bytes()), &mut buf)
+            .map_err(|_| anyhow!("rpc credential does not open under this key"))?;
+        serde_json::from_slice(plain).context("decoding rpc credential")
+    }
+
+    fn key(&self) -> LessSafeKey {
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &self.0).expect("32-byte AES-256 key"))
+    }
+}
+
+// The node's stored credentials, opened. A row that exists but can't be
+// opened (no key configured, key changed) is an error: polling without the
+// credentials would only earn a 401.
+pub async fn load(state: &AppState, node_id: Uuid) -> anyhow::Result<Option<RpcAuth>> {
+    let Some(sealed) = state.store().get_rpc_credential(node_id).await? else {
+        return Ok(None);
+    };
+    let key = state
+        .config()
+        .rpc_credentials_key
+        .as_ref()
+        .ok_or_else(|| anyhow!("node has stored rpc credentials but RPC_CREDENTIALS_KEY is not set"))?;
+    key.open(node_id, &sealed).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn basic() -> RpcAuth {
+        RpcAuth::Basic {
+            username: "zebra".into(),
+            password: "correct horse".into(),
+        }
+    }
+
+    #[test]
+    fn seal_open_round_trip_without_plaintext() {
+        let key = CredentialKey::new([7; 32]);
+        let id = Uuid::new_v4();
+        let sealed = key.seal(id, &basic()).unwrap();
+        assert!(sealed.starts_with("v1:"));
+        assert!(!sealed.contains("zebra") && !sealed.contains("horse"));
+        assert_eq!(key.open(id, &sealed).unwrap(), basic());
+        // Fresh nonce every time.
+        assert_ne!(sealed, key.seal(id, &basic()).unwrap());
+    }
+
+    #[test]
+    fn open_fails_for_other_node_other_key_or_tampering() {
+        let key = CredentialKey::new([7; 32]);
+        let id = Uuid::new_v4();
+        let sealed = key.seal(id, &RpcAuth::Bearer { token: "t0k".into() }).unwrap();
+        assert!(key.open(Uuid::new_v4(), &sealed).is_err());
+        assert!(CredentialKey::new([8; 32]).open(id, &sealed).is_err());
+        let mut tampered = sealed.clone();
+        let last = if tampered.ends_with('0') { '1' } else { '0' };
+        tampered.pop();
+        tampered.push(last);
+        assert!(key.open(id, &tampered).is_err());
+        assert!(key.open(id, "v0:00").is_err());
+    }
+
+    #[test]
+    fn key_parsing_and_debug() {
+        let hex64 = "11".repeat(32);
+        let key = CredentialKey::from_hex(&hex64).unwrap();
+        assert_eq!(format!("{key:?}"), "CredentialKey(..)");
+        assert!(CredentialKey::from_hex("11").is_err());
+        assert!(CredentialKey::from_hex(&"zz".repeat(32)).is_err());
+        assert_eq!(format!("{:?}", basic()), "Basic(zebra:***)");
+    }
+}
@@ -0,0 +1,256 @@
+// Outbound connections to operator-supplied endpoints.
+//
+// Registration only rejects literal private IPs and the localhost names; a
+// public-looking hostname can still resolve to 10.x, 127.0.0.1 or the cloud
+// metadata address, and DNS can change between registration and a poll. So
+// every poll resolves the host here first, refuses it if any address lands
+// in a forbidden range, and hands back the checked address for the caller to
+// dial directly — no second lookup that could answer differently.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Context};
+use async_trait::async_trait;
+use url::Url;
+
+#[async_trait]
+pub trait Resolver: Send + Sync {
+    async fn lookup(&self, host: &str, port: u16) -> std::io::Result<Vec<SocketAddr>>;
+}
+
+// The OS resolver (getaddrinfo via tokio's blocking pool).
+pub struct SystemResolver;
+
+#[async_trait]
+impl Resolver for SystemResolver {
+    async fn lookup(&self, host: &str, port: u16) -> std::io::Result<Vec<SocketAddr>> {
+        Ok(tokio::net::lookup_host((host, port)).await?.collect())
+    }
+}
+
+#[derive(Clone)]
+pub struct EgressGuard {
+    resolver: Arc<dyn Resolver>,
+    // ALLOW_PRIVATE_ENDPOINTS: local dev and tests, where the "operator" is
+    // a mock on 127.0.0.1. Never set in production.
+    allow_private: bool,
+}
+
+impl EgressGuard {
+    pub fn new(allow_private: bool) -> Self {
+        Self::with_resolver(Arc::new(SystemResolver), allow_private)
+    }
+
+    pub fn with_resolver(resolver: Arc<dyn Resolver>, allow_private: bool) -> Self {
+        Self {
+            resolver,
+            allow_private,
+        }
+    }
+
+    // Resolve `host` and return the address to dial. Rejects the host if
+    // *any* of its addresses is forbidden: a name answering with one public
+    // and one private record would otherwise be a coin flip.
+    pub async fn check_host(&self, host: &str, port: u16) -> anyhow::Result<SocketAddr> {
+        let bare = host.trim_start_matches('[').trim_end_matches(']');
+        let addrs = match bare.parse::<IpAddr>() {
+            Ok(ip) => vec![SocketAddr::new(ip, port)],
+            Err(_) => self
+                .resolver
+                .lookup(bare, port)
+                .await
+                .with_context(|| format!("resolving {bare}"))?,
+        };
+        let Some(&first) = addrs.first() else {
+            bail!("{bare} resolved to no addresses");
+        };
+        if !self.allow_private {
+            if let Some(bad) = addrs.iter().find(|a| is_forbidden_ip(a.ip())) {
+                bail!(
+                    "{bare} resolves to {}, a private/loopback/link-local/metadata address",
+                    bad.ip()
+                );
+            }
+        }
+        Ok(first)
+    }
+
+    // For rpc_endpoint URLs; the port defaults by scheme.
+    pub async fn check_url(&self, endpoint: &str) -> anyhow::Result<SocketAddr> {
+        let url = Url::parse(endpoint).with_context(|| format!("parsing endpoint url: {endpoint}"))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("endpoint {endpoint} has no host"))?;
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| anyhow!("endpoint {endpoint} has no port"))?;
+        self.check_host(host, port).await
+    }
+
+    // For p2p_address, "host:port" with IPv6 literals bracketed.
+    pub async fn check_address(&self, address: &str) -> anyhow::Result<SocketAddr> {
+        let (host, port) = address
+            .rsplit_once(':')
+            .ok_or_else(|| anyhow!("{address} is not host:port"))?;
+        let port = port
+            .parse::<u16>()
+            .with_context(|| format!("invalid port in {address}"))?;
+        self.check_host(host, port).await
+    }
+}
+
+// Addresses the server must never dial on an operator's behalf.
+pub fn is_forbidden_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_forbidden_v4(v4),
+        IpAddr::V6(v6) => {
+            // ::ffff:a.b.c.d and the deprecated ::a.b.c.d reach the v4 host.
+            if let Some(v4) = v6.to_ipv4() {
+                return is_forbidden_v4(v4) || v6.is_loopback() || v6.is_unspecified();
+            }
+            let seg = v6.segments();
+            // 64:ff9b::/96 NAT64 — judge the embedded v4 address.
+            if seg[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let o = v6.octets();
+                return is_forbidden_v4(Ipv4Addr::new(o[12], o[13], o[14], o[15]));
+            }
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // fc00::/7 unique-local: Fly's 6PN (fdaa::/16) and AWS's
+                // fd00:ec2::254 metadata endpoint live here.
+                || seg[0] & 0xfe00 == 0xfc00
+                // fe80::/10 link-local.
+                || seg[0] & 0xffc0 == 0xfe80
+        }
+    }
+}
+
+fn is_forbidden_v4(v4: Ipv4Addr) -> bool {
+    let o = v4.octets();
+    v4.is_loopback()
+        || v4.is_private()
+        // 169.254/16, including the 169.254.169.254 metadata endpoint.
+        || v4.is_link_local()
+        || v4.is_unspecified()
+        || v4.is_broadcast()
+        || v4.is_multicast()
+        // 0/8 "this network".
+        || o[0] == 0
+        // 100.64/10 carrier-grade NAT (Alibaba's metadata is 100.100.100.200).
+        || (o[0] == 100 && o[1] & 0xc0 == 64)
+        // 192.0.0/24 IETF protocol assignments (Oracle's metadata is 192.0.0.192).
+        || (o[0] == 192 && o[1] == 0 && o[2] == 0)
+        // 198.18/15 benchmarking.
+        || (o[0] == 198 && o[1] & 0xfe == 18)
+        // 240/4 reserved.
+        || o[0] >= 240
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Stub(Vec<IpAddr>);
+
+    #[async_trait]
+    impl Resolver for Stub {
+        async fn lookup(&self, _host: &str, port: u16) -> std::io::Result<Vec<SocketAddr>> {
+            Ok(self.0.iter().map(|ip| SocketAddr::new(*ip, port)).collect())
+        }
+    }
+
+    fn guard(ips: &[&str]) -> EgressGuard {
+        let ips = ips.iter().map(|s| s.parse().unwrap()).collect();
+        EgressGuard::with_resolver(Arc::new(Stub(ips)), false)
+    }
+
+    fn forbidden(s: &str) -> bool {
+        is_forbidden_ip(s.parse().unwrap())
+    }
+
+    #[test]
+    fn forbidden_v4_ranges() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "0.1.2.3",
+            "255.255.255.255",
+            "224.0.0.1",
+            "100.64.0.1",
+            "100.100.100.200",
+            "192.0.0.192",
+            "198.18.0.1",
+            "240.0.0.1",
+        ] {
+            assert!(forbidden(ip), "{ip}");
+        }
+        for ip in ["1.1.1.1", "8.8.8.8", "100.128.0.1", "172.32.0.1", "198.20.0.1"] {
+            assert!(!forbidden(ip), "{ip}");
+        }
+    }
+
+    #[test]
+    fn forbidden_v6_ranges_and_embedded_v4() {
+        for ip in [
+            "::1",
+            "::",
+            "fd00:ec2::254",
+            "fdaa::3",
+            "fe80::1",
+            "ff02::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+            "64:ff9b::a00:1",
+        ] {
+            assert!(forbidden(ip), "{ip}");
+        }
+        for ip in ["2606:4700::1111", "::ffff:1.1.1.1", "64:ff9b::808:808"] {
+            assert!(!forbidden(ip), "{ip}");
+        }
+    }
+
+    #[tokio::test]
+    async fn check_host_pins_the_resolved_address() {
+        let addr = guard(&["203.0.113.7"]).check_host("rpc.example.com", 8232).await.unwrap();
+        assert_eq!(addr, "203.0.113.7:8232".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn check_host_rejects_if_any_address_is_private() {
+        let err = guard(&["203.0.113.7", "10.0.0.5"])
+            .check_host("rpc.example.com", 8232)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("10.0.0.5"), "{err}");
+        assert!(guard(&[]).check_host("rpc.example.com", 8232).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn literals_skip_the_resolver_but_not_the_check() {
+        // The stub would answer public; a literal must not consult it.
+        let g = guard(&["203.0.113.7"]);
+        assert!(g.check_url("http://169.254.169.254/latest").await.is_err());
+        assert!(g.check_address("[::1]:8233").await.is_err());
+        assert_eq!(
+            g.check_url("https://198.51.100.1").await.unwrap(),
+            "198.51.100.1:443".parse().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn allow_private_lets_loopback_through() {
+        let g = EgressGuard::with_resolver(Arc::new(Stub(vec!["127.0.0.1".parse().unwrap()])), true);
+        assert_eq!(
+            g.check_address("node.test:8233").await.unwrap(),
+            "127.0.0.1:8233".parse().unwrap()
+        );
+    }
+}
@@ -0,0 +1,178 @@
+// Scheduler job leases and run schedule.
+//
+// Several server instances can share one database. Every scheduler job that
+// writes shared state holds a row in `job_leases` while it runs, so the job
+// runs on exactly one instance at a time. Each instance tries to take or
+// renew the lease every `ttl / 3` in the background. The holder keeps it for
+// as long as it stays up, and another instance takes over within one `ttl`
+// once the holder stops renewing (crash, deploy, lost database). The check
+// before each run is a renewal too, so a holder that lost the lease while
+// stalled finds out before doing any work.
+//
+// Lease times come from each instance's clock, so instances are assumed to
+// agree to well within a `ttl`.
+//
+// `ScheduledJob` adds the schedule on top: when each job last ran and when it
+// is next due live in `job_runs`, so a restart picks the cadence up where it
+// left off and an overdue job runs straight away.
+
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use tokio::{task::JoinHandle, time::interval};
+
+use crate::state::AppState;
+
+pub const LEASE_TTL: Duration = Duration::from_secs(90);
+
+// How long to wait before checking again when a due job is leased elsewhere
+// or the schedule couldn't be read.
+const RECHECK: Duration = Duration::from_secs(30);
+
+#[derive(Clone)]
+pub struct JobLease {
+    state: AppState,
+    job: &'static str,
+    ttl: Duration,
+}
+
+impl JobLease {
+    pub fn new(state: &AppState, job: &'static str, ttl: Duration) -> Self {
+        Self {
+            state: state.clone(),
+            job,
+            ttl,
+        }
+    }
+
+    pub fn job(&self) -> &'static str {
+        self.job
+    }
+
+    // Takes or renews the lease. False if another instance holds it; a
+    // database error counts as not holding it.
+    pub async fn acquire(&self) -> bool {
+        let now = Utc::now();
+        let until = now + chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::seconds(90));
+        match self
+            .state
+            .store()
+            .acquire_lease(self.job, self.state.instance_id(), now, until)
+            .await
+        {
+            Ok(held) => held,
+            Err(e) => {
+                tracing::warn!(error = ?e, job = self.job, "job lease acquire failed");
+                false
+            }
+        }
+    }
+
+    // Renews in the background so a job whose interval is longer than the
+    // ttl doesn't lose its lease between runs. Runs until aborted.
+    pub fn keep_alive(&self) -> JoinHandle<()> {
+        let lease = self.clone();
+        tokio::spawn(async move {
+            let mut tick = interval((lease.ttl / 3).max(Duration::from_millis(10)));
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut held = false;
+            loop {
+                tick.tick().await;
+                let now_held = lease.acquire().await;
+                if now_held != held {
+                    tracing::info!(job = lease.job, held = now_held, "job lease changed hands");
+                    held = now_held;
+                }
+            }
+        })
+    }
+}
+
+pub struct ScheduledJob {
+    lease: JobLease,
+    every: Duration,
+    first_run: Duration,
+    started_at: Option<DateTime<Utc>>,
+}
+
+impl ScheduledJob {
+    // Runs every `every` under the job's lease, which it starts renewing. A
+    // job with no history is first due `first_run` from now.
+    pub fn start(state: &AppState, job: &'static str, every: Duration, first_run: Duration) -> Self {
+        let lease = JobLease::new(state, job, LEASE_TTL);
+        lease.keep_alive();
+        Self {
+            lease,
+            every,
+            first_run,
+            started_at: None,
+        }
+    }
+
+    pub fn job(&self) -> &'static str {
+        self.lease.job()
+    }
+
+    // Waits until the job is due and this instance holds its lease.
+    pub async fn due(&mut self) {
+        loop {
+            let wait = match self.until_due().await {
+                Ok(Some(wait)) => wait,
+                Ok(None) if self.lease.acquire().await => {
+                    self.started_at = Some(Utc::now());
+                    return;
+                }
+                Ok(None) => RECHECK.min(self.every),
+                Err(e) => {
+                    tracing::warn!(error = ?e, job = self.job(), "reading job schedule failed");
+                    RECHECK.min(self.every)
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    // Records the run `due` released and schedules the next one `every` after
+    // it started. Errors are stored with their cause chain.
+    pub async fn finished<E: std::fmt::Display>(&mut self, error: Option<&E>) {
+        let started_at = self.started_at.take().unwrap_or_else(Utc::now);
+        let next = started_at + self.every_chrono();
+        let error = error.map(|e| format!("{e:#}"));
+        if let Err(e) = self
+            .lease
+            .state
+            .store()
+            .finish_job_run(self.job(), started_at, Utc::now(), next, error.as_deref())
+            .await
+        {
+            tracing::warn!(error = ?e, job = self.job(), "recording job run failed");
+        }
+    }
+
+    // None once the job is due. The next run is capped at one interval after
+    // the last, so shortening the interval takes effect on restart.
+    async fn until_due(&self) -> anyhow::Result<Option<Duration>> {
+        let store = self.lease.state.store();
+        let run = match store.get_job_run(self.job()).await? {
+            Some(run) => run,
+            None => {
+                let first = Utc::now() + chrono::Duration::from_std(self.first_run)?;
+                store.schedule_job(self.job(), first).await?;
+                store
+                    .get_job_run(self.job())
+                    .await?
+                    .context("job schedule row missing after insert")?
+            }
+        };
+        let next = match run.last_run_at {
+            Some(last) => run.next_run_at.min(last + self.every_chrono()),
+            None => run.next_run_at,
+        };
+        Ok((next - Utc::now()).to_std().ok().filter(|d| !d.is_zero()))
+    }
+
+    fn every_chrono(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.every).unwrap_or(chrono::Duration::days(365))
+    }
+}
@@ -1,10 +1,17 @@
 pub mod api;
+pub mod archival;
 pub mod auth;
 pub mod config;
+pub mod credentials;
+pub mod egress;
 pub mod error;
+pub mod jobs;
+pub mod lightwalletd;
 pub mod merkle;
+pub mod p2p;
 pub mod rpc;
 pub mod scheduler;
 pub mod state;
 pub mod store;
 pub mod types;
+pub mod upgrades;
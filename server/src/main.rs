@@ -1,7 +1,14 @@
 use std::net::SocketAddr;
 
 use anyhow::Context;
-use depinzcash_server::{api, config::Config, rpc::ZcashRpcQuorum, scheduler, state::AppState, store::SqliteStore};
+use depinzcash_server::{
+    api,
+    config::Config,
+    rpc::{QuorumPolicy, ZcashRpcQuorum},
+    scheduler,
+    state::AppState,
+    store,
+};
 use tracing_subscriber::{prelude::*, EnvFilter};
 
 #[tokio::main]
@@ -12,14 +19,21 @@ async fn main() -> anyhow::Result<()> {
     let config = Config::from_env().context("loading config from env")?;
     tracing::info!(?config.bind_addr, db = %config.database_url, "starting depinzcash-server");
 
-    let store = SqliteStore::connect(&config.database_url)
+    let store = store::connect(&config.database_url)
         .await
-        .context("connecting to sqlite store")?;
+        .context("connecting to store")?;
     store.migrate().await.context("running migrations")?;
 
-    let quorum = ZcashRpcQuorum::new(config.trusted_rpcs.clone(), config.rpc_timeout);
+    let quorum = ZcashRpcQuorum::new(config.trusted_rpcs.clone(), config.rpc_timeout)
+        .with_weights(config.trusted_rpc_weights.clone())
+        .with_policy(QuorumPolicy {
+            threshold: config.rpc_quorum_threshold,
+            breaker_failures: config.rpc_breaker_failures,
+            breaker_cooldown: config.rpc_breaker_cooldown,
+        })
+        .with_hash_cache(config.block_hash_cache_size, config.block_hash_cache_depth);
 
-    let state = AppState::new(config.clone(), store, quorum);
+    let state = AppState::with_store(config.clone(), store, quorum);
 
     if config.scheduler_enabled {
         scheduler::spawn(state.clone());
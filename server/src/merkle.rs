@@ -2,17 +2,24 @@ use anyhow::Context;
 use chrono::Utc;
 use sha2::{Digest, Sha256};
 
-use crate::state::AppState;
+use crate::{state::AppState, types::NewSnapshotLeaf};
 
 // Snapshot layout for the $ZePIN claim distributor on Solana — deliberately
 // Solana-friendly so the on-chain program can verify a Merkle proof without
 // any tricks:
 //
-//   leaf  = sha256( base58_wallet || u64_le(points) )
+//   leaf  = sha256( base58_wallet || u64_le(points) )    (points earned this cycle only)
 //   node  = sha256( sort(left, right) )                  (sorted-pair so proofs work without index)
 //
 // All hashes are 32 bytes, hex-encoded for storage / JSON.
 
+struct DeltaLeaf {
+    wallet: String,
+    points: u64,
+    from: u64,
+    to: u64,
+}
+
 #[derive(Debug)]
 pub struct PublishResult {
     pub cycle: i64,
@@ -23,21 +30,45 @@ pub struct PublishResult {
 
 pub async fn publish_snapshot(state: &AppState) -> anyhow::Result<PublishResult> {
     let cfg = state.config();
-    let mut leaves = state
+    // Without a quorum nothing can ever be confirmed, so finality gating would
+    // just zero out every proof-earned point.
+    let finalized_only = cfg.finality_depth > 0 && state.rpc().is_configured();
+    let totals = state
         .store()
-        .total_points_per_wallet(cfg.network.as_str())
+        .total_points_per_wallet(cfg.network.as_str(), finalized_only)
         .await
         .context("loading per-wallet point totals")?;
-    leaves.sort_by(|a, b| a.0.cmp(&b.0));
+    let baselines = state
+        .store()
+        .snapshot_baselines()
+        .await
+        .context("loading snapshot baselines")?;
+
+    // Each cycle pays only what was earned since the wallet's last snapshot.
+    // A total that dropped below its baseline (clawback after publish) pays
+    // nothing until it climbs back over.
+    let mut leaves: Vec<DeltaLeaf> = totals
+        .into_iter()
+        .filter_map(|(wallet, total)| {
+            let from = baselines.get(&wallet).copied().unwrap_or(0);
+            (total > from).then(|| DeltaLeaf {
+                wallet,
+                points: total - from,
+                from,
+                to: total,
+            })
+        })
+        .collect();
+    leaves.sort_by(|a, b| a.wallet.cmp(&b.wallet));
 
     if leaves.is_empty() {
         anyhow::bail!("no eligible wallets — cannot publish empty snapshot");
     }
 
-    let total_points: u64 = leaves.iter().map(|(_, p)| *p).sum();
+    let total_points: u64 = leaves.iter().map(|l| l.points).sum();
     let leaf_hashes: Vec<[u8; 32]> = leaves
         .iter()
-        .map(|(wallet, pts)| hash_leaf(wallet, *pts))
+        .map(|l| hash_leaf(&l.wallet, l.points))
         .collect();
 
     let tree = build_tree(&leaf_hashes);
@@ -49,29 +80,29 @@ pub async fn publish_snapshot(state: &AppState) -> anyhow::Result<PublishResult>
         None => 0,
     };
     let cycle = last_cycle + 1;
-    let snapshot_id = state
+    let stored = leaves
+        .iter()
+        .enumerate()
+        .map(|(idx, leaf)| {
+            let proof_json = serde_json::json!({
+                "siblings": tree.proof_for(idx).iter().map(hex::encode).collect::<Vec<_>>(),
+                "leaf_index": idx,
+            });
+            Ok(NewSnapshotLeaf {
+                wallet: leaf.wallet.clone(),
+                points: leaf.points,
+                points_from: leaf.from,
+                points_to: leaf.to,
+                leaf_hash: hex::encode(leaf_hashes[idx]),
+                proof_json: serde_json::to_string(&proof_json)?,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    state
         .store()
-        .insert_snapshot(cycle, &root_hex, total_points, cfg.spl_mint.as_deref())
-        .await?;
-
-    for (idx, (wallet, points)) in leaves.iter().enumerate() {
-        let leaf_hash = leaf_hashes[idx];
-        let proof = tree.proof_for(idx);
-        let proof_json = serde_json::json!({
-            "siblings": proof.iter().map(hex::encode).collect::<Vec<_>>(),
-            "leaf_index": idx,
-        });
-        state
-            .store()
-            .insert_snapshot_leaf(
-                snapshot_id,
-                wallet,
-                *points,
-                &hex::encode(leaf_hash),
-                &serde_json::to_string(&proof_json)?,
-            )
-            .await?;
-    }
+        .insert_snapshot_with_leaves(cycle, &root_hex, total_points, cfg.spl_mint.as_deref(), &stored)
+        .await
+        .context("storing snapshot")?;
 
     tracing::info!(
         cycle,
@@ -147,7 +178,7 @@ fn build_tree(leaves: &[[u8; 32]]) -> MerkleTree {
         if last.len() == 1 {
             break;
         }
-        let mut next = Vec::with_capacity((last.len() + 1) / 2);
+        let mut next = Vec::with_capacity(last.len().div_ceil(2));
         let mut i = 0;
         while i < last.len() {
             if i + 1 < last.len() {
@@ -353,9 +384,9 @@ mod tests {
     fn duplicate_leaves_still_verify() {
         let leaves = vec![h(1), h(1), h(1), h(1)];
         let tree = build_tree(&leaves);
-        for i in 0..leaves.len() {
+        for (i, leaf) in leaves.iter().enumerate() {
             let proof = tree.proof_for(i);
-            assert!(verify_proof(&leaves[i], &proof, &tree.root));
+            assert!(verify_proof(leaf, &proof, &tree.root));
         }
     }
 
@@ -0,0 +1,296 @@
+// Zcash P2P reachability probe.
+//
+// A full node only helps the network if it takes inbound peers, which RPC
+// polling can't tell us. The probe dials the node's advertised p2p_address
+// and runs the start of the peer protocol: send `version`, read the peer's
+// `version`, answer `verack`, wait for its `verack`. Then it hangs up. We
+// never ask for blocks or addresses.
+//
+// Wire format is Bitcoin's: a 24-byte header (network magic, NUL-padded
+// command, LE payload length, first 4 bytes of SHA-256d(payload)) followed by
+// the payload.
+
+use std::{net::SocketAddr, time::Duration};
+
+use anyhow::{anyhow, bail, Context};
+use sha2::{Digest, Sha256};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::config::ZcashNetwork;
+
+// NU6.1. Peers drop anyone advertising less than the current upgrade's version.
+pub const PROTOCOL_VERSION: u32 = 170_140;
+// Zcash caps protocol messages at 2 MiB.
+pub const MAX_PAYLOAD: usize = 2 * 1024 * 1024;
+// Same limit as zcashd's MAX_SUBVERSION_LENGTH.
+pub const MAX_USER_AGENT: usize = 256;
+const HEADER_LEN: usize = 24;
+// Peers may interleave sendaddrv2/ping/etc. with the handshake; give up after
+// this many messages without a version + verack.
+const MAX_HANDSHAKE_MESSAGES: usize = 16;
+
+pub fn magic(network: ZcashNetwork) -> [u8; 4] {
+    match network {
+        ZcashNetwork::Mainnet => [0x24, 0xe9, 0x27, 0x64],
+        ZcashNetwork::Testnet => [0xfa, 0x1a, 0xf9, 0xbf],
+    }
+}
+
+pub fn checksum(payload: &[u8]) -> [u8; 4] {
+    let once = Sha256::digest(payload);
+    let twice = Sha256::digest(once);
+    [twice[0], twice[1], twice[2], twice[3]]
+}
+
+pub fn encode_message(magic: [u8; 4], command: &str, payload: &[u8]) -> Vec<u8> {
+    let mut cmd = [0u8; 12];
+    cmd[..command.len()].copy_from_slice(command.as_bytes());
+    let mut out = Vec::with_capacity(HEADER_LEN + payload.len());
+    out.extend_from_slice(&magic);
+    out.extend_from_slice(&cmd);
+    out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    out.extend_from_slice(&checksum(payload));
+    out.extend_from_slice(payload);
+    out
+}
+
+pub async fn write_message<W: AsyncWrite + Unpin>(
+    w: &mut W,
+    magic: [u8; 4],
+    command: &str,
+    payload: &[u8],
+) -> anyhow::Result<()> {
+    w.write_all(&encode_message(magic, command, payload)).await?;
+    w.flush().await?;
+    Ok(())
+}
+
+// Returns (command, payload).
+pub async fn read_message<R: AsyncRead + Unpin>(
+    r: &mut R,
+    magic: [u8; 4],
+) -> anyhow::Result<(String, Vec<u8>)> {
+    let mut header = [0u8; HEADER_LEN];
+    r.read_exact(&mut header).await.context("reading p2p header")?;
+    if header[..4] != magic {
+        bail!("unexpected network magic {}", hex::encode(&header[..4]));
+    }
+    let command = std::str::from_utf8(&header[4..16])
+        .map_err(|_| anyhow!("non-ascii p2p command"))?
+        .trim_end_matches('\0')
+        .to_string();
+    let len = u32::from_le_bytes(header[16..20].try_into().unwrap()) as usize;
+    if len > MAX_PAYLOAD {
+        bail!("{command} payload of {len} bytes exceeds {MAX_PAYLOAD}");
+    }
+    let mut payload = vec![0u8; len];
+    r.read_exact(&mut payload)
+        .await
+        .with_context(|| format!("reading {command} payload"))?;
+    if header[20..24] != checksum(&payload) {
+        bail!("{command} checksum mismatch");
+    }
+    Ok((command, payload))
+}
+
+// The fields of a `version` message we care about. Both network addresses
+// are skipped on decode and sent zeroed — the peer learns ours from the
+// socket anyway.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionMessage {
+    pub version: u32,
+    pub services: u64,
+    pub timestamp: i64,
+    pub nonce: u64,
+    pub user_agent: String,
+    pub start_height: u32,
+    pub relay: bool,
+}
+
+impl VersionMessage {
+    // What the probe announces: no services, height 0, no tx relay.
+    pub fn probe() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            services: 0,
+            timestamp: chrono::Utc::now().timestamp(),
+            nonce: rand::random(),
+            user_agent: format!("/DePINZcash-probe:{}/", env!("CARGO_PKG_VERSION")),
+            start_height: 0,
+            relay: false,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(86 + self.user_agent.len());
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.extend_from_slice(&self.services.to_le_bytes());
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        // addr_recv, addr_from: services(8) + ipv6(16) + port(2) each.
+        out.extend_from_slice(&[0u8; 52]);
+        out.extend_from_slice(&self.nonce.to_le_bytes());
+        write_compact_size(&mut out, self.user_agent.len() as u64);
+        out.extend_from_slice(self.user_agent.as_bytes());
+        out.extend_from_slice(&self.start_height.to_le_bytes());
+        out.push(self.relay as u8);
+        out
+    }
+
+    pub fn decode(payload: &[u8]) -> anyhow::Result<Self> {
+        let mut r = Cursor { buf: payload, pos: 0 };
+        let version = u32::from_le_bytes(r.take(4)?.try_into().unwrap());
+        let services = u64::from_le_bytes(r.take(8)?.try_into().unwrap());
+        let timestamp = i64::from_le_bytes(r.take(8)?.try_into().unwrap());
+        r.take(52)?;
+        let nonce = u64::from_le_bytes(r.take(8)?.try_into().unwrap());
+        let ua_len = r.compact_size()? as usize;
+        if ua_len > MAX_USER_AGENT {
+            bail!("user agent of {ua_len} bytes exceeds {MAX_USER_AGENT}");
+        }
+        let user_agent = String::from_utf8_lossy(r.take(ua_len)?).into_owned();
+        let start_height = u32::from_le_bytes(r.take(4)?.try_into().unwrap());
+        // Optional since BIP 37; absent means "relay".
+        let relay = r.take(1).map(|b| b[0] != 0).unwrap_or(true);
+        Ok(Self {
+            version,
+            services,
+            timestamp,
+            nonce,
+            user_agent,
+            start_height,
+            relay,
+        })
+    }
+}
+
+// Dial `addr` (the vetted resolution of the node's "host:port") and complete
+// version/verack. Returns what the peer said in its `version`.
+pub async fn handshake(
+    addr: SocketAddr,
+    network: ZcashNetwork,
+    timeout: Duration,
+) -> anyhow::Result<VersionMessage> {
+    tokio::time::timeout(timeout, handshake_inner(addr, network))
+        .await
+        .map_err(|_| anyhow!("p2p handshake with {addr} timed out after {timeout:?}"))?
+}
+
+async fn handshake_inner(addr: SocketAddr, network: ZcashNetwork) -> anyhow::Result<VersionMessage> {
+    let magic = magic(network);
+    let mut stream = TcpStream::connect(addr)
+        .await
+        .with_context(|| format!("connecting to {addr}"))?;
+    let ours = VersionMessage::probe();
+    write_message(&mut stream, magic, "version", &ours.encode()).await?;
+
+    let mut peer: Option<VersionMessage> = None;
+    let mut got_verack = false;
+    for _ in 0..MAX_HANDSHAKE_MESSAGES {
+        let (command, payload) = read_message(&mut stream, magic).await?;
+        match command.as_str() {
+            "version" if peer.is_none() => {
+                peer = Some(VersionMessage::decode(&payload)?);
+                write_message(&mut stream, magic, "verack", &[]).await?;
+            }
+            "verack" => got_verack = true,
+            "reject" => bail!("peer rejected our version"),
+            _ => {}
+        }
+        if let (true, Some(v)) = (got_verack, &peer) {
+            return Ok(v.clone());
+        }
+    }
+    bail!("no version/verack from {addr} within {MAX_HANDSHAKE_MESSAGES} messages")
+}
+
+fn write_compact_size(out: &mut Vec<u8>, n: u64) {
+    match n {
+        0..=0xfc => out.push(n as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+    }
+}
+
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&e| e <= self.buf.len())
+            .ok_or_else(|| anyhow!("version message truncated"))?;
+        let s = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(s)
+    }
+
+    fn compact_size(&mut self) -> anyhow::Result<u64> {
+        Ok(match self.take(1)?[0] {
+            0xfd => u16::from_le_bytes(self.take(2)?.try_into().unwrap()) as u64,
+            0xfe => u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as u64,
+            0xff => u64::from_le_bytes(self.take(8)?.try_into().unwrap()),
+            n => n as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_payload_checksum_matches_bitcoin() {
+        // Every verack on the wire carries this checksum.
+        assert_eq!(checksum(&[]), [0x5d, 0xf6, 0xe0, 0xe2]);
+        let msg = encode_message(magic(ZcashNetwork::Mainnet), "verack", &[]);
+        assert_eq!(hex::encode(&msg), "24e9276476657261636b000000000000000000005df6e0e2");
+    }
+
+    #[test]
+    fn version_roundtrips_and_tolerates_missing_relay() {
+        let v = VersionMessage {
+            version: 170_140,
+            services: 1,
+            timestamp: 1_700_000_000,
+            nonce: 42,
+            user_agent: "/Zebra:2.4.0/".into(),
+            start_height: 3_000_000,
+            relay: false,
+        };
+        let bytes = v.encode();
+        assert_eq!(VersionMessage::decode(&bytes).unwrap(), v);
+
+        let without_relay = VersionMessage::decode(&bytes[..bytes.len() - 1]).unwrap();
+        assert!(without_relay.relay);
+        assert_eq!(without_relay.start_height, 3_000_000);
+        assert!(VersionMessage::decode(&bytes[..60]).is_err());
+    }
+
+    #[test]
+    fn compact_size_boundaries() {
+        for n in [0u64, 0xfc, 0xfd, 0xffff, 0x1_0000, 0xffff_ffff, 0x1_0000_0000] {
+            let mut out = Vec::new();
+            write_compact_size(&mut out, n);
+            let mut c = Cursor { buf: &out, pos: 0 };
+            assert_eq!(c.compact_size().unwrap(), n);
+            assert_eq!(c.pos, out.len());
+        }
+    }
+}
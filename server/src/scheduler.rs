@@ -1,28 +1,60 @@
-use chrono::{Duration as ChronoDuration, Utc};
-use serde_json::json;
-use std::time::Duration;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures::{stream, StreamExt};
+use rand::seq::SliceRandom;
+use serde_json::{json, Value};
+use std::{collections::HashMap, time::Duration};
 use tokio::time::{interval, Instant};
 use uuid::Uuid;
 
 use crate::{
+    api::proofs::{drift_from_tip, judge_against_quorum, normalize_hash, points_from_parts},
+    archival, credentials,
+    jobs::ScheduledJob,
+    lightwalletd, p2p,
     rpc::RpcError,
     state::AppState,
-    types::{Node, NodeStatus, Proof, ProofVerdict},
+    types::{
+        ChainInfo, ChainInfoSource, Challenge, ChallengeKind, ChallengeOrigin, ChallengeStatus, EndpointDissent,
+        LedgerSource, Node, NodeKind, NodeStatus, P2pProbe, Proof, ProofVerdict, RpcPollOutcome, RpcPollStats,
+    },
+    upgrades,
 };
 
 pub fn spawn(state: AppState) {
+    tracing::info!(instance = state.instance_id(), "scheduler starting");
     tokio::spawn(tip_refresh_loop(state.clone()));
     tokio::spawn(uptime_loop(state.clone()));
     tokio::spawn(staleness_loop(state.clone()));
     tokio::spawn(challenge_expiry_loop(state.clone()));
+    tokio::spawn(nonce_prune_loop(state.clone()));
+    tokio::spawn(disagreement_flush_loop(state.clone()));
+    tokio::spawn(rpc_dissent_loop(state.clone()));
     if state.config().exposed_rpc_poll_interval.is_some() {
         tokio::spawn(exposed_rpc_loop(state.clone()));
     }
-    if let Some(_) = state.config().snapshot_interval {
+    if state.config().auto_challenge_interval.is_some() {
+        tokio::spawn(auto_challenge_loop(state.clone()));
+    }
+    if state.config().p2p_probe_interval.is_some() {
+        tokio::spawn(p2p_probe_loop(state.clone()));
+    }
+    if state.config().pending_recheck_interval.is_some() {
+        tokio::spawn(pending_recheck_loop(state.clone()));
+    }
+    if state.config().finality_depth > 0 {
+        tokio::spawn(finality_loop(state.clone()));
+    }
+    if state.config().snapshot_interval.is_some() {
         tokio::spawn(snapshot_loop(state));
     }
 }
 
+// Every loop below except tip_refresh_loop and disagreement_flush_loop writes
+// shared state, so each is a `ScheduledJob`: it runs on whichever instance
+// holds its lease, on a cadence kept in the database across restarts. The
+// trusted tip and the pending rpc disagreements are per-process, and every
+// instance looks after its own on a plain interval.
+
 async fn tip_refresh_loop(state: AppState) {
     let mut tick = interval(state.config().heartbeat_interval.max(Duration::from_secs(15)));
     tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
@@ -46,87 +78,229 @@ async fn tip_refresh_loop(state: AppState) {
 // last `2 * uptime_reward_interval`. Mirrors DePINonBNB's uptime ticker concept.
 async fn uptime_loop(state: AppState) {
     let interval_dur = state.config().uptime_reward_interval.max(Duration::from_secs(60));
-    let mut tick = interval(interval_dur);
-    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
-    // Skip the first immediate tick — gives the server a moment to come up before crediting.
-    tick.tick().await;
-    let _start = Instant::now();
+    // First run one interval in — gives the server a moment to come up before crediting.
+    let mut job = ScheduledJob::start(&state, "uptime", interval_dur, interval_dur);
 
     loop {
-        tick.tick().await;
-        match state.store().list_all_nodes().await {
-            Ok(nodes) => {
-                let cutoff =
-                    Utc::now() - ChronoDuration::from_std(interval_dur * 2).unwrap_or(ChronoDuration::minutes(10));
-                for node in nodes {
-                    if node.status == NodeStatus::Suspended {
-                        continue;
-                    }
-                    let Some(last_proof) = node.last_proof_at else { continue };
-                    if last_proof < cutoff {
-                        continue;
-                    }
-                    // 1 point per tier-unit per tick.
-                    let pts = node.kind.reward_tier() as u64;
-                    if let Err(e) = state
-                        .store()
-                        .add_uptime_and_points(node.id, interval_dur.as_secs(), pts)
-                        .await
-                    {
-                        tracing::warn!(error = ?e, node_id = %node.id, "uptime credit failed");
-                    }
-                }
-            }
-            Err(e) => tracing::warn!(error = ?e, "uptime loop list_all_nodes failed"),
+        job.due().await;
+        let res = credit_uptime(&state, interval_dur).await;
+        if let Err(e) = &res {
+            tracing::warn!(error = ?e, "uptime loop list_all_nodes failed");
+        }
+        job.finished(res.as_ref().err()).await;
+    }
+}
+
+async fn credit_uptime(state: &AppState, interval_dur: Duration) -> anyhow::Result<()> {
+    let nodes = state.store().list_all_nodes().await?;
+    let cutoff = Utc::now() - ChronoDuration::from_std(interval_dur * 2).unwrap_or(ChronoDuration::minutes(10));
+    for node in nodes {
+        if matches!(
+            node.status,
+            NodeStatus::Suspended | NodeStatus::Paused | NodeStatus::Deregistered
+        ) {
+            continue;
+        }
+        let Some(last_proof) = node.last_proof_at else { continue };
+        if last_proof < cutoff {
+            continue;
+        }
+        // 1 point per tier-unit per tick.
+        let pts = node.kind.reward_tier() as u64;
+        if let Err(e) = state
+            .store()
+            .add_uptime_and_points(node.id, interval_dur.as_secs(), pts)
+            .await
+        {
+            tracing::warn!(error = ?e, node_id = %node.id, "uptime credit failed");
         }
     }
+    Ok(())
 }
 
 // Mark nodes Stale if no proof in last `staleness_threshold`. Resurrects to Active
 // on next accepted proof (handled in proofs handler).
 async fn staleness_loop(state: AppState) {
     let dur = state.config().challenge_check_interval.max(Duration::from_secs(60));
-    let mut tick = interval(dur * 2);
-    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut job = ScheduledJob::start(&state, "staleness", dur * 2, Duration::ZERO);
     loop {
-        tick.tick().await;
-        let stale_after = ChronoDuration::from_std(state.config().heartbeat_interval * 6)
-            .unwrap_or(ChronoDuration::minutes(30));
-        let cutoff = Utc::now() - stale_after;
-        let nodes = match state.store().list_all_nodes().await {
-            Ok(n) => n,
-            Err(e) => {
-                tracing::warn!(error = ?e, "staleness list failed");
-                continue;
-            }
-        };
-        for node in nodes {
-            if node.status != NodeStatus::Active {
-                continue;
-            }
-            let Some(last) = node.last_proof_at else { continue };
-            if last < cutoff {
-                if let Err(e) = state.store().update_node_status(node.id, NodeStatus::Stale).await {
-                    tracing::warn!(error = ?e, node_id = %node.id, "marking stale failed");
-                }
+        job.due().await;
+        let res = mark_stale_nodes(&state).await;
+        if let Err(e) = &res {
+            tracing::warn!(error = ?e, "staleness list failed");
+        }
+        job.finished(res.as_ref().err()).await;
+    }
+}
+
+// No accepted proof for this long and an active node is marked stale.
+fn stale_after(state: &AppState) -> ChronoDuration {
+    ChronoDuration::from_std(state.config().heartbeat_interval * 6).unwrap_or(ChronoDuration::minutes(30))
+}
+
+async fn mark_stale_nodes(state: &AppState) -> anyhow::Result<()> {
+    let cutoff = Utc::now() - stale_after(state);
+    let nodes = state.store().list_all_nodes().await?;
+    for node in nodes {
+        if node.status != NodeStatus::Active {
+            continue;
+        }
+        let Some(last) = node.last_proof_at else { continue };
+        if last < cutoff {
+            if let Err(e) = state.store().update_node_status(node.id, NodeStatus::Stale).await {
+                tracing::warn!(error = ?e, node_id = %node.id, "marking stale failed");
             }
         }
     }
+    Ok(())
 }
 
 async fn challenge_expiry_loop(state: AppState) {
-    let mut tick = interval(state.config().challenge_check_interval.max(Duration::from_secs(30)));
-    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let every = state.config().challenge_check_interval.max(Duration::from_secs(30));
+    let mut job = ScheduledJob::start(&state, "challenge_expiry", every, Duration::ZERO);
     loop {
-        tick.tick().await;
-        match state.store().expire_old_challenges(Utc::now()).await {
-            Ok(n) if n > 0 => tracing::info!(expired = n, "challenges expired"),
+        job.due().await;
+        let res = state.store().expire_old_challenges(Utc::now()).await;
+        match &res {
+            Ok(n) if *n > 0 => tracing::info!(expired = n, "challenges expired"),
             Ok(_) => {}
             Err(e) => tracing::warn!(error = ?e, "challenge expiry failed"),
         }
+        job.finished(res.as_ref().err()).await;
+    }
+}
+
+// A request signed more than `max_clock_skew` ago is refused by its
+// timestamp before its nonce is checked, so its nonce row can go. The grace
+// covers a request that passed the timestamp check just before a prune and
+// records its nonce just after, plus clock differences between instances.
+const NONCE_PRUNE_GRACE: Duration = Duration::from_secs(5 * 60);
+
+async fn nonce_prune_loop(state: AppState) {
+    let every = state.config().max_clock_skew.max(Duration::from_secs(60));
+    let mut job = ScheduledJob::start(&state, "nonce_prune", every, every);
+    loop {
+        job.due().await;
+        let res = prune_used_nonces(&state).await;
+        match &res {
+            Ok(n) if *n > 0 => tracing::info!(pruned = n, "expired nonces pruned"),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = ?e, "nonce prune failed"),
+        }
+        job.finished(res.as_ref().err()).await;
+    }
+}
+
+// Deletes nonces that can no longer be replayed. Returns how many.
+pub async fn prune_used_nonces(state: &AppState) -> anyhow::Result<u64> {
+    let window = ChronoDuration::from_std(state.config().max_clock_skew + NONCE_PRUNE_GRACE)?;
+    state.store().prune_nonces(Utc::now() - window).await
+}
+
+// Trusted-rpc disagreements.
+//
+// The quorum keeps the rounds its rpcs disagreed on in memory, and each
+// instance moves its own into the database. The `rpc_dissent` job then
+// counts how often each rpc was a dissenter within `rpc_dissent_window` and
+// raises an error-level alert for any that reached the threshold: an rpc
+// that keeps disagreeing with the rest is on a fork or compromised. It also
+// drops records older than DISAGREEMENT_RETENTION.
+const DISAGREEMENT_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+const DISAGREEMENT_RETENTION: Duration = Duration::from_secs(30 * 86_400);
+// Most records one dissent count looks at.
+const DISSENT_SCAN_LIMIT: i64 = 10_000;
+
+async fn disagreement_flush_loop(state: AppState) {
+    if !state.rpc().is_configured() {
+        return;
+    }
+    let mut tick = interval(DISAGREEMENT_FLUSH_INTERVAL);
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tick.tick().await;
+        if let Err(e) = flush_rpc_disagreements(&state).await {
+            tracing::warn!(error = ?e, "storing rpc disagreements failed");
+        }
+    }
+}
+
+// Stores the disagreements this instance's quorum has seen since the last
+// flush. Returns how many.
+pub async fn flush_rpc_disagreements(state: &AppState) -> anyhow::Result<usize> {
+    let pending = state.rpc().take_disagreements();
+    for d in &pending {
+        state.store().insert_rpc_disagreement(d).await?;
+    }
+    Ok(pending.len())
+}
+
+async fn rpc_dissent_loop(state: AppState) {
+    let every = Duration::from_secs(300);
+    let mut job = ScheduledJob::start(&state, "rpc_dissent", every, every);
+    loop {
+        job.due().await;
+        let res = check_rpc_dissent(&state).await;
+        match &res {
+            Ok(flagged) => {
+                for e in flagged {
+                    tracing::error!(
+                        endpoint = %e.endpoint,
+                        dissents = e.dissents,
+                        window_secs = state.config().rpc_dissent_window.as_secs(),
+                        "trusted rpc keeps disagreeing with the quorum: forked or compromised?"
+                    );
+                }
+            }
+            Err(e) => tracing::warn!(error = ?e, "rpc dissent check failed"),
+        }
+        job.finished(res.as_ref().err()).await;
     }
 }
 
+// Prunes old disagreements, then returns the rpcs at or over the alert
+// threshold within the window.
+pub async fn check_rpc_dissent(state: &AppState) -> anyhow::Result<Vec<EndpointDissent>> {
+    let now = Utc::now();
+    state
+        .store()
+        .prune_rpc_disagreements(now - ChronoDuration::from_std(DISAGREEMENT_RETENTION)?)
+        .await?;
+    Ok(recent_dissent(state, now)
+        .await?
+        .into_iter()
+        .filter(|e| e.alerting)
+        .collect())
+}
+
+// Per-rpc dissent within `rpc_dissent_window` before `now`, most first.
+pub async fn recent_dissent(state: &AppState, now: DateTime<Utc>) -> anyhow::Result<Vec<EndpointDissent>> {
+    let since = now - ChronoDuration::from_std(state.config().rpc_dissent_window)?;
+    let records = state.store().list_rpc_disagreements(since, DISSENT_SCAN_LIMIT).await?;
+    let threshold = state.config().rpc_dissent_alert_threshold;
+    let mut by_endpoint: HashMap<&str, EndpointDissent> = HashMap::new();
+    for d in &records {
+        for ep in &d.dissenters {
+            let e = by_endpoint.entry(ep).or_insert_with(|| EndpointDissent {
+                endpoint: ep.clone(),
+                dissents: 0,
+                last_dissent_at: d.observed_at,
+                alerting: false,
+            });
+            e.dissents += 1;
+            e.last_dissent_at = e.last_dissent_at.max(d.observed_at);
+        }
+    }
+    let mut out: Vec<EndpointDissent> = by_endpoint
+        .into_values()
+        .map(|mut e| {
+            e.alerting = threshold > 0 && e.dissents >= threshold;
+            e
+        })
+        .collect();
+    out.sort_by(|a, b| b.dissents.cmp(&a.dissents).then_with(|| a.endpoint.cmp(&b.endpoint)));
+    Ok(out)
+}
+
 // Exposed RPC verification mode.
 //
 // Operators register with a public `rpc_endpoint`. This loop polls each one
@@ -137,6 +311,9 @@ async fn challenge_expiry_loop(state: AppState) {
 // This is the "zero-install" path: no relay binary on the node side. The
 // operator's reachability (we can fetch the URL) and their RPC's truthfulness
 // (hash matches the quorum) substitute for the signed proof.
+//
+// Lightwalletd nodes get the same treatment over CompactTxStreamer gRPC
+// instead of JSON-RPC — see `lightwalletd`.
 async fn exposed_rpc_loop(state: AppState) {
     let Some(poll_interval) = state.config().exposed_rpc_poll_interval else {
         return;
@@ -146,70 +323,274 @@ async fn exposed_rpc_loop(state: AppState) {
         return;
     }
     let interval_dur = poll_interval.max(Duration::from_secs(60));
-    let mut tick = interval(interval_dur);
-    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
-    tick.tick().await; // skip immediate fire
+    let mut job = ScheduledJob::start(&state, "exposed_rpc", interval_dur, interval_dur);
 
     loop {
-        tick.tick().await;
-        let nodes = match state
-            .store()
-            .list_nodes_with_rpc(state.config().network.as_str())
-            .await
-        {
-            Ok(n) => n,
-            Err(e) => {
-                tracing::warn!(error = ?e, "exposed_rpc_loop: list_nodes_with_rpc failed");
-                continue;
-            }
-        };
-        if nodes.is_empty() {
+        job.due().await;
+        let res = run_exposed_rpc_polls(&state).await;
+        match &res {
+            Ok(sum) if sum.polled > 0 => tracing::info!(
+                polled = sum.polled,
+                credited = sum.credited,
+                unreachable = sum.unreachable,
+                backed_off = sum.backed_off,
+                "exposed rpc polls done"
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = ?e, "exposed_rpc_loop: list_nodes_with_rpc failed"),
+        }
+        job.finished(res.as_ref().err()).await;
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExposedPollSummary {
+    pub polled: u32,
+    pub credited: u32,
+    pub unreachable: u32,
+    // Skipped this tick because the endpoint is backing off.
+    pub backed_off: u32,
+}
+
+// Polls every exposed-RPC node that isn't backing off, up to
+// EXPOSED_RPC_POLL_CONCURRENCY at a time, and records each result in
+// rpc_poll_stats. A dead endpoint costs one RPC_TIMEOUT of one slot, not of
+// the whole tick.
+pub async fn run_exposed_rpc_polls(state: &AppState) -> anyhow::Result<ExposedPollSummary> {
+    let mut sum = ExposedPollSummary::default();
+    let network = state.config().network.as_str();
+    let nodes = state.store().list_nodes_with_rpc(network).await?;
+    let mut prev: HashMap<Uuid, RpcPollStats> = state
+        .store()
+        .list_rpc_poll_stats(network)
+        .await?
+        .into_iter()
+        .map(|s| (s.node_id, s))
+        .collect();
+
+    let started = Utc::now();
+    let mut due = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let last = prev.remove(&node.id);
+        if last.as_ref().is_some_and(|l| l.next_poll_at > started) {
+            sum.backed_off += 1;
             continue;
         }
-        let trusted_tip = state.trusted_tip().await;
-        for node in nodes {
-            if let Err(e) = poll_one_node(&state, &node, trusted_tip).await {
+        due.push((node, last));
+    }
+
+    let trusted_tip = state.trusted_tip().await;
+    let limit = state.config().exposed_rpc_poll_concurrency.max(1);
+    let outcomes: Vec<Option<RpcPollOutcome>> = stream::iter(due)
+        .map(|(node, last)| poll_and_record(state, node, last, trusted_tip, started))
+        .buffer_unordered(limit)
+        .collect()
+        .await;
+    for outcome in outcomes.into_iter().flatten() {
+        sum.polled += 1;
+        match outcome {
+            RpcPollOutcome::Credited => sum.credited += 1,
+            RpcPollOutcome::Unreachable => sum.unreachable += 1,
+            _ => {}
+        }
+    }
+    Ok(sum)
+}
+
+// How long an endpoint sits out after `failures` unreachable polls in a row:
+// 0, 0, 1, 3, 7, ... intervals, capped at `max`.
+pub fn poll_backoff(every: Duration, failures: u32, max: Duration) -> Duration {
+    let skipped_ticks = (1u32 << failures.saturating_sub(1).min(20)) - 1;
+    every.saturating_mul(skipped_ticks).min(max)
+}
+
+async fn poll_and_record(
+    state: &AppState,
+    node: Node,
+    last: Option<RpcPollStats>,
+    trusted_tip: Option<u64>,
+    tick_started: DateTime<Utc>,
+) -> Option<RpcPollOutcome> {
+    let endpoint = node.rpc_endpoint.as_deref()?;
+    let t0 = Instant::now();
+    let fetched = fetch_operator_tip(state, &node, endpoint).await;
+    let latency = t0.elapsed();
+    let (outcome, error) = match fetched {
+        Err(e) => {
+            tracing::warn!(error = ?e, node_id = %node.id, "exposed_rpc poll failed");
+            (RpcPollOutcome::Unreachable, Some(format!("{e:#}")))
+        }
+        Ok(tip) => match judge_and_observe(state, &node, tip, trusted_tip).await {
+            Ok(outcome) => (outcome, None),
+            Err(e) => {
                 tracing::warn!(error = ?e, node_id = %node.id, "exposed_rpc poll failed");
+                (RpcPollOutcome::Error, Some(format!("{e:#}")))
             }
-        }
+        },
+    };
+
+    let now = Utc::now();
+    let reachable = outcome != RpcPollOutcome::Unreachable;
+    let failures = if reachable {
+        0
+    } else {
+        last.as_ref().map_or(0, |l| l.consecutive_failures) + 1
+    };
+    let every = state
+        .config()
+        .exposed_rpc_poll_interval
+        .unwrap_or(Duration::from_secs(60))
+        .max(Duration::from_secs(60));
+    let backoff = poll_backoff(every, failures, state.config().exposed_rpc_max_backoff);
+    // Measured from the start of this tick, less half an interval, so
+    // "skip one tick" can't slip into skipping two on timing jitter.
+    let next_poll_at = if backoff.is_zero() {
+        tick_started
+    } else {
+        tick_started + ChronoDuration::from_std(backoff - every / 2).unwrap_or(ChronoDuration::zero())
+    };
+    let stats = RpcPollStats {
+        node_id: node.id,
+        last_polled_at: now,
+        last_outcome: outcome,
+        last_error: error,
+        last_latency_ms: Some(latency.as_millis() as u64),
+        last_success_at: if reachable { Some(now) } else { last.and_then(|l| l.last_success_at) },
+        consecutive_failures: failures,
+        next_poll_at,
+    };
+    if let Err(e) = state.store().upsert_rpc_poll_stats(&stats).await {
+        tracing::warn!(error = ?e, node_id = %node.id, "recording rpc poll failed");
     }
+    Some(outcome)
 }
 
-pub async fn poll_one_node(state: &AppState, node: &Node, trusted_tip: Option<u64>) -> anyhow::Result<()> {
+// One poll without the bookkeeping: ask the node, judge the answer. None if
+// the node has no rpc_endpoint. Errors are the endpoint's or the quorum's.
+pub async fn poll_one_node(
+    state: &AppState,
+    node: &Node,
+    trusted_tip: Option<u64>,
+) -> anyhow::Result<Option<RpcPollOutcome>> {
     let Some(endpoint) = node.rpc_endpoint.as_deref() else {
-        return Ok(());
+        return Ok(None);
     };
+    let tip = fetch_operator_tip(state, node, endpoint).await?;
+    judge_and_observe(state, node, tip, trusted_tip).await.map(Some)
+}
+
+// What a poll learned from the operator's node. `chain_info` is None when
+// the node didn't answer getblockchaininfo usefully.
+struct OperatorTip {
+    height: u64,
+    hash: String,
+    marker: &'static str,
+    chain_info: Option<ChainInfo>,
+}
+
+// Asks the operator's node for its tip + hash at that tip, and which chain
+// and consensus branch it's on. Zebra/zcashd speak JSON-RPC; lightwalletd
+// only speaks CompactTxStreamer gRPC.
+async fn fetch_operator_tip(state: &AppState, node: &Node, endpoint: &str) -> anyhow::Result<OperatorTip> {
+    // Resolve and vet the host once; every call goes to that address.
+    let addr = state.egress().check_url(endpoint).await?;
+    let auth = credentials::load(state, node.id).await?;
+    let auth = auth.as_ref();
+    Ok(match node.kind {
+        NodeKind::ZebraFull => {
+            // One batch for the tip and the chain info, then the hash at that
+            // height. A batch's entries may run in any order, so a best hash
+            // batched alongside the count can belong to a block that landed
+            // in between.
+            let calls = [("getblockcount", json!([])), ("getblockchaininfo", json!([]))];
+            let mut answers = state.rpc().call_pinned_batch(endpoint, addr, auth, &calls).await?.into_iter();
+            let mut next = || answers.next().unwrap_or_else(|| Err(anyhow::anyhow!("missing batch answer")));
+            let height = as_height(next()?)?;
+            let chain_info = match next().and_then(|v| upgrades::parse_blockchain_info(&v)) {
+                Ok(info) => Some(info),
+                Err(e) => {
+                    tracing::debug!(node_id = %node.id, error = %e, "exposed_rpc: no usable getblockchaininfo");
+                    None
+                }
+            };
+            let hash_v = state.rpc().call_pinned(endpoint, addr, auth, "getblockhash", json!([height])).await?;
+            let hash = hash_v
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("block hash is not a string: {hash_v}"))?
+                .to_string();
+            OperatorTip { height, hash, marker: "exposed-rpc-poll", chain_info }
+        }
+        NodeKind::Lightwalletd => {
+            let cfg = state.config();
+            let tip = lightwalletd::fetch_tip(endpoint, addr, auth, cfg.rpc_timeout, cfg.network).await?;
+            tracing::debug!(
+                node_id = %node.id,
+                vendor = %tip.vendor,
+                version = %tip.version,
+                height = tip.height,
+                "exposed_rpc: lightwalletd tip"
+            );
+            // GetLightdInfo names the chain and branch but not the upgrades.
+            let chain_info = Some(ChainInfo {
+                chain: tip.chain_name,
+                consensus_branch_id: tip.consensus_branch_id.to_ascii_lowercase(),
+                next_branch_id: None,
+                upgrades: vec![],
+            });
+            OperatorTip { height: tip.height, hash: tip.block_hash, marker: "exposed-lwd-grpc", chain_info }
+        }
+    })
+}
 
-    // 1) Ask the operator's RPC for its tip + hash at that tip.
-    let height_v = state.rpc().call_single(endpoint, "getblockcount", json!([])).await?;
-    let height = height_v
-        .as_u64()
-        .ok_or_else(|| anyhow::anyhow!("getblockcount returned non-u64: {height_v}"))?;
-    let hash_v = state.rpc().call_single(endpoint, "getblockhash", json!([height])).await?;
-    let claimed_hash = hash_v
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("getblockhash returned non-string: {hash_v}"))?
-        .to_string();
-
-    // 2) Drift check against the trusted tip.
+// Judges the tip, then checks and stores the chain info. A node is in sync
+// for readiness purposes when its tip was credited now or on an earlier poll.
+async fn judge_and_observe(
+    state: &AppState,
+    node: &Node,
+    tip: OperatorTip,
+    trusted_tip: Option<u64>,
+) -> anyhow::Result<RpcPollOutcome> {
+    let outcome = judge_operator_tip(state, node, tip.height, &tip.hash, tip.marker, trusted_tip).await?;
+    if let Some(info) = tip.chain_info {
+        let in_sync = matches!(outcome, RpcPollOutcome::Credited | RpcPollOutcome::Unchanged);
+        if let Err(e) = upgrades::observe(state, node, ChainInfoSource::ExposedRpc, info, in_sync).await {
+            tracing::warn!(error = ?e, node_id = %node.id, "exposed_rpc: recording chain info failed");
+        }
+    }
+    Ok(outcome)
+}
+
+fn as_height(v: Value) -> anyhow::Result<u64> {
+    v.as_u64().ok_or_else(|| anyhow::anyhow!("getblockcount returned non-u64: {v}"))
+}
+
+async fn judge_operator_tip(
+    state: &AppState,
+    node: &Node,
+    height: u64,
+    claimed_hash: &str,
+    poll_marker: &str,
+    trusted_tip: Option<u64>,
+) -> anyhow::Result<RpcPollOutcome> {
+    // 1) Drift check against the trusted tip.
     let cfg = state.config();
     if let Some(tip) = trusted_tip {
         if height + cfg.max_height_drift < tip || height > tip + cfg.max_height_drift {
             tracing::debug!(node_id = %node.id, height, tip, "exposed_rpc: out of drift, skipping");
-            return Ok(());
+            return Ok(RpcPollOutcome::OutOfDrift);
         }
     }
 
-    // 3) Cross-check the hash against the trusted quorum at the same height.
+    // 2) Cross-check the hash against the trusted quorum at the same height.
     let trusted_hash = match state.rpc().get_block_hash(height).await {
         Ok(h) => h,
-        Err(RpcError::NoQuorum) => {
+        Err(RpcError::NoQuorum | RpcError::Unhealthy { .. }) => {
             tracing::debug!(node_id = %node.id, height, "exposed_rpc: trusted quorum has no answer yet");
-            return Ok(());
+            return Ok(RpcPollOutcome::QuorumUnavailable);
         }
         Err(e) => return Err(anyhow::anyhow!("quorum get_block_hash: {e}")),
     };
-    let claimed = normalize_hash(&claimed_hash);
+    let claimed = normalize_hash(claimed_hash);
     let expected = normalize_hash(&trusted_hash);
     let verdict = if claimed == expected {
         ProofVerdict::Accepted
@@ -224,7 +605,7 @@ pub async fn poll_one_node(state: &AppState, node: &Node, trusted_tip: Option<u6
         ProofVerdict::Rejected
     };
 
-    // 4) Build a synthetic proof row. The "wallet signature" check is replaced by
+    // 3) Build a synthetic proof row. The "wallet signature" check is replaced by
     //    the operator-controlled rpc_endpoint they signed during registration.
     let drift = trusted_tip
         .map(|t| t.saturating_sub(height))
@@ -244,9 +625,9 @@ pub async fn poll_one_node(state: &AppState, node: &Node, trusted_tip: Option<u6
         node_id: node.id,
         wallet: node.wallet.clone(),
         claimed_height: height,
-        claimed_block_hash: claimed_hash.clone(),
+        claimed_block_hash: claimed_hash.to_string(),
         proof_timestamp: now,
-        binary_hash: Some("exposed-rpc-poll".to_string()),
+        binary_hash: Some(poll_marker.to_string()),
         uptime_seconds: None,
         peers: None,
         verdict,
@@ -257,48 +638,695 @@ pub async fn poll_one_node(state: &AppState, node: &Node, trusted_tip: Option<u6
         },
         points_awarded: points,
         received_at: now,
+        trusted_tip_height: trusted_tip,
+        finalized_at: None,
     };
 
     let inserted = state.store().try_insert_proof(&proof).await?;
+    if verdict != ProofVerdict::Accepted {
+        return Ok(RpcPollOutcome::HashMismatch);
+    }
     if !inserted {
         // Same (node, height, hash) already on file — operator's tip hasn't moved
         // since our last poll. No-op, no double credit.
-        return Ok(());
+        return Ok(RpcPollOutcome::Unchanged);
     }
 
-    if verdict == ProofVerdict::Accepted {
+    state
+        .store()
+        .apply_proof_acceptance(node.id, proof.id, height, claimed_hash, points, now)
+        .await?;
+    tracing::info!(
+        node_id = %node.id,
+        height,
+        points,
+        "exposed_rpc: accepted proof and credited"
+    );
+    Ok(RpcPollOutcome::Credited)
+}
+
+// Auto challenges.
+//
+// Exposed-RPC nodes are the ones nobody signs for, so they're the ones worth
+// auditing. Every `AUTO_CHALLENGE_INTERVAL` each one gets a challenge of a
+// kind drawn from `AUTO_CHALLENGE_KINDS` (see `archival`); the scheduler asks
+// the node's rpc_endpoint itself and records the challenge already answered.
+// A pass pays `reward_tier * kind.reward_multiplier()`. An endpoint that
+// errors or can't be reached fails too. After `CHALLENGE_FAILURE_THRESHOLD`
+// failures in a row the node is suspended. Nodes are challenged
+// `EXPOSED_RPC_POLL_CONCURRENCY` at a time.
+async fn auto_challenge_loop(state: AppState) {
+    let Some(challenge_interval) = state.config().auto_challenge_interval else {
+        return;
+    };
+    if !state.rpc().is_configured() {
+        tracing::warn!("auto_challenge_loop: no trusted rpcs — disabling (no expected hash to compare)");
+        return;
+    }
+    let every = challenge_interval.max(Duration::from_secs(60));
+    let mut job = ScheduledJob::start(&state, "auto_challenge", every, every);
+
+    loop {
+        job.due().await;
+        let res = run_auto_challenges(&state).await;
+        match &res {
+            Ok(sum) if sum.suspended > 0 => tracing::warn!(
+                issued = sum.issued,
+                passed = sum.passed,
+                failed = sum.failed,
+                suspended = sum.suspended,
+                "auto challenges: nodes suspended"
+            ),
+            Ok(sum) if sum.issued > 0 => tracing::info!(
+                issued = sum.issued,
+                passed = sum.passed,
+                failed = sum.failed,
+                "auto challenges resolved"
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = ?e, "auto challenges failed"),
+        }
+        job.finished(res.as_ref().err()).await;
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AutoChallengeSummary {
+    pub issued: u32,
+    pub passed: u32,
+    pub failed: u32,
+    pub suspended: u32,
+    // Quorum couldn't produce an expected hash — node not challenged this round.
+    pub skipped: u32,
+}
+
+pub async fn run_auto_challenges(state: &AppState) -> anyhow::Result<AutoChallengeSummary> {
+    let mut sum = AutoChallengeSummary::default();
+    let nodes = state
+        .store()
+        .list_nodes_with_rpc(state.config().network.as_str())
+        .await?;
+    if nodes.is_empty() {
+        return Ok(sum);
+    }
+    let tip = state
+        .rpc()
+        .get_block_count()
+        .await
+        .map_err(|e| anyhow::anyhow!("quorum get_block_count: {e}"))?;
+
+    // Same endpoints the exposed-RPC poll calls, so the same cap on how many
+    // are in flight: a few hundred dead ones can't hold the round past its
+    // interval.
+    let limit = state.config().exposed_rpc_poll_concurrency.max(1);
+    let rounds: Vec<ChallengeRound> = stream::iter(nodes)
+        .map(|node| async move { challenge_and_suspend(state, &node, tip).await })
+        .buffer_unordered(limit)
+        .collect()
+        .await;
+    for round in rounds {
+        match round {
+            ChallengeRound::Skipped => sum.skipped += 1,
+            ChallengeRound::Errored => {}
+            ChallengeRound::Passed => {
+                sum.issued += 1;
+                sum.passed += 1;
+            }
+            ChallengeRound::Failed { suspended } => {
+                sum.issued += 1;
+                sum.failed += 1;
+                if suspended {
+                    sum.suspended += 1;
+                }
+            }
+        }
+    }
+    Ok(sum)
+}
+
+enum ChallengeRound {
+    Skipped,
+    Errored,
+    Passed,
+    Failed { suspended: bool },
+}
+
+async fn challenge_and_suspend(state: &AppState, node: &Node, tip: u64) -> ChallengeRound {
+    let ch = match challenge_one_node(state, node, tip).await {
+        Ok(Some(ch)) => ch,
+        Ok(None) => return ChallengeRound::Skipped,
+        Err(e) => {
+            tracing::warn!(error = ?e, node_id = %node.id, "auto challenge failed");
+            return ChallengeRound::Errored;
+        }
+    };
+    if ch.passed == Some(true) {
+        return ChallengeRound::Passed;
+    }
+    let suspended = match suspend_if_over_threshold(state, node).await {
+        Ok(suspended) => suspended,
+        // The failure is already recorded; the next round re-checks.
+        Err(e) => {
+            tracing::warn!(error = ?e, node_id = %node.id, "challenge suspension check failed");
+            false
+        }
+    };
+    ChallengeRound::Failed { suspended }
+}
+
+// Ok(None) = the quorum couldn't give us an expected hash; that's on us, not
+// the node, so nothing is recorded.
+async fn challenge_one_node(state: &AppState, node: &Node, tip: u64) -> anyhow::Result<Option<Challenge>> {
+    let Some(endpoint) = node.rpc_endpoint.as_deref() else {
+        return Ok(None);
+    };
+    let cfg = state.config();
+    // Lightwalletd serves compact blocks, not full-node RPC — BlockHash via
+    // GetBlock is the only kind it can answer.
+    let kind = match node.kind {
+        NodeKind::Lightwalletd if cfg.auto_challenge_kinds.contains(&ChallengeKind::BlockHash) => {
+            ChallengeKind::BlockHash
+        }
+        NodeKind::Lightwalletd => return Ok(None),
+        NodeKind::ZebraFull => match cfg.auto_challenge_kinds.choose(&mut rand::thread_rng()) {
+            Some(&kind) => kind,
+            None => return Ok(None),
+        },
+    };
+    let spec = match archival::build_spec(state.rpc(), kind, tip, cfg.network).await {
+        Ok(Some(spec)) => spec,
+        Ok(None) => return Ok(None),
+        Err(e) => {
+            tracing::debug!(node_id = %node.id, kind = kind.as_str(), error = ?e, "auto challenge: no quorum answer");
+            return Ok(None);
+        }
+    };
+
+    let target = match state.egress().check_url(endpoint).await {
+        Ok(addr) => credentials::load(state, node.id).await.map(|auth| (addr, auth)),
+        Err(e) => Err(e),
+    };
+    let response = match target {
+        Err(e) => Err(e),
+        Ok((addr, auth)) => match node.kind {
+            NodeKind::ZebraFull => {
+                state
+                    .rpc()
+                    .call_pinned(endpoint, addr, auth.as_ref(), spec.method, spec.params.clone())
+                    .await
+            }
+            NodeKind::Lightwalletd => {
+                lightwalletd::block_hash_at(endpoint, addr, auth.as_ref(), cfg.rpc_timeout, spec.target_height)
+                    .await
+                    .map(Value::String)
+            }
+        },
+    };
+    let (answer, fail_reason) = match response {
+        Ok(v) => match archival::fingerprint(kind, &v) {
+            Some(fp) if fp == spec.expected => (Some(fp), None),
+            Some(fp) => (Some(fp), Some(format!("{} mismatch with trusted quorum", kind.as_str()))),
+            None => (None, Some(format!("{} returned unexpected shape", spec.method))),
+        },
+        Err(e) => (None, Some(format!("rpc error: {e:#}"))),
+    };
+    let passed = fail_reason.is_none();
+
+    let now = Utc::now();
+    let ch = Challenge {
+        id: Uuid::new_v4(),
+        node_id: node.id,
+        kind,
+        target_height: spec.target_height,
+        expected_hash: spec.expected,
+        issued_at: now,
+        expires_at: now,
+        status: ChallengeStatus::Answered,
+        answered_at: Some(now),
+        passed: Some(passed),
+        origin: ChallengeOrigin::Auto,
+        subject: spec.subject,
+        answer_hash: answer,
+        fail_reason,
+    };
+    state.store().insert_challenge(&ch).await?;
+
+    if passed {
         state
             .store()
-            .apply_proof_acceptance(node.id, height, &claimed_hash, points, now)
+            .adjust_points(
+                node.id,
+                (node.kind.reward_tier() * kind.reward_multiplier()) as i64,
+                LedgerSource::ChallengeBonus,
+                Some(&ch.id.to_string()),
+            )
             .await?;
-        tracing::info!(
+    } else {
+        tracing::warn!(
             node_id = %node.id,
-            height,
-            points,
-            "exposed_rpc: accepted proof and credited"
+            kind = kind.as_str(),
+            target = ch.target_height,
+            reason = ch.fail_reason.as_deref().unwrap_or(""),
+            "auto challenge failed"
         );
     }
-    Ok(())
+    Ok(Some(ch))
+}
+
+async fn suspend_if_over_threshold(state: &AppState, node: &Node) -> anyhow::Result<bool> {
+    let threshold = state.config().challenge_failure_threshold;
+    if threshold == 0 {
+        return Ok(false);
+    }
+    let failures = state.store().consecutive_challenge_failures(node.id).await?;
+    if failures < threshold {
+        return Ok(false);
+    }
+    state
+        .store()
+        .update_node_status(node.id, NodeStatus::Suspended)
+        .await?;
+    tracing::warn!(node_id = %node.id, failures, "node suspended after consecutive challenge failures");
+    Ok(true)
+}
+
+// P2P reachability.
+//
+// Every P2P_PROBE_INTERVAL each node with a p2p_address gets a version/verack
+// handshake (see `p2p`), P2P_PROBE_CONCURRENCY at a time. A refused or
+// timed-out handshake is recorded with its error and earns nothing. A
+// completed one is judged before it is paid:
+//  - the peer's start height must be within max_height_drift of the trusted
+//    tip (when we have one), else the probe is recorded out of sync;
+//  - any public Zcash node completes a handshake, so the reward tier is paid
+//    per probe only while the address is proven the node's own — same host
+//    as its rpc_endpoint, or an accepted proof within the stale window. An
+//    unproven address earns it once.
+async fn p2p_probe_loop(state: AppState) {
+    let Some(probe_interval) = state.config().p2p_probe_interval else {
+        return;
+    };
+    let every = probe_interval.max(Duration::from_secs(60));
+    let mut job = ScheduledJob::start(&state, "p2p_probe", every, every);
+
+    loop {
+        job.due().await;
+        let res = run_p2p_probes(&state).await;
+        match &res {
+            Ok(sum) if sum.probed > 0 => tracing::info!(
+                probed = sum.probed,
+                reachable = sum.reachable,
+                unreachable = sum.unreachable,
+                out_of_sync = sum.out_of_sync,
+                "p2p probes done"
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = ?e, "p2p probes failed"),
+        }
+        job.finished(res.as_ref().err()).await;
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct P2pProbeSummary {
+    pub probed: u32,
+    pub reachable: u32,
+    pub unreachable: u32,
+    // Reachable, but the start height was too far from the trusted tip.
+    pub out_of_sync: u32,
+}
+
+pub async fn run_p2p_probes(state: &AppState) -> anyhow::Result<P2pProbeSummary> {
+    let mut sum = P2pProbeSummary::default();
+    let nodes = state
+        .store()
+        .list_nodes_with_p2p(state.config().network.as_str())
+        .await?;
+    let limit = state.config().p2p_probe_concurrency.max(1);
+    let results: Vec<(Uuid, anyhow::Result<Option<P2pProbe>>)> = stream::iter(nodes)
+        .map(|node| async move { (node.id, probe_one_node(state, &node).await) })
+        .buffer_unordered(limit)
+        .collect()
+        .await;
+    for (node_id, res) in results {
+        match res {
+            Ok(Some(probe)) => {
+                sum.probed += 1;
+                if !probe.reachable {
+                    sum.unreachable += 1;
+                    continue;
+                }
+                sum.reachable += 1;
+                if probe.error.is_some() {
+                    sum.out_of_sync += 1;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(%node_id, error = ?e, "p2p probe: store error"),
+        }
+    }
+    Ok(sum)
+}
+
+// Ok(None) = node has no p2p_address. Unreachable is a recorded outcome, not
+// an error; Err only means the probe couldn't be stored.
+pub async fn probe_one_node(state: &AppState, node: &Node) -> anyhow::Result<Option<P2pProbe>> {
+    let Some(address) = node.p2p_address.as_deref() else {
+        return Ok(None);
+    };
+    let cfg = state.config();
+    let outcome = match state.egress().check_address(address).await {
+        Ok(addr) => p2p::handshake(addr, cfg.network, cfg.rpc_timeout).await,
+        Err(e) => Err(e),
+    };
+    let mut probe = P2pProbe {
+        id: Uuid::new_v4(),
+        node_id: node.id,
+        address: address.to_string(),
+        reachable: false,
+        user_agent: None,
+        protocol_version: None,
+        start_height: None,
+        error: None,
+        points_awarded: 0,
+        probed_at: Utc::now(),
+    };
+    match outcome {
+        Ok(peer) => {
+            let start_height = peer.start_height as u64;
+            probe.reachable = true;
+            probe.user_agent = Some(peer.user_agent);
+            probe.protocol_version = Some(peer.version);
+            probe.start_height = Some(start_height);
+            match judge_start_height(start_height, state.trusted_tip().await, cfg.max_height_drift) {
+                Some(reason) => probe.error = Some(reason),
+                None => {
+                    let proven = p2p_address_is_proven(node, stale_after(state), probe.probed_at);
+                    if proven || !state.store().p2p_probe_paid(node.id).await? {
+                        probe.points_awarded = node.kind.reward_tier() as u64;
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            tracing::debug!(node_id = %node.id, address, error = %e, "p2p probe: unreachable");
+            probe.error = Some(format!("{e:#}"));
+        }
+    }
+    state.store().record_p2p_probe(&probe).await?;
+    Ok(Some(probe))
+}
+
+// None = in sync, or no trusted tip to judge against.
+fn judge_start_height(start_height: u64, trusted_tip: Option<u64>, max_height_drift: u64) -> Option<String> {
+    let tip = trusted_tip?;
+    if start_height.saturating_add(max_height_drift) < tip {
+        Some(format!("start_height {start_height} too far behind trusted tip {tip}"))
+    } else if start_height > tip.saturating_add(max_height_drift) {
+        Some(format!("start_height {start_height} ahead of trusted tip {tip}"))
+    } else {
+        None
+    }
+}
+
+// The address is the node's own if it sits on the host we already poll as
+// its rpc_endpoint, or if the node has had a proof accepted recently.
+fn p2p_address_is_proven(node: &Node, stale_after: ChronoDuration, now: DateTime<Utc>) -> bool {
+    if node.last_proof_at.is_some_and(|at| now - at < stale_after) {
+        return true;
+    }
+    let (Some(rpc), Some(p2p)) = (node.rpc_endpoint.as_deref(), node.p2p_address.as_deref()) else {
+        return false;
+    };
+    // IPv6 hosts come bracketed from both sides; compare them bare.
+    let bare = |host: &str| host.trim_matches(|c| c == '[' || c == ']').to_ascii_lowercase();
+    let rpc_host = url::Url::parse(rpc).ok().and_then(|u| u.host_str().map(bare));
+    let p2p_host = p2p.rsplit_once(':').map(|(host, _)| bare(host));
+    matches!((rpc_host, p2p_host), (Some(a), Some(b)) if a == b)
+}
+
+// Pending re-check.
+//
+// `api::proofs::submit` stores a proof as Pending when the trusted quorum
+// errors or can't agree. This loop picks those up oldest-first and asks the
+// quorum again. A proof still unresolved after `PENDING_PROOF_MAX_AGE` is
+// rejected so the queue can't grow without bound.
+async fn pending_recheck_loop(state: AppState) {
+    let Some(recheck_interval) = state.config().pending_recheck_interval else {
+        return;
+    };
+    if !state.rpc().is_configured() {
+        tracing::warn!("pending_recheck_loop: no trusted rpcs — disabling (nothing is ever stored pending)");
+        return;
+    }
+    let every = recheck_interval.max(Duration::from_secs(15));
+    let mut job = ScheduledJob::start(&state, "pending_recheck", every, every);
+
+    loop {
+        job.due().await;
+        let res = recheck_pending_proofs(&state).await;
+        match &res {
+            Ok(sum) if sum.accepted + sum.rejected + sum.expired > 0 => tracing::info!(
+                accepted = sum.accepted,
+                rejected = sum.rejected,
+                expired = sum.expired,
+                still_pending = sum.still_pending,
+                "pending proofs resolved"
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = ?e, "pending re-check failed"),
+        }
+        job.finished(res.as_ref().err()).await;
+    }
 }
 
-fn normalize_hash(s: &str) -> String {
-    let lower = s.trim().to_lowercase();
-    lower.trim_start_matches("0x").to_string()
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PendingRecheckSummary {
+    pub accepted: u32,
+    pub rejected: u32,
+    pub expired: u32,
+    pub still_pending: u32,
+}
+
+pub async fn recheck_pending_proofs(state: &AppState) -> anyhow::Result<PendingRecheckSummary> {
+    let cfg = state.config();
+    let store = state.store();
+    let mut sum = PendingRecheckSummary::default();
+    let proofs = store
+        .list_pending_proofs(cfg.pending_recheck_batch.max(1) as i64)
+        .await?;
+    if proofs.is_empty() {
+        return Ok(sum);
+    }
+    let expire_before = Utc::now()
+        - ChronoDuration::from_std(cfg.pending_proof_max_age).unwrap_or(ChronoDuration::hours(24));
+
+    for proof in proofs {
+        if proof.received_at < expire_before {
+            if store
+                .resolve_pending_proof(
+                    proof.id,
+                    ProofVerdict::Rejected,
+                    Some("pending-expired: trusted quorum never confirmed"),
+                    0,
+                )
+                .await?
+            {
+                sum.expired += 1;
+            }
+            continue;
+        }
+
+        let Some(node) = store.get_node(proof.node_id).await? else {
+            // Node rows are never deleted, but don't wedge the queue if one is.
+            if store
+                .resolve_pending_proof(proof.id, ProofVerdict::Rejected, Some("pending: node not found"), 0)
+                .await?
+            {
+                sum.rejected += 1;
+            }
+            continue;
+        };
+        if node.status == NodeStatus::Suspended {
+            if store
+                .resolve_pending_proof(proof.id, ProofVerdict::Rejected, Some("pending: node suspended"), 0)
+                .await?
+            {
+                sum.rejected += 1;
+            }
+            continue;
+        }
+        if node.status == NodeStatus::Deregistered {
+            if store
+                .resolve_pending_proof(proof.id, ProofVerdict::Rejected, Some("pending: node deregistered"), 0)
+                .await?
+            {
+                sum.rejected += 1;
+            }
+            continue;
+        }
+
+        let trusted_hash = match state.rpc().get_block_hash(proof.claimed_height).await {
+            Ok(h) => h,
+            Err(e) => {
+                tracing::debug!(proof_id = %proof.id, error = ?e, "pending re-check: quorum still unavailable");
+                sum.still_pending += 1;
+                continue;
+            }
+        };
+        // Judge against the tip cached at submit time, not the current one —
+        // the proof shouldn't lose out on freshness because the quorum was slow.
+        let (verdict, reason) = judge_against_quorum(
+            proof.claimed_height,
+            &proof.claimed_block_hash,
+            &trusted_hash,
+            proof.trusted_tip_height,
+            cfg.max_height_drift,
+        );
+        let points = if verdict == ProofVerdict::Accepted {
+            points_from_parts(
+                node.kind.reward_tier() as u64,
+                drift_from_tip(proof.trusted_tip_height, proof.claimed_height),
+                proof.uptime_seconds.unwrap_or(0),
+                proof.peers.unwrap_or(0),
+            )
+        } else {
+            0
+        };
+        // The verdict flips together with the credit, so a failure in between
+        // leaves the proof pending for the next pass. False = another worker
+        // got there first and did the crediting.
+        if verdict == ProofVerdict::Accepted {
+            if store.accept_pending_proof(&proof, points).await? {
+                sum.accepted += 1;
+            }
+        } else if store
+            .resolve_pending_proof(proof.id, verdict, reason.as_deref(), 0)
+            .await?
+        {
+            sum.rejected += 1;
+        }
+    }
+    Ok(sum)
+}
+
+// Reorg finality.
+//
+// Proofs near the tip are accepted at depth 0. Once an accepted proof is
+// `FINALITY_DEPTH` blocks below the trusted tip, ask the quorum for the hash
+// at its height again: still the same = finalized; different = the block was
+// reorged out, so the proof flips to Reorged and its points are clawed back.
+async fn finality_loop(state: AppState) {
+    if !state.rpc().is_configured() {
+        tracing::warn!("finality_loop: no trusted rpcs — disabling (cannot re-check without quorum)");
+        return;
+    }
+    let every = state.config().finality_check_interval.max(Duration::from_secs(30));
+    // First run one interval in — the tip cache is still cold at startup.
+    let mut job = ScheduledJob::start(&state, "finality", every, every);
+
+    loop {
+        job.due().await;
+        let res = confirm_finality(&state).await;
+        match &res {
+            Ok(sum) if sum.reorged > 0 => tracing::warn!(
+                finalized = sum.finalized,
+                reorged = sum.reorged,
+                points_revoked = sum.points_revoked,
+                "finality: reorged proofs revoked"
+            ),
+            Ok(sum) if sum.finalized > 0 => tracing::debug!(finalized = sum.finalized, "finality: proofs confirmed"),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = ?e, "finality check failed"),
+        }
+        job.finished(res.as_ref().err()).await;
+    }
+}
+
+// Upper bound on proofs confirmed per tick; a backlog drains over a few ticks.
+const FINALITY_BATCH: i64 = 500;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FinalitySummary {
+    pub finalized: u32,
+    pub reorged: u32,
+    pub points_revoked: u64,
+}
+
+pub async fn confirm_finality(state: &AppState) -> anyhow::Result<FinalitySummary> {
+    let mut sum = FinalitySummary::default();
+    let depth = state.config().finality_depth;
+    if depth == 0 {
+        return Ok(sum);
+    }
+    let Some(tip) = state.trusted_tip().await else {
+        return Ok(sum);
+    };
+    let Some(max_height) = tip.checked_sub(depth) else {
+        return Ok(sum);
+    };
+    let store = state.store();
+    let proofs = store.list_unfinalized_proofs(max_height, FINALITY_BATCH).await?;
+
+    // Many nodes prove the same heights — one quorum call per height.
+    let mut hashes: HashMap<u64, String> = HashMap::new();
+    for proof in proofs {
+        let trusted_hash = match hashes.get(&proof.claimed_height) {
+            Some(h) => h.clone(),
+            None => match state.rpc().get_block_hash(proof.claimed_height).await {
+                Ok(h) => {
+                    hashes.insert(proof.claimed_height, h.clone());
+                    h
+                }
+                Err(e) => {
+                    // Leave it provisional; next tick retries.
+                    tracing::debug!(height = proof.claimed_height, error = ?e, "finality: quorum unavailable");
+                    continue;
+                }
+            },
+        };
+        let now = Utc::now();
+        if normalize_hash(&trusted_hash) == normalize_hash(&proof.claimed_block_hash) {
+            if store.finalize_proof(proof.id, now).await? {
+                sum.finalized += 1;
+            }
+            continue;
+        }
+        let reason = format!(
+            "reorged: hash at height {} is now {} (was {})",
+            proof.claimed_height,
+            normalize_hash(&trusted_hash),
+            normalize_hash(&proof.claimed_block_hash)
+        );
+        if store.mark_proof_reorged(proof.id, &reason, now).await? {
+            tracing::warn!(
+                proof_id = %proof.id,
+                node_id = %proof.node_id,
+                height = proof.claimed_height,
+                points = proof.points_awarded,
+                "finality: proof reorged, points revoked"
+            );
+            sum.reorged += 1;
+            sum.points_revoked += proof.points_awarded;
+        }
+    }
+    Ok(sum)
 }
 
 async fn snapshot_loop(state: AppState) {
     let Some(snap_interval) = state.config().snapshot_interval else {
         return;
     };
-    let mut tick = interval(snap_interval);
-    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
-    // Skip immediate tick — give operators a chance to earn before first snapshot.
-    tick.tick().await;
+    // First snapshot one interval in — give operators a chance to earn. After
+    // that the schedule survives restarts, so deploys don't push it back.
+    let mut job = ScheduledJob::start(&state, "snapshot", snap_interval, snap_interval);
     loop {
-        tick.tick().await;
-        match crate::merkle::publish_snapshot(&state).await {
+        job.due().await;
+        let res = crate::merkle::publish_snapshot(&state).await;
+        match &res {
             Ok(res) => tracing::info!(cycle = res.cycle, leaves = res.leaves, root = %res.merkle_root, "scheduled snapshot published"),
             Err(e) => tracing::warn!(error = ?e, "scheduled snapshot failed"),
         }
+        job.finished(res.as_ref().err()).await;
     }
 }
@@ -6,13 +6,16 @@ use tokio::sync::Mutex;
 
 use crate::{
     config::Config,
+    egress::EgressGuard,
     rpc::ZcashRpcQuorum,
-    store::SqliteStore,
-    types::{NetworkStats, Node, Proof, WalletStats},
+    store::Store,
+    types::{ChainInfo, NetworkStats, Node, Proof, WalletStats},
 };
 
 const STATS_CACHE_TTL: Duration = Duration::from_secs(300);
 const LIST_CACHE_TTL: Duration = Duration::from_secs(300);
+// Every node report is checked against this; a minute-old view is plenty.
+const QUORUM_CHAIN_INFO_TTL: Duration = Duration::from_secs(60);
 
 #[derive(Clone)]
 pub struct AppState {
@@ -21,10 +24,16 @@ pub struct AppState {
 
 struct AppStateInner {
     pub config: Config,
-    pub store: SqliteStore,
+    pub store: Arc<dyn Store>,
     pub rpc: ZcashRpcQuorum,
+    // Resolves and vets operator endpoints before the scheduler dials them.
+    pub egress: EgressGuard,
+    // Random per process. Names this instance as a job-lease holder.
+    pub instance_id: String,
     // Cached trusted tip height (refreshed by the scheduler). None until first scheduler tick.
     pub trusted_tip: Mutex<Option<u64>>,
+    // Quorum-agreed getblockchaininfo (see `upgrades::quorum_view`).
+    pub quorum_chain_info: Mutex<Option<(Instant, ChainInfo)>>,
     // Network-stats cache. The 5-COUNT aggregate over 200K+ rows takes ~20s,
     // so we serve a 30s-stale snapshot to the public counter instead.
     pub network_stats_cache: Mutex<Option<(Instant, NetworkStats)>>,
@@ -39,13 +48,27 @@ struct AppStateInner {
 }
 
 impl AppState {
-    pub fn new(config: Config, store: SqliteStore, rpc: ZcashRpcQuorum) -> Self {
+    pub fn new<S: Store + 'static>(config: Config, store: S, rpc: ZcashRpcQuorum) -> Self {
+        Self::with_store(config, Arc::new(store), rpc)
+    }
+
+    // For a backend picked at runtime (`store::connect`).
+    pub fn with_store(config: Config, store: Arc<dyn Store>, rpc: ZcashRpcQuorum) -> Self {
+        let egress = EgressGuard::new(config.allow_private_endpoints);
+        Self::with_egress(config, store, rpc, egress)
+    }
+
+    // Tests swap in a stub resolver here.
+    pub fn with_egress(config: Config, store: Arc<dyn Store>, rpc: ZcashRpcQuorum, egress: EgressGuard) -> Self {
         Self {
             inner: Arc::new(AppStateInner {
                 config,
                 store,
                 rpc,
+                egress,
+                instance_id: uuid::Uuid::new_v4().to_string(),
                 trusted_tip: Mutex::new(None),
+                quorum_chain_info: Mutex::new(None),
                 network_stats_cache: Mutex::new(None),
                 leaderboard_cache: Mutex::new(HashMap::new()),
                 active_nodes_cache: Mutex::new(HashMap::new()),
@@ -58,14 +81,22 @@ impl AppState {
         &self.inner.config
     }
 
-    pub fn store(&self) -> &SqliteStore {
-        &self.inner.store
+    pub fn store(&self) -> &dyn Store {
+        self.inner.store.as_ref()
     }
 
     pub fn rpc(&self) -> &ZcashRpcQuorum {
         &self.inner.rpc
     }
 
+    pub fn egress(&self) -> &EgressGuard {
+        &self.inner.egress
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.inner.instance_id
+    }
+
     pub async fn trusted_tip(&self) -> Option<u64> {
         *self.inner.trusted_tip.lock().await
     }
@@ -74,6 +105,18 @@ impl AppState {
         *self.inner.trusted_tip.lock().await = Some(height);
     }
 
+    pub async fn cached_quorum_chain_info(&self) -> Option<ChainInfo> {
+        let guard = self.inner.quorum_chain_info.lock().await;
+        match &*guard {
+            Some((at, info)) if at.elapsed() < QUORUM_CHAIN_INFO_TTL => Some(info.clone()),
+            _ => None,
+        }
+    }
+
+    pub async fn store_quorum_chain_info(&self, info: ChainInfo) {
+        *self.inner.quorum_chain_info.lock().await = Some((Instant::now(), info));
+    }
+
     // Returns a cached NetworkStats if the last refresh was within
     // STATS_CACHE_TTL; otherwise None. Cheap mutex lookup, no DB hit.
     pub async fn cached_network_stats(&self) -> Option<NetworkStats> {
@@ -1,3 +1,353 @@
+// Persistence. `Store` is everything the API, scheduler and admin handlers
+// need from the database; `SqliteStore` and `PgStore` implement it and
+// `connect` picks one from the DATABASE_URL scheme. Both keep ids as uuid
+// TEXT and timestamps as RFC3339 TEXT, so rows map to the same types and
+// sort the same way on either backend.
+
+pub mod postgres;
 pub mod sqlite;
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::types::{
+    Challenge, ChallengeKind, DelegateScope, Delegation, JobRun, LedgerEntry, LedgerSource, NetworkStats, Node,
+    NewSnapshotLeaf, NodeChainInfo, NodeDailyBucket, NodeStatus, P2pProbe, PointsMismatch, Proof, ProofVerdict, RpcDisagreement, RpcPollStats, SnapshotLeaf,
+    WalletStats,
+};
+
+pub use postgres::PgStore;
 pub use sqlite::SqliteStore;
+
+// Opens the backend named by the URL scheme. Does not migrate.
+pub async fn connect(url: &str) -> anyhow::Result<Arc<dyn Store>> {
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        Ok(Arc::new(PgStore::connect(url).await?))
+    } else if url.starts_with("sqlite:") {
+        Ok(Arc::new(SqliteStore::connect(url).await?))
+    } else {
+        bail!("unsupported DATABASE_URL scheme (expected sqlite: or postgres://)")
+    }
+}
+
+#[async_trait]
+pub trait Store: Send + Sync {
+    async fn migrate(&self) -> anyhow::Result<()>;
+
+    // Cheapest round-trip the backend offers. Used by /readyz.
+    async fn ping(&self) -> anyhow::Result<()>;
+
+    // ---- node management ----------------------------------------------------
+
+    // Auth tokens reach the store only as auth::hash_auth_token output.
+    async fn insert_node(&self, node: &Node, auth_token_hash: &str) -> anyhow::Result<()>;
+    async fn get_node(&self, id: Uuid) -> anyhow::Result<Option<Node>>;
+    async fn get_node_by_auth_token_hash(&self, token_hash: &str) -> anyhow::Result<Option<Node>>;
+    // Swaps in `new` only while `current` is still the node's hash, so two
+    // rotations racing on one token can't both succeed.
+    async fn replace_auth_token_hash(&self, id: Uuid, current: &str, new: &str) -> anyhow::Result<bool>;
+    async fn list_nodes_by_wallet(&self, wallet: &str) -> anyhow::Result<Vec<Node>>;
+    async fn list_nodes_with_rpc(&self, network: &str) -> anyhow::Result<Vec<Node>>;
+    async fn list_nodes_with_p2p(&self, network: &str) -> anyhow::Result<Vec<Node>>;
+    // Addresses are unique across every node, whatever its status or network.
+    async fn p2p_address_in_use(&self, address: &str) -> anyhow::Result<bool>;
+    async fn list_all_nodes(&self) -> anyhow::Result<Vec<Node>>;
+    async fn list_active_nodes(
+        &self,
+        network: &str,
+        min_height: u64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Node>>;
+    async fn list_recent_proofs(
+        &self,
+        network: &str,
+        limit: i64,
+        verdict: Option<&str>,
+        wallet: Option<&str>,
+    ) -> anyhow::Result<Vec<Proof>>;
+    async fn update_node_status(&self, id: Uuid, status: NodeStatus) -> anyhow::Result<()>;
+    // Moves the node to `to` only if it is still `from`. False otherwise, so a
+    // concurrent suspension can't be undone by an operator's resume.
+    async fn transition_node_status(&self, id: Uuid, from: NodeStatus, to: NodeStatus) -> anyhow::Result<bool>;
+    // Replaces both columns; None clears. False if there was no such node.
+    async fn update_node_details(
+        &self,
+        id: Uuid,
+        label: Option<&str>,
+        rpc_endpoint: Option<&str>,
+    ) -> anyhow::Result<bool>;
+    async fn add_uptime_and_points(
+        &self,
+        id: Uuid,
+        uptime_delta_secs: u64,
+        points_delta: u64,
+    ) -> anyhow::Result<()>;
+    // Debits clamp at zero; returns the delta actually applied.
+    async fn adjust_points(
+        &self,
+        node_id: Uuid,
+        delta: i64,
+        source: LedgerSource,
+        ref_id: Option<&str>,
+    ) -> anyhow::Result<i64>;
+    async fn apply_proof_acceptance(
+        &self,
+        node_id: Uuid,
+        proof_id: Uuid,
+        height: u64,
+        block_hash: &str,
+        points_awarded: u64,
+        proof_at: DateTime<Utc>,
+    ) -> anyhow::Result<()>;
+    // Removes the node and (by cascade) everything hanging off it. False if
+    // there was no such node.
+    async fn delete_node(&self, id: Uuid) -> anyhow::Result<bool>;
+    // Soft delete: status 'deregistered', token, endpoints, label and sealed
+    // RPC credentials cleared; proofs, ledger and points kept. False if there
+    // was no such node or it was already deregistered.
+    async fn deregister_node(&self, id: Uuid) -> anyhow::Result<bool>;
+
+    // ---- admin cleanup ------------------------------------------------------
+
+    // Nodes that proved a height below `min_height` (but above zero).
+    async fn count_fake_height_nodes(&self, min_height: u64) -> anyhow::Result<i64>;
+    // Nodes beyond the first `max_per_wallet` each wallet registered.
+    async fn count_excess_wallet_nodes(&self, max_per_wallet: i64) -> anyhow::Result<i64>;
+    async fn delete_fake_height_nodes(&self, min_height: u64, batch: i64) -> anyhow::Result<u64>;
+    async fn delete_excess_wallet_nodes(&self, max_per_wallet: i64, batch: i64) -> anyhow::Result<u64>;
+
+    // ---- proofs -------------------------------------------------------------
+
+    async fn insert_proof(&self, proof: &Proof) -> anyhow::Result<()>;
+    // False if the (node, height, hash) triple was already there.
+    async fn try_insert_proof(&self, proof: &Proof) -> anyhow::Result<bool>;
+    async fn list_proofs_by_wallet(&self, wallet: &str, limit: i64) -> anyhow::Result<Vec<Proof>>;
+    async fn list_proofs_by_node(&self, node_id: Uuid, limit: i64) -> anyhow::Result<Vec<Proof>>;
+    async fn count_proof(&self, node_id: Uuid, height: u64, block_hash: &str) -> anyhow::Result<i64>;
+    async fn node_daily_series(&self, node_id: Uuid, days: i64) -> anyhow::Result<Vec<NodeDailyBucket>>;
+    async fn last_accepted_proof_for_node(&self, node_id: Uuid) -> anyhow::Result<Option<Proof>>;
+    async fn list_pending_proofs(&self, limit: i64) -> anyhow::Result<Vec<Proof>>;
+    // False if the proof was no longer pending; only credit points on true.
+    async fn resolve_pending_proof(
+        &self,
+        id: Uuid,
+        verdict: ProofVerdict,
+        reject_reason: Option<&str>,
+        points_awarded: u64,
+    ) -> anyhow::Result<bool>;
+    // Pending -> accepted plus apply_proof_acceptance's node credit and ledger
+    // row, all in one transaction. False if the proof was no longer pending.
+    async fn accept_pending_proof(&self, proof: &Proof, points_awarded: u64) -> anyhow::Result<bool>;
+    async fn list_unfinalized_proofs(&self, max_height: u64, limit: i64) -> anyhow::Result<Vec<Proof>>;
+    async fn finalize_proof(&self, id: Uuid, at: DateTime<Utc>) -> anyhow::Result<bool>;
+    async fn mark_proof_reorged(&self, id: Uuid, reason: &str, at: DateTime<Utc>) -> anyhow::Result<bool>;
+
+    // ---- points ledger ------------------------------------------------------
+
+    async fn ledger_for_node(&self, node_id: Uuid, limit: i64) -> anyhow::Result<Vec<LedgerEntry>>;
+    async fn points_mismatches(&self) -> anyhow::Result<Vec<PointsMismatch>>;
+
+    // ---- challenges ---------------------------------------------------------
+
+    async fn insert_challenge(&self, ch: &Challenge) -> anyhow::Result<()>;
+    async fn get_open_challenge_for_node(&self, node_id: Uuid) -> anyhow::Result<Option<Challenge>>;
+    async fn get_challenge(&self, id: Uuid) -> anyhow::Result<Option<Challenge>>;
+    async fn mark_challenge_answered(
+        &self,
+        id: Uuid,
+        passed: bool,
+        answer_hash: Option<&str>,
+        when: DateTime<Utc>,
+    ) -> anyhow::Result<()>;
+    async fn list_challenges_for_node(&self, node_id: Uuid, limit: i64) -> anyhow::Result<Vec<Challenge>>;
+    async fn consecutive_challenge_failures(&self, node_id: Uuid) -> anyhow::Result<u32>;
+    async fn expire_old_challenges(&self, now: DateTime<Utc>) -> anyhow::Result<u64>;
+
+    // ---- p2p probes ---------------------------------------------------------
+
+    async fn record_p2p_probe(&self, probe: &P2pProbe) -> anyhow::Result<()>;
+    // Whether any probe of this node has been paid yet.
+    async fn p2p_probe_paid(&self, node_id: Uuid) -> anyhow::Result<bool>;
+    async fn list_p2p_probes_for_node(&self, node_id: Uuid, limit: i64) -> anyhow::Result<Vec<P2pProbe>>;
+
+    // ---- operator RPC credentials -------------------------------------------
+
+    // `sealed` is `CredentialKey::seal` output; the store never sees plaintext.
+    async fn put_rpc_credential(&self, node_id: Uuid, sealed: &str, now: DateTime<Utc>) -> anyhow::Result<()>;
+    async fn get_rpc_credential(&self, node_id: Uuid) -> anyhow::Result<Option<String>>;
+    async fn delete_rpc_credential(&self, node_id: Uuid) -> anyhow::Result<()>;
+
+    // ---- delegated hot keys -------------------------------------------------
+
+    // Inserts, or replaces the row for the same (node, delegate) — including
+    // a revoked one.
+    async fn upsert_delegation(&self, delegation: &Delegation) -> anyhow::Result<()>;
+    async fn get_delegation(&self, node_id: Uuid, delegate: &str) -> anyhow::Result<Option<Delegation>>;
+    // Newest first, revoked and expired included.
+    async fn list_delegations(&self, node_id: Uuid) -> anyhow::Result<Vec<Delegation>>;
+    // False if there is no such delegation or it was already revoked.
+    async fn revoke_delegation(&self, node_id: Uuid, delegate: &str, at: DateTime<Utc>) -> anyhow::Result<bool>;
+
+    // ---- exposed-RPC polls --------------------------------------------------
+
+    async fn upsert_rpc_poll_stats(&self, stats: &RpcPollStats) -> anyhow::Result<()>;
+    async fn get_rpc_poll_stats(&self, node_id: Uuid) -> anyhow::Result<Option<RpcPollStats>>;
+    // Every polled node on `network`, keyed for the poll loop's backoff check.
+    async fn list_rpc_poll_stats(&self, network: &str) -> anyhow::Result<Vec<RpcPollStats>>;
+
+    // ---- network-upgrade readiness ------------------------------------------
+
+    async fn upsert_node_chain_info(&self, info: &NodeChainInfo) -> anyhow::Result<()>;
+    async fn get_node_chain_info(&self, node_id: Uuid) -> anyhow::Result<Option<NodeChainInfo>>;
+    // Nodes on `network` that reported at or after `since`.
+    async fn list_node_chain_info(&self, network: &str, since: DateTime<Utc>) -> anyhow::Result<Vec<NodeChainInfo>>;
+    // Records the node as ready for the upgrade with `branch_id` and credits
+    // `points` (UpgradeReadiness, ref_id = branch_id). False, and nothing
+    // credited, if it already was.
+    async fn record_upgrade_readiness(
+        &self,
+        node_id: Uuid,
+        branch_id: &str,
+        points: u64,
+        at: DateTime<Utc>,
+    ) -> anyhow::Result<bool>;
+
+    // ---- stats --------------------------------------------------------------
+
+    async fn network_stats(&self, network: &str, min_height: u64) -> anyhow::Result<NetworkStats>;
+    async fn leaderboard(&self, network: &str, min_height: u64, limit: i64) -> anyhow::Result<Vec<WalletStats>>;
+    async fn wallet_stats(&self, wallet: &str) -> anyhow::Result<WalletStats>;
+
+    // ---- replay protection --------------------------------------------------
+
+    // True the first time `wallet` uses `nonce`. `signed_at` is the timestamp
+    // the request was signed with, which `prune_nonces` goes by.
+    async fn try_use_nonce(&self, nonce: &str, wallet: &str, signed_at: DateTime<Utc>) -> anyhow::Result<bool>;
+    // Deletes nonces signed before `before`. Returns how many.
+    async fn prune_nonces(&self, before: DateTime<Utc>) -> anyhow::Result<u64>;
+
+    // ---- job leases ---------------------------------------------------------
+
+    // Takes `job` for `holder` until `expires_at`, or extends it if `holder`
+    // already has it. False while another holder's lease is unexpired at `now`.
+    async fn acquire_lease(
+        &self,
+        job: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<bool>;
+
+    // ---- job schedule -------------------------------------------------------
+
+    // Creates the job's row due at `next_run_at` unless it already has one.
+    async fn schedule_job(&self, job: &str, next_run_at: DateTime<Utc>) -> anyhow::Result<()>;
+    async fn get_job_run(&self, job: &str) -> anyhow::Result<Option<JobRun>>;
+    // Records a run that started at `started_at`. An `error` also becomes the
+    // job's last error; a clean run leaves the previous one in place.
+    async fn finish_job_run(
+        &self,
+        job: &str,
+        started_at: DateTime<Utc>,
+        finished_at: DateTime<Utc>,
+        next_run_at: DateTime<Utc>,
+        error: Option<&str>,
+    ) -> anyhow::Result<()>;
+    async fn list_job_runs(&self) -> anyhow::Result<Vec<JobRun>>;
+
+    // ---- trusted-rpc disagreements ------------------------------------------
+
+    async fn insert_rpc_disagreement(&self, d: &RpcDisagreement) -> anyhow::Result<()>;
+    // Observed at or after `since`, newest first.
+    async fn list_rpc_disagreements(&self, since: DateTime<Utc>, limit: i64) -> anyhow::Result<Vec<RpcDisagreement>>;
+    // Deletes those observed before `before`. Returns how many.
+    async fn prune_rpc_disagreements(&self, before: DateTime<Utc>) -> anyhow::Result<u64>;
+
+    // ---- snapshots ----------------------------------------------------------
+
+    // The snapshot row, every leaf, and each leaf's wallet baseline advanced to
+    // `points_to`, in one transaction: a root is never published over leaves
+    // that weren't stored, and no baseline moves unless the whole cycle lands.
+    // Returns the snapshot id.
+    async fn insert_snapshot_with_leaves(
+        &self,
+        cycle: i64,
+        merkle_root: &str,
+        total_points: u64,
+        spl_mint: Option<&str>,
+        leaves: &[NewSnapshotLeaf],
+    ) -> anyhow::Result<i64>;
+    async fn snapshot_baselines(&self) -> anyhow::Result<HashMap<String, u64>>;
+    async fn latest_snapshot(&self) -> anyhow::Result<Option<(i64, i64, String, u64)>>;
+    async fn snapshot_leaf_for_wallet(
+        &self,
+        snapshot_id: i64,
+        wallet: &str,
+    ) -> anyhow::Result<Option<(u64, String, String)>>;
+    async fn latest_snapshot_leaf_for_wallet(&self, wallet: &str) -> anyhow::Result<Option<SnapshotLeaf>>;
+    // Each delta cycle is its own claim, so a wallet that skipped one still
+    // needs that cycle's leaf. Oldest first.
+    async fn snapshot_leaves_for_wallet(
+        &self,
+        wallet: &str,
+        after_cycle: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<SnapshotLeaf>>;
+    async fn total_points_per_wallet(
+        &self,
+        network: &str,
+        finalized_only: bool,
+    ) -> anyhow::Result<Vec<(String, u64)>>;
+}
+
+// Timestamps are RFC3339 text, so time windows are computed here and bound
+// as strings rather than left to each dialect's date functions (SQLite's
+// datetime() output doesn't even sort against RFC3339).
+
+// "Active" = proved within the last hour.
+pub(crate) fn active_cutoff() -> String {
+    (Utc::now() - chrono::Duration::hours(1)).to_rfc3339()
+}
+
+// Whole days: a bare date sorts before every timestamp on that day.
+pub(crate) fn daily_series_cutoff(days: i64) -> String {
+    (Utc::now() - chrono::Duration::days(days)).format("%Y-%m-%d").to_string()
+}
+
+pub(crate) fn parse_challenge_kind(s: &str) -> anyhow::Result<ChallengeKind> {
+    ChallengeKind::parse(s).ok_or_else(|| anyhow!("unknown challenge kind: {}", s))
+}
+
+// Delegation scopes are stored comma-separated.
+pub(crate) fn parse_delegate_scopes(s: &str) -> anyhow::Result<Vec<DelegateScope>> {
+    DelegateScope::parse_list(s).ok_or_else(|| anyhow!("unknown delegate scopes: {}", s))
+}
+
+pub(crate) fn join_delegate_scopes(scopes: &[DelegateScope]) -> String {
+    scopes.iter().map(DelegateScope::as_str).collect::<Vec<_>>().join(",")
+}
+
+pub(crate) fn parse_dt(s: &str) -> anyhow::Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(s)
+        .with_context(|| format!("parsing rfc3339 timestamp {:?}", s))?
+        .with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn connect_picks_backend_by_scheme() {
+        let store = connect("sqlite::memory:").await.unwrap();
+        store.migrate().await.unwrap();
+        store.ping().await.unwrap();
+        let err = connect("mysql://localhost/depinzcash").await.err().unwrap();
+        assert!(err.to_string().contains("unsupported DATABASE_URL scheme"));
+    }
+}
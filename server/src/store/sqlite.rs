@@ -1,16 +1,26 @@
 use anyhow::{anyhow, Context};
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::{
-    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteSynchronous},
+    sqlite::{
+        SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePool, SqlitePoolOptions,
+        SqliteSynchronous,
+    },
     ConnectOptions, Row,
 };
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::time::Duration;
 use uuid::Uuid;
 
+use super::{
+    active_cutoff, daily_series_cutoff, join_delegate_scopes, parse_challenge_kind, parse_delegate_scopes,
+    parse_dt, Store,
+};
 use crate::types::{
-    Challenge, ChallengeKind, ChallengeStatus, NetworkStats, Node, NodeDailyBucket, NodeKind,
-    NodeStatus, Proof, ProofVerdict, WalletStats,
+    ChainInfo, ChainInfoSource, Challenge, ChallengeOrigin, Delegation, ChallengeStatus, JobRun, LedgerEntry,
+    LedgerSource, NetworkStats, Node, NodeChainInfo, NodeDailyBucket, NodeKind, NodeStatus, P2pProbe, PointsMismatch, Proof,
+    ProofVerdict, RpcDisagreement, RpcPollOutcome, RpcPollStats, NewSnapshotLeaf, SnapshotLeaf, WalletStats,
 };
 
 #[derive(Clone)]
@@ -46,36 +56,45 @@ impl SqliteStore {
         Ok(Self { pool })
     }
 
-    pub async fn migrate(&self) -> anyhow::Result<()> {
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn migrate(&self) -> anyhow::Result<()> {
         sqlx::migrate!("./migrations")
             .run(&self.pool)
             .await
             .context("running migrations")?;
-        Ok(())
+        hash_legacy_auth_tokens(&self.pool).await
     }
 
-    pub fn pool(&self) -> &SqlitePool {
-        &self.pool
+    async fn ping(&self) -> anyhow::Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
     }
 
     // ---- node management ----------------------------------------------------
 
     #[allow(clippy::too_many_arguments)]
-    pub async fn insert_node(
+    async fn insert_node(
         &self,
         node: &Node,
-        auth_token: &str,
+        auth_token_hash: &str,
     ) -> anyhow::Result<()> {
         sqlx::query(
-            r#"INSERT INTO nodes (id, wallet, kind, label, rpc_endpoint, network, status,
-                last_height, last_block_hash, last_proof_at, registered_at, points, uptime_seconds, auth_token)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)"#,
+            r#"INSERT INTO nodes (id, wallet, kind, label, rpc_endpoint, p2p_address, network, status,
+                last_height, last_block_hash, last_proof_at, registered_at, points, uptime_seconds, auth_token, auth_token_hash)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, '', ?15)"#,
         )
         .bind(node.id.to_string())
         .bind(&node.wallet)
         .bind(node.kind.as_str())
         .bind(&node.label)
         .bind(&node.rpc_endpoint)
+        .bind(&node.p2p_address)
         .bind(&node.network)
         .bind(node.status.as_str())
         .bind(node.last_height.map(|h| h as i64))
@@ -84,16 +103,16 @@ impl SqliteStore {
         .bind(node.registered_at.to_rfc3339())
         .bind(node.points as i64)
         .bind(node.uptime_seconds as i64)
-        .bind(auth_token)
+        .bind(auth_token_hash)
         .execute(&self.pool)
         .await
         .context("inserting node")?;
         Ok(())
     }
 
-    pub async fn get_node(&self, id: Uuid) -> anyhow::Result<Option<Node>> {
+    async fn get_node(&self, id: Uuid) -> anyhow::Result<Option<Node>> {
         let row = sqlx::query(
-            r#"SELECT id, wallet, kind, label, rpc_endpoint, network, status,
+            r#"SELECT id, wallet, kind, label, rpc_endpoint, p2p_address, network, status,
                 last_height, last_block_hash, last_proof_at, registered_at, points, uptime_seconds
                 FROM nodes WHERE id = ?1"#,
         )
@@ -103,21 +122,31 @@ impl SqliteStore {
         row.map(node_from_row).transpose()
     }
 
-    pub async fn get_node_by_auth_token(&self, token: &str) -> anyhow::Result<Option<Node>> {
+    async fn get_node_by_auth_token_hash(&self, token_hash: &str) -> anyhow::Result<Option<Node>> {
         let row = sqlx::query(
-            r#"SELECT id, wallet, kind, label, rpc_endpoint, network, status,
+            r#"SELECT id, wallet, kind, label, rpc_endpoint, p2p_address, network, status,
                 last_height, last_block_hash, last_proof_at, registered_at, points, uptime_seconds
-                FROM nodes WHERE auth_token = ?1"#,
+                FROM nodes WHERE auth_token_hash = ?1"#,
         )
-        .bind(token)
+        .bind(token_hash)
         .fetch_optional(&self.pool)
         .await?;
         row.map(node_from_row).transpose()
     }
 
-    pub async fn list_nodes_by_wallet(&self, wallet: &str) -> anyhow::Result<Vec<Node>> {
+    async fn replace_auth_token_hash(&self, id: Uuid, current: &str, new: &str) -> anyhow::Result<bool> {
+        let res = sqlx::query("UPDATE nodes SET auth_token_hash = ?1 WHERE id = ?2 AND auth_token_hash = ?3")
+            .bind(new)
+            .bind(id.to_string())
+            .bind(current)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    async fn list_nodes_by_wallet(&self, wallet: &str) -> anyhow::Result<Vec<Node>> {
         let rows = sqlx::query(
-            r#"SELECT id, wallet, kind, label, rpc_endpoint, network, status,
+            r#"SELECT id, wallet, kind, label, rpc_endpoint, p2p_address, network, status,
                 last_height, last_block_hash, last_proof_at, registered_at, points, uptime_seconds
                 FROM nodes WHERE wallet = ?1 ORDER BY registered_at ASC"#,
         )
@@ -129,14 +158,14 @@ impl SqliteStore {
 
     // Nodes that registered with an exposed JSON-RPC URL — the targets for the
     // exposed_rpc_loop poller.
-    pub async fn list_nodes_with_rpc(&self, network: &str) -> anyhow::Result<Vec<Node>> {
+    async fn list_nodes_with_rpc(&self, network: &str) -> anyhow::Result<Vec<Node>> {
         let rows = sqlx::query(
-            r#"SELECT id, wallet, kind, label, rpc_endpoint, network, status,
+            r#"SELECT id, wallet, kind, label, rpc_endpoint, p2p_address, network, status,
                 last_height, last_block_hash, last_proof_at, registered_at, points, uptime_seconds
                 FROM nodes
                 WHERE network = ?1
                   AND rpc_endpoint IS NOT NULL
-                  AND status != 'suspended'
+                  AND status NOT IN ('suspended', 'paused', 'deregistered')
                 ORDER BY registered_at ASC"#,
         )
         .bind(network)
@@ -145,9 +174,34 @@ impl SqliteStore {
         rows.into_iter().map(node_from_row).collect()
     }
 
-    pub async fn list_all_nodes(&self) -> anyhow::Result<Vec<Node>> {
+    // Full nodes that advertised a P2P address — the targets for p2p_probe_loop.
+    async fn list_nodes_with_p2p(&self, network: &str) -> anyhow::Result<Vec<Node>> {
+        let rows = sqlx::query(
+            r#"SELECT id, wallet, kind, label, rpc_endpoint, p2p_address, network, status,
+                last_height, last_block_hash, last_proof_at, registered_at, points, uptime_seconds
+                FROM nodes
+                WHERE network = ?1
+                  AND p2p_address IS NOT NULL
+                  AND status NOT IN ('suspended', 'paused', 'deregistered')
+                ORDER BY registered_at ASC"#,
+        )
+        .bind(network)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(node_from_row).collect()
+    }
+
+    async fn p2p_address_in_use(&self, address: &str) -> anyhow::Result<bool> {
+        let found: Option<String> = sqlx::query_scalar("SELECT id FROM nodes WHERE p2p_address = ?1")
+            .bind(address)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(found.is_some())
+    }
+
+    async fn list_all_nodes(&self) -> anyhow::Result<Vec<Node>> {
         let rows = sqlx::query(
-            r#"SELECT id, wallet, kind, label, rpc_endpoint, network, status,
+            r#"SELECT id, wallet, kind, label, rpc_endpoint, p2p_address, network, status,
                 last_height, last_block_hash, last_proof_at, registered_at, points, uptime_seconds
                 FROM nodes ORDER BY registered_at ASC"#,
         )
@@ -159,24 +213,25 @@ impl SqliteStore {
     // Public explorer: nodes that have at least one accepted proof at a real
     // height, ordered by most recently active. Filters out registration-only
     // spam and height-fabricators.
-    pub async fn list_active_nodes(
+    async fn list_active_nodes(
         &self,
         network: &str,
         min_height: u64,
         limit: i64,
     ) -> anyhow::Result<Vec<Node>> {
         let rows = sqlx::query(
-            r#"SELECT id, wallet, kind, label, rpc_endpoint, network, status,
+            r#"SELECT id, wallet, kind, label, rpc_endpoint, p2p_address, network, status,
                 last_height, last_block_hash, last_proof_at, registered_at, points, uptime_seconds
                 FROM nodes
                 WHERE network = ?1 AND last_proof_at IS NOT NULL AND last_height >= ?2
-                  AND last_proof_at >= datetime('now', '-1 hour')
+                  AND last_proof_at >= ?4
                 ORDER BY last_proof_at DESC
                 LIMIT ?3"#,
         )
         .bind(network)
         .bind(min_height as i64)
         .bind(limit)
+        .bind(active_cutoff())
         .fetch_all(&self.pool)
         .await?;
         rows.into_iter().map(node_from_row).collect()
@@ -184,7 +239,7 @@ impl SqliteStore {
 
     // Global recent proofs feed for the explorer page, with optional verdict
     // and wallet filters. Filters are AND'd together.
-    pub async fn list_recent_proofs(
+    async fn list_recent_proofs(
         &self,
         network: &str,
         limit: i64,
@@ -194,7 +249,8 @@ impl SqliteStore {
         let mut sql = String::from(
             "SELECT p.id, p.node_id, p.wallet, p.claimed_height, p.claimed_block_hash,
                     p.proof_timestamp, p.binary_hash, p.uptime_seconds, p.peers,
-                    p.verdict, p.reject_reason, p.points_awarded, p.received_at
+                    p.verdict, p.reject_reason, p.points_awarded, p.received_at,
+                    p.trusted_tip_height, p.finalized_at
              FROM proofs p
              JOIN nodes n ON n.id = p.node_id
              WHERE n.network = ?",
@@ -219,7 +275,7 @@ impl SqliteStore {
         rows.into_iter().map(proof_from_row).collect()
     }
 
-    pub async fn update_node_status(&self, id: Uuid, status: NodeStatus) -> anyhow::Result<()> {
+    async fn update_node_status(&self, id: Uuid, status: NodeStatus) -> anyhow::Result<()> {
         sqlx::query("UPDATE nodes SET status = ?1 WHERE id = ?2")
             .bind(status.as_str())
             .bind(id.to_string())
@@ -228,58 +284,244 @@ impl SqliteStore {
         Ok(())
     }
 
-    pub async fn add_uptime_and_points(
+    async fn transition_node_status(&self, id: Uuid, from: NodeStatus, to: NodeStatus) -> anyhow::Result<bool> {
+        let res = sqlx::query("UPDATE nodes SET status = ?1 WHERE id = ?2 AND status = ?3")
+            .bind(to.as_str())
+            .bind(id.to_string())
+            .bind(from.as_str())
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    async fn update_node_details(
+        &self,
+        id: Uuid,
+        label: Option<&str>,
+        rpc_endpoint: Option<&str>,
+    ) -> anyhow::Result<bool> {
+        let res = sqlx::query("UPDATE nodes SET label = ?1, rpc_endpoint = ?2 WHERE id = ?3")
+            .bind(label)
+            .bind(rpc_endpoint)
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    // Uptime tick. Ledger source is always `uptime`.
+    async fn add_uptime_and_points(
         &self,
         id: Uuid,
         uptime_delta_secs: u64,
         points_delta: u64,
     ) -> anyhow::Result<()> {
-        sqlx::query(
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(
             "UPDATE nodes SET uptime_seconds = uptime_seconds + ?1, points = points + ?2 WHERE id = ?3",
         )
         .bind(uptime_delta_secs as i64)
         .bind(points_delta as i64)
         .bind(id.to_string())
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
+        if res.rows_affected() == 1 {
+            insert_ledger_row(&mut tx, id, points_delta as i64, LedgerSource::Uptime, None).await?;
+        }
+        tx.commit().await?;
         Ok(())
     }
 
-    pub async fn apply_proof_acceptance(
+    // Any non-proof, non-uptime change to a node's points (challenge bonus,
+    // admin correction). Debits clamp at zero; returns the delta actually
+    // applied, which is also what the ledger records.
+    async fn adjust_points(
+        &self,
+        node_id: Uuid,
+        delta: i64,
+        source: LedgerSource,
+        ref_id: Option<&str>,
+    ) -> anyhow::Result<i64> {
+        let mut tx = self.pool.begin().await?;
+        // Clamp against the balance and record the debit in one statement.
+        // It is the transaction's first write, so SQLite holds the write lock
+        // from here to commit and no other adjustment can slip in between.
+        let applied: Option<i64> = sqlx::query_scalar(
+            r#"INSERT INTO points_ledger (node_id, delta, source, ref_id, created_at)
+               SELECT id, MAX(?1, -points), ?2, ?3, ?4
+               FROM nodes WHERE id = ?5 AND MAX(?1, -points) <> 0
+               RETURNING delta"#,
+        )
+        .bind(delta)
+        .bind(source.as_str())
+        .bind(ref_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(node_id.to_string())
+        .fetch_optional(&mut *tx)
+        .await
+        .context("writing points ledger")?;
+        let Some(applied) = applied else {
+            // Nothing to apply (zero delta, or a debit from an empty balance)
+            // — as long as the node exists.
+            let exists: Option<String> = sqlx::query_scalar("SELECT id FROM nodes WHERE id = ?1")
+                .bind(node_id.to_string())
+                .fetch_optional(&mut *tx)
+                .await?;
+            return match exists {
+                Some(_) => Ok(0),
+                None => Err(anyhow!("node {node_id} not found")),
+            };
+        };
+        sqlx::query("UPDATE nodes SET points = points + ?1 WHERE id = ?2")
+            .bind(applied)
+            .bind(node_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(applied)
+    }
+
+    async fn apply_proof_acceptance(
         &self,
         node_id: Uuid,
+        proof_id: Uuid,
         height: u64,
         block_hash: &str,
         points_awarded: u64,
         proof_at: DateTime<Utc>,
     ) -> anyhow::Result<()> {
-        sqlx::query(
-            r#"UPDATE nodes
-               SET last_height = ?1,
-                   last_block_hash = ?2,
-                   last_proof_at = ?3,
-                   points = points + ?4,
-                   status = 'active'
-               WHERE id = ?5"#,
+        let mut tx = self.pool.begin().await?;
+        credit_proof_acceptance(&mut tx, node_id, proof_id, height, block_hash, points_awarded, proof_at).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn accept_pending_proof(&self, proof: &Proof, points_awarded: u64) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(
+            r#"UPDATE proofs
+               SET verdict = 'accepted', reject_reason = NULL, points_awarded = ?1
+               WHERE id = ?2 AND verdict = 'pending'"#,
         )
-        .bind(height as i64)
-        .bind(block_hash)
-        .bind(proof_at.to_rfc3339())
         .bind(points_awarded as i64)
-        .bind(node_id.to_string())
+        .bind(proof.id.to_string())
+        .execute(&mut *tx)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        credit_proof_acceptance(
+            &mut tx,
+            proof.node_id,
+            proof.id,
+            proof.claimed_height,
+            &proof.claimed_block_hash,
+            points_awarded,
+            proof.proof_timestamp,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn delete_node(&self, id: Uuid) -> anyhow::Result<bool> {
+        let res = sqlx::query("DELETE FROM nodes WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    async fn deregister_node(&self, id: Uuid) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(
+            r#"UPDATE nodes
+               SET status = 'deregistered', auth_token_hash = NULL, label = NULL,
+                   rpc_endpoint = NULL, p2p_address = NULL
+               WHERE id = ?1 AND status <> 'deregistered'"#,
+        )
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query("DELETE FROM node_rpc_credentials WHERE node_id = ?1")
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    // ---- admin cleanup ------------------------------------------------------
+
+    async fn count_fake_height_nodes(&self, min_height: u64) -> anyhow::Result<i64> {
+        let n: i64 = sqlx::query_scalar(
+            r#"SELECT COUNT(1) FROM nodes
+               WHERE last_height IS NOT NULL AND last_height < ?1 AND last_height > 0"#,
+        )
+        .bind(min_height as i64)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(n)
+    }
+
+    // ROW_NUMBER over (PARTITION BY wallet ORDER BY registered_at ASC) — keep
+    // the first N, count the rest. Deregistered nodes don't take a slot.
+    async fn count_excess_wallet_nodes(&self, max_per_wallet: i64) -> anyhow::Result<i64> {
+        let n: i64 = sqlx::query_scalar(
+            r#"SELECT COUNT(1) FROM (
+                 SELECT id, ROW_NUMBER() OVER (PARTITION BY wallet ORDER BY registered_at ASC) AS rn
+                 FROM nodes WHERE status <> 'deregistered'
+               ) WHERE rn > ?1"#,
+        )
+        .bind(max_per_wallet)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(n)
+    }
+
+    async fn delete_fake_height_nodes(&self, min_height: u64, batch: i64) -> anyhow::Result<u64> {
+        let res = sqlx::query(
+            r#"DELETE FROM nodes WHERE id IN (
+                 SELECT id FROM nodes
+                 WHERE last_height IS NOT NULL AND last_height < ?1 AND last_height > 0
+                 LIMIT ?2
+               )"#,
+        )
+        .bind(min_height as i64)
+        .bind(batch)
         .execute(&self.pool)
         .await?;
-        Ok(())
+        Ok(res.rows_affected())
+    }
+
+    async fn delete_excess_wallet_nodes(&self, max_per_wallet: i64, batch: i64) -> anyhow::Result<u64> {
+        let res = sqlx::query(
+            r#"DELETE FROM nodes WHERE id IN (
+                 SELECT id FROM (
+                   SELECT id, ROW_NUMBER() OVER (PARTITION BY wallet ORDER BY registered_at ASC) AS rn
+                   FROM nodes WHERE status <> 'deregistered'
+                 ) WHERE rn > ?1
+                 LIMIT ?2
+               )"#,
+        )
+        .bind(max_per_wallet)
+        .bind(batch)
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected())
     }
 
     // ---- proofs -------------------------------------------------------------
 
-    pub async fn insert_proof(&self, proof: &Proof) -> anyhow::Result<()> {
+    async fn insert_proof(&self, proof: &Proof) -> anyhow::Result<()> {
         sqlx::query(
             r#"INSERT INTO proofs (id, node_id, wallet, claimed_height, claimed_block_hash,
                 proof_timestamp, binary_hash, uptime_seconds, peers, verdict, reject_reason,
-                points_awarded, received_at)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"#,
+                points_awarded, received_at, trusted_tip_height, finalized_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)"#,
         )
         .bind(proof.id.to_string())
         .bind(proof.node_id.to_string())
@@ -294,6 +536,8 @@ impl SqliteStore {
         .bind(&proof.reject_reason)
         .bind(proof.points_awarded as i64)
         .bind(proof.received_at.to_rfc3339())
+        .bind(proof.trusted_tip_height.map(|h| h as i64))
+        .bind(proof.finalized_at.map(|t| t.to_rfc3339()))
         .execute(&self.pool)
         .await
         .context("inserting proof")?;
@@ -303,12 +547,12 @@ impl SqliteStore {
     // Race-safe insert. Returns true if the row was inserted, false if a row
     // with the same (node_id, claimed_height, claimed_block_hash) already
     // existed — concurrent preflight + insert no longer can produce a 500.
-    pub async fn try_insert_proof(&self, proof: &Proof) -> anyhow::Result<bool> {
+    async fn try_insert_proof(&self, proof: &Proof) -> anyhow::Result<bool> {
         let res = sqlx::query(
             r#"INSERT OR IGNORE INTO proofs (id, node_id, wallet, claimed_height, claimed_block_hash,
                 proof_timestamp, binary_hash, uptime_seconds, peers, verdict, reject_reason,
-                points_awarded, received_at)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"#,
+                points_awarded, received_at, trusted_tip_height, finalized_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)"#,
         )
         .bind(proof.id.to_string())
         .bind(proof.node_id.to_string())
@@ -323,16 +567,19 @@ impl SqliteStore {
         .bind(&proof.reject_reason)
         .bind(proof.points_awarded as i64)
         .bind(proof.received_at.to_rfc3339())
+        .bind(proof.trusted_tip_height.map(|h| h as i64))
+        .bind(proof.finalized_at.map(|t| t.to_rfc3339()))
         .execute(&self.pool)
         .await
         .context("try-inserting proof")?;
         Ok(res.rows_affected() == 1)
     }
 
-    pub async fn list_proofs_by_wallet(&self, wallet: &str, limit: i64) -> anyhow::Result<Vec<Proof>> {
+    async fn list_proofs_by_wallet(&self, wallet: &str, limit: i64) -> anyhow::Result<Vec<Proof>> {
         let rows = sqlx::query(
             r#"SELECT id, node_id, wallet, claimed_height, claimed_block_hash, proof_timestamp,
-                binary_hash, uptime_seconds, peers, verdict, reject_reason, points_awarded, received_at
+                binary_hash, uptime_seconds, peers, verdict, reject_reason, points_awarded, received_at,
+                trusted_tip_height, finalized_at
                 FROM proofs WHERE wallet = ?1 ORDER BY received_at DESC LIMIT ?2"#,
         )
         .bind(wallet)
@@ -342,10 +589,11 @@ impl SqliteStore {
         rows.into_iter().map(proof_from_row).collect()
     }
 
-    pub async fn list_proofs_by_node(&self, node_id: Uuid, limit: i64) -> anyhow::Result<Vec<Proof>> {
+    async fn list_proofs_by_node(&self, node_id: Uuid, limit: i64) -> anyhow::Result<Vec<Proof>> {
         let rows = sqlx::query(
             r#"SELECT id, node_id, wallet, claimed_height, claimed_block_hash, proof_timestamp,
-                binary_hash, uptime_seconds, peers, verdict, reject_reason, points_awarded, received_at
+                binary_hash, uptime_seconds, peers, verdict, reject_reason, points_awarded, received_at,
+                trusted_tip_height, finalized_at
                 FROM proofs WHERE node_id = ?1 ORDER BY received_at DESC LIMIT ?2"#,
         )
         .bind(node_id.to_string())
@@ -355,7 +603,7 @@ impl SqliteStore {
         rows.into_iter().map(proof_from_row).collect()
     }
 
-    pub async fn count_proof(&self, node_id: Uuid, height: u64, block_hash: &str) -> anyhow::Result<i64> {
+    async fn count_proof(&self, node_id: Uuid, height: u64, block_hash: &str) -> anyhow::Result<i64> {
         let count: i64 = sqlx::query_scalar(
             "SELECT COUNT(1) FROM proofs WHERE node_id = ?1 AND claimed_height = ?2 AND claimed_block_hash = ?3",
         )
@@ -369,7 +617,7 @@ impl SqliteStore {
 
     // Daily aggregates for a node, oldest first. Used by the per-node dashboard's
     // bar chart — date strings are ISO yyyy-mm-dd derived from received_at.
-    pub async fn node_daily_series(
+    async fn node_daily_series(
         &self,
         node_id: Uuid,
         days: i64,
@@ -379,15 +627,15 @@ impl SqliteStore {
             r#"SELECT substr(received_at, 1, 10) AS day,
                       COUNT(1) AS proofs,
                       SUM(CASE WHEN verdict = 'accepted' THEN 1 ELSE 0 END) AS accepted,
-                      COALESCE(SUM(points_awarded), 0) AS points
+                      COALESCE(SUM(CASE WHEN verdict = 'accepted' THEN points_awarded ELSE 0 END), 0) AS points
                FROM proofs
                WHERE node_id = ?1
-                 AND received_at >= datetime('now', '-' || ?2 || ' days')
+                 AND received_at >= ?2
                GROUP BY day
                ORDER BY day ASC"#,
         )
         .bind(node_id.to_string())
-        .bind(days)
+        .bind(daily_series_cutoff(days))
         .fetch_all(&self.pool)
         .await?;
 
@@ -403,10 +651,11 @@ impl SqliteStore {
             .collect()
     }
 
-    pub async fn last_accepted_proof_for_node(&self, node_id: Uuid) -> anyhow::Result<Option<Proof>> {
+    async fn last_accepted_proof_for_node(&self, node_id: Uuid) -> anyhow::Result<Option<Proof>> {
         let row = sqlx::query(
             r#"SELECT id, node_id, wallet, claimed_height, claimed_block_hash, proof_timestamp,
-                binary_hash, uptime_seconds, peers, verdict, reject_reason, points_awarded, received_at
+                binary_hash, uptime_seconds, peers, verdict, reject_reason, points_awarded, received_at,
+                trusted_tip_height, finalized_at
                 FROM proofs WHERE node_id = ?1 AND verdict = 'accepted'
                 ORDER BY received_at DESC LIMIT 1"#,
         )
@@ -416,17 +665,190 @@ impl SqliteStore {
         row.map(proof_from_row).transpose()
     }
 
+    // Oldest-first page of proofs still waiting on the trusted quorum. Feeds
+    // the scheduler's pending re-check.
+    async fn list_pending_proofs(&self, limit: i64) -> anyhow::Result<Vec<Proof>> {
+        let rows = sqlx::query(
+            r#"SELECT id, node_id, wallet, claimed_height, claimed_block_hash, proof_timestamp,
+                binary_hash, uptime_seconds, peers, verdict, reject_reason, points_awarded, received_at,
+                trusted_tip_height, finalized_at
+                FROM proofs WHERE verdict = 'pending'
+                ORDER BY received_at ASC LIMIT ?1"#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(proof_from_row).collect()
+    }
+
+    // Moves a proof out of 'pending'. Returns false if it was no longer pending
+    // (already resolved by a concurrent worker) — callers must only credit
+    // points when this returns true.
+    async fn resolve_pending_proof(
+        &self,
+        id: Uuid,
+        verdict: ProofVerdict,
+        reject_reason: Option<&str>,
+        points_awarded: u64,
+    ) -> anyhow::Result<bool> {
+        let res = sqlx::query(
+            r#"UPDATE proofs
+               SET verdict = ?1, reject_reason = ?2, points_awarded = ?3
+               WHERE id = ?4 AND verdict = 'pending'"#,
+        )
+        .bind(verdict.as_str())
+        .bind(reject_reason)
+        .bind(points_awarded as i64)
+        .bind(id.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    // Accepted proofs at or below `max_height` that the confirmation job has
+    // not yet re-checked. Lowest height first so the oldest exposure clears first.
+    async fn list_unfinalized_proofs(&self, max_height: u64, limit: i64) -> anyhow::Result<Vec<Proof>> {
+        let rows = sqlx::query(
+            r#"SELECT id, node_id, wallet, claimed_height, claimed_block_hash, proof_timestamp,
+                binary_hash, uptime_seconds, peers, verdict, reject_reason, points_awarded, received_at,
+                trusted_tip_height, finalized_at
+                FROM proofs
+                WHERE verdict = 'accepted' AND finalized_at IS NULL AND claimed_height <= ?1
+                ORDER BY claimed_height ASC LIMIT ?2"#,
+        )
+        .bind(max_height as i64)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(proof_from_row).collect()
+    }
+
+    async fn finalize_proof(&self, id: Uuid, at: DateTime<Utc>) -> anyhow::Result<bool> {
+        let res = sqlx::query(
+            "UPDATE proofs SET finalized_at = ?1 WHERE id = ?2 AND verdict = 'accepted' AND finalized_at IS NULL",
+        )
+        .bind(at.to_rfc3339())
+        .bind(id.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    // Flips an unfinalized accepted proof to 'reorged' and claws its points
+    // back off the node in one transaction. `points_awarded` is left on the
+    // proof row as the record of what was revoked. Returns false (and touches
+    // nothing) if the proof was already finalized or revoked.
+    async fn mark_proof_reorged(
+        &self,
+        id: Uuid,
+        reason: &str,
+        at: DateTime<Utc>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
+            r#"UPDATE proofs
+               SET verdict = 'reorged', reject_reason = ?1, finalized_at = ?2
+               WHERE id = ?3 AND verdict = 'accepted' AND finalized_at IS NULL
+               RETURNING node_id, points_awarded"#,
+        )
+        .bind(reason)
+        .bind(at.to_rfc3339())
+        .bind(id.to_string())
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        let node_id = Uuid::parse_str(&row.try_get::<String, _>("node_id")?)?;
+        let points: i64 = row.try_get("points_awarded")?;
+        // Never go negative — the ledger records what was actually taken back.
+        // Clamped and written in one statement, after the proofs UPDATE took
+        // SQLite's write lock, so no debit can land in between.
+        let taken: Option<i64> = sqlx::query_scalar(
+            r#"INSERT INTO points_ledger (node_id, delta, source, ref_id, created_at)
+               SELECT id, -MIN(?1, points), ?2, ?3, ?4
+               FROM nodes WHERE id = ?5 AND MIN(?1, points) > 0
+               RETURNING delta"#,
+        )
+        .bind(points)
+        .bind(LedgerSource::Revocation.as_str())
+        .bind(id.to_string())
+        .bind(Utc::now().to_rfc3339())
+        .bind(node_id.to_string())
+        .fetch_optional(&mut *tx)
+        .await
+        .context("writing points ledger")?;
+        if let Some(taken) = taken {
+            sqlx::query("UPDATE nodes SET points = points + ?1 WHERE id = ?2")
+                .bind(taken)
+                .bind(node_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    // ---- points ledger ------------------------------------------------------
+
+    // Newest first.
+    async fn ledger_for_node(
+        &self,
+        node_id: Uuid,
+        limit: i64,
+    ) -> anyhow::Result<Vec<LedgerEntry>> {
+        let rows = sqlx::query(
+            r#"SELECT id, node_id, delta, source, ref_id, created_at
+                FROM points_ledger WHERE node_id = ?1
+                ORDER BY id DESC LIMIT ?2"#,
+        )
+        .bind(node_id.to_string())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(ledger_from_row).collect()
+    }
+
+    // Nodes whose cached nodes.points differs from SUM(points_ledger.delta).
+    // Empty = books balance.
+    async fn points_mismatches(&self) -> anyhow::Result<Vec<PointsMismatch>> {
+        let rows = sqlx::query(
+            r#"SELECT id, wallet, cached, ledger FROM (
+                 SELECT n.id, n.wallet, n.points AS cached,
+                        COALESCE((SELECT SUM(l.delta) FROM points_ledger l
+                                  WHERE l.node_id = n.id), 0) AS ledger
+                 FROM nodes n
+               ) WHERE cached != ledger
+               ORDER BY ABS(cached - ledger) DESC"#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|r| {
+                let cached: i64 = r.try_get("cached")?;
+                let ledger: i64 = r.try_get("ledger")?;
+                Ok(PointsMismatch {
+                    node_id: Uuid::parse_str(&r.try_get::<String, _>("id")?)?,
+                    wallet: r.try_get("wallet")?,
+                    cached_points: cached,
+                    ledger_points: ledger,
+                    difference: cached - ledger,
+                })
+            })
+            .collect()
+    }
+
     // ---- challenges ---------------------------------------------------------
 
-    pub async fn insert_challenge(&self, ch: &Challenge) -> anyhow::Result<()> {
+    async fn insert_challenge(&self, ch: &Challenge) -> anyhow::Result<()> {
         sqlx::query(
             r#"INSERT INTO challenges (id, node_id, kind, target_height, expected_hash, issued_at,
-                expires_at, status, answered_at, passed)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"#,
+                expires_at, status, answered_at, passed, origin, answer_hash, fail_reason, subject)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)"#,
         )
         .bind(ch.id.to_string())
         .bind(ch.node_id.to_string())
-        .bind(challenge_kind_str(ch.kind))
+        .bind(ch.kind.as_str())
         .bind(ch.target_height as i64)
         .bind(&ch.expected_hash)
         .bind(ch.issued_at.to_rfc3339())
@@ -434,15 +856,19 @@ impl SqliteStore {
         .bind(ch.status.as_str())
         .bind(ch.answered_at.map(|t| t.to_rfc3339()))
         .bind(ch.passed.map(|p| if p { 1 } else { 0 }))
+        .bind(ch.origin.as_str())
+        .bind(ch.answer_hash.as_deref())
+        .bind(ch.fail_reason.as_deref())
+        .bind(ch.subject.as_deref())
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
-    pub async fn get_open_challenge_for_node(&self, node_id: Uuid) -> anyhow::Result<Option<Challenge>> {
+    async fn get_open_challenge_for_node(&self, node_id: Uuid) -> anyhow::Result<Option<Challenge>> {
         let row = sqlx::query(
             r#"SELECT id, node_id, kind, target_height, expected_hash, issued_at, expires_at, status,
-                answered_at, passed FROM challenges
+                answered_at, passed, origin, answer_hash, fail_reason, subject FROM challenges
                 WHERE node_id = ?1 AND status = 'open'
                 ORDER BY issued_at DESC LIMIT 1"#,
         )
@@ -452,10 +878,10 @@ impl SqliteStore {
         row.map(challenge_from_row).transpose()
     }
 
-    pub async fn get_challenge(&self, id: Uuid) -> anyhow::Result<Option<Challenge>> {
+    async fn get_challenge(&self, id: Uuid) -> anyhow::Result<Option<Challenge>> {
         let row = sqlx::query(
             r#"SELECT id, node_id, kind, target_height, expected_hash, issued_at, expires_at, status,
-                answered_at, passed FROM challenges WHERE id = ?1"#,
+                answered_at, passed, origin, answer_hash, fail_reason, subject FROM challenges WHERE id = ?1"#,
         )
         .bind(id.to_string())
         .fetch_optional(&self.pool)
@@ -463,26 +889,62 @@ impl SqliteStore {
         row.map(challenge_from_row).transpose()
     }
 
-    pub async fn mark_challenge_answered(
+    async fn mark_challenge_answered(
         &self,
         id: Uuid,
         passed: bool,
+        answer_hash: Option<&str>,
         when: DateTime<Utc>,
     ) -> anyhow::Result<()> {
         sqlx::query(
             r#"UPDATE challenges
-               SET status = 'answered', answered_at = ?1, passed = ?2
-               WHERE id = ?3"#,
+               SET status = 'answered', answered_at = ?1, passed = ?2, answer_hash = ?3
+               WHERE id = ?4"#,
         )
         .bind(when.to_rfc3339())
         .bind(if passed { 1 } else { 0 })
+        .bind(answer_hash)
         .bind(id.to_string())
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
-    pub async fn expire_old_challenges(&self, now: DateTime<Utc>) -> anyhow::Result<u64> {
+    // Newest first, both origins.
+    async fn list_challenges_for_node(
+        &self,
+        node_id: Uuid,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Challenge>> {
+        let rows = sqlx::query(
+            r#"SELECT id, node_id, kind, target_height, expected_hash, issued_at, expires_at, status,
+                answered_at, passed, origin, answer_hash, fail_reason, subject FROM challenges
+                WHERE node_id = ?1
+                ORDER BY issued_at DESC LIMIT ?2"#,
+        )
+        .bind(node_id.to_string())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(challenge_from_row).collect()
+    }
+
+    // Failed challenges since the node's most recent pass (or ever, if it has
+    // never passed). Open and expired challenges don't count either way.
+    async fn consecutive_challenge_failures(&self, node_id: Uuid) -> anyhow::Result<u32> {
+        let n: i64 = sqlx::query_scalar(
+            r#"SELECT COUNT(1) FROM challenges
+                WHERE node_id = ?1 AND passed = 0
+                  AND issued_at > COALESCE(
+                      (SELECT MAX(issued_at) FROM challenges WHERE node_id = ?1 AND passed = 1), '')"#,
+        )
+        .bind(node_id.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(n as u32)
+    }
+
+    async fn expire_old_challenges(&self, now: DateTime<Utc>) -> anyhow::Result<u64> {
         let res = sqlx::query(
             "UPDATE challenges SET status = 'expired' WHERE status = 'open' AND expires_at < ?1",
         )
@@ -492,9 +954,309 @@ impl SqliteStore {
         Ok(res.rows_affected())
     }
 
+    // ---- p2p probes ---------------------------------------------------------
+
+    // Stores the probe and credits its bonus (if any) in one transaction.
+    async fn record_p2p_probe(&self, probe: &P2pProbe) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"INSERT INTO p2p_probes (id, node_id, address, reachable, user_agent,
+                protocol_version, start_height, error, points_awarded, probed_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"#,
+        )
+        .bind(probe.id.to_string())
+        .bind(probe.node_id.to_string())
+        .bind(&probe.address)
+        .bind(probe.reachable as i64)
+        .bind(&probe.user_agent)
+        .bind(probe.protocol_version.map(|v| v as i64))
+        .bind(probe.start_height.map(|h| h as i64))
+        .bind(&probe.error)
+        .bind(probe.points_awarded as i64)
+        .bind(probe.probed_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .context("inserting p2p probe")?;
+        if probe.points_awarded > 0 {
+            sqlx::query("UPDATE nodes SET points = points + ?1 WHERE id = ?2")
+                .bind(probe.points_awarded as i64)
+                .bind(probe.node_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+            let ref_id = probe.id.to_string();
+            insert_ledger_row(
+                &mut tx,
+                probe.node_id,
+                probe.points_awarded as i64,
+                LedgerSource::P2pReachability,
+                Some(&ref_id),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn p2p_probe_paid(&self, node_id: Uuid) -> anyhow::Result<bool> {
+        let found: Option<String> =
+            sqlx::query_scalar("SELECT id FROM p2p_probes WHERE node_id = ?1 AND points_awarded > 0 LIMIT 1")
+                .bind(node_id.to_string())
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(found.is_some())
+    }
+
+    async fn list_p2p_probes_for_node(
+        &self,
+        node_id: Uuid,
+        limit: i64,
+    ) -> anyhow::Result<Vec<P2pProbe>> {
+        let rows = sqlx::query(
+            r#"SELECT id, node_id, address, reachable, user_agent, protocol_version,
+                start_height, error, points_awarded, probed_at FROM p2p_probes
+                WHERE node_id = ?1
+                ORDER BY probed_at DESC LIMIT ?2"#,
+        )
+        .bind(node_id.to_string())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(p2p_probe_from_row).collect()
+    }
+
+    // ---- operator RPC credentials -------------------------------------------
+
+    async fn put_rpc_credential(&self, node_id: Uuid, sealed: &str, now: DateTime<Utc>) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"INSERT INTO node_rpc_credentials (node_id, sealed, updated_at)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT(node_id) DO UPDATE SET
+                    sealed = excluded.sealed,
+                    updated_at = excluded.updated_at"#,
+        )
+        .bind(node_id.to_string())
+        .bind(sealed)
+        .bind(now.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .context("storing rpc credential")?;
+        Ok(())
+    }
+
+    async fn get_rpc_credential(&self, node_id: Uuid) -> anyhow::Result<Option<String>> {
+        let sealed = sqlx::query_scalar::<_, String>(
+            "SELECT sealed FROM node_rpc_credentials WHERE node_id = ?1",
+        )
+        .bind(node_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(sealed)
+    }
+
+    async fn delete_rpc_credential(&self, node_id: Uuid) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM node_rpc_credentials WHERE node_id = ?1")
+            .bind(node_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // ---- delegated hot keys -------------------------------------------------
+
+    async fn upsert_delegation(&self, d: &Delegation) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"INSERT INTO node_delegates (node_id, delegate, scopes, expires_at, created_at, revoked_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                ON CONFLICT(node_id, delegate) DO UPDATE SET
+                    scopes = excluded.scopes,
+                    expires_at = excluded.expires_at,
+                    created_at = excluded.created_at,
+                    revoked_at = excluded.revoked_at"#,
+        )
+        .bind(d.node_id.to_string())
+        .bind(&d.delegate)
+        .bind(join_delegate_scopes(&d.scopes))
+        .bind(d.expires_at.to_rfc3339())
+        .bind(d.created_at.to_rfc3339())
+        .bind(d.revoked_at.map(|t| t.to_rfc3339()))
+        .execute(&self.pool)
+        .await
+        .context("storing delegation")?;
+        Ok(())
+    }
+
+    async fn get_delegation(&self, node_id: Uuid, delegate: &str) -> anyhow::Result<Option<Delegation>> {
+        let row = sqlx::query(
+            r#"SELECT node_id, delegate, scopes, expires_at, created_at, revoked_at
+                FROM node_delegates WHERE node_id = ?1 AND delegate = ?2"#,
+        )
+        .bind(node_id.to_string())
+        .bind(delegate)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(delegation_from_row).transpose()
+    }
+
+    async fn list_delegations(&self, node_id: Uuid) -> anyhow::Result<Vec<Delegation>> {
+        let rows = sqlx::query(
+            r#"SELECT node_id, delegate, scopes, expires_at, created_at, revoked_at
+                FROM node_delegates WHERE node_id = ?1
+                ORDER BY created_at DESC"#,
+        )
+        .bind(node_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(delegation_from_row).collect()
+    }
+
+    async fn revoke_delegation(&self, node_id: Uuid, delegate: &str, at: DateTime<Utc>) -> anyhow::Result<bool> {
+        let res = sqlx::query(
+            "UPDATE node_delegates SET revoked_at = ?1 WHERE node_id = ?2 AND delegate = ?3 AND revoked_at IS NULL",
+        )
+        .bind(at.to_rfc3339())
+        .bind(node_id.to_string())
+        .bind(delegate)
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    // ---- exposed-RPC polls --------------------------------------------------
+
+    async fn upsert_rpc_poll_stats(&self, stats: &RpcPollStats) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"INSERT INTO rpc_poll_stats (node_id, last_polled_at, last_outcome, last_error,
+                last_latency_ms, last_success_at, consecutive_failures, next_poll_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                ON CONFLICT(node_id) DO UPDATE SET
+                    last_polled_at = excluded.last_polled_at,
+                    last_outcome = excluded.last_outcome,
+                    last_error = excluded.last_error,
+                    last_latency_ms = excluded.last_latency_ms,
+                    last_success_at = excluded.last_success_at,
+                    consecutive_failures = excluded.consecutive_failures,
+                    next_poll_at = excluded.next_poll_at"#,
+        )
+        .bind(stats.node_id.to_string())
+        .bind(stats.last_polled_at.to_rfc3339())
+        .bind(stats.last_outcome.as_str())
+        .bind(stats.last_error.as_deref())
+        .bind(stats.last_latency_ms.map(|ms| ms as i64))
+        .bind(stats.last_success_at.map(|t| t.to_rfc3339()))
+        .bind(stats.consecutive_failures as i64)
+        .bind(stats.next_poll_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .context("recording rpc poll")?;
+        Ok(())
+    }
+
+    async fn get_rpc_poll_stats(&self, node_id: Uuid) -> anyhow::Result<Option<RpcPollStats>> {
+        let row = sqlx::query(&format!("{RPC_POLL_SELECT} WHERE s.node_id = ?1"))
+            .bind(node_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(rpc_poll_stats_from_row).transpose()
+    }
+
+    async fn list_rpc_poll_stats(&self, network: &str) -> anyhow::Result<Vec<RpcPollStats>> {
+        let rows = sqlx::query(&format!(
+            "{RPC_POLL_SELECT} JOIN nodes n ON n.id = s.node_id WHERE n.network = ?1"
+        ))
+        .bind(network)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(rpc_poll_stats_from_row).collect()
+    }
+
+    // ---- network-upgrade readiness ------------------------------------------
+
+    async fn upsert_node_chain_info(&self, info: &NodeChainInfo) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"INSERT INTO node_chain_info (node_id, source, chain, consensus_branch_id,
+                next_branch_id, upgrades, quorum_match, mismatch, observed_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                ON CONFLICT(node_id) DO UPDATE SET
+                    source = excluded.source,
+                    chain = excluded.chain,
+                    consensus_branch_id = excluded.consensus_branch_id,
+                    next_branch_id = excluded.next_branch_id,
+                    upgrades = excluded.upgrades,
+                    quorum_match = excluded.quorum_match,
+                    mismatch = excluded.mismatch,
+                    observed_at = excluded.observed_at"#,
+        )
+        .bind(info.node_id.to_string())
+        .bind(info.source.as_str())
+        .bind(&info.info.chain)
+        .bind(&info.info.consensus_branch_id)
+        .bind(info.info.next_branch_id.as_deref())
+        .bind(serde_json::to_string(&info.info.upgrades)?)
+        .bind(info.quorum_match.map(|m| m as i64))
+        .bind(info.mismatch.as_deref())
+        .bind(info.observed_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .context("recording node chain info")?;
+        Ok(())
+    }
+
+    async fn get_node_chain_info(&self, node_id: Uuid) -> anyhow::Result<Option<NodeChainInfo>> {
+        let row = sqlx::query(&format!("{CHAIN_INFO_SELECT} WHERE c.node_id = ?1"))
+            .bind(node_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(node_chain_info_from_row).transpose()
+    }
+
+    async fn list_node_chain_info(&self, network: &str, since: DateTime<Utc>) -> anyhow::Result<Vec<NodeChainInfo>> {
+        let rows = sqlx::query(&format!(
+            "{CHAIN_INFO_SELECT} JOIN nodes n ON n.id = c.node_id WHERE n.network = ?1 AND c.observed_at >= ?2"
+        ))
+        .bind(network)
+        .bind(since.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(node_chain_info_from_row).collect()
+    }
+
+    async fn record_upgrade_readiness(
+        &self,
+        node_id: Uuid,
+        branch_id: &str,
+        points: u64,
+        at: DateTime<Utc>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let inserted = sqlx::query(
+            r#"INSERT INTO upgrade_readiness (node_id, branch_id, points_awarded, ready_at)
+                VALUES (?1, ?2, ?3, ?4) ON CONFLICT (node_id, branch_id) DO NOTHING"#,
+        )
+        .bind(node_id.to_string())
+        .bind(branch_id)
+        .bind(points as i64)
+        .bind(at.to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .context("recording upgrade readiness")?
+        .rows_affected()
+            > 0;
+        if !inserted {
+            return Ok(false);
+        }
+        sqlx::query("UPDATE nodes SET points = points + ?1 WHERE id = ?2")
+            .bind(points as i64)
+            .bind(node_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        insert_ledger_row(&mut tx, node_id, points as i64, LedgerSource::UpgradeReadiness, Some(branch_id)).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
     // ---- stats --------------------------------------------------------------
 
-    pub async fn network_stats(&self, network: &str, min_height: u64) -> anyhow::Result<NetworkStats> {
+    async fn network_stats(&self, network: &str, min_height: u64) -> anyhow::Result<NetworkStats> {
         let min_h = min_height as i64;
         // Run the 5 aggregates in parallel — they all hit the same pool but
         // SQLite serialises writes anyway, and reads can interleave. On a hot
@@ -515,10 +1277,11 @@ impl SqliteStore {
             "SELECT COUNT(1) FROM nodes
                 WHERE network = ?1
                   AND last_proof_at IS NOT NULL AND last_height >= ?2
-                  AND last_proof_at >= datetime('now', '-1 hour')",
+                  AND last_proof_at >= ?3",
         )
         .bind(network)
         .bind(min_h)
+        .bind(active_cutoff())
         .fetch_one(&self.pool);
         let total_proofs_f = sqlx::query_scalar::<_, i64>(
             "SELECT COUNT(1) FROM proofs p
@@ -566,7 +1329,7 @@ impl SqliteStore {
         })
     }
 
-    pub async fn leaderboard(
+    async fn leaderboard(
         &self,
         network: &str,
         min_height: u64,
@@ -610,7 +1373,7 @@ impl SqliteStore {
             .collect()
     }
 
-    pub async fn wallet_stats(&self, wallet: &str) -> anyhow::Result<WalletStats> {
+    async fn wallet_stats(&self, wallet: &str) -> anyhow::Result<WalletStats> {
         let row = sqlx::query(
             r#"SELECT
                 COUNT(1) AS nodes,
@@ -635,27 +1398,170 @@ impl SqliteStore {
 
     // ---- replay protection --------------------------------------------------
 
-    pub async fn try_use_nonce(&self, nonce: &str, wallet: &str) -> anyhow::Result<bool> {
+    async fn try_use_nonce(&self, nonce: &str, wallet: &str, signed_at: DateTime<Utc>) -> anyhow::Result<bool> {
         let res = sqlx::query(
-            "INSERT OR IGNORE INTO used_nonces (nonce, wallet, used_at) VALUES (?1, ?2, ?3)",
+            "INSERT OR IGNORE INTO used_nonces (nonce, wallet, signed_at, used_at) VALUES (?1, ?2, ?3, ?4)",
         )
         .bind(nonce)
         .bind(wallet)
+        .bind(signed_at.to_rfc3339())
         .bind(Utc::now().to_rfc3339())
         .execute(&self.pool)
         .await?;
         Ok(res.rows_affected() == 1)
     }
 
+    async fn prune_nonces(&self, before: DateTime<Utc>) -> anyhow::Result<u64> {
+        let res = sqlx::query("DELETE FROM used_nonces WHERE signed_at < ?1")
+            .bind(before.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    // ---- job leases ---------------------------------------------------------
+
+    async fn acquire_lease(
+        &self,
+        job: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<bool> {
+        // One statement, so two instances racing for a free lease can't both
+        // win: the conflicting upsert only updates when the row is already
+        // ours or has lapsed.
+        let res = sqlx::query(
+            r#"INSERT INTO job_leases (job, holder, acquired_at, renewed_at, expires_at)
+                VALUES (?1, ?2, ?3, ?3, ?4)
+                ON CONFLICT(job) DO UPDATE SET
+                    acquired_at = CASE WHEN job_leases.holder = excluded.holder
+                                       THEN job_leases.acquired_at ELSE excluded.acquired_at END,
+                    holder = excluded.holder,
+                    renewed_at = excluded.renewed_at,
+                    expires_at = excluded.expires_at
+                WHERE job_leases.holder = excluded.holder
+                   OR job_leases.expires_at <= excluded.renewed_at"#,
+        )
+        .bind(job)
+        .bind(holder)
+        .bind(now.to_rfc3339())
+        .bind(expires_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .context("acquiring job lease")?;
+        Ok(res.rows_affected() == 1)
+    }
+
+    // ---- job schedule -------------------------------------------------------
+
+    async fn schedule_job(&self, job: &str, next_run_at: DateTime<Utc>) -> anyhow::Result<()> {
+        sqlx::query("INSERT INTO job_runs (job, next_run_at) VALUES (?1, ?2) ON CONFLICT(job) DO NOTHING")
+            .bind(job)
+            .bind(next_run_at.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .context("scheduling job")?;
+        Ok(())
+    }
+
+    async fn get_job_run(&self, job: &str) -> anyhow::Result<Option<JobRun>> {
+        let row = sqlx::query(&format!("{JOB_RUN_SELECT} WHERE r.job = ?1"))
+            .bind(job)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(job_run_from_row).transpose()
+    }
+
+    async fn finish_job_run(
+        &self,
+        job: &str,
+        started_at: DateTime<Utc>,
+        finished_at: DateTime<Utc>,
+        next_run_at: DateTime<Utc>,
+        error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"INSERT INTO job_runs (job, next_run_at, last_run_at, last_finished_at, last_error, last_error_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, CASE WHEN ?5 IS NULL THEN NULL ELSE ?4 END)
+                ON CONFLICT(job) DO UPDATE SET
+                    next_run_at = excluded.next_run_at,
+                    last_run_at = excluded.last_run_at,
+                    last_finished_at = excluded.last_finished_at,
+                    last_error = COALESCE(excluded.last_error, job_runs.last_error),
+                    last_error_at = COALESCE(excluded.last_error_at, job_runs.last_error_at)"#,
+        )
+        .bind(job)
+        .bind(next_run_at.to_rfc3339())
+        .bind(started_at.to_rfc3339())
+        .bind(finished_at.to_rfc3339())
+        .bind(error)
+        .execute(&self.pool)
+        .await
+        .context("recording job run")?;
+        Ok(())
+    }
+
+    async fn list_job_runs(&self) -> anyhow::Result<Vec<JobRun>> {
+        let rows = sqlx::query(&format!("{JOB_RUN_SELECT} ORDER BY r.job"))
+            .fetch_all(&self.pool)
+            .await?;
+        rows.into_iter().map(job_run_from_row).collect()
+    }
+
+    // ---- trusted-rpc disagreements ------------------------------------------
+
+    async fn insert_rpc_disagreement(&self, d: &RpcDisagreement) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"INSERT INTO rpc_disagreements (id, method, params, agreed, answers, dissenters, observed_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+        )
+        .bind(d.id.to_string())
+        .bind(&d.method)
+        .bind(d.params.to_string())
+        .bind(d.agreed.as_ref().map(|v| v.to_string()))
+        .bind(serde_json::to_string(&d.answers)?)
+        .bind(serde_json::to_string(&d.dissenters)?)
+        .bind(d.observed_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .context("inserting rpc disagreement")?;
+        Ok(())
+    }
+
+    async fn list_rpc_disagreements(&self, since: DateTime<Utc>, limit: i64) -> anyhow::Result<Vec<RpcDisagreement>> {
+        let rows = sqlx::query(
+            r#"SELECT id, method, params, agreed, answers, dissenters, observed_at
+                FROM rpc_disagreements WHERE observed_at >= ?1
+                ORDER BY observed_at DESC LIMIT ?2"#,
+        )
+        .bind(since.to_rfc3339())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(rpc_disagreement_from_row).collect()
+    }
+
+    async fn prune_rpc_disagreements(&self, before: DateTime<Utc>) -> anyhow::Result<u64> {
+        let res = sqlx::query("DELETE FROM rpc_disagreements WHERE observed_at < ?1")
+            .bind(before.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
     // ---- snapshots ----------------------------------------------------------
 
-    pub async fn insert_snapshot(
+    async fn insert_snapshot_with_leaves(
         &self,
         cycle: i64,
         merkle_root: &str,
         total_points: u64,
         spl_mint: Option<&str>,
+        leaves: &[NewSnapshotLeaf],
     ) -> anyhow::Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
         let row = sqlx::query(
             r#"INSERT INTO snapshots (cycle, merkle_root, total_points, spl_mint, published_at)
                 VALUES (?1, ?2, ?3, ?4, ?5)
@@ -665,35 +1571,61 @@ impl SqliteStore {
         .bind(merkle_root)
         .bind(total_points as i64)
         .bind(spl_mint)
-        .bind(Utc::now().to_rfc3339())
-        .fetch_one(&self.pool)
+        .bind(&now)
+        .fetch_one(&mut *tx)
         .await?;
-        Ok(row.try_get::<i64, _>("id")?)
+        let snapshot_id = row.try_get::<i64, _>("id")?;
+        for leaf in leaves {
+            sqlx::query(
+                r#"INSERT INTO snapshot_leaves (snapshot_id, wallet, points, leaf_hash, proof_json,
+                    points_from, points_to)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+            )
+            .bind(snapshot_id)
+            .bind(&leaf.wallet)
+            .bind(leaf.points as i64)
+            .bind(&leaf.leaf_hash)
+            .bind(&leaf.proof_json)
+            .bind(leaf.points_from as i64)
+            .bind(leaf.points_to as i64)
+            .execute(&mut *tx)
+            .await?;
+            // Never moves a baseline back down.
+            sqlx::query(
+                r#"INSERT INTO snapshot_baselines (wallet, points, cycle, updated_at)
+                    VALUES (?1, ?2, ?3, ?4)
+                    ON CONFLICT(wallet) DO UPDATE SET
+                        points = MAX(snapshot_baselines.points, excluded.points),
+                        cycle = excluded.cycle,
+                        updated_at = excluded.updated_at"#,
+            )
+            .bind(&leaf.wallet)
+            .bind(leaf.points_to as i64)
+            .bind(cycle)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(snapshot_id)
     }
 
-    pub async fn insert_snapshot_leaf(
-        &self,
-        snapshot_id: i64,
-        wallet: &str,
-        points: u64,
-        leaf_hash: &str,
-        proof_json: &str,
-    ) -> anyhow::Result<()> {
-        sqlx::query(
-            r#"INSERT INTO snapshot_leaves (snapshot_id, wallet, points, leaf_hash, proof_json)
-                VALUES (?1, ?2, ?3, ?4, ?5)"#,
-        )
-        .bind(snapshot_id)
-        .bind(wallet)
-        .bind(points as i64)
-        .bind(leaf_hash)
-        .bind(proof_json)
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+    // Cumulative points already covered by published snapshots, per wallet.
+    async fn snapshot_baselines(&self) -> anyhow::Result<HashMap<String, u64>> {
+        let rows = sqlx::query("SELECT wallet, points FROM snapshot_baselines")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.into_iter()
+            .map(|r| {
+                Ok((
+                    r.try_get::<String, _>("wallet")?,
+                    r.try_get::<i64, _>("points")? as u64,
+                ))
+            })
+            .collect()
     }
 
-    pub async fn latest_snapshot(&self) -> anyhow::Result<Option<(i64, i64, String, u64)>> {
+    async fn latest_snapshot(&self) -> anyhow::Result<Option<(i64, i64, String, u64)>> {
         let row = sqlx::query(
             "SELECT id, cycle, merkle_root, total_points FROM snapshots ORDER BY cycle DESC LIMIT 1",
         )
@@ -710,7 +1642,7 @@ impl SqliteStore {
         }
     }
 
-    pub async fn snapshot_leaf_for_wallet(
+    async fn snapshot_leaf_for_wallet(
         &self,
         snapshot_id: i64,
         wallet: &str,
@@ -733,14 +1665,68 @@ impl SqliteStore {
         }
     }
 
-    pub async fn total_points_per_wallet(&self, network: &str) -> anyhow::Result<Vec<(String, u64)>> {
+    // Newest snapshot that has a leaf for this wallet. With delta snapshots a
+    // wallet that earned nothing last cycle has no leaf in the latest one.
+    async fn latest_snapshot_leaf_for_wallet(
+        &self,
+        wallet: &str,
+    ) -> anyhow::Result<Option<SnapshotLeaf>> {
+        let row = sqlx::query(
+            r#"SELECT s.cycle, s.merkle_root, l.points, l.points_from, l.points_to,
+                      l.leaf_hash, l.proof_json
+                FROM snapshot_leaves l
+                JOIN snapshots s ON s.id = l.snapshot_id
+                WHERE l.wallet = ?1
+                ORDER BY s.cycle DESC LIMIT 1"#,
+        )
+        .bind(wallet)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(snapshot_leaf_from_row).transpose()
+    }
+
+    // Every cycle the wallet has a leaf in after `after_cycle`, oldest first.
+    async fn snapshot_leaves_for_wallet(
+        &self,
+        wallet: &str,
+        after_cycle: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<SnapshotLeaf>> {
         let rows = sqlx::query(
-            r#"SELECT wallet, COALESCE(SUM(points), 0) AS pts
-                FROM nodes WHERE network = ?1
-                GROUP BY wallet HAVING pts > 0
-                ORDER BY wallet ASC"#,
+            r#"SELECT s.cycle, s.merkle_root, l.points, l.points_from, l.points_to,
+                      l.leaf_hash, l.proof_json
+                FROM snapshot_leaves l
+                JOIN snapshots s ON s.id = l.snapshot_id
+                WHERE l.wallet = ?1 AND s.cycle > ?2
+                ORDER BY s.cycle ASC LIMIT ?3"#,
+        )
+        .bind(wallet)
+        .bind(after_cycle)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(snapshot_leaf_from_row).collect()
+    }
+
+    // With `finalized_only`, points from accepted proofs that haven't cleared
+    // the confirmation depth yet are held back — they could still be reorged.
+    async fn total_points_per_wallet(
+        &self,
+        network: &str,
+        finalized_only: bool,
+    ) -> anyhow::Result<Vec<(String, u64)>> {
+        let rows = sqlx::query(
+            r#"SELECT n.wallet,
+                      COALESCE(SUM(MAX(n.points - CASE WHEN ?2 THEN COALESCE(
+                          (SELECT SUM(p.points_awarded) FROM proofs p
+                           WHERE p.node_id = n.id AND p.verdict = 'accepted'
+                             AND p.finalized_at IS NULL), 0) ELSE 0 END, 0)), 0) AS pts
+                FROM nodes n WHERE n.network = ?1
+                GROUP BY n.wallet HAVING pts > 0
+                ORDER BY n.wallet ASC"#,
         )
         .bind(network)
+        .bind(finalized_only)
         .fetch_all(&self.pool)
         .await?;
         rows.into_iter()
@@ -754,6 +1740,29 @@ impl SqliteStore {
     }
 }
 
+// Migration 0015 in Rust: hash tokens of nodes registered before it and blank
+// the plain-text column. Runs on every start; a no-op once nothing is left.
+async fn hash_legacy_auth_tokens(pool: &SqlitePool) -> anyhow::Result<()> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, auth_token FROM nodes WHERE auth_token <> ''")
+            .fetch_all(pool)
+            .await?;
+    if rows.is_empty() {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    for (id, token) in &rows {
+        sqlx::query("UPDATE nodes SET auth_token_hash = ?1, auth_token = '' WHERE id = ?2")
+            .bind(crate::auth::hash_auth_token(token))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await.context("hashing legacy auth tokens")?;
+    tracing::info!(nodes = rows.len(), "hashed legacy node auth tokens");
+    Ok(())
+}
+
 // ---- row mappers ------------------------------------------------------------
 
 fn node_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<Node> {
@@ -769,6 +1778,7 @@ fn node_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<Node> {
         kind: NodeKind::parse(&kind_str).ok_or_else(|| anyhow!("unknown node kind: {}", kind_str))?,
         label: row.try_get("label")?,
         rpc_endpoint: row.try_get("rpc_endpoint")?,
+        p2p_address: row.try_get("p2p_address")?,
         network: row.try_get("network")?,
         status: NodeStatus::parse(&status_str)
             .ok_or_else(|| anyhow!("unknown node status: {}", status_str))?,
@@ -789,6 +1799,8 @@ fn proof_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<Proof> {
     let received_at: String = row.try_get("received_at")?;
     let uptime: Option<i64> = row.try_get("uptime_seconds")?;
     let peers: Option<i64> = row.try_get("peers")?;
+    let trusted_tip: Option<i64> = row.try_get("trusted_tip_height")?;
+    let finalized_at: Option<String> = row.try_get("finalized_at")?;
     Ok(Proof {
         id: Uuid::parse_str(&id_str)?,
         node_id: Uuid::parse_str(&node_id_str)?,
@@ -804,6 +1816,155 @@ fn proof_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<Proof> {
         reject_reason: row.try_get("reject_reason")?,
         points_awarded: row.try_get::<i64, _>("points_awarded")? as u64,
         received_at: parse_dt(&received_at)?,
+        trusted_tip_height: trusted_tip.map(|h| h as u64),
+        finalized_at: finalized_at.as_deref().map(parse_dt).transpose()?,
+    })
+}
+
+fn ledger_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<LedgerEntry> {
+    let node_id_str: String = row.try_get("node_id")?;
+    let source_str: String = row.try_get("source")?;
+    let created_at: String = row.try_get("created_at")?;
+    Ok(LedgerEntry {
+        id: row.try_get("id")?,
+        node_id: Uuid::parse_str(&node_id_str)?,
+        delta: row.try_get("delta")?,
+        source: LedgerSource::parse(&source_str)
+            .ok_or_else(|| anyhow!("unknown ledger source: {}", source_str))?,
+        ref_id: row.try_get("ref_id")?,
+        created_at: parse_dt(&created_at)?,
+    })
+}
+
+// Moves the node's tip forward and credits an accepted proof, with its ledger
+// row, inside the caller's transaction. Never moves the tip backwards: a
+// pending proof resolved late can be older than what the node has already
+// proven since.
+async fn credit_proof_acceptance(
+    conn: &mut SqliteConnection,
+    node_id: Uuid,
+    proof_id: Uuid,
+    height: u64,
+    block_hash: &str,
+    points_awarded: u64,
+    proof_at: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let res = sqlx::query(
+        r#"UPDATE nodes
+           SET last_height = CASE WHEN last_height IS NULL OR ?1 >= last_height
+                                  THEN ?1 ELSE last_height END,
+               last_block_hash = CASE WHEN last_height IS NULL OR ?1 >= last_height
+                                      THEN ?2 ELSE last_block_hash END,
+               last_proof_at = CASE WHEN last_proof_at IS NULL OR ?3 > last_proof_at
+                                    THEN ?3 ELSE last_proof_at END,
+               points = points + ?4,
+               status = CASE WHEN status IN ('paused', 'suspended', 'deregistered') THEN status ELSE 'active' END
+           WHERE id = ?5"#,
+    )
+    .bind(height as i64)
+    .bind(block_hash)
+    .bind(proof_at.to_rfc3339())
+    .bind(points_awarded as i64)
+    .bind(node_id.to_string())
+    .execute(&mut *conn)
+    .await?;
+    if res.rows_affected() == 1 {
+        let proof_ref = proof_id.to_string();
+        insert_ledger_row(conn, node_id, points_awarded as i64, LedgerSource::Proof, Some(&proof_ref)).await?;
+    }
+    Ok(())
+}
+
+// Every write to nodes.points goes through one of the store methods above,
+// each of which calls this inside its own transaction. Zero deltas are noise.
+async fn insert_ledger_row(
+    conn: &mut SqliteConnection,
+    node_id: Uuid,
+    delta: i64,
+    source: LedgerSource,
+    ref_id: Option<&str>,
+) -> anyhow::Result<()> {
+    if delta == 0 {
+        return Ok(());
+    }
+    sqlx::query(
+        r#"INSERT INTO points_ledger (node_id, delta, source, ref_id, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)"#,
+    )
+    .bind(node_id.to_string())
+    .bind(delta)
+    .bind(source.as_str())
+    .bind(ref_id)
+    .bind(Utc::now().to_rfc3339())
+    .execute(conn)
+    .await
+    .context("writing points ledger")?;
+    Ok(())
+}
+
+fn snapshot_leaf_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<SnapshotLeaf> {
+    Ok(SnapshotLeaf {
+        cycle: row.try_get("cycle")?,
+        merkle_root: row.try_get("merkle_root")?,
+        points: row.try_get::<i64, _>("points")? as u64,
+        points_from: row.try_get::<i64, _>("points_from")? as u64,
+        points_to: row.try_get::<i64, _>("points_to")? as u64,
+        leaf_hash: row.try_get("leaf_hash")?,
+        proof_json: row.try_get("proof_json")?,
+    })
+}
+
+fn delegation_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<Delegation> {
+    let node_id: String = row.try_get("node_id")?;
+    let scopes: String = row.try_get("scopes")?;
+    let expires_at: String = row.try_get("expires_at")?;
+    let created_at: String = row.try_get("created_at")?;
+    let revoked_at: Option<String> = row.try_get("revoked_at")?;
+    Ok(Delegation {
+        node_id: Uuid::parse_str(&node_id)?,
+        delegate: row.try_get("delegate")?,
+        scopes: parse_delegate_scopes(&scopes)?,
+        expires_at: parse_dt(&expires_at)?,
+        created_at: parse_dt(&created_at)?,
+        revoked_at: revoked_at.as_deref().map(parse_dt).transpose()?,
+    })
+}
+
+fn p2p_probe_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<P2pProbe> {
+    let id_str: String = row.try_get("id")?;
+    let node_id_str: String = row.try_get("node_id")?;
+    let protocol_version: Option<i64> = row.try_get("protocol_version")?;
+    let start_height: Option<i64> = row.try_get("start_height")?;
+    let probed_at: String = row.try_get("probed_at")?;
+    Ok(P2pProbe {
+        id: Uuid::parse_str(&id_str)?,
+        node_id: Uuid::parse_str(&node_id_str)?,
+        address: row.try_get("address")?,
+        reachable: row.try_get::<i64, _>("reachable")? != 0,
+        user_agent: row.try_get("user_agent")?,
+        protocol_version: protocol_version.map(|v| v as u32),
+        start_height: start_height.map(|h| h as u64),
+        error: row.try_get("error")?,
+        points_awarded: row.try_get::<i64, _>("points_awarded")? as u64,
+        probed_at: parse_dt(&probed_at)?,
+    })
+}
+
+fn rpc_disagreement_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<RpcDisagreement> {
+    let id_str: String = row.try_get("id")?;
+    let params: String = row.try_get("params")?;
+    let agreed: Option<String> = row.try_get("agreed")?;
+    let answers: String = row.try_get("answers")?;
+    let dissenters: String = row.try_get("dissenters")?;
+    let observed_at: String = row.try_get("observed_at")?;
+    Ok(RpcDisagreement {
+        id: Uuid::parse_str(&id_str)?,
+        method: row.try_get("method")?,
+        params: serde_json::from_str(&params)?,
+        agreed: agreed.as_deref().map(serde_json::from_str).transpose()?,
+        answers: serde_json::from_str(&answers)?,
+        dissenters: serde_json::from_str(&dissenters)?,
+        observed_at: parse_dt(&observed_at)?,
     })
 }
 
@@ -816,6 +1977,7 @@ fn challenge_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<Challenge>
     let expires_at: String = row.try_get("expires_at")?;
     let answered_at: Option<String> = row.try_get("answered_at")?;
     let passed: Option<i64> = row.try_get("passed")?;
+    let origin_str: String = row.try_get("origin")?;
     Ok(Challenge {
         id: Uuid::parse_str(&id_str)?,
         node_id: Uuid::parse_str(&node_id_str)?,
@@ -828,24 +1990,81 @@ fn challenge_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<Challenge>
             .ok_or_else(|| anyhow!("unknown challenge status: {}", status_str))?,
         answered_at: answered_at.as_deref().map(parse_dt).transpose()?,
         passed: passed.map(|p| p != 0),
+        origin: ChallengeOrigin::parse(&origin_str)
+            .ok_or_else(|| anyhow!("unknown challenge origin: {}", origin_str))?,
+        answer_hash: row.try_get("answer_hash")?,
+        fail_reason: row.try_get("fail_reason")?,
+        subject: row.try_get("subject")?,
     })
 }
 
-fn challenge_kind_str(k: ChallengeKind) -> &'static str {
-    match k {
-        ChallengeKind::BlockHash => "block_hash",
-    }
+const JOB_RUN_SELECT: &str = r#"SELECT r.job, r.next_run_at, r.last_run_at, r.last_finished_at,
+    r.last_error, r.last_error_at, l.holder AS lease_holder, l.expires_at AS lease_expires_at
+    FROM job_runs r LEFT JOIN job_leases l ON l.job = r.job"#;
+
+fn job_run_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<JobRun> {
+    let opt_dt = |col: &str| -> anyhow::Result<Option<DateTime<Utc>>> {
+        let s: Option<String> = row.try_get(col)?;
+        s.as_deref().map(parse_dt).transpose()
+    };
+    let next_run_at: String = row.try_get("next_run_at")?;
+    Ok(JobRun {
+        job: row.try_get("job")?,
+        next_run_at: parse_dt(&next_run_at)?,
+        last_run_at: opt_dt("last_run_at")?,
+        last_finished_at: opt_dt("last_finished_at")?,
+        last_error: row.try_get("last_error")?,
+        last_error_at: opt_dt("last_error_at")?,
+        lease_holder: row.try_get("lease_holder")?,
+        lease_expires_at: opt_dt("lease_expires_at")?,
+    })
 }
 
-fn parse_challenge_kind(s: &str) -> anyhow::Result<ChallengeKind> {
-    match s {
-        "block_hash" => Ok(ChallengeKind::BlockHash),
-        other => Err(anyhow!("unknown challenge kind: {}", other)),
-    }
+const CHAIN_INFO_SELECT: &str = r#"SELECT c.node_id, c.source, c.chain, c.consensus_branch_id,
+    c.next_branch_id, c.upgrades, c.quorum_match, c.mismatch, c.observed_at
+    FROM node_chain_info c"#;
+
+fn node_chain_info_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<NodeChainInfo> {
+    let node_id: String = row.try_get("node_id")?;
+    let source: String = row.try_get("source")?;
+    let upgrades: String = row.try_get("upgrades")?;
+    let quorum_match: Option<i64> = row.try_get("quorum_match")?;
+    let observed_at: String = row.try_get("observed_at")?;
+    Ok(NodeChainInfo {
+        node_id: Uuid::parse_str(&node_id)?,
+        source: ChainInfoSource::parse(&source).ok_or_else(|| anyhow!("unknown chain info source: {}", source))?,
+        info: ChainInfo {
+            chain: row.try_get("chain")?,
+            consensus_branch_id: row.try_get("consensus_branch_id")?,
+            next_branch_id: row.try_get("next_branch_id")?,
+            upgrades: serde_json::from_str(&upgrades)?,
+        },
+        quorum_match: quorum_match.map(|m| m != 0),
+        mismatch: row.try_get("mismatch")?,
+        observed_at: parse_dt(&observed_at)?,
+    })
 }
 
-fn parse_dt(s: &str) -> anyhow::Result<DateTime<Utc>> {
-    Ok(DateTime::parse_from_rfc3339(s)
-        .with_context(|| format!("parsing rfc3339 timestamp {:?}", s))?
-        .with_timezone(&Utc))
+const RPC_POLL_SELECT: &str = r#"SELECT s.node_id, s.last_polled_at, s.last_outcome, s.last_error,
+    s.last_latency_ms, s.last_success_at, s.consecutive_failures, s.next_poll_at
+    FROM rpc_poll_stats s"#;
+
+fn rpc_poll_stats_from_row(row: sqlx::sqlite::SqliteRow) -> anyhow::Result<RpcPollStats> {
+    let node_id: String = row.try_get("node_id")?;
+    let last_polled_at: String = row.try_get("last_polled_at")?;
+    let outcome: String = row.try_get("last_outcome")?;
+    let latency: Option<i64> = row.try_get("last_latency_ms")?;
+    let last_success_at: Option<String> = row.try_get("last_success_at")?;
+    let next_poll_at: String = row.try_get("next_poll_at")?;
+    Ok(RpcPollStats {
+        node_id: Uuid::parse_str(&node_id)?,
+        last_polled_at: parse_dt(&last_polled_at)?,
+        last_outcome: RpcPollOutcome::parse(&outcome)
+            .ok_or_else(|| anyhow!("unknown rpc poll outcome: {}", outcome))?,
+        last_error: row.try_get("last_error")?,
+        last_latency_ms: latency.map(|ms| ms as u64),
+        last_success_at: last_success_at.as_deref().map(parse_dt).transpose()?,
+        consecutive_failures: row.try_get::<i64, _>("consecutive_failures")? as u32,
+        next_poll_at: parse_dt(&next_poll_at)?,
+    })
 }
@@ -41,6 +41,12 @@ pub enum NodeStatus {
     Active,
     Stale,
     Suspended,
+    // Operator took the node offline on purpose: not polled, probed or
+    // credited, and proofs are refused until it resumes.
+    Paused,
+    // Retired by its operator. The row stays so its proofs and points ledger
+    // (and what the wallet has earned) survive; nothing else touches it.
+    Deregistered,
 }
 
 impl NodeStatus {
@@ -50,6 +56,8 @@ impl NodeStatus {
             NodeStatus::Active => "active",
             NodeStatus::Stale => "stale",
             NodeStatus::Suspended => "suspended",
+            NodeStatus::Paused => "paused",
+            NodeStatus::Deregistered => "deregistered",
         }
     }
 
@@ -59,6 +67,8 @@ impl NodeStatus {
             "active" => Some(NodeStatus::Active),
             "stale" => Some(NodeStatus::Stale),
             "suspended" => Some(NodeStatus::Suspended),
+            "paused" => Some(NodeStatus::Paused),
+            "deregistered" => Some(NodeStatus::Deregistered),
             _ => None,
         }
     }
@@ -71,6 +81,7 @@ pub struct Node {
     pub kind: NodeKind,
     pub label: Option<String>,
     pub rpc_endpoint: Option<String>,
+    pub p2p_address: Option<String>, // "host:port" accepting inbound peers
     pub network: String, // "mainnet" or "testnet"
     pub status: NodeStatus,
     pub last_height: Option<u64>,
@@ -87,6 +98,9 @@ pub enum ProofVerdict {
     Pending,
     Accepted,
     Rejected,
+    // Was accepted, but the block it vouched for is no longer on the trusted
+    // chain once the proof reached finality depth. Points were revoked.
+    Reorged,
 }
 
 impl ProofVerdict {
@@ -95,6 +109,7 @@ impl ProofVerdict {
             ProofVerdict::Pending => "pending",
             ProofVerdict::Accepted => "accepted",
             ProofVerdict::Rejected => "rejected",
+            ProofVerdict::Reorged => "reorged",
         }
     }
 
@@ -103,6 +118,7 @@ impl ProofVerdict {
             "pending" => Some(ProofVerdict::Pending),
             "accepted" => Some(ProofVerdict::Accepted),
             "rejected" => Some(ProofVerdict::Rejected),
+            "reorged" => Some(ProofVerdict::Reorged),
             _ => None,
         }
     }
@@ -123,6 +139,12 @@ pub struct Proof {
     pub reject_reason: Option<String>,
     pub points_awarded: u64,
     pub received_at: DateTime<Utc>,
+    // Trusted tip the server had cached when the proof arrived. Lets a pending
+    // proof be judged later against the same drift window.
+    pub trusted_tip_height: Option<u64>,
+    // Set once an accepted proof has been re-confirmed at finality depth (or
+    // revoked as reorged). None = still provisional.
+    pub finalized_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -130,6 +152,53 @@ pub struct Proof {
 pub enum ChallengeKind {
     // "What's the block hash at height H?" — server picks H from trusted quorum, operator answers.
     BlockHash,
+    // The rest are archival: answers a pruned or freshly-synced node can't
+    // produce. Issued by the scheduler only — see `archival`.
+    // `getblock` header fields at a deep height.
+    BlockHeader,
+    // `getrawtransaction` for a txid from a historical block.
+    RawTransaction,
+    // `z_gettreestate` note commitment tree state at a post-Sapling height.
+    TreeState,
+}
+
+impl ChallengeKind {
+    pub const ALL: [ChallengeKind; 4] = [
+        ChallengeKind::BlockHash,
+        ChallengeKind::BlockHeader,
+        ChallengeKind::RawTransaction,
+        ChallengeKind::TreeState,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallengeKind::BlockHash => "block_hash",
+            ChallengeKind::BlockHeader => "block_header",
+            ChallengeKind::RawTransaction => "raw_transaction",
+            ChallengeKind::TreeState => "tree_state",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "block_hash" => Some(ChallengeKind::BlockHash),
+            "block_header" => Some(ChallengeKind::BlockHeader),
+            "raw_transaction" => Some(ChallengeKind::RawTransaction),
+            "tree_state" => Some(ChallengeKind::TreeState),
+            _ => None,
+        }
+    }
+
+    // Bonus for a pass is `reward_tier * reward_multiplier`. Scales with how
+    // much chain data the answer needs.
+    pub fn reward_multiplier(&self) -> u32 {
+        match self {
+            ChallengeKind::BlockHash => 1,
+            ChallengeKind::BlockHeader => 2,
+            ChallengeKind::RawTransaction => 3,
+            ChallengeKind::TreeState => 3,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -159,6 +228,33 @@ impl ChallengeStatus {
     }
 }
 
+// Who asked for the challenge. Operator = POST /api/challenges/request and a
+// signed answer; Auto = issued and answered by the scheduler against the
+// node's own rpc_endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChallengeOrigin {
+    Operator,
+    Auto,
+}
+
+impl ChallengeOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallengeOrigin::Operator => "operator",
+            ChallengeOrigin::Auto => "auto",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "operator" => Some(ChallengeOrigin::Operator),
+            "auto" => Some(ChallengeOrigin::Auto),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Challenge {
     pub id: Uuid,
@@ -171,6 +267,399 @@ pub struct Challenge {
     pub status: ChallengeStatus,
     pub answered_at: Option<DateTime<Utc>>,
     pub passed: Option<bool>,
+    pub origin: ChallengeOrigin,
+    // What was asked about beyond the height — the txid for RawTransaction.
+    pub subject: Option<String>,
+    pub answer_hash: Option<String>,
+    pub fail_reason: Option<String>,
+}
+
+// Why a node's points changed. One variant per code path that touches
+// nodes.points — see the points_ledger migration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerSource {
+    Proof,
+    Uptime,
+    ChallengeBonus,
+    Admin,
+    Revocation,
+    OpeningBalance,
+    P2pReachability,
+    UpgradeReadiness,
+}
+
+impl LedgerSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerSource::Proof => "proof",
+            LedgerSource::Uptime => "uptime",
+            LedgerSource::ChallengeBonus => "challenge_bonus",
+            LedgerSource::Admin => "admin",
+            LedgerSource::Revocation => "revocation",
+            LedgerSource::OpeningBalance => "opening_balance",
+            LedgerSource::P2pReachability => "p2p_reachability",
+            LedgerSource::UpgradeReadiness => "upgrade_readiness",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "proof" => Some(LedgerSource::Proof),
+            "uptime" => Some(LedgerSource::Uptime),
+            "challenge_bonus" => Some(LedgerSource::ChallengeBonus),
+            "admin" => Some(LedgerSource::Admin),
+            "revocation" => Some(LedgerSource::Revocation),
+            "opening_balance" => Some(LedgerSource::OpeningBalance),
+            "p2p_reachability" => Some(LedgerSource::P2pReachability),
+            "upgrade_readiness" => Some(LedgerSource::UpgradeReadiness),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub id: i64,
+    pub node_id: Uuid,
+    pub delta: i64,
+    pub source: LedgerSource,
+    pub ref_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// One version/verack handshake against a node's p2p_address. The peer fields
+// are what it claimed in its `version` message; None when unreachable.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct P2pProbe {
+    pub id: Uuid,
+    pub node_id: Uuid,
+    pub address: String,
+    pub reachable: bool,
+    pub user_agent: Option<String>,
+    pub protocol_version: Option<u32>,
+    pub start_height: Option<u64>,
+    pub error: Option<String>,
+    pub points_awarded: u64,
+    pub probed_at: DateTime<Utc>,
+}
+
+// What a delegated hot key may sign for. Registration, node management and
+// delegation itself always need the wallet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DelegateScope {
+    Proofs,
+    Challenges,
+}
+
+impl DelegateScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DelegateScope::Proofs => "proofs",
+            DelegateScope::Challenges => "challenges",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "proofs" => Some(DelegateScope::Proofs),
+            "challenges" => Some(DelegateScope::Challenges),
+            _ => None,
+        }
+    }
+
+    // "proofs,challenges" → both. Rejects unknown and empty entries.
+    pub fn parse_list(s: &str) -> Option<Vec<Self>> {
+        let mut scopes = Vec::new();
+        for part in s.split(',') {
+            let scope = Self::parse(part.trim())?;
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+        Some(scopes)
+    }
+}
+
+// A wallet's signed permission for `delegate` (a base58 ed25519 public key)
+// to sign `scopes` on the node's behalf until `expires_at`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Delegation {
+    pub node_id: Uuid,
+    pub delegate: String,
+    pub scopes: Vec<DelegateScope>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl Delegation {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now
+    }
+
+    pub fn allows(&self, scope: DelegateScope, now: DateTime<Utc>) -> bool {
+        self.is_active(now) && self.scopes.contains(&scope)
+    }
+}
+
+// What the last exposed-RPC poll of a node came to. Only `Unreachable` is
+// the endpoint's fault and counts toward its backoff; `Error` is ours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcPollOutcome {
+    Credited,
+    // Same tip and hash as the last poll; nothing new to credit.
+    Unchanged,
+    HashMismatch,
+    OutOfDrift,
+    QuorumUnavailable,
+    Unreachable,
+    Error,
+}
+
+impl RpcPollOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RpcPollOutcome::Credited => "credited",
+            RpcPollOutcome::Unchanged => "unchanged",
+            RpcPollOutcome::HashMismatch => "hash_mismatch",
+            RpcPollOutcome::OutOfDrift => "out_of_drift",
+            RpcPollOutcome::QuorumUnavailable => "quorum_unavailable",
+            RpcPollOutcome::Unreachable => "unreachable",
+            RpcPollOutcome::Error => "error",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "credited" => Some(RpcPollOutcome::Credited),
+            "unchanged" => Some(RpcPollOutcome::Unchanged),
+            "hash_mismatch" => Some(RpcPollOutcome::HashMismatch),
+            "out_of_drift" => Some(RpcPollOutcome::OutOfDrift),
+            "quorum_unavailable" => Some(RpcPollOutcome::QuorumUnavailable),
+            "unreachable" => Some(RpcPollOutcome::Unreachable),
+            "error" => Some(RpcPollOutcome::Error),
+            _ => None,
+        }
+    }
+}
+
+// Credentials the server presents to a node's rpc_endpoint. Stored sealed
+// (`credentials::CredentialKey`) and never part of an API response; Debug
+// leaves the secret out.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scheme", rename_all = "lowercase")]
+pub enum RpcAuth {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+}
+
+impl RpcAuth {
+    pub fn scheme(&self) -> &'static str {
+        match self {
+            RpcAuth::Basic { .. } => "basic",
+            RpcAuth::Bearer { .. } => "bearer",
+        }
+    }
+}
+
+impl std::fmt::Debug for RpcAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RpcAuth::Basic { username, .. } => write!(f, "Basic({username}:***)"),
+            RpcAuth::Bearer { .. } => write!(f, "Bearer(***)"),
+        }
+    }
+}
+
+// Per-node exposed-RPC poll state. `next_poll_at` is pushed out while the
+// endpoint keeps failing; `last_latency_ms` is the endpoint's own round-trip.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RpcPollStats {
+    pub node_id: Uuid,
+    pub last_polled_at: DateTime<Utc>,
+    pub last_outcome: RpcPollOutcome,
+    pub last_error: Option<String>,
+    pub last_latency_ms: Option<u64>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub consecutive_failures: u32,
+    pub next_poll_at: DateTime<Utc>,
+}
+
+// A node whose cached nodes.points disagrees with its ledger sum.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PointsMismatch {
+    pub node_id: Uuid,
+    pub wallet: String,
+    pub cached_points: i64,
+    pub ledger_points: i64,
+    pub difference: i64,
+}
+
+// A scheduler job's bookkeeping row, joined with its lease. `last_error` is
+// the most recent failure, which may predate later successful runs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobRun {
+    pub job: String,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_finished_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    pub lease_holder: Option<String>,
+    pub lease_expires_at: Option<DateTime<Utc>>,
+}
+
+// A quorum round the trusted rpcs didn't all agree on. `agreed` is the value
+// that won when a dissenting answer arrived anyway; None when nothing reached
+// the threshold. Answers are what the endpoints voted on (the projection, for
+// projected calls), and endpoints are scheme://host:port as in /readyz.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RpcDisagreement {
+    pub id: Uuid,
+    pub method: String,
+    pub params: serde_json::Value,
+    pub agreed: Option<serde_json::Value>,
+    pub answers: Vec<RpcAnswer>,
+    // Endpoints that answered something other than the leading value: the
+    // winner, or failing that a value with more weight than any other.
+    pub dissenters: Vec<String>,
+    pub observed_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RpcAnswer {
+    pub endpoint: String,
+    pub weight: u32,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+// How often one trusted rpc was among a disagreement's dissenters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EndpointDissent {
+    pub endpoint: String,
+    pub dissents: u32,
+    pub last_dissent_at: DateTime<Utc>,
+    // At or over RPC_DISSENT_ALERT_THRESHOLD.
+    pub alerting: bool,
+}
+
+// A network upgrade as getblockchaininfo lists it. `branch_id` is the
+// consensus branch id in hex; `status` is "active", "pending" or "disabled"
+// as the reporting node sees it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkUpgrade {
+    pub branch_id: String,
+    pub name: String,
+    pub activation_height: u64,
+    pub status: String,
+}
+
+// The consensus-relevant part of getblockchaininfo. `upgrades` is empty when
+// the source doesn't list them (lightwalletd only reports the branch).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainInfo {
+    pub chain: String,
+    pub consensus_branch_id: String,
+    pub next_branch_id: Option<String>,
+    pub upgrades: Vec<NetworkUpgrade>,
+}
+
+// The next network upgrade as announced (ANNOUNCED_UPGRADE), e.g. NU7 with
+// its branch id and activation height.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnnouncedUpgrade {
+    pub name: String,
+    pub branch_id: String,
+    pub activation_height: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainInfoSource {
+    ExposedRpc,
+    Relay,
+}
+
+impl ChainInfoSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChainInfoSource::ExposedRpc => "exposed_rpc",
+            ChainInfoSource::Relay => "relay",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "exposed_rpc" => Some(ChainInfoSource::ExposedRpc),
+            "relay" => Some(ChainInfoSource::Relay),
+            _ => None,
+        }
+    }
+}
+
+// The last chain info a node reported, and how it compared with the trusted
+// quorum's. `quorum_match` is None when the quorum had no answer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeChainInfo {
+    pub node_id: Uuid,
+    pub source: ChainInfoSource,
+    pub info: ChainInfo,
+    pub quorum_match: Option<bool>,
+    pub mismatch: Option<String>,
+    pub observed_at: DateTime<Utc>,
+}
+
+// Network-wide adoption of the announced upgrade, over nodes that reported
+// chain info recently. `ready` counts nodes that list the announced upgrade
+// at its activation height and agree with the quorum.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpgradeReadiness {
+    pub network: String,
+    pub announced: Option<AnnouncedUpgrade>,
+    pub trusted_tip_height: Option<u64>,
+    pub blocks_until_activation: Option<u64>,
+    pub quorum: Option<ChainInfo>,
+    pub since: DateTime<Utc>,
+    pub reporting_nodes: u32,
+    pub matching_quorum: u32,
+    pub mismatched: u32,
+    pub ready: u32,
+    pub ready_share: f64,
+    pub by_branch: Vec<BranchCount>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BranchCount {
+    pub branch_id: String,
+    pub nodes: u32,
+}
+
+// One wallet's leaf in a published snapshot. `points` is what the leaf pays;
+// `points_from..points_to` is the cumulative range it covers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotLeaf {
+    pub cycle: i64,
+    pub merkle_root: String,
+    pub points: u64,
+    pub points_from: u64,
+    pub points_to: u64,
+    pub leaf_hash: String,
+    pub proof_json: String,
+}
+
+// A leaf as publish_snapshot computed it, for insert_snapshot_with_leaves.
+#[derive(Clone, Debug)]
+pub struct NewSnapshotLeaf {
+    pub wallet: String,
+    pub points: u64,
+    pub points_from: u64,
+    pub points_to: u64,
+    pub leaf_hash: String,
+    pub proof_json: String,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
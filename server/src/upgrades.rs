@@ -0,0 +1,373 @@
+// Network-upgrade readiness.
+//
+// A node can agree with the quorum on every block hash and still run a
+// release that forks off at the next network upgrade. getblockchaininfo says
+// which consensus branch the node is on and which upgrades it knows about,
+// with their activation heights. Exposed polls ask for it in the same batch
+// as the tip; relays send it with their proofs, signed (proof message v3).
+//
+// Each report is checked against the trusted quorum's view: same chain, same
+// branch at the tip, and every upgrade the quorum has activated at the same
+// height. Until the announced upgrade (ANNOUNCED_UPGRADE) activates, a node
+// that is in sync and already lists it earns UPGRADE_READINESS_BONUS, once.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{Duration as ChronoDuration, Utc};
+use serde_json::Value;
+
+use crate::{
+    rpc::RpcError,
+    state::AppState,
+    types::{
+        AnnouncedUpgrade, BranchCount, ChainInfo, ChainInfoSource, Node, NodeChainInfo, NetworkUpgrade,
+        UpgradeReadiness,
+    },
+};
+
+// The dashboard counts nodes that reported within this long.
+pub const READINESS_WINDOW: ChronoDuration = ChronoDuration::days(1);
+
+// Most upgrades a relay may report; mainnet has had eight so far.
+const MAX_REPORTED_UPGRADES: usize = 32;
+
+// Reduces a getblockchaininfo result to the fields that matter. Branch ids
+// are lowercased; upgrades come out by activation height.
+pub fn parse_blockchain_info(v: &Value) -> anyhow::Result<ChainInfo> {
+    let chain = v
+        .get("chain")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("getblockchaininfo has no chain"))?;
+    let consensus = v.get("consensus").ok_or_else(|| anyhow!("getblockchaininfo has no consensus"))?;
+    let consensus_branch_id = consensus
+        .get("chaintip")
+        .and_then(Value::as_str)
+        .map(str::to_ascii_lowercase)
+        .ok_or_else(|| anyhow!("getblockchaininfo has no consensus.chaintip"))?;
+    let next_branch_id = consensus.get("nextblock").and_then(Value::as_str).map(str::to_ascii_lowercase);
+    let mut upgrades = Vec::new();
+    if let Some(map) = v.get("upgrades").and_then(Value::as_object) {
+        for (branch_id, u) in map {
+            upgrades.push(NetworkUpgrade {
+                branch_id: branch_id.to_ascii_lowercase(),
+                name: u.get("name").and_then(Value::as_str).unwrap_or_default().to_string(),
+                activation_height: u
+                    .get("activationheight")
+                    .and_then(Value::as_u64)
+                    .with_context(|| format!("upgrade {branch_id} has no activationheight"))?,
+                status: u.get("status").and_then(Value::as_str).unwrap_or_default().to_string(),
+            });
+        }
+    }
+    upgrades.sort_by_key(|u| u.activation_height);
+    Ok(ChainInfo {
+        chain: chain.to_string(),
+        consensus_branch_id,
+        next_branch_id,
+        upgrades,
+    })
+}
+
+// Bounds-checks chain info a relay sent and puts it in the same form as
+// `parse_blockchain_info` output.
+pub fn normalize_reported(mut info: ChainInfo) -> anyhow::Result<ChainInfo> {
+    if info.chain.is_empty() || info.chain.len() > 16 {
+        bail!("chain must be 1-16 characters");
+    }
+    if info.upgrades.len() > MAX_REPORTED_UPGRADES {
+        bail!("at most {MAX_REPORTED_UPGRADES} upgrades");
+    }
+    info.consensus_branch_id = branch_id(&info.consensus_branch_id)?;
+    info.next_branch_id = info.next_branch_id.as_deref().map(branch_id).transpose()?;
+    for u in &mut info.upgrades {
+        u.branch_id = branch_id(&u.branch_id)?;
+        if u.name.len() > 32 || u.status.len() > 16 {
+            bail!("upgrade {} has an oversized name or status", u.branch_id);
+        }
+    }
+    info.upgrades.sort_by_key(|u| u.activation_height);
+    Ok(info)
+}
+
+// Consensus branch ids are 4 bytes, written as 8 hex digits.
+fn branch_id(s: &str) -> anyhow::Result<String> {
+    let id = s.trim().to_ascii_lowercase();
+    if id.len() != 8 || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!("branch id must be 8 hex digits, got {s:?}");
+    }
+    Ok(id)
+}
+
+// "NU7:77190ad8:3500000" → name, branch id, activation height.
+pub fn parse_announced(s: &str) -> anyhow::Result<AnnouncedUpgrade> {
+    let mut parts = s.trim().split(':');
+    let (Some(name), Some(branch_id), Some(height), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        bail!("expected NAME:BRANCH_ID:ACTIVATION_HEIGHT, got {s:?}");
+    };
+    let branch_id = self::branch_id(branch_id)?;
+    if name.trim().is_empty() {
+        bail!("upgrade name is empty");
+    }
+    Ok(AnnouncedUpgrade {
+        name: name.trim().to_string(),
+        branch_id,
+        activation_height: height.trim().parse().context("parsing activation height")?,
+    })
+}
+
+// What the trusted rpcs vote on: the chain, the tip's branch and the upgrades
+// already active. Pending upgrades and the next block's branch are left out —
+// trusted nodes on different releases may disagree on those.
+pub fn quorum_view(info: ChainInfo) -> ChainInfo {
+    ChainInfo {
+        chain: info.chain,
+        consensus_branch_id: info.consensus_branch_id,
+        next_branch_id: None,
+        upgrades: info.upgrades.into_iter().filter(|u| u.status == "active").collect(),
+    }
+}
+
+// Why a node's chain info disagrees with the quorum's, if it does. A node
+// that doesn't list upgrades is only checked on chain and branch.
+pub fn compare(node: &ChainInfo, quorum: &ChainInfo) -> Option<String> {
+    if node.chain != quorum.chain {
+        return Some(format!("chain {:?}, quorum is on {:?}", node.chain, quorum.chain));
+    }
+    if node.consensus_branch_id != quorum.consensus_branch_id {
+        return Some(format!(
+            "consensus branch {} at tip, quorum is on {}",
+            node.consensus_branch_id, quorum.consensus_branch_id
+        ));
+    }
+    if node.upgrades.is_empty() {
+        return None;
+    }
+    for want in &quorum.upgrades {
+        match node.upgrades.iter().find(|u| u.branch_id == want.branch_id) {
+            None => return Some(format!("doesn't list {} ({})", want.name, want.branch_id)),
+            Some(u) if u.activation_height != want.activation_height => {
+                return Some(format!(
+                    "{} activates at {}, quorum says {}",
+                    want.name, u.activation_height, want.activation_height
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+    None
+}
+
+// The node lists the announced upgrade's branch at its activation height.
+pub fn is_ready(info: &ChainInfo, announced: &AnnouncedUpgrade) -> bool {
+    info.upgrades
+        .iter()
+        .any(|u| u.branch_id == announced.branch_id && u.activation_height == announced.activation_height)
+}
+
+// The quorum's view, at most QUORUM_CHAIN_INFO_TTL old.
+pub async fn quorum_chain_info(state: &AppState) -> Result<ChainInfo, RpcError> {
+    if let Some(info) = state.cached_quorum_chain_info().await {
+        return Ok(info);
+    }
+    let info = state.rpc().get_chain_info().await?;
+    state.store_quorum_chain_info(info.clone()).await;
+    Ok(info)
+}
+
+// Checks a node's report against the quorum, stores it, and pays the
+// readiness bonus if it's due. `in_sync` = the report came with a tip the
+// quorum agreed with. Returns the stored record and the points paid.
+pub async fn observe(
+    state: &AppState,
+    node: &Node,
+    source: ChainInfoSource,
+    info: ChainInfo,
+    in_sync: bool,
+) -> anyhow::Result<(NodeChainInfo, u64)> {
+    let (quorum_match, mismatch) = match quorum_chain_info(state).await {
+        Ok(quorum) => {
+            let mismatch = compare(&info, &quorum);
+            (Some(mismatch.is_none()), mismatch)
+        }
+        Err(e) => {
+            tracing::debug!(node_id = %node.id, error = %e, "chain info: quorum has no answer");
+            (None, None)
+        }
+    };
+    if let Some(why) = &mismatch {
+        tracing::warn!(node_id = %node.id, source = source.as_str(), mismatch = %why, "chain info disagrees with quorum");
+    }
+    let record = NodeChainInfo {
+        node_id: node.id,
+        source,
+        info,
+        quorum_match,
+        mismatch,
+        observed_at: Utc::now(),
+    };
+    state.store().upsert_node_chain_info(&record).await?;
+
+    let cfg = state.config();
+    let mut paid = 0;
+    if let (Some(announced), Some(tip)) = (&cfg.announced_upgrade, state.trusted_tip().await) {
+        let due = in_sync
+            && quorum_match == Some(true)
+            && tip < announced.activation_height
+            && cfg.upgrade_readiness_bonus > 0
+            && is_ready(&record.info, announced);
+        if due
+            && state
+                .store()
+                .record_upgrade_readiness(node.id, &announced.branch_id, cfg.upgrade_readiness_bonus, record.observed_at)
+                .await?
+        {
+            tracing::info!(node_id = %node.id, upgrade = %announced.name, "node ready for upgrade, bonus paid");
+            paid = cfg.upgrade_readiness_bonus;
+        }
+    }
+    Ok((record, paid))
+}
+
+// Adoption of the announced upgrade across nodes that reported within
+// READINESS_WINDOW.
+pub async fn readiness(state: &AppState) -> anyhow::Result<UpgradeReadiness> {
+    let cfg = state.config();
+    let since = Utc::now() - READINESS_WINDOW;
+    let rows = state.store().list_node_chain_info(cfg.network.as_str(), since).await?;
+    let announced = cfg.announced_upgrade.clone();
+    let tip = state.trusted_tip().await;
+
+    let mut branches: HashMap<String, u32> = HashMap::new();
+    let (mut matching, mut mismatched, mut ready) = (0u32, 0u32, 0u32);
+    for row in &rows {
+        *branches.entry(row.info.consensus_branch_id.clone()).or_default() += 1;
+        match row.quorum_match {
+            Some(true) => {
+                matching += 1;
+                if announced.as_ref().is_some_and(|a| is_ready(&row.info, a)) {
+                    ready += 1;
+                }
+            }
+            Some(false) => mismatched += 1,
+            None => {}
+        }
+    }
+    let mut by_branch: Vec<BranchCount> =
+        branches.into_iter().map(|(branch_id, nodes)| BranchCount { branch_id, nodes }).collect();
+    by_branch.sort_by(|a, b| b.nodes.cmp(&a.nodes).then_with(|| a.branch_id.cmp(&b.branch_id)));
+
+    let reporting = rows.len() as u32;
+    Ok(UpgradeReadiness {
+        network: cfg.network.as_str().to_string(),
+        blocks_until_activation: announced
+            .as_ref()
+            .zip(tip)
+            .map(|(a, tip)| a.activation_height.saturating_sub(tip)),
+        announced,
+        trusted_tip_height: tip,
+        quorum: quorum_chain_info(state).await.ok(),
+        since,
+        reporting_nodes: reporting,
+        matching_quorum: matching,
+        mismatched,
+        ready,
+        ready_share: if reporting == 0 { 0.0 } else { ready as f64 / reporting as f64 },
+        by_branch,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn info() -> Value {
+        json!({
+            "chain": "main",
+            "blocks": 3_100_000,
+            "upgrades": {
+                "C2D6D0B4": { "name": "NU5", "activationheight": 1_687_104, "status": "active" },
+                "5ba81b19": { "name": "Overwinter", "activationheight": 347_500, "status": "active" },
+                "77190ad8": { "name": "NU7", "activationheight": 3_500_000, "status": "pending" },
+            },
+            "consensus": { "chaintip": "c2d6d0b4", "nextblock": "c2d6d0b4" },
+        })
+    }
+
+    #[test]
+    fn parses_getblockchaininfo() {
+        let parsed = parse_blockchain_info(&info()).unwrap();
+        assert_eq!(parsed.chain, "main");
+        assert_eq!(parsed.consensus_branch_id, "c2d6d0b4");
+        assert_eq!(parsed.next_branch_id.as_deref(), Some("c2d6d0b4"));
+        let names: Vec<_> = parsed.upgrades.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, ["Overwinter", "NU5", "NU7"], "ordered by activation height");
+        assert_eq!(parsed.upgrades[1].branch_id, "c2d6d0b4", "branch ids lowercased");
+        assert!(parse_blockchain_info(&json!({ "chain": "main" })).is_err());
+    }
+
+    #[test]
+    fn announced_upgrade_parsing() {
+        let a = parse_announced("NU7:77190AD8:3500000").unwrap();
+        assert_eq!(a.name, "NU7");
+        assert_eq!(a.branch_id, "77190ad8");
+        assert_eq!(a.activation_height, 3_500_000);
+        assert!(parse_announced("NU7:77190ad8").is_err());
+        assert!(parse_announced("NU7:xyz:3500000").is_err());
+        assert!(parse_announced(":77190ad8:3500000").is_err());
+        assert!(parse_announced("NU7:77190ad8:soon").is_err());
+    }
+
+    #[test]
+    fn node_is_compared_with_the_active_upgrades_only() {
+        let node = parse_blockchain_info(&info()).unwrap();
+        let mut quorum = node.clone();
+        quorum.upgrades.retain(|u| u.name != "NU7");
+        let quorum = quorum_view(quorum);
+        assert_eq!(compare(&node, &quorum), None, "a pending upgrade the quorum lacks is fine");
+
+        let mut behind = node.clone();
+        behind.upgrades.retain(|u| u.name != "NU5");
+        assert!(compare(&behind, &quorum).unwrap().contains("NU5"));
+
+        let mut moved = node.clone();
+        moved.upgrades[1].activation_height += 1;
+        assert!(compare(&moved, &quorum).unwrap().contains("activates at"));
+
+        let mut testnet = node.clone();
+        testnet.chain = "test".into();
+        assert!(compare(&testnet, &quorum).is_some());
+
+        let lwd = ChainInfo { upgrades: vec![], next_branch_id: None, ..node };
+        assert_eq!(compare(&lwd, &quorum), None, "no upgrade list, only chain + branch checked");
+    }
+
+    #[test]
+    fn reported_chain_info_is_bounded_and_normalized() {
+        let mut info = parse_blockchain_info(&info()).unwrap();
+        info.upgrades.reverse();
+        info.consensus_branch_id = "C2D6D0B4".into();
+        let normalized = normalize_reported(info.clone()).unwrap();
+        assert_eq!(normalized.consensus_branch_id, "c2d6d0b4");
+        assert_eq!(normalized.upgrades[0].name, "Overwinter");
+
+        let mut bad = info.clone();
+        bad.upgrades[0].branch_id = "nope".into();
+        assert!(normalize_reported(bad).is_err());
+        let mut long = info.clone();
+        long.upgrades = vec![long.upgrades[0].clone(); MAX_REPORTED_UPGRADES + 1];
+        assert!(normalize_reported(long).is_err());
+        assert!(normalize_reported(ChainInfo { chain: String::new(), ..info }).is_err());
+    }
+
+    #[test]
+    fn readiness_needs_branch_and_height() {
+        let node = parse_blockchain_info(&info()).unwrap();
+        let mut nu7 = parse_announced("NU7:77190ad8:3500000").unwrap();
+        assert!(is_ready(&node, &nu7));
+        nu7.activation_height = 3_600_000;
+        assert!(!is_ready(&node, &nu7), "a rescheduled activation needs the new release");
+    }
+}
@@ -8,11 +8,12 @@ use axum::{
 use chrono::{Duration as ChronoDuration, Utc};
 use depinzcash_server::{
     api,
-    auth::{proof_message, registration_message},
+    auth::{proof_message, proof_message_v2, registration_message},
     config::{Config, ZcashNetwork},
     rpc::ZcashRpcQuorum,
     state::AppState,
-    store::SqliteStore,
+    store::{SqliteStore, Store},
+    types::ChallengeKind,
 };
 use ed25519_dalek::{Signer, SigningKey};
 use http_body_util::BodyExt;
@@ -28,6 +29,14 @@ fn test_config() -> Config {
         database_url: "sqlite::memory:".into(),
         trusted_rpcs: vec![],
         rpc_timeout: Duration::from_secs(1),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
         admin_api_key: Some("test-admin-key".into()),
         cors_allowed_origins: vec![],
         scheduler_enabled: false,
@@ -36,6 +45,22 @@ fn test_config() -> Config {
         uptime_reward_interval: Duration::from_secs(60),
         snapshot_interval: None,
         exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
         max_height_drift: 8,
         max_clock_skew: Duration::from_secs(15 * 60),
         rate_limit_enabled: false,
@@ -48,14 +73,19 @@ fn test_config() -> Config {
         spl_mint: None,
         solana_cluster: "devnet".into(),
         network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
     }
 }
 
 async fn build_state() -> AppState {
+    build_state_with(test_config()).await
+}
+
+async fn build_state_with(config: Config) -> AppState {
     let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
     store.migrate().await.unwrap();
     AppState::new(
-        test_config(),
+        config,
         store,
         ZcashRpcQuorum::new(vec![], Duration::from_secs(1)),
     )
@@ -236,6 +266,117 @@ async fn rejects_same_height_and_hash_twice_with_different_nonces() {
     assert_eq!(s2, StatusCode::CONFLICT);
 }
 
+// ---- proof message v2 ----------------------------------------------------
+
+fn proof_body_v2(
+    wallet: &str,
+    sk: &SigningKey,
+    node_id: &str,
+    nonce: &str,
+    height: u64,
+    uptime_seconds: u64,
+    peers: u32,
+) -> Value {
+    let ts = Utc::now();
+    let msg = proof_message_v2(
+        wallet,
+        node_id,
+        height,
+        "abc",
+        &ts.to_rfc3339(),
+        nonce,
+        Some(uptime_seconds),
+        Some(peers),
+        Some("bin-hash"),
+    );
+    let sig = bs58::encode(sk.sign(&msg).to_bytes()).into_string();
+    json!({
+        "wallet": wallet, "node_id": node_id, "signature": sig, "nonce": nonce,
+        "claimed_height": height, "claimed_block_hash": "abc",
+        "proof_timestamp": ts.to_rfc3339(),
+        "uptime_seconds": uptime_seconds, "peers": peers, "binary_hash": "bin-hash",
+        "message_version": 2,
+    })
+}
+
+#[tokio::test]
+async fn accepts_v2_signed_proof() {
+    let state = build_state().await;
+    let (wallet, sk, node_id) = fresh_node(state.clone()).await;
+    let body = proof_body_v2(&wallet, &sk, &node_id, "v2-ok-1234567890abcdef", 100, 3600, 8);
+    let (s, body) = post_json(api::router(state), "/api/proofs/submit", body).await;
+    assert_eq!(s, StatusCode::OK, "v2 submit: {body}");
+    assert_eq!(body["verdict"], "accepted");
+}
+
+#[tokio::test]
+async fn rejects_v2_proof_with_tampered_uptime_or_peers() {
+    let state = build_state().await;
+    let (wallet, sk, node_id) = fresh_node(state.clone()).await;
+
+    let mut inflated_uptime = proof_body_v2(&wallet, &sk, &node_id, "v2-up-1234567890abcdef", 100, 3600, 8);
+    inflated_uptime["uptime_seconds"] = json!(86_400u64);
+    let (s, _) = post_json(api::router(state.clone()), "/api/proofs/submit", inflated_uptime).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+
+    let mut inflated_peers = proof_body_v2(&wallet, &sk, &node_id, "v2-pe-1234567890abcdef", 100, 3600, 8);
+    inflated_peers["peers"] = json!(64);
+    let (s, _) = post_json(api::router(state.clone()), "/api/proofs/submit", inflated_peers).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+
+    let mut swapped_binary = proof_body_v2(&wallet, &sk, &node_id, "v2-bh-1234567890abcdef", 100, 3600, 8);
+    swapped_binary["binary_hash"] = json!("other");
+    let (s, _) = post_json(api::router(state), "/api/proofs/submit", swapped_binary).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn rejects_v1_signature_submitted_as_v2() {
+    let state = build_state().await;
+    let (wallet, sk, node_id) = fresh_node(state.clone()).await;
+    let mut body = proof_body(&wallet, &sk, &node_id, "v1-as-v2-1234567890abc", 100, "abc");
+    body["message_version"] = json!(2);
+    let (s, _) = post_json(api::router(state), "/api/proofs/submit", body).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn rejects_unknown_message_version() {
+    let state = build_state().await;
+    let (wallet, sk, node_id) = fresh_node(state.clone()).await;
+    let mut body = proof_body(&wallet, &sk, &node_id, "v9-1234567890abcdefgh", 100, "abc");
+    body["message_version"] = json!(9);
+    let (s, _) = post_json(api::router(state), "/api/proofs/submit", body).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn v1_rejected_after_cutoff_but_v2_still_accepted() {
+    let mut cfg = test_config();
+    cfg.proof_v1_cutoff = Some(Utc::now() - ChronoDuration::minutes(1));
+    let state = build_state_with(cfg).await;
+    let (wallet, sk, node_id) = fresh_node(state.clone()).await;
+
+    let v1 = proof_body(&wallet, &sk, &node_id, "cutoff-v1-1234567890ab", 100, "abc");
+    let (s, _) = post_json(api::router(state.clone()), "/api/proofs/submit", v1).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+
+    let v2 = proof_body_v2(&wallet, &sk, &node_id, "cutoff-v2-1234567890ab", 100, 3600, 8);
+    let (s, body) = post_json(api::router(state), "/api/proofs/submit", v2).await;
+    assert_eq!(s, StatusCode::OK, "v2 after cutoff: {body}");
+}
+
+#[tokio::test]
+async fn v1_still_accepted_before_cutoff() {
+    let mut cfg = test_config();
+    cfg.proof_v1_cutoff = Some(Utc::now() + ChronoDuration::days(30));
+    let state = build_state_with(cfg).await;
+    let (wallet, sk, node_id) = fresh_node(state.clone()).await;
+    let v1 = proof_body(&wallet, &sk, &node_id, "precut-v1-1234567890ab", 100, "abc");
+    let (s, body) = post_json(api::router(state), "/api/proofs/submit", v1).await;
+    assert_eq!(s, StatusCode::OK, "v1 before cutoff: {body}");
+}
+
 // ---- monotonic-height guard ---------------------------------------------
 
 #[tokio::test]
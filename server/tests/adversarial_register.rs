@@ -12,7 +12,8 @@ use depinzcash_server::{
     config::{Config, ZcashNetwork},
     rpc::ZcashRpcQuorum,
     state::AppState,
-    store::SqliteStore,
+    store::{SqliteStore, Store},
+    types::ChallengeKind,
 };
 use ed25519_dalek::{Signer, SigningKey};
 use http_body_util::BodyExt;
@@ -27,6 +28,14 @@ fn test_config() -> Config {
         database_url: "sqlite::memory:".into(),
         trusted_rpcs: vec![],
         rpc_timeout: Duration::from_secs(1),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
         admin_api_key: Some("test-admin-key".into()),
         cors_allowed_origins: vec![],
         scheduler_enabled: false,
@@ -35,6 +44,22 @@ fn test_config() -> Config {
         uptime_reward_interval: Duration::from_secs(60),
         snapshot_interval: None,
         exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
         max_height_drift: 8,
         max_clock_skew: Duration::from_secs(15 * 60),
         rate_limit_enabled: false,
@@ -47,6 +72,7 @@ fn test_config() -> Config {
         spl_mint: None,
         solana_cluster: "devnet".into(),
         network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
     }
 }
 
@@ -296,6 +322,59 @@ async fn rejects_garbage_rpc_endpoint() {
     assert_eq!(s, StatusCode::BAD_REQUEST);
 }
 
+#[tokio::test]
+async fn rejects_p2p_address_on_lightwalletd() {
+    let state = build_state().await;
+    let app = api::router(state);
+    let (wallet, sk) = fresh_kp();
+    let mut body = register_body(&wallet, &sk, "p2p-lwd-abcdef1234567890", "lightwalletd", "");
+    body["p2p_address"] = json!("zebra.example.com:8233");
+    let (s, _) = post_json(app, "/api/nodes/register", body).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn rejects_private_p2p_address() {
+    let state = build_state().await;
+    let app = api::router(state);
+    let (wallet, sk) = fresh_kp();
+    let mut body = register_body(&wallet, &sk, "p2p-private-abcdef123456", "zebra-full", "");
+    body["p2p_address"] = json!("192.168.1.10:8233");
+    let (s, _) = post_json(app, "/api/nodes/register", body).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn accepts_public_p2p_address_and_echoes_it() {
+    let state = build_state().await;
+    let app = api::router(state);
+    let (wallet, sk) = fresh_kp();
+    let mut body = register_body(&wallet, &sk, "p2p-public-abcdef1234567", "zebra-full", "");
+    body["p2p_address"] = json!("zebra.example.com:8233");
+    let (s, b) = post_json(app, "/api/nodes/register", body).await;
+    assert_eq!(s, StatusCode::OK);
+    assert_eq!(b["node"]["p2p_address"], "zebra.example.com:8233");
+}
+
+#[tokio::test]
+async fn rejects_p2p_address_registered_to_another_node() {
+    let state = build_state().await;
+    let app = || api::router(state.clone());
+    let (w1, sk1) = fresh_kp();
+    let mut body = register_body(&w1, &sk1, "p2p-first-abcdef12345678", "zebra-full", "");
+    body["p2p_address"] = json!("Zebra.Example.com:8233");
+    let (s, b) = post_json(app(), "/api/nodes/register", body).await;
+    assert_eq!(s, StatusCode::OK);
+    assert_eq!(b["node"]["p2p_address"], "zebra.example.com:8233", "stored lower-cased");
+
+    // Another wallet can't claim the same peer, however it spells the host.
+    let (w2, sk2) = fresh_kp();
+    let mut body = register_body(&w2, &sk2, "p2p-second-abcdef1234567", "zebra-full", "");
+    body["p2p_address"] = json!("zebra.example.COM:8233");
+    let (s, _) = post_json(app(), "/api/nodes/register", body).await;
+    assert_eq!(s, StatusCode::CONFLICT);
+}
+
 // ---- happy paths around uniqueness ---------------------------------------
 
 #[tokio::test]
@@ -0,0 +1,548 @@
+// Integration tests for scheduler-issued challenges (block hash + archival
+// kinds).
+//
+// A mock trusted quorum supplies the tip and expected answers; a second mock
+// plays the operator's exposed RPC (honest, lying or pruned). The worker is driven
+// directly via `scheduler::run_auto_challenges`, and the history endpoint
+// through the router.
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use chrono::Utc;
+use depinzcash_server::{
+    api,
+    config::{Config, ZcashNetwork},
+    rpc::ZcashRpcQuorum,
+    scheduler,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::{
+        Challenge, ChallengeKind, ChallengeOrigin, ChallengeStatus, LedgerSource, Node, NodeKind,
+        NodeStatus,
+    },
+};
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+// ---- mock zcashd ----------------------------------------------------------
+
+#[derive(Deserialize)]
+struct JsonRpcReq {
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize)]
+struct JsonRpcResp {
+    jsonrpc: &'static str,
+    id: u32,
+    result: Value,
+}
+
+struct MockNode {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+// Same answers for every height — the worker picks the target at random.
+#[derive(Clone)]
+struct MockChain {
+    tip: u64,
+    block_hash: String,
+    merkle_root: String,
+    // (txid, raw hex)
+    txs: Vec<(String, String)>,
+    sapling_state: String,
+    // Per-node noise that must not affect the header fingerprint.
+    confirmations: u64,
+    // No block bodies, transactions or tree states — only hashes.
+    pruned: bool,
+}
+
+impl MockChain {
+    fn honest(block_hash: &str) -> Self {
+        MockChain {
+            tip: TIP,
+            block_hash: block_hash.to_string(),
+            merkle_root: "aa".repeat(32),
+            txs: vec![
+                ("11".repeat(32), "0400008085202f89".to_string()),
+                ("22".repeat(32), "0400008085202f8a".to_string()),
+            ],
+            sapling_state: "01bb".to_string(),
+            confirmations: 1_000,
+            pruned: false,
+        }
+    }
+
+    fn answer(&self, req: &JsonRpcReq) -> Value {
+        let archival = matches!(
+            req.method.as_str(),
+            "getblock" | "getrawtransaction" | "z_gettreestate"
+        );
+        if archival && self.pruned {
+            return Value::Null;
+        }
+        match req.method.as_str() {
+            "getblockcount" => json!(self.tip),
+            "getblockhash" => json!(self.block_hash),
+            "getblock" => json!({
+                "hash": self.block_hash,
+                "confirmations": self.confirmations,
+                "merkleroot": self.merkle_root,
+                "time": 1_700_000_000u64,
+                "tx": self.txs.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>(),
+            }),
+            "getrawtransaction" => {
+                let txid = req.params.get(0).and_then(|v| v.as_str()).unwrap_or("");
+                self.txs
+                    .iter()
+                    .find(|(id, _)| id == txid)
+                    .map(|(_, raw)| json!(raw))
+                    .unwrap_or(Value::Null)
+            }
+            "z_gettreestate" => json!({
+                "hash": self.block_hash,
+                "sapling": {"commitments": {"finalState": self.sapling_state}},
+            }),
+            _ => Value::Null,
+        }
+    }
+}
+
+impl MockNode {
+    async fn start(tip: u64, block_hash: &str) -> Self {
+        Self::start_chain(MockChain {
+            tip,
+            ..MockChain::honest(block_hash)
+        })
+        .await
+    }
+
+    async fn start_chain(chain: MockChain) -> Self {
+        let app = Router::new().route(
+            "/",
+            post(move |Json(req): Json<JsonRpcReq>| {
+                let chain = chain.clone();
+                async move {
+                    let result = chain.answer(&req);
+                    Json(JsonRpcResp {
+                        jsonrpc: "2.0",
+                        id: 1,
+                        result,
+                    })
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        MockNode { addr, handle }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    fn shutdown(self) {
+        self.handle.abort();
+    }
+}
+
+// Bound then dropped — connections get refused.
+async fn dead_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{addr}")
+}
+
+// ---- fixture builders -----------------------------------------------------
+
+fn cfg(trusted_rpcs: Vec<String>, kinds: &[ChallengeKind]) -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs,
+        rpc_timeout: Duration::from_secs(2),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: Some(Duration::from_secs(600)),
+        auto_challenge_kinds: kinds.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: true,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: Duration::from_secs(15 * 60),
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: None,
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+async fn build_state(trusted_rpcs: Vec<String>, kinds: &[ChallengeKind]) -> AppState {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let rpc = ZcashRpcQuorum::new(trusted_rpcs.clone(), Duration::from_secs(2));
+    AppState::new(cfg(trusted_rpcs, kinds), store, rpc)
+}
+
+fn make_node(rpc_endpoint: &str) -> Node {
+    Node {
+        id: Uuid::new_v4(),
+        wallet: "WalletABC".into(),
+        kind: NodeKind::ZebraFull,
+        label: Some("test-node".into()),
+        rpc_endpoint: Some(rpc_endpoint.to_string()),
+        p2p_address: None,
+        network: "mainnet".into(),
+        status: NodeStatus::Active,
+        last_height: None,
+        last_block_hash: None,
+        last_proof_at: None,
+        registered_at: Utc::now(),
+        points: 0,
+        uptime_seconds: 0,
+    }
+}
+
+async fn get_json(state: &AppState, path: &str) -> (StatusCode, Value) {
+    let req = Request::builder()
+        .method(Method::GET)
+        .uri(path)
+        .body(Body::empty())
+        .unwrap();
+    let resp = api::router(state.clone()).oneshot(req).await.unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+const TIP: u64 = 3_350_000;
+const HASH: &str = "0000000000abcdef1234567890abcdef1234567890abcdef1234567890abcd01";
+
+// ---- tests ----------------------------------------------------------------
+
+#[tokio::test]
+async fn honest_node_passes_and_earns_bonus() {
+    let trusted = MockNode::start(TIP, HASH).await;
+    let operator = MockNode::start(TIP, HASH).await;
+    let state = build_state(vec![trusted.url()], &[ChallengeKind::BlockHash]).await;
+    let node = make_node(&operator.url());
+    state.store().insert_node(&node, "tok").await.unwrap();
+
+    let sum = scheduler::run_auto_challenges(&state).await.unwrap();
+    assert_eq!(sum.issued, 1);
+    assert_eq!(sum.passed, 1);
+
+    let history = state.store().list_challenges_for_node(node.id, 10).await.unwrap();
+    assert_eq!(history.len(), 1);
+    let ch = &history[0];
+    assert_eq!(ch.origin, ChallengeOrigin::Auto);
+    assert_eq!(ch.status, ChallengeStatus::Answered);
+    assert_eq!(ch.passed, Some(true));
+    assert!((TIP - 256..=TIP - 32).contains(&ch.target_height));
+
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.points, 10);
+    let ledger = state.store().ledger_for_node(node.id, 10).await.unwrap();
+    assert_eq!(ledger[0].source, LedgerSource::ChallengeBonus);
+    assert_eq!(ledger[0].ref_id.as_deref(), Some(ch.id.to_string().as_str()));
+
+    trusted.shutdown();
+    operator.shutdown();
+}
+
+#[tokio::test]
+async fn challenges_go_out_concurrently_up_to_the_poll_limit() {
+    let trusted = MockNode::start(TIP, HASH).await;
+
+    // Operator endpoint that answers slowly and records peak concurrency.
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let app = {
+        let (in_flight, peak) = (in_flight.clone(), peak.clone());
+        Router::new().route(
+            "/",
+            post(move |Json(req): Json<JsonRpcReq>| {
+                let (in_flight, peak) = (in_flight.clone(), peak.clone());
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let result = MockChain::honest(HASH).answer(&req);
+                    Json(JsonRpcResp { jsonrpc: "2.0", id: 1, result })
+                }
+            }),
+        )
+    };
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = cfg(vec![trusted.url()], &[ChallengeKind::BlockHash]);
+    config.exposed_rpc_poll_concurrency = 3;
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let state = AppState::new(config, store, ZcashRpcQuorum::new(vec![trusted.url()], Duration::from_secs(2)));
+    for i in 0..8 {
+        let mut node = make_node(&format!("http://{addr}"));
+        node.label = Some(format!("slow-{i}"));
+        state.store().insert_node(&node, &format!("tok-{i}")).await.unwrap();
+    }
+
+    let sum = scheduler::run_auto_challenges(&state).await.unwrap();
+    assert_eq!((sum.issued, sum.passed), (8, 8));
+    assert_eq!(peak.load(Ordering::SeqCst), 3, "challenges overlap, but never more than the limit");
+
+    server.abort();
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn lying_node_is_suspended_after_threshold_failures() {
+    let trusted = MockNode::start(TIP, HASH).await;
+    let operator = MockNode::start(TIP, &"ee".repeat(32)).await;
+    let state = build_state(vec![trusted.url()], &[ChallengeKind::BlockHash]).await;
+    let node = make_node(&operator.url());
+    state.store().insert_node(&node, "tok").await.unwrap();
+
+    for round in 1..=2 {
+        let sum = scheduler::run_auto_challenges(&state).await.unwrap();
+        assert_eq!(sum.failed, 1, "round {round}");
+        assert_eq!(sum.suspended, 0, "round {round}");
+    }
+    let sum = scheduler::run_auto_challenges(&state).await.unwrap();
+    assert_eq!(sum.suspended, 1);
+
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.status, NodeStatus::Suspended);
+    assert_eq!(n.points, 0);
+    let history = state.store().list_challenges_for_node(node.id, 10).await.unwrap();
+    assert_eq!(history.len(), 3);
+    assert!(history
+        .iter()
+        .all(|c| c.fail_reason.as_deref() == Some("block_hash mismatch with trusted quorum")));
+
+    // Suspended nodes drop out of the exposed-RPC set — no further challenges.
+    let sum = scheduler::run_auto_challenges(&state).await.unwrap();
+    assert_eq!(sum, scheduler::AutoChallengeSummary::default());
+
+    trusted.shutdown();
+    operator.shutdown();
+}
+
+#[tokio::test]
+async fn unreachable_endpoint_counts_as_failure() {
+    let trusted = MockNode::start(TIP, HASH).await;
+    let state = build_state(vec![trusted.url()], &[ChallengeKind::BlockHash]).await;
+    let node = make_node(&dead_url().await);
+    state.store().insert_node(&node, "tok").await.unwrap();
+
+    let sum = scheduler::run_auto_challenges(&state).await.unwrap();
+    assert_eq!(sum.failed, 1);
+    let ch = &state.store().list_challenges_for_node(node.id, 10).await.unwrap()[0];
+    assert_eq!(ch.passed, Some(false));
+    assert!(ch.answer_hash.is_none());
+    assert!(ch.fail_reason.as_deref().unwrap().starts_with("rpc error"));
+    assert_eq!(state.store().consecutive_challenge_failures(node.id).await.unwrap(), 1);
+
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn history_endpoint_hides_expected_hash_of_open_challenge() {
+    let trusted = MockNode::start(TIP, HASH).await;
+    let operator = MockNode::start(TIP, HASH).await;
+    let state = build_state(vec![trusted.url()], &[ChallengeKind::BlockHash]).await;
+    let node = make_node(&operator.url());
+    state.store().insert_node(&node, "tok").await.unwrap();
+    scheduler::run_auto_challenges(&state).await.unwrap();
+
+    let now = Utc::now();
+    let open = Challenge {
+        id: Uuid::new_v4(),
+        node_id: node.id,
+        kind: ChallengeKind::BlockHash,
+        target_height: TIP - 40,
+        expected_hash: HASH.into(),
+        issued_at: now + chrono::Duration::seconds(1),
+        expires_at: now + chrono::Duration::minutes(10),
+        status: ChallengeStatus::Open,
+        answered_at: None,
+        passed: None,
+        origin: ChallengeOrigin::Operator,
+        subject: None,
+        answer_hash: None,
+        fail_reason: None,
+    };
+    state.store().insert_challenge(&open).await.unwrap();
+
+    let (status, body) = get_json(&state, &format!("/api/nodes/{}/challenges", node.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = body.as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["status"], "open");
+    assert!(rows[0]["expected_hash"].is_null());
+    assert_eq!(rows[1]["origin"], "auto");
+    assert_eq!(rows[1]["passed"], true);
+    assert_eq!(rows[1]["expected_hash"], HASH);
+
+    let (status, _) = get_json(&state, &format!("/api/nodes/{}/challenges", Uuid::new_v4())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    trusted.shutdown();
+    operator.shutdown();
+}
+
+#[tokio::test]
+async fn archival_kinds_pass_against_full_node_with_their_multiplier() {
+    let trusted = MockNode::start_chain(MockChain::honest(HASH)).await;
+    // Different confirmations count — header fingerprint must not care.
+    let operator = MockNode::start_chain(MockChain {
+        confirmations: 7,
+        ..MockChain::honest(HASH)
+    })
+    .await;
+
+    for (kind, bonus) in [
+        (ChallengeKind::BlockHeader, 20),
+        (ChallengeKind::RawTransaction, 30),
+        (ChallengeKind::TreeState, 30),
+    ] {
+        let state = build_state(vec![trusted.url()], &[kind]).await;
+        let node = make_node(&operator.url());
+        state.store().insert_node(&node, "tok").await.unwrap();
+
+        let sum = scheduler::run_auto_challenges(&state).await.unwrap();
+        assert_eq!(sum.passed, 1, "{kind:?}");
+        let ch = &state.store().list_challenges_for_node(node.id, 10).await.unwrap()[0];
+        assert_eq!(ch.kind, kind);
+        assert!(ch.target_height <= TIP - 1_000, "{kind:?} too shallow");
+        assert_eq!(ch.answer_hash.as_deref(), Some(ch.expected_hash.as_str()));
+        if kind == ChallengeKind::RawTransaction {
+            let txid = ch.subject.as_deref().unwrap();
+            assert!(txid == "11".repeat(32) || txid == "22".repeat(32));
+        } else {
+            assert!(ch.subject.is_none());
+        }
+        if kind == ChallengeKind::TreeState {
+            assert!(ch.target_height >= 419_200);
+        }
+        let n = state.store().get_node(node.id).await.unwrap().unwrap();
+        assert_eq!(n.points, bonus, "{kind:?}");
+    }
+
+    trusted.shutdown();
+    operator.shutdown();
+}
+
+#[tokio::test]
+async fn pruned_node_fails_archival_but_passes_block_hash() {
+    let trusted = MockNode::start_chain(MockChain::honest(HASH)).await;
+    let pruned = MockNode::start_chain(MockChain {
+        pruned: true,
+        ..MockChain::honest(HASH)
+    })
+    .await;
+
+    let state = build_state(vec![trusted.url()], &[ChallengeKind::BlockHash]).await;
+    let node = make_node(&pruned.url());
+    state.store().insert_node(&node, "tok").await.unwrap();
+    assert_eq!(scheduler::run_auto_challenges(&state).await.unwrap().passed, 1);
+
+    for kind in [
+        ChallengeKind::BlockHeader,
+        ChallengeKind::RawTransaction,
+        ChallengeKind::TreeState,
+    ] {
+        let state = build_state(vec![trusted.url()], &[kind]).await;
+        let node = make_node(&pruned.url());
+        state.store().insert_node(&node, "tok").await.unwrap();
+        let sum = scheduler::run_auto_challenges(&state).await.unwrap();
+        assert_eq!(sum.failed, 1, "{kind:?}");
+        assert_eq!(state.store().get_node(node.id).await.unwrap().unwrap().points, 0);
+    }
+
+    trusted.shutdown();
+    pruned.shutdown();
+}
+
+#[tokio::test]
+async fn tampered_transaction_fails_raw_transaction_challenge() {
+    let trusted = MockNode::start_chain(MockChain::honest(HASH)).await;
+    let mut chain = MockChain::honest(HASH);
+    for (_, raw) in chain.txs.iter_mut() {
+        raw.push_str("00");
+    }
+    let operator = MockNode::start_chain(chain).await;
+    let state = build_state(vec![trusted.url()], &[ChallengeKind::RawTransaction]).await;
+    let node = make_node(&operator.url());
+    state.store().insert_node(&node, "tok").await.unwrap();
+
+    let sum = scheduler::run_auto_challenges(&state).await.unwrap();
+    assert_eq!(sum.failed, 1);
+    let ch = &state.store().list_challenges_for_node(node.id, 10).await.unwrap()[0];
+    assert_eq!(
+        ch.fail_reason.as_deref(),
+        Some("raw_transaction mismatch with trusted quorum")
+    );
+
+    trusted.shutdown();
+    operator.shutdown();
+}
@@ -14,14 +14,22 @@ use depinzcash_server::{
     config::{Config, ZcashNetwork},
     rpc::ZcashRpcQuorum,
     state::AppState,
-    store::SqliteStore,
+    store::{SqliteStore, Store},
+    types::ChallengeKind,
 };
 use ed25519_dalek::{Signer, SigningKey};
 use http_body_util::BodyExt;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tokio::{net::TcpListener, sync::Mutex, task::JoinHandle};
 use tower::ServiceExt;
 
@@ -29,6 +37,8 @@ use tower::ServiceExt;
 
 #[derive(Deserialize)]
 struct JsonRpcReq {
+    #[serde(default)]
+    id: Value,
     method: String,
     #[serde(default)]
     params: Value,
@@ -37,7 +47,7 @@ struct JsonRpcReq {
 #[derive(Serialize)]
 struct JsonRpcResp {
     jsonrpc: &'static str,
-    id: u32,
+    id: Value,
     result: Value,
 }
 
@@ -60,42 +70,52 @@ impl MockState {
 struct MockServer {
     addr: SocketAddr,
     handle: JoinHandle<()>,
+    hits: Arc<AtomicUsize>,
+}
+
+fn answer(s: &MockState, call: &Value) -> Value {
+    let req: JsonRpcReq = serde_json::from_value(call.clone()).unwrap();
+    let result = match req.method.as_str() {
+        "getblockcount" => json!(s.tip),
+        "getbestblockhash" => json!(s.block_hashes.get(&s.tip).cloned().unwrap_or_default()),
+        "getblockhash" => {
+            let h = req.params.get(0).and_then(|v| v.as_u64()).unwrap_or(0);
+            json!(s.block_hashes.get(&h).cloned().unwrap_or_default())
+        }
+        _ => json!(null),
+    };
+    serde_json::to_value(JsonRpcResp { jsonrpc: "2.0", id: req.id, result }).unwrap()
 }
 
 impl MockServer {
     async fn start(state: MockState) -> Self {
         let state = Arc::new(Mutex::new(state));
-        let app = Router::new().route(
-            "/",
-            post(move |Json(req): Json<JsonRpcReq>| {
-                let state = state.clone();
-                async move {
-                    let s = state.lock().await.clone();
-                    let result = match req.method.as_str() {
-                        "getblockcount" => json!(s.tip),
-                        "getbestblockhash" => {
-                            json!(s.block_hashes.get(&s.tip).cloned().unwrap_or_default())
-                        }
-                        "getblockhash" => {
-                            let h = req.params.get(0).and_then(|v| v.as_u64()).unwrap_or(0);
-                            json!(s.block_hashes.get(&h).cloned().unwrap_or_default())
-                        }
-                        _ => json!(null),
-                    };
-                    Ok::<_, axum::http::StatusCode>(Json(JsonRpcResp {
-                        jsonrpc: "2.0",
-                        id: 1,
-                        result,
-                    }))
-                }
-            }),
-        );
+        let hits = Arc::new(AtomicUsize::new(0));
+        let app = {
+            let hits = hits.clone();
+            Router::new().route(
+                "/",
+                post(move |Json(body): Json<Value>| {
+                    let (state, hits) = (state.clone(), hits.clone());
+                    async move {
+                        hits.fetch_add(1, Ordering::SeqCst);
+                        let s = state.lock().await.clone();
+                        // Batches get one answer per call, like zcashd.
+                        let reply = match body {
+                            Value::Array(calls) => Value::Array(calls.iter().map(|c| answer(&s, c)).collect()),
+                            call => answer(&s, &call),
+                        };
+                        Ok::<_, axum::http::StatusCode>(Json(reply))
+                    }
+                }),
+            )
+        };
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
         let handle = tokio::spawn(async move {
             axum::serve(listener, app.into_make_service()).await.unwrap();
         });
-        Self { addr, handle }
+        Self { addr, handle, hits }
     }
     fn url(&self) -> String {
         format!("http://{}/", self.addr)
@@ -115,6 +135,14 @@ fn test_config(rpcs: Vec<String>) -> Config {
         database_url: "sqlite::memory:".into(),
         trusted_rpcs: rpcs,
         rpc_timeout: Duration::from_secs(2),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
         admin_api_key: Some("test-admin-key".into()),
         cors_allowed_origins: vec![],
         scheduler_enabled: false,
@@ -123,6 +151,22 @@ fn test_config(rpcs: Vec<String>) -> Config {
         uptime_reward_interval: Duration::from_secs(60),
         snapshot_interval: None,
         exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
         max_height_drift: 8,
         max_clock_skew: Duration::from_secs(15 * 60),
         rate_limit_enabled: false,
@@ -135,6 +179,7 @@ fn test_config(rpcs: Vec<String>) -> Config {
         spl_mint: None,
         solana_cluster: "devnet".into(),
         network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
     }
 }
 
@@ -323,3 +368,29 @@ async fn challenge_submit_normalises_0x_prefix() {
     assert_eq!(s, StatusCode::OK);
     assert_eq!(body["passed"], true);
 }
+
+#[tokio::test]
+async fn challenge_request_with_a_known_tip_is_one_batch() {
+    let mock = MockServer::start(MockState::new(2_500_000)).await;
+    let state = build_state(vec![mock.url()]).await;
+    let (wallet, sk, node_id) = register_node(state.clone()).await;
+
+    // The first request learns the tip (getblockcount, then getblockhash).
+    let req_body = challenge_request_body(&wallet, &sk, &node_id, "ch-batch-1-1234567890ab");
+    let (s, _) = post_json(api::router(state.clone()), "/api/challenges/request", req_body).await;
+    assert_eq!(s, StatusCode::OK);
+    let before = mock.hits.load(Ordering::SeqCst);
+
+    // The next asks for the tip and the target's hash in a single batch.
+    let req_body = challenge_request_body(&wallet, &sk, &node_id, "ch-batch-2-1234567890ab");
+    let (s, body) = post_json(api::router(state.clone()), "/api/challenges/request", req_body).await;
+    assert_eq!(s, StatusCode::OK, "challenge request failed: {body}");
+    assert_eq!(mock.hits.load(Ordering::SeqCst) - before, 1);
+
+    let cid = body["challenge_id"].as_str().unwrap().to_string();
+    let target = body["target_height"].as_u64().unwrap();
+    let ans = challenge_submit_body(&wallet, &sk, &cid, &format!("hash-{target:08x}"), "ch-batch-ans-1234567890");
+    let (s, body) = post_json(api::router(state), "/api/challenges/submit", ans).await;
+    assert_eq!(s, StatusCode::OK);
+    assert_eq!(body["passed"], true);
+}
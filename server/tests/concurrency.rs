@@ -16,7 +16,8 @@ use depinzcash_server::{
     config::{Config, ZcashNetwork},
     rpc::ZcashRpcQuorum,
     state::AppState,
-    store::SqliteStore,
+    store::{SqliteStore, Store},
+    types::ChallengeKind,
 };
 use ed25519_dalek::{Signer, SigningKey};
 use http_body_util::BodyExt;
@@ -31,6 +32,14 @@ fn test_config() -> Config {
         database_url: "sqlite::memory:".into(),
         trusted_rpcs: vec![],
         rpc_timeout: Duration::from_secs(1),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
         admin_api_key: Some("test-admin-key".into()),
         cors_allowed_origins: vec![],
         scheduler_enabled: false,
@@ -39,6 +48,22 @@ fn test_config() -> Config {
         uptime_reward_interval: Duration::from_secs(60),
         snapshot_interval: None,
         exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
         max_height_drift: 8,
         max_clock_skew: Duration::from_secs(15 * 60),
         rate_limit_enabled: false,
@@ -51,6 +76,7 @@ fn test_config() -> Config {
         spl_mint: None,
         solana_cluster: "devnet".into(),
         network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
     }
 }
 
@@ -289,7 +315,7 @@ async fn store_try_use_nonce_is_atomic() {
     for _ in 0..50 {
         let store = store.clone();
         handles.push(tokio::spawn(async move {
-            store.try_use_nonce("shared-race-nonce", "walletX").await.unwrap()
+            store.try_use_nonce("shared-race-nonce", "walletX", Utc::now()).await.unwrap()
         }));
     }
     let mut wins = 0;
@@ -0,0 +1,337 @@
+// Delegated relay hot keys: the wallet signs a scoped, expiring delegation
+// and the hot key then signs proofs / challenges in its place, until the
+// delegation expires or the wallet revokes it.
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Router,
+};
+use chrono::{Duration as ChronoDuration, Utc};
+use depinzcash_server::{
+    api,
+    auth::{delegation_message, proof_message_v2, registration_message, revocation_message},
+    config::{Config, ZcashNetwork},
+    rpc::ZcashRpcQuorum,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::{ChallengeKind, DelegateScope, Delegation},
+};
+use ed25519_dalek::{Signer, SigningKey};
+use http_body_util::BodyExt;
+use rand::RngCore;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+const HASH: &str = "0000000000abcdef1234567890abcdef1234567890abcdef1234567890abcd";
+
+fn test_config() -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs: vec![],
+        rpc_timeout: Duration::from_secs(2),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: Some(Duration::from_secs(60)),
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: true,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: Duration::from_secs(15 * 60),
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: None,
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+async fn build_state() -> AppState {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    AppState::new(test_config(), store, ZcashRpcQuorum::new(vec![], Duration::from_secs(2)))
+}
+
+fn fresh_kp() -> (String, SigningKey) {
+    let mut s = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut s);
+    let sk = SigningKey::from_bytes(&s);
+    (bs58::encode(sk.verifying_key().to_bytes()).into_string(), sk)
+}
+
+fn nonce() -> String {
+    format!("dlg-{}", Uuid::new_v4().simple())
+}
+
+fn sign(sk: &SigningKey, msg: &[u8]) -> String {
+    bs58::encode(sk.sign(msg).to_bytes()).into_string()
+}
+
+async fn call(app: Router, method: Method, path: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let req = Request::builder()
+        .method(method)
+        .uri(path)
+        .header("content-type", "application/json")
+        .body(body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty))
+        .unwrap();
+    let resp = app.oneshot(req).await.unwrap();
+    let s = resp.status();
+    let b = resp.into_body().collect().await.unwrap().to_bytes();
+    (s, serde_json::from_slice(&b).unwrap_or(Value::Null))
+}
+
+async fn post(state: &AppState, path: &str, body: Value) -> (StatusCode, Value) {
+    call(api::router(state.clone()), Method::POST, path, Some(body)).await
+}
+
+struct Setup {
+    state: AppState,
+    node: Uuid,
+    wallet: String,
+    cold: SigningKey,
+    hot_pub: String,
+    hot: SigningKey,
+}
+
+async fn setup() -> Setup {
+    let state = build_state().await;
+    let (wallet, cold) = fresh_kp();
+    let ts = Utc::now().to_rfc3339();
+    let n = nonce();
+    let msg = registration_message(&wallet, &n, &ts, "zebra-full", "mainnet", "");
+    let body = json!({
+        "wallet": wallet, "signature": sign(&cold, &msg), "nonce": n, "timestamp": ts,
+        "kind": "zebra-full",
+    });
+    let (s, b) = post(&state, "/api/nodes/register", body).await;
+    assert_eq!(s, StatusCode::OK, "{b}");
+    let (hot_pub, hot) = fresh_kp();
+    Setup {
+        node: b["node"]["id"].as_str().unwrap().parse().unwrap(),
+        state,
+        wallet,
+        cold,
+        hot_pub,
+        hot,
+    }
+}
+
+// A delegation body signed by `signer` (normally the cold wallet).
+fn delegate_body(t: &Setup, signer: &SigningKey, delegate: &str, scopes: &str, expires_at: &str) -> Value {
+    let ts = Utc::now().to_rfc3339();
+    let n = nonce();
+    let msg = delegation_message(&t.wallet, &t.node.to_string(), delegate, scopes, expires_at, &n, &ts);
+    json!({
+        "wallet": t.wallet, "delegate": delegate, "scopes": scopes, "expires_at": expires_at,
+        "nonce": n, "timestamp": ts, "signature": sign(signer, &msg),
+    })
+}
+
+async fn delegate(t: &Setup, scopes: &str) {
+    let expires = (Utc::now() + ChronoDuration::days(30)).to_rfc3339();
+    let body = delegate_body(t, &t.cold, &t.hot_pub, scopes, &expires);
+    let (s, b) = post(&t.state, &format!("/api/nodes/{}/delegates", t.node), body).await;
+    assert_eq!(s, StatusCode::OK, "{b}");
+}
+
+fn proof_body(t: &Setup, signer: &SigningKey, delegate: Option<&str>, height: u64) -> Value {
+    let ts = Utc::now().to_rfc3339();
+    let n = nonce();
+    let msg = proof_message_v2(&t.wallet, &t.node.to_string(), height, HASH, &ts, &n, Some(60), Some(8), None);
+    json!({
+        "wallet": t.wallet, "node_id": t.node, "signature": sign(signer, &msg), "nonce": n,
+        "claimed_height": height, "claimed_block_hash": HASH, "proof_timestamp": ts,
+        "uptime_seconds": 60, "peers": 8, "message_version": 2, "delegate": delegate,
+    })
+}
+
+fn challenge_request_body(t: &Setup, signer: &SigningKey, delegate: Option<&str>) -> Value {
+    let ts = Utc::now();
+    let n = nonce();
+    let msg = format!(
+        "depinzcash:challenge:request:v1\n{}\n{}\n{}\n{}\n",
+        t.wallet,
+        t.node,
+        n,
+        ts.to_rfc3339()
+    );
+    json!({
+        "node_id": t.node, "wallet": t.wallet, "signature": sign(signer, msg.as_bytes()),
+        "nonce": n, "timestamp": ts.to_rfc3339(), "delegate": delegate,
+    })
+}
+
+// ---- delegated signing ------------------------------------------------------
+
+#[tokio::test]
+async fn delegate_signs_proofs_in_place_of_the_wallet() {
+    let t = setup().await;
+    // Before any delegation the hot key is nobody.
+    let (s, _) = post(&t.state, "/api/proofs/submit", proof_body(&t, &t.hot, Some(&t.hot_pub), 2_500_000)).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+
+    delegate(&t, "proofs").await;
+    let (s, b) = post(&t.state, "/api/proofs/submit", proof_body(&t, &t.hot, Some(&t.hot_pub), 2_500_000)).await;
+    assert_eq!(s, StatusCode::OK, "{b}");
+    assert_eq!(b["verdict"], "accepted");
+
+    // Hot signature without naming the delegate is checked against the wallet.
+    let (s, _) = post(&t.state, "/api/proofs/submit", proof_body(&t, &t.hot, None, 2_500_001)).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+    // The wallet itself still works.
+    let (s, b) = post(&t.state, "/api/proofs/submit", proof_body(&t, &t.cold, None, 2_500_002)).await;
+    assert_eq!(s, StatusCode::OK, "{b}");
+}
+
+#[tokio::test]
+async fn delegate_scope_is_enforced() {
+    let t = setup().await;
+    delegate(&t, "proofs").await;
+    let (s, b) = post(&t.state, "/api/challenges/request", challenge_request_body(&t, &t.hot, Some(&t.hot_pub))).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST, "{b}");
+    assert!(b["message"].as_str().unwrap().contains("challenges"), "{b}");
+
+    // Re-delegating replaces the scopes. The signature now passes; with no
+    // trusted quorum configured the request stops at the upstream check.
+    delegate(&t, "proofs,challenges").await;
+    let (s, b) = post(&t.state, "/api/challenges/request", challenge_request_body(&t, &t.hot, Some(&t.hot_pub))).await;
+    assert_eq!(s, StatusCode::BAD_GATEWAY, "{b}");
+}
+
+#[tokio::test]
+async fn expired_and_revoked_delegates_are_refused() {
+    let t = setup().await;
+    delegate(&t, "proofs").await;
+
+    // Revocation needs the wallet; the delegate can't revoke (or renew) itself.
+    let revoke = |signer: &SigningKey| {
+        let ts = Utc::now().to_rfc3339();
+        let n = nonce();
+        let msg = revocation_message(&t.wallet, &t.node.to_string(), &t.hot_pub, &n, &ts);
+        json!({
+            "wallet": t.wallet, "delegate": t.hot_pub, "nonce": n, "timestamp": ts,
+            "signature": sign(signer, &msg),
+        })
+    };
+    let path = format!("/api/nodes/{}/delegates/revoke", t.node);
+    let (s, _) = post(&t.state, &path, revoke(&t.hot)).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+    let (s, b) = post(&t.state, &path, revoke(&t.cold)).await;
+    assert_eq!(s, StatusCode::OK, "{b}");
+    assert!(b["revoked_at"].is_string());
+    let (s, _) = post(&t.state, &path, revoke(&t.cold)).await;
+    assert_eq!(s, StatusCode::NOT_FOUND, "already revoked");
+
+    let (s, b) = post(&t.state, "/api/proofs/submit", proof_body(&t, &t.hot, Some(&t.hot_pub), 2_500_000)).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST, "{b}");
+    assert!(b["message"].as_str().unwrap().contains("revoked"), "{b}");
+
+    // Expired: stored directly, since the API won't accept a past expiry.
+    let now = Utc::now();
+    t.state
+        .store()
+        .upsert_delegation(&Delegation {
+            node_id: t.node,
+            delegate: t.hot_pub.clone(),
+            scopes: vec![DelegateScope::Proofs],
+            expires_at: now - ChronoDuration::seconds(1),
+            created_at: now - ChronoDuration::days(1),
+            revoked_at: None,
+        })
+        .await
+        .unwrap();
+    let (s, _) = post(&t.state, "/api/proofs/submit", proof_body(&t, &t.hot, Some(&t.hot_pub), 2_500_000)).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+}
+
+// ---- delegation requests ----------------------------------------------------
+
+#[tokio::test]
+async fn delegations_need_the_wallet_and_sane_bounds() {
+    let t = setup().await;
+    let path = format!("/api/nodes/{}/delegates", t.node);
+    let in_30d = (Utc::now() + ChronoDuration::days(30)).to_rfc3339();
+
+    let cases = [
+        // A delegate can't mint delegations.
+        delegate_body(&t, &t.hot, &t.hot_pub, "proofs", &in_30d),
+        delegate_body(&t, &t.cold, &t.wallet, "proofs", &in_30d),
+        delegate_body(&t, &t.cold, &t.hot_pub, "proofs,register", &in_30d),
+        delegate_body(&t, &t.cold, &t.hot_pub, "", &in_30d),
+        delegate_body(&t, &t.cold, "not-a-key", "proofs", &in_30d),
+        delegate_body(&t, &t.cold, &t.hot_pub, "proofs", &(Utc::now() - ChronoDuration::minutes(1)).to_rfc3339()),
+        delegate_body(&t, &t.cold, &t.hot_pub, "proofs", &(Utc::now() + ChronoDuration::days(400)).to_rfc3339()),
+    ];
+    for body in cases {
+        let (s, b) = post(&t.state, &path, body.clone()).await;
+        assert_eq!(s, StatusCode::BAD_REQUEST, "{body}: {b}");
+    }
+
+    // Someone else's node.
+    let other = setup().await;
+    let body = delegate_body(&other, &other.cold, &other.hot_pub, "proofs", &in_30d);
+    let (s, _) = post(&t.state, &path, body).await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+
+    // Replay.
+    let body = delegate_body(&t, &t.cold, &t.hot_pub, "proofs", &in_30d);
+    let (s, _) = post(&t.state, &path, body.clone()).await;
+    assert_eq!(s, StatusCode::OK);
+    let (s, _) = post(&t.state, &path, body).await;
+    assert_eq!(s, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn active_delegates_are_capped_and_listed() {
+    let t = setup().await;
+    let path = format!("/api/nodes/{}/delegates", t.node);
+    let in_30d = (Utc::now() + ChronoDuration::days(30)).to_rfc3339();
+    for _ in 0..8 {
+        let (hot_pub, _) = fresh_kp();
+        let (s, b) = post(&t.state, &path, delegate_body(&t, &t.cold, &hot_pub, "proofs", &in_30d)).await;
+        assert_eq!(s, StatusCode::OK, "{b}");
+    }
+    let (s, _) = post(&t.state, &path, delegate_body(&t, &t.cold, &t.hot_pub, "proofs", &in_30d)).await;
+    assert_eq!(s, StatusCode::CONFLICT);
+
+    let (s, b) = call(api::router(t.state.clone()), Method::GET, &path, None).await;
+    assert_eq!(s, StatusCode::OK);
+    let list = b.as_array().unwrap();
+    assert_eq!(list.len(), 8);
+    assert_eq!(list[0]["scopes"], json!(["proofs"]));
+}
@@ -5,11 +5,12 @@ use axum::{
 use chrono::Utc;
 use depinzcash_server::{
     api,
-    auth::{proof_message, registration_message},
+    auth::{offchain_message, proof_message, registration_message},
     config::{Config, ZcashNetwork},
     rpc::ZcashRpcQuorum,
     state::AppState,
-    store::SqliteStore,
+    store::{SqliteStore, Store},
+    types::{ChallengeKind, Node, NodeKind, NodeStatus},
 };
 use ed25519_dalek::{Signer, SigningKey};
 use http_body_util::BodyExt;
@@ -17,6 +18,7 @@ use rand::RngCore;
 use serde_json::{json, Value};
 use std::time::Duration;
 use tower::ServiceExt;
+use uuid::Uuid;
 
 fn test_config() -> Config {
     Config {
@@ -24,6 +26,14 @@ fn test_config() -> Config {
         database_url: "sqlite::memory:".into(),
         trusted_rpcs: vec![],
         rpc_timeout: Duration::from_secs(1),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
         admin_api_key: Some("test-admin-key".into()),
         cors_allowed_origins: vec![],
         scheduler_enabled: false,
@@ -32,6 +42,22 @@ fn test_config() -> Config {
         uptime_reward_interval: Duration::from_secs(60),
         snapshot_interval: None,
         exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
         max_height_drift: 8,
         max_clock_skew: Duration::from_secs(15 * 60),
         rate_limit_enabled: false,
@@ -44,14 +70,20 @@ fn test_config() -> Config {
         spl_mint: Some("So11111111111111111111111111111111111111112".into()),
         solana_cluster: "devnet".into(),
         network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
     }
 }
 
 async fn build_state() -> AppState {
+    build_state_with_store().await.0
+}
+
+// Also hands back the SQLite store so a test can write behind the API's back.
+async fn build_state_with_store() -> (AppState, SqliteStore) {
     let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
     store.migrate().await.unwrap();
     let rpc = ZcashRpcQuorum::new(vec![], Duration::from_secs(1));
-    AppState::new(test_config(), store, rpc)
+    (AppState::new(test_config(), store.clone(), rpc), store)
 }
 
 fn fresh_keypair() -> (String, SigningKey) {
@@ -188,6 +220,75 @@ async fn register_then_submit_proof_full_flow() {
     assert!(arr.iter().any(|w| w["wallet"] == wallet), "leaderboard missing wallet: {body}");
 }
 
+// Hardware / browser wallets sign the off-chain message envelope rather than
+// raw bytes; registration, proofs and challenges all accept that.
+#[tokio::test]
+async fn offchain_envelope_signatures_are_accepted() {
+    let state = build_state().await;
+    let app = || api::router(state.clone());
+    let (wallet, sk) = fresh_keypair();
+    let enveloped = |msg: &[u8]| b58_sig(&sk, &offchain_message(msg).unwrap());
+
+    let ts = Utc::now().to_rfc3339();
+    let nonce = "envelope-reg-nonce-1234567890";
+    let reg_msg = registration_message(&wallet, nonce, &ts, "zebra-full", "mainnet", "ledger");
+    let (status, body) = json_post(
+        app(),
+        "/api/nodes/register",
+        json!({
+            "wallet": wallet,
+            "signature": enveloped(&reg_msg),
+            "nonce": nonce,
+            "timestamp": ts,
+            "kind": "zebra-full",
+            "label": "ledger",
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "register failed: {body}");
+    let node_id = body["node"]["id"].as_str().unwrap().to_string();
+
+    let block_hash = "0000000000abcdef1234567890abcdef1234567890abcdef1234567890abcd";
+    let proof_nonce = "envelope-proof-nonce-1234567890";
+    let proof_ts = Utc::now().to_rfc3339();
+    let proof_msg = proof_message(&wallet, &node_id, 2_500_000, block_hash, &proof_ts, proof_nonce);
+    let (status, body) = json_post(
+        app(),
+        "/api/proofs/submit",
+        json!({
+            "wallet": wallet,
+            "node_id": node_id,
+            "signature": enveloped(&proof_msg),
+            "nonce": proof_nonce,
+            "claimed_height": 2_500_000,
+            "claimed_block_hash": block_hash,
+            "proof_timestamp": proof_ts,
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "proof submit failed: {body}");
+    assert_eq!(body["verdict"], "accepted", "body={body}");
+
+    // Signature accepted; with no trusted quorum the request then stops at
+    // the upstream check instead of a 400.
+    let ch_nonce = "envelope-challenge-nonce-12345";
+    let ch_ts = Utc::now().to_rfc3339();
+    let ch_msg = format!("depinzcash:challenge:request:v1\n{wallet}\n{node_id}\n{ch_nonce}\n{ch_ts}\n");
+    let (status, body) = json_post(
+        app(),
+        "/api/challenges/request",
+        json!({
+            "node_id": node_id,
+            "wallet": wallet,
+            "signature": enveloped(ch_msg.as_bytes()),
+            "nonce": ch_nonce,
+            "timestamp": ch_ts,
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY, "body={body}");
+}
+
 #[tokio::test]
 async fn register_rejects_bad_signature() {
     let state = build_state().await;
@@ -314,3 +415,106 @@ async fn admin_endpoint_rejects_without_key() {
     let resp = app.oneshot(req).await.unwrap();
     assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
 }
+
+async fn admin_get(app: axum::Router, path: &str) -> (StatusCode, Value) {
+    let req = Request::builder()
+        .method(Method::GET)
+        .uri(path)
+        .header("x-admin-key", "test-admin-key")
+        .body(Body::empty())
+        .unwrap();
+    let resp = app.oneshot(req).await.unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, body)
+}
+
+#[tokio::test]
+async fn admin_ledger_reconcile_reports_drifted_nodes() {
+    let (state, sqlite) = build_state_with_store().await;
+    let app = || api::router(state.clone());
+    let node = Node {
+        id: Uuid::new_v4(),
+        wallet: "WalletLedger".into(),
+        kind: NodeKind::ZebraFull,
+        label: None,
+        rpc_endpoint: None,
+        p2p_address: None,
+        network: "mainnet".into(),
+        status: NodeStatus::Active,
+        last_height: None,
+        last_block_hash: None,
+        last_proof_at: None,
+        registered_at: Utc::now(),
+        points: 0,
+        uptime_seconds: 0,
+    };
+    state.store().insert_node(&node, "tok").await.unwrap();
+    state.store().add_uptime_and_points(node.id, 60, 12).await.unwrap();
+
+    let (status, body) = admin_get(app(), "/api/admin/ledger/reconcile").await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["balanced"], true);
+
+    sqlx::query("UPDATE nodes SET points = 0 WHERE id = ?1")
+        .bind(node.id.to_string())
+        .execute(sqlite.pool())
+        .await
+        .unwrap();
+    let (_, body) = admin_get(app(), "/api/admin/ledger/reconcile").await;
+    assert_eq!(body["balanced"], false);
+    assert_eq!(body["mismatches"][0]["node_id"], node.id.to_string());
+    assert_eq!(body["mismatches"][0]["ledger_points"], 12);
+    assert_eq!(body["mismatches"][0]["difference"], -12);
+
+    let (status, body) = admin_get(app(), &format!("/api/admin/nodes/{}/ledger", node.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body[0]["source"], "uptime");
+    assert_eq!(body[0]["delta"], 12);
+
+    let (status, _) = json_get(app(), "/api/admin/ledger/reconcile").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn admin_reads_are_rate_limited_like_admin_writes() {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let cfg = Config {
+        rate_limit_enabled: true,
+        // One token back per minute, three to start with.
+        rate_limit_per_second: 60,
+        rate_limit_burst: 3,
+        ..test_config()
+    };
+    let app = api::router(AppState::new(cfg, store, ZcashRpcQuorum::new(vec![], Duration::from_secs(1))));
+    let get = |path: String| {
+        let app = app.clone();
+        async move {
+            let req = Request::builder()
+                .method(Method::GET)
+                .uri(path)
+                .header("x-admin-key", "guess")
+                .header("fly-client-ip", "203.0.113.9")
+                .body(Body::empty())
+                .unwrap();
+            app.oneshot(req).await.unwrap().status()
+        }
+    };
+
+    let paths = [
+        "/api/admin/ledger/reconcile".to_string(),
+        "/api/admin/jobs".to_string(),
+        "/api/admin/rpc/disagreements".to_string(),
+        format!("/api/admin/nodes/{}/ledger", Uuid::new_v4()),
+    ];
+    for path in &paths[..3] {
+        assert_eq!(get(path.clone()).await, StatusCode::UNAUTHORIZED, "{path}");
+    }
+    for path in &paths {
+        assert_eq!(get(path.clone()).await, StatusCode::TOO_MANY_REQUESTS, "{path}");
+    }
+    // Public reads from the same client are not throttled.
+    assert_eq!(get("/api/stats/network".into()).await, StatusCode::OK);
+}
@@ -0,0 +1,432 @@
+// Integration tests for exposed-mode lightwalletd verification.
+//
+// The operator's node is an in-process mock CompactTxStreamer gRPC server; the
+// trusted quorum is a mock zcashd JSON-RPC server. We drive the scheduler's
+// poll_one_node / run_auto_challenges directly. No live network, no protoc.
+
+use axum::{routing::post, Json, Router};
+use chrono::Utc;
+use depinzcash_server::{
+    config::{Config, ZcashNetwork},
+    lightwalletd::{proto, SERVICE},
+    rpc::ZcashRpcQuorum,
+    scheduler,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::{ChallengeKind, ChallengeStatus, Node, NodeKind, NodeStatus, ProofVerdict},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    convert::Infallible,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+use tonic::{
+    body::BoxBody,
+    codec::ProstCodec,
+    codegen::{http, Service},
+    server::{Grpc, NamedService, UnaryService},
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+use uuid::Uuid;
+
+// ---- mock trusted zcashd --------------------------------------------------
+
+#[derive(Deserialize)]
+struct JsonRpcReq {
+    method: String,
+    #[serde(default, rename = "params")]
+    _params: Value,
+}
+
+#[derive(Serialize)]
+struct JsonRpcResp {
+    jsonrpc: &'static str,
+    id: u32,
+    result: Value,
+}
+
+struct MockNode {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl MockNode {
+    async fn start(height: u64, block_hash: &str) -> Self {
+        let hash = Arc::new(block_hash.to_string());
+        let app = Router::new().route(
+            "/",
+            post(move |Json(req): Json<JsonRpcReq>| {
+                let hash = hash.clone();
+                async move {
+                    let result = match req.method.as_str() {
+                        "getblockcount" => json!(height),
+                        "getblockhash" => json!(hash.as_str()),
+                        _ => Value::Null,
+                    };
+                    Json(JsonRpcResp {
+                        jsonrpc: "2.0",
+                        id: 1,
+                        result,
+                    })
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        MockNode { addr, handle }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    fn shutdown(self) {
+        self.handle.abort();
+    }
+}
+
+// ---- mock lightwalletd ----------------------------------------------------
+
+// Every block "has" the same hash; GetBlock echoes the requested height.
+#[derive(Clone)]
+struct MockLwd {
+    chain_name: String,
+    tip: u64,
+    // Display hex, as zcashd would print it.
+    block_hash: String,
+}
+
+impl MockLwd {
+    fn internal_hash(&self) -> Vec<u8> {
+        let mut bytes = hex::decode(&self.block_hash).unwrap();
+        bytes.reverse();
+        bytes
+    }
+}
+
+impl NamedService for MockLwd {
+    const NAME: &'static str = SERVICE;
+}
+
+// Adapts an infallible closure to tonic's UnaryService.
+#[derive(Clone)]
+struct Unary<F>(F);
+
+impl<Req, Resp, F> UnaryService<Req> for Unary<F>
+where
+    F: FnMut(Req) -> Resp,
+    Resp: Send + 'static,
+{
+    type Response = Resp;
+    type Future = std::future::Ready<Result<Response<Resp>, Status>>;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        std::future::ready(Ok(Response::new((self.0)(request.into_inner()))))
+    }
+}
+
+impl Service<http::Request<BoxBody>> for MockLwd {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        let this = self.clone();
+        Box::pin(async move {
+            let method = req.uri().path().rsplit('/').next().unwrap_or("").to_string();
+            let resp = match method.as_str() {
+                "GetLightdInfo" => {
+                    let info = proto::LightdInfo {
+                        version: "v0.4.17".into(),
+                        vendor: "ECC LightWalletD".into(),
+                        chain_name: this.chain_name.clone(),
+                        block_height: this.tip,
+                        ..Default::default()
+                    };
+                    let svc = Unary(move |_: proto::Empty| info.clone());
+                    Grpc::new(ProstCodec::default()).unary(svc, req).await
+                }
+                "GetLatestBlock" => {
+                    let id = proto::BlockId {
+                        height: this.tip,
+                        hash: this.internal_hash(),
+                    };
+                    let svc = Unary(move |_: proto::ChainSpec| id.clone());
+                    Grpc::new(ProstCodec::default()).unary(svc, req).await
+                }
+                "GetBlock" => {
+                    let hash = this.internal_hash();
+                    let svc = Unary(move |id: proto::BlockId| {
+                        proto::CompactBlock {
+                            proto_version: 1,
+                            height: id.height,
+                            hash: hash.clone(),
+                            ..Default::default()
+                        }
+                    });
+                    Grpc::new(ProstCodec::default()).unary(svc, req).await
+                }
+                _ => Status::unimplemented(method).into_http(),
+            };
+            Ok(resp)
+        })
+    }
+}
+
+struct MockLwdServer {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl MockLwdServer {
+    async fn start(chain_name: &str, tip: u64, block_hash: &str) -> Self {
+        let svc = MockLwd {
+            chain_name: chain_name.into(),
+            tip,
+            block_hash: block_hash.into(),
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        let handle = tokio::spawn(async move {
+            Server::builder()
+                .add_service(svc)
+                .serve_with_incoming(incoming)
+                .await
+                .unwrap();
+        });
+        MockLwdServer { addr, handle }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    fn shutdown(self) {
+        self.handle.abort();
+    }
+}
+
+// ---- fixture builders -----------------------------------------------------
+
+fn cfg(trusted_rpcs: Vec<String>) -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs,
+        rpc_timeout: Duration::from_secs(2),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: Some(Duration::from_secs(60)),
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: Some(Duration::from_secs(600)),
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: true,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: Duration::from_secs(15 * 60),
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: None,
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+async fn build_state(trusted_rpcs: Vec<String>) -> AppState {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let rpc = ZcashRpcQuorum::new(trusted_rpcs.clone(), Duration::from_secs(2));
+    AppState::new(cfg(trusted_rpcs), store, rpc)
+}
+
+fn make_node(rpc_endpoint: &str) -> Node {
+    Node {
+        id: Uuid::new_v4(),
+        wallet: "WalletABC".into(),
+        kind: NodeKind::Lightwalletd,
+        label: Some("lwd-node".into()),
+        rpc_endpoint: Some(rpc_endpoint.to_string()),
+        p2p_address: None,
+        network: "mainnet".into(),
+        status: NodeStatus::Registered,
+        last_height: None,
+        last_block_hash: None,
+        last_proof_at: None,
+        registered_at: Utc::now(),
+        points: 0,
+        uptime_seconds: 0,
+    }
+}
+
+const HEIGHT: u64 = 3_350_000;
+const HASH: &str = "0000000000abcdef1234567890abcdef1234567890abcdef1234567890abcd01";
+
+// ---- tests ----------------------------------------------------------------
+
+#[tokio::test]
+async fn lightwalletd_hash_matching_quorum_is_credited() {
+    let operator = MockLwdServer::start("main", HEIGHT, HASH).await;
+    let trusted = MockNode::start(HEIGHT, HASH).await;
+
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node(&operator.url());
+    state.store().insert_node(&node, "auth-token-1").await.unwrap();
+
+    scheduler::poll_one_node(&state, &node, Some(HEIGHT))
+        .await
+        .expect("poll should succeed");
+
+    let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
+    assert_eq!(proofs.len(), 1);
+    let p = &proofs[0];
+    assert_eq!(p.verdict, ProofVerdict::Accepted);
+    assert_eq!(p.claimed_height, HEIGHT);
+    assert_eq!(p.claimed_block_hash, HASH, "hash must be reported in display order");
+    assert_eq!(p.binary_hash.as_deref(), Some("exposed-lwd-grpc"));
+    assert!(p.points_awarded > 0);
+
+    let refreshed = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(refreshed.status, NodeStatus::Active);
+    assert_eq!(refreshed.last_block_hash.as_deref(), Some(HASH));
+
+    operator.shutdown();
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn lightwalletd_hash_mismatch_is_rejected_without_credit() {
+    let operator = MockLwdServer::start("main", HEIGHT, &"de".repeat(32)).await;
+    let trusted = MockNode::start(HEIGHT, HASH).await;
+
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node(&operator.url());
+    state.store().insert_node(&node, "auth-token-2").await.unwrap();
+
+    scheduler::poll_one_node(&state, &node, Some(HEIGHT))
+        .await
+        .expect("poll should succeed even when hashes disagree");
+
+    let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
+    assert_eq!(proofs.len(), 1);
+    assert_eq!(proofs[0].verdict, ProofVerdict::Rejected);
+    assert_eq!(proofs[0].points_awarded, 0);
+    let refreshed = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(refreshed.points, 0);
+
+    operator.shutdown();
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn lightwalletd_on_wrong_chain_errors_without_proof() {
+    let operator = MockLwdServer::start("test", HEIGHT, HASH).await;
+    let trusted = MockNode::start(HEIGHT, HASH).await;
+
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node(&operator.url());
+    state.store().insert_node(&node, "auth-token-3").await.unwrap();
+
+    let err = scheduler::poll_one_node(&state, &node, Some(HEIGHT))
+        .await
+        .expect_err("testnet lightwalletd must not pass a mainnet poll");
+    assert!(err.to_string().contains("chain"), "unexpected error: {err}");
+
+    let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
+    assert!(proofs.is_empty());
+
+    operator.shutdown();
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn lightwalletd_too_far_behind_is_skipped() {
+    let operator = MockLwdServer::start("main", 60_000, HASH).await;
+    let trusted = MockNode::start(HEIGHT, HASH).await;
+
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node(&operator.url());
+    state.store().insert_node(&node, "auth-token-4").await.unwrap();
+
+    scheduler::poll_one_node(&state, &node, Some(HEIGHT))
+        .await
+        .unwrap();
+
+    let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
+    assert!(proofs.is_empty(), "drift-out node must not produce a proof");
+
+    operator.shutdown();
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn lightwalletd_gets_block_hash_auto_challenges_only() {
+    let operator = MockLwdServer::start("main", HEIGHT, HASH).await;
+    let trusted = MockNode::start(HEIGHT, HASH).await;
+
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node(&operator.url());
+    state.store().insert_node(&node, "auth-token-5").await.unwrap();
+
+    for _ in 0..4 {
+        let sum = scheduler::run_auto_challenges(&state).await.unwrap();
+        assert_eq!(sum.issued, 1);
+        assert_eq!(sum.passed, 1);
+    }
+
+    let challenges = state.store().list_challenges_for_node(node.id, 10).await.unwrap();
+    assert_eq!(challenges.len(), 4);
+    for ch in &challenges {
+        assert_eq!(ch.kind, ChallengeKind::BlockHash);
+        assert_eq!(ch.status, ChallengeStatus::Answered);
+        assert_eq!(ch.passed, Some(true));
+    }
+
+    operator.shutdown();
+    trusted.shutdown();
+}
@@ -2,22 +2,39 @@
 //
 // We stand up two mock Zcash JSON-RPC servers — one acting as the operator's
 // publicly-exposed node, one as the trusted quorum — and exercise the
-// scheduler's poll_one_node directly. No live network, no flakiness.
-
-use axum::{routing::post, Json, Router};
+// scheduler's poll_one_node / run_exposed_rpc_polls directly. No live
+// network, no flakiness.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Json, Router,
+};
 use chrono::Utc;
 use depinzcash_server::{
+    api,
     config::{Config, ZcashNetwork},
     rpc::ZcashRpcQuorum,
-    scheduler,
+    scheduler::{self, ExposedPollSummary},
     state::AppState,
-    store::SqliteStore,
-    types::{Node, NodeKind, NodeStatus, ProofVerdict},
+    store::{SqliteStore, Store},
+    types::{ChallengeKind, Node, NodeKind, NodeStatus, ProofVerdict, RpcPollOutcome},
 };
+use http_body_util::BodyExt;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tokio::{net::TcpListener, sync::Mutex, task::JoinHandle};
+use tower::ServiceExt;
 use uuid::Uuid;
 
 // ---- mock zcashd ----------------------------------------------------------
@@ -92,6 +109,14 @@ fn cfg(trusted_rpcs: Vec<String>) -> Config {
         database_url: "sqlite::memory:".into(),
         trusted_rpcs,
         rpc_timeout: Duration::from_secs(2),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
         admin_api_key: Some("admin-key".into()),
         cors_allowed_origins: vec![],
         scheduler_enabled: false,
@@ -100,6 +125,22 @@ fn cfg(trusted_rpcs: Vec<String>) -> Config {
         uptime_reward_interval: Duration::from_secs(60),
         snapshot_interval: None,
         exposed_rpc_poll_interval: Some(Duration::from_secs(60)),
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: true,
+        rpc_credentials_key: None,
         max_height_drift: 8,
         max_clock_skew: Duration::from_secs(15 * 60),
         rate_limit_enabled: false,
@@ -112,14 +153,19 @@ fn cfg(trusted_rpcs: Vec<String>) -> Config {
         spl_mint: None,
         solana_cluster: "devnet".into(),
         network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
     }
 }
 
 async fn build_state(trusted_rpcs: Vec<String>) -> AppState {
+    build_state_with(cfg(trusted_rpcs)).await
+}
+
+async fn build_state_with(config: Config) -> AppState {
     let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
     store.migrate().await.unwrap();
-    let rpc = ZcashRpcQuorum::new(trusted_rpcs.clone(), Duration::from_secs(2));
-    AppState::new(cfg(trusted_rpcs), store, rpc)
+    let rpc = ZcashRpcQuorum::new(config.trusted_rpcs.clone(), Duration::from_secs(2));
+    AppState::new(config, store, rpc)
 }
 
 fn make_node(rpc_endpoint: &str) -> Node {
@@ -129,6 +175,7 @@ fn make_node(rpc_endpoint: &str) -> Node {
         kind: NodeKind::ZebraFull,
         label: Some("test-node".into()),
         rpc_endpoint: Some(rpc_endpoint.to_string()),
+        p2p_address: None,
         network: "mainnet".into(),
         status: NodeStatus::Registered,
         last_height: None,
@@ -154,9 +201,10 @@ async fn happy_path_credits_node_when_hash_matches_quorum() {
     let node = make_node(&operator.url());
     state.store().insert_node(&node, "auth-token-1").await.unwrap();
 
-    scheduler::poll_one_node(&state, &node, Some(height))
+    let outcome = scheduler::poll_one_node(&state, &node, Some(height))
         .await
         .expect("poll should succeed");
+    assert_eq!(outcome, Some(RpcPollOutcome::Credited));
 
     let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
     assert_eq!(proofs.len(), 1, "exactly one proof inserted");
@@ -191,9 +239,10 @@ async fn hash_mismatch_marks_proof_rejected_and_does_not_credit() {
     let node = make_node(&operator.url());
     state.store().insert_node(&node, "auth-token-2").await.unwrap();
 
-    scheduler::poll_one_node(&state, &node, Some(height))
+    let outcome = scheduler::poll_one_node(&state, &node, Some(height))
         .await
         .expect("poll should succeed even when hashes disagree");
+    assert_eq!(outcome, Some(RpcPollOutcome::HashMismatch));
 
     let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
     assert_eq!(proofs.len(), 1);
@@ -231,9 +280,10 @@ async fn polling_same_tip_twice_is_idempotent() {
     let credited_once = state.store().get_node(node.id).await.unwrap().unwrap().points;
 
     // Second poll on the unchanged tip: no new row, no extra points.
-    scheduler::poll_one_node(&state, &node, Some(height))
+    let outcome = scheduler::poll_one_node(&state, &node, Some(height))
         .await
         .unwrap();
+    assert_eq!(outcome, Some(RpcPollOutcome::Unchanged));
     let second = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
     assert_eq!(second.len(), 1, "same (height, hash) must dedupe via UNIQUE");
     let credited_twice = state.store().get_node(node.id).await.unwrap().unwrap().points;
@@ -256,9 +306,10 @@ async fn node_too_far_behind_trusted_tip_is_skipped() {
     let node = make_node(&operator.url());
     state.store().insert_node(&node, "auth-token-4").await.unwrap();
 
-    scheduler::poll_one_node(&state, &node, Some(trusted_height))
+    let outcome = scheduler::poll_one_node(&state, &node, Some(trusted_height))
         .await
         .unwrap();
+    assert_eq!(outcome, Some(RpcPollOutcome::OutOfDrift));
 
     let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
     assert!(proofs.is_empty(), "drift-out node must not produce a proof");
@@ -278,12 +329,240 @@ async fn node_without_rpc_endpoint_is_no_op() {
     node.rpc_endpoint = None; // simulate registered without exposed url
     state.store().insert_node(&node, "auth-token-5").await.unwrap();
 
-    scheduler::poll_one_node(&state, &node, Some(100))
+    let outcome = scheduler::poll_one_node(&state, &node, Some(100))
         .await
         .expect("missing rpc_endpoint is not an error, just a no-op");
+    assert_eq!(outcome, None);
 
     let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
     assert!(proofs.is_empty());
 
     trusted.shutdown();
 }
+
+// ---- polling run: concurrency, backoff, stats --------------------------------
+
+// A port nothing listens on, so connecting is refused straight away.
+async fn dead_endpoint() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{addr}")
+}
+
+#[test]
+fn poll_backoff_doubles_and_caps() {
+    let every = Duration::from_secs(300);
+    let max = Duration::from_secs(3600);
+    let ticks: Vec<u64> = (0..7)
+        .map(|f| scheduler::poll_backoff(every, f, max).as_secs() / 300)
+        .collect();
+    assert_eq!(ticks, vec![0, 0, 1, 3, 7, 12, 12]);
+    assert_eq!(scheduler::poll_backoff(every, u32::MAX, max), max);
+}
+
+#[tokio::test]
+async fn run_records_poll_stats_and_backs_off_dead_endpoints() {
+    let height = 3_352_000u64;
+    let hash = "0000000000000000000000000000000000000000000000000000000000beef01";
+    let operator = MockNode::start(responses(height, hash)).await;
+    let trusted = MockNode::start(responses(height, hash)).await;
+
+    let state = build_state(vec![trusted.url()]).await;
+    state.set_trusted_tip(height).await;
+    let good = make_node(&operator.url());
+    let mut dead = make_node(&dead_endpoint().await);
+    dead.label = Some("dead-node".into());
+    state.store().insert_node(&good, "auth-good").await.unwrap();
+    state.store().insert_node(&dead, "auth-dead").await.unwrap();
+
+    let sum = scheduler::run_exposed_rpc_polls(&state).await.unwrap();
+    assert_eq!(
+        sum,
+        ExposedPollSummary {
+            polled: 2,
+            credited: 1,
+            unreachable: 1,
+            backed_off: 0
+        }
+    );
+    let g = state.store().get_rpc_poll_stats(good.id).await.unwrap().unwrap();
+    assert_eq!(g.last_outcome, RpcPollOutcome::Credited);
+    assert_eq!(g.consecutive_failures, 0);
+    assert!(g.last_success_at.is_some() && g.last_error.is_none() && g.last_latency_ms.is_some());
+    let d = state.store().get_rpc_poll_stats(dead.id).await.unwrap().unwrap();
+    assert_eq!(d.last_outcome, RpcPollOutcome::Unreachable);
+    assert_eq!(d.consecutive_failures, 1);
+    assert!(d.last_success_at.is_none());
+    assert!(d.last_error.is_some());
+    assert!(d.next_poll_at <= Utc::now(), "first failure doesn't back off");
+
+    // Second failure in a row: sits out the next tick.
+    let sum = scheduler::run_exposed_rpc_polls(&state).await.unwrap();
+    assert_eq!((sum.polled, sum.credited, sum.unreachable), (2, 0, 1));
+    let g = state.store().get_rpc_poll_stats(good.id).await.unwrap().unwrap();
+    assert_eq!(g.last_outcome, RpcPollOutcome::Unchanged);
+    let d = state.store().get_rpc_poll_stats(dead.id).await.unwrap().unwrap();
+    assert_eq!(d.consecutive_failures, 2);
+    assert!(d.next_poll_at > Utc::now());
+
+    let sum = scheduler::run_exposed_rpc_polls(&state).await.unwrap();
+    assert_eq!((sum.polled, sum.backed_off), (1, 1));
+
+    operator.shutdown();
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn run_polls_concurrently_up_to_the_limit() {
+    let height = 3_352_100u64;
+    let hash = "0000000000000000000000000000000000000000000000000000000000beef02";
+    let trusted = MockNode::start(responses(height, hash)).await;
+
+    // Operator endpoint that answers slowly and records peak concurrency.
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let app = {
+        let (in_flight, peak) = (in_flight.clone(), peak.clone());
+        Router::new().route(
+            "/",
+            post(move |Json(req): Json<JsonRpcReq>| {
+                let (in_flight, peak) = (in_flight.clone(), peak.clone());
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let result = match req.method.as_str() {
+                        "getblockcount" => json!(height),
+                        _ => json!(hash),
+                    };
+                    Json(JsonRpcResp { jsonrpc: "2.0", id: 1, result })
+                }
+            }),
+        )
+    };
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let mut config = cfg(vec![trusted.url()]);
+    config.exposed_rpc_poll_concurrency = 3;
+    let state = build_state_with(config).await;
+    for i in 0..8 {
+        let mut node = make_node(&format!("http://{addr}"));
+        node.label = Some(format!("slow-{i}"));
+        state.store().insert_node(&node, &format!("auth-{i}")).await.unwrap();
+    }
+
+    let sum = scheduler::run_exposed_rpc_polls(&state).await.unwrap();
+    assert_eq!(sum.polled, 8);
+    assert_eq!(peak.load(Ordering::SeqCst), 3, "polls overlap, but never more than the limit");
+
+    server.abort();
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn rpc_poll_endpoint_explains_the_last_poll() {
+    let height = 3_352_200u64;
+    let operator = MockNode::start(responses(height, "aa")).await;
+    let trusted = MockNode::start(responses(height, "bb")).await;
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node(&operator.url());
+    state.store().insert_node(&node, "auth-poll").await.unwrap();
+
+    let get = |state: AppState| async move {
+        let req = Request::builder()
+            .uri(format!("/api/nodes/{}/rpc-poll", node.id))
+            .body(Body::empty())
+            .unwrap();
+        let resp = api::router(state).oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice::<Value>(&bytes).unwrap()
+    };
+    assert!(get(state.clone()).await.is_null(), "null before the first poll");
+
+    scheduler::run_exposed_rpc_polls(&state).await.unwrap();
+    let body = get(state.clone()).await;
+    assert_eq!(body["last_outcome"], "hash_mismatch");
+    assert_eq!(body["consecutive_failures"], 0);
+    assert!(body["last_latency_ms"].is_u64());
+
+    let req = Request::builder()
+        .uri(format!("/api/nodes/{}/rpc-poll", Uuid::new_v4()))
+        .body(Body::empty())
+        .unwrap();
+    let resp = api::router(state).oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    operator.shutdown();
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn block_landing_mid_batch_does_not_reject_an_honest_node() {
+    let height = 3_352_300u64;
+    let hash = "0000000000000000000000000000000000000000000000000000000000beef03";
+    let next_hash = "0000000000000000000000000000000000000000000000000000000000beef04";
+    let trusted = MockNode::start(responses(height, hash)).await;
+
+    // Operator node that answers batches, where a block lands right after
+    // getblockcount is read: its best hash is already the next block's.
+    let asked = Arc::new(Mutex::new(Vec::<Value>::new()));
+    let app = {
+        let asked = asked.clone();
+        Router::new().route(
+            "/",
+            post(move |Json(body): Json<Value>| {
+                let asked = asked.clone();
+                async move {
+                    asked.lock().await.push(body.clone());
+                    let answer = |call: &Value| {
+                        let result = match call["method"].as_str() {
+                            Some("getblockcount") => json!(height),
+                            Some("getbestblockhash") => json!(next_hash),
+                            Some("getblockhash") if call["params"][0] == json!(height) => json!(hash),
+                            Some("getblockhash") => json!(next_hash),
+                            _ => Value::Null,
+                        };
+                        json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })
+                    };
+                    match body {
+                        Value::Array(calls) => Json(Value::Array(calls.iter().map(answer).collect())),
+                        call => Json(answer(&call)),
+                    }
+                }
+            }),
+        )
+    };
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node(&format!("http://{addr}"));
+    state.store().insert_node(&node, "auth-race").await.unwrap();
+
+    let outcome = scheduler::poll_one_node(&state, &node, Some(height)).await.unwrap();
+    assert_eq!(outcome, Some(RpcPollOutcome::Credited));
+    let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
+    assert_eq!(proofs[0].verdict, ProofVerdict::Accepted);
+    assert_eq!(proofs[0].claimed_height, height);
+    assert_eq!(proofs[0].claimed_block_hash, hash);
+
+    // One batch (tip + chain info), then the hash asked for by height.
+    let asked = asked.lock().await;
+    assert_eq!(asked.len(), 2);
+    assert!(asked[0].is_array());
+    assert_eq!(asked[1]["method"], "getblockhash");
+    assert_eq!(asked[1]["params"], json!([height]));
+
+    server.abort();
+    trusted.shutdown();
+}
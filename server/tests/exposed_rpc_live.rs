@@ -23,8 +23,8 @@ use depinzcash_server::{
     rpc::ZcashRpcQuorum,
     scheduler,
     state::AppState,
-    store::SqliteStore,
-    types::{Node, NodeKind, NodeStatus, ProofVerdict},
+    store::{SqliteStore, Store},
+    types::{ChallengeKind, Node, NodeKind, NodeStatus, ProofVerdict},
 };
 use serde_json::json;
 use std::time::Duration;
@@ -50,6 +50,14 @@ fn cfg(trusted_rpcs: Vec<String>) -> Config {
         database_url: "sqlite::memory:".into(),
         trusted_rpcs,
         rpc_timeout: Duration::from_secs(15),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
         admin_api_key: Some("admin-key".into()),
         cors_allowed_origins: vec![],
         scheduler_enabled: false,
@@ -58,6 +66,22 @@ fn cfg(trusted_rpcs: Vec<String>) -> Config {
         uptime_reward_interval: Duration::from_secs(60),
         snapshot_interval: None,
         exposed_rpc_poll_interval: Some(Duration::from_secs(60)),
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: true,
+        rpc_credentials_key: None,
         max_height_drift: 8,
         max_clock_skew: Duration::from_secs(15 * 60),
         rate_limit_enabled: false,
@@ -70,6 +94,7 @@ fn cfg(trusted_rpcs: Vec<String>) -> Config {
         spl_mint: None,
         solana_cluster: "devnet".into(),
         network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
     }
 }
 
@@ -87,6 +112,7 @@ fn make_node(rpc_endpoint: &str) -> Node {
         kind: NodeKind::ZebraFull,
         label: Some("live-test".into()),
         rpc_endpoint: Some(rpc_endpoint.to_string()),
+        p2p_address: None,
         network: "mainnet".into(),
         status: NodeStatus::Registered,
         last_height: None,
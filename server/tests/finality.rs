@@ -0,0 +1,297 @@
+// Integration tests for reorg-aware finality.
+//
+// Accepted proofs are inserted directly, the trusted tip is pinned, and
+// `scheduler::confirm_finality` runs against a mock trusted RPC that answers
+// getblockhash with the "current" chain.
+
+use axum::{routing::post, Json, Router};
+use chrono::Utc;
+use depinzcash_server::{
+    config::{Config, ZcashNetwork},
+    merkle,
+    rpc::ZcashRpcQuorum,
+    scheduler,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::{ChallengeKind, Node, NodeKind, NodeStatus, Proof, ProofVerdict},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{net::TcpListener, task::JoinHandle};
+use uuid::Uuid;
+
+// ---- mock trusted zcashd ---------------------------------------------------
+
+#[derive(Deserialize)]
+struct JsonRpcReq {
+    method: String,
+    #[serde(default, rename = "params")]
+    _params: Value,
+}
+
+#[derive(Serialize)]
+struct JsonRpcResp {
+    jsonrpc: &'static str,
+    id: u32,
+    result: Value,
+}
+
+struct MockNode {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl MockNode {
+    async fn start(block_hash: &str) -> Self {
+        let hash = Arc::new(block_hash.to_string());
+        let app = Router::new().route(
+            "/",
+            post(move |Json(req): Json<JsonRpcReq>| {
+                let hash = hash.clone();
+                async move {
+                    let result = match req.method.as_str() {
+                        "getblockhash" => json!(hash.as_str()),
+                        _ => Value::Null,
+                    };
+                    Json(JsonRpcResp {
+                        jsonrpc: "2.0",
+                        id: 1,
+                        result,
+                    })
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        MockNode { addr, handle }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    fn shutdown(self) {
+        self.handle.abort();
+    }
+}
+
+// ---- fixture builders -----------------------------------------------------
+
+fn cfg(trusted_rpcs: Vec<String>) -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs,
+        rpc_timeout: Duration::from_secs(2),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: DEPTH,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: Duration::from_secs(15 * 60),
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: None,
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+async fn build_state(trusted_rpcs: Vec<String>) -> AppState {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let rpc = ZcashRpcQuorum::new(trusted_rpcs.clone(), Duration::from_secs(2));
+    AppState::new(cfg(trusted_rpcs), store, rpc)
+}
+
+fn make_node(wallet: &str) -> Node {
+    Node {
+        id: Uuid::new_v4(),
+        wallet: wallet.into(),
+        kind: NodeKind::ZebraFull,
+        label: Some("test-node".into()),
+        rpc_endpoint: None,
+        p2p_address: None,
+        network: "mainnet".into(),
+        status: NodeStatus::Registered,
+        last_height: None,
+        last_block_hash: None,
+        last_proof_at: None,
+        registered_at: Utc::now(),
+        points: 0,
+        uptime_seconds: 0,
+    }
+}
+
+// Inserts an accepted proof and credits the node, the way submit does.
+async fn accept(state: &AppState, node: &Node, height: u64, hash: &str, points: u64) -> Proof {
+    let now = Utc::now();
+    let proof = Proof {
+        id: Uuid::new_v4(),
+        node_id: node.id,
+        wallet: node.wallet.clone(),
+        claimed_height: height,
+        claimed_block_hash: hash.into(),
+        proof_timestamp: now,
+        binary_hash: None,
+        uptime_seconds: None,
+        peers: None,
+        verdict: ProofVerdict::Accepted,
+        reject_reason: None,
+        points_awarded: points,
+        received_at: now,
+        trusted_tip_height: Some(height),
+        finalized_at: None,
+    };
+    state.store().insert_proof(&proof).await.unwrap();
+    state
+        .store()
+        .apply_proof_acceptance(node.id, proof.id, height, hash, points, now)
+        .await
+        .unwrap();
+    proof
+}
+
+const DEPTH: u64 = 10;
+const HEIGHT: u64 = 3_350_000;
+const HASH: &str = "0000000000abcdef1234567890abcdef1234567890abcdef1234567890abcd01";
+const ORPHAN: &str = "00000000000000000000000000000000000000000000000000000000000dead0";
+
+// ---- tests ----------------------------------------------------------------
+
+#[tokio::test]
+async fn deep_matching_proof_is_finalized_and_shallow_one_waits() {
+    let trusted = MockNode::start(HASH).await;
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node("WalletA");
+    state.store().insert_node(&node, "tok").await.unwrap();
+    let deep = accept(&state, &node, HEIGHT, HASH, 60).await;
+    let shallow = accept(&state, &node, HEIGHT + 5, HASH, 60).await;
+    state.set_trusted_tip(HEIGHT + DEPTH).await;
+
+    let sum = scheduler::confirm_finality(&state).await.unwrap();
+    assert_eq!(sum.finalized, 1);
+    assert_eq!(sum.reorged, 0);
+
+    let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
+    let get = |id| proofs.iter().find(|p| p.id == id).unwrap();
+    assert!(get(deep.id).finalized_at.is_some());
+    assert_eq!(get(deep.id).verdict, ProofVerdict::Accepted);
+    assert!(get(shallow.id).finalized_at.is_none(), "not {DEPTH} deep yet");
+
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.points, 120);
+
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn reorged_proof_is_revoked_and_points_clawed_back() {
+    // Quorum's chain no longer has ORPHAN at HEIGHT.
+    let trusted = MockNode::start(HASH).await;
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node("WalletA");
+    state.store().insert_node(&node, "tok").await.unwrap();
+    let orphaned = accept(&state, &node, HEIGHT, ORPHAN, 60).await;
+    let kept = accept(&state, &node, HEIGHT + 1, HASH, 40).await;
+    state.set_trusted_tip(HEIGHT + 1 + DEPTH).await;
+
+    let sum = scheduler::confirm_finality(&state).await.unwrap();
+    assert_eq!(sum.reorged, 1);
+    assert_eq!(sum.finalized, 1);
+    assert_eq!(sum.points_revoked, 60);
+
+    let proofs = state.store().list_proofs_by_node(node.id, 10).await.unwrap();
+    let p = proofs.iter().find(|p| p.id == orphaned.id).unwrap();
+    assert_eq!(p.verdict, ProofVerdict::Reorged);
+    assert!(p.reject_reason.as_deref().unwrap().starts_with("reorged:"));
+    let k = proofs.iter().find(|p| p.id == kept.id).unwrap();
+    assert_eq!(k.verdict, ProofVerdict::Accepted);
+
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.points, 40);
+
+    // Re-running is a no-op — nothing left to confirm, no double clawback.
+    let sum = scheduler::confirm_finality(&state).await.unwrap();
+    assert_eq!(sum, scheduler::FinalitySummary::default());
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.points, 40);
+
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn snapshot_counts_only_finalized_points() {
+    let trusted = MockNode::start(HASH).await;
+    let state = build_state(vec![trusted.url()]).await;
+    let a = make_node("WalletA");
+    let b = make_node("WalletB");
+    state.store().insert_node(&a, "tok-a").await.unwrap();
+    state.store().insert_node(&b, "tok-b").await.unwrap();
+    accept(&state, &a, HEIGHT, HASH, 60).await;
+    // B's proof is still inside the reorg window at snapshot time.
+    accept(&state, &b, HEIGHT + 5, HASH, 60).await;
+    state.set_trusted_tip(HEIGHT + DEPTH).await;
+    scheduler::confirm_finality(&state).await.unwrap();
+
+    let res = merkle::publish_snapshot(&state).await.unwrap();
+    assert_eq!(res.leaves, 1, "B has no finalized points yet");
+    assert_eq!(res.total_points, 60);
+    let (_, _, _, total) = state.store().latest_snapshot().await.unwrap().unwrap();
+    assert_eq!(total, 60);
+
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn no_trusted_tip_means_nothing_is_checked() {
+    let trusted = MockNode::start(ORPHAN).await;
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node("WalletA");
+    state.store().insert_node(&node, "tok").await.unwrap();
+    accept(&state, &node, HEIGHT, HASH, 60).await;
+
+    let sum = scheduler::confirm_finality(&state).await.unwrap();
+    assert_eq!(sum, scheduler::FinalitySummary::default());
+    assert_eq!(state.store().get_node(node.id).await.unwrap().unwrap().points, 60);
+
+    trusted.shutdown();
+}
@@ -0,0 +1,196 @@
+// Nonce expiry: `used_nonces` is pruned once a nonce's signed timestamp falls
+// outside MAX_CLOCK_SKEW (plus a grace period), and a replay inside the
+// window is still refused after a prune.
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+};
+use chrono::{Duration as ChronoDuration, Utc};
+use depinzcash_server::{
+    api,
+    auth::{proof_message, registration_message},
+    config::{Config, ZcashNetwork},
+    rpc::ZcashRpcQuorum,
+    scheduler::prune_used_nonces,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::ChallengeKind,
+};
+use ed25519_dalek::{Signer, SigningKey};
+use http_body_util::BodyExt;
+use rand::RngCore;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tower::ServiceExt;
+
+const SKEW: Duration = Duration::from_secs(60);
+
+fn test_config() -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs: vec![],
+        rpc_timeout: Duration::from_secs(1),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("test-admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: SKEW,
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: Some("So11111111111111111111111111111111111111112".into()),
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+async fn build_state() -> (AppState, SqliteStore) {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let rpc = ZcashRpcQuorum::new(vec![], Duration::from_secs(1));
+    (AppState::new(test_config(), store.clone(), rpc), store)
+}
+
+fn fresh_keypair() -> (String, SigningKey) {
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let sk = SigningKey::from_bytes(&secret);
+    let wallet = bs58::encode(sk.verifying_key().to_bytes()).into_string();
+    (wallet, sk)
+}
+
+fn b58_sig(sk: &SigningKey, msg: &[u8]) -> String {
+    bs58::encode(sk.sign(msg).to_bytes()).into_string()
+}
+
+async fn json_post(state: &AppState, path: &str, body: Value) -> (StatusCode, Value) {
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(path)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let resp = api::router(state.clone()).oneshot(req).await.unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn nonce_count(store: &SqliteStore) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM used_nonces")
+        .fetch_one(store.pool())
+        .await
+        .unwrap()
+}
+
+fn register_body(wallet: &str, sk: &SigningKey, nonce: &str) -> Value {
+    let ts = Utc::now().to_rfc3339();
+    let msg = registration_message(wallet, nonce, &ts, "zebra-full", "mainnet", "");
+    json!({
+        "wallet": wallet, "signature": b58_sig(sk, &msg), "nonce": nonce,
+        "timestamp": ts, "kind": "zebra-full",
+    })
+}
+
+#[tokio::test]
+async fn replay_inside_the_window_is_rejected_after_a_prune() {
+    let (state, store) = build_state().await;
+    let (wallet, sk) = fresh_keypair();
+    let reg = register_body(&wallet, &sk, "expiry-reg-nonce-1234567890");
+    let (status, body) = json_post(&state, "/api/nodes/register", reg.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let node_id = body["node"]["id"].as_str().unwrap().to_string();
+
+    let nonce = "expiry-proof-nonce-1234567890";
+    let hash = "0000000000abcdef1234567890abcdef1234567890abcdef1234567890abcd";
+    let ts = Utc::now().to_rfc3339();
+    let msg = proof_message(&wallet, &node_id, 2_500_000, hash, &ts, nonce);
+    let proof = json!({
+        "wallet": wallet, "node_id": node_id, "signature": b58_sig(&sk, &msg), "nonce": nonce,
+        "claimed_height": 2_500_000, "claimed_block_hash": hash, "proof_timestamp": ts,
+    });
+    let (status, body) = json_post(&state, "/api/proofs/submit", proof.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    assert_eq!(prune_used_nonces(&state).await.unwrap(), 0, "both nonces are inside the window");
+    assert_eq!(nonce_count(&store).await, 2);
+
+    let (status, body) = json_post(&state, "/api/proofs/submit", proof).await;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    let (status, body) = json_post(&state, "/api/nodes/register", reg).await;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+}
+
+#[tokio::test]
+async fn nonces_past_the_skew_and_grace_are_pruned() {
+    let (state, store) = build_state().await;
+    let now = Utc::now();
+    // Outside the skew but inside the grace: kept for now.
+    store.try_use_nonce("recent-nonce", "w", now - ChronoDuration::minutes(3)).await.unwrap();
+    store.try_use_nonce("old-nonce", "w", now - ChronoDuration::minutes(30)).await.unwrap();
+    store.try_use_nonce("fresh-nonce", "w", now).await.unwrap();
+
+    assert_eq!(prune_used_nonces(&state).await.unwrap(), 1);
+    assert_eq!(nonce_count(&store).await, 2);
+    assert!(!store.try_use_nonce("recent-nonce", "w", now).await.unwrap());
+}
+
+#[tokio::test]
+async fn a_pruned_nonce_cant_be_replayed_because_its_timestamp_is_stale() {
+    let (state, store) = build_state().await;
+    let (wallet, sk) = fresh_keypair();
+    // A registration signed (and its nonce used) ten minutes ago.
+    let nonce = "stale-reg-nonce-1234567890ab";
+    let ts = (Utc::now() - ChronoDuration::minutes(10)).to_rfc3339();
+    let msg = registration_message(&wallet, nonce, &ts, "zebra-full", "mainnet", "");
+    let body = json!({
+        "wallet": wallet, "signature": b58_sig(&sk, &msg), "nonce": nonce,
+        "timestamp": ts, "kind": "zebra-full",
+    });
+    store
+        .try_use_nonce(nonce, &wallet, Utc::now() - ChronoDuration::minutes(10))
+        .await
+        .unwrap();
+    assert_eq!(prune_used_nonces(&state).await.unwrap(), 1);
+
+    let (status, body) = json_post(&state, "/api/nodes/register", body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert!(body["message"].as_str().unwrap().contains("timestamp"), "{body}");
+    assert_eq!(nonce_count(&store).await, 0);
+}
@@ -0,0 +1,320 @@
+// Integration tests for the egress guard on operator endpoints.
+//
+// A stub resolver stands in for DNS so hostnames can "resolve" to loopback,
+// private or metadata addresses on demand. Every mock counts its hits, so the
+// tests check not just the poll outcome but that nothing was dialled.
+
+use axum::{
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::post,
+    Json, Router,
+};
+use chrono::Utc;
+use depinzcash_server::{
+    config::{Config, ZcashNetwork},
+    egress::{EgressGuard, Resolver},
+    rpc::ZcashRpcQuorum,
+    scheduler,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::{ChallengeKind, Node, NodeKind, NodeStatus, RpcPollOutcome},
+};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+const HEIGHT: u64 = 3_360_000;
+const HASH: &str = "0000000000000000000000000000000000000000000000000000000000e9e551";
+
+// ---- stub resolver ----------------------------------------------------------
+
+#[derive(Default)]
+struct StubResolver {
+    names: HashMap<String, Vec<IpAddr>>,
+    lookups: AtomicUsize,
+}
+
+impl StubResolver {
+    fn with(mut self, host: &str, ips: &[&str]) -> Self {
+        self.names
+            .insert(host.into(), ips.iter().map(|s| s.parse().unwrap()).collect());
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl Resolver for StubResolver {
+    async fn lookup(&self, host: &str, port: u16) -> std::io::Result<Vec<SocketAddr>> {
+        self.lookups.fetch_add(1, Ordering::SeqCst);
+        match self.names.get(host) {
+            Some(ips) => Ok(ips.iter().map(|ip| SocketAddr::new(*ip, port)).collect()),
+            None => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "no such host")),
+        }
+    }
+}
+
+// ---- mocks ------------------------------------------------------------------
+
+// Answers getblockcount / getblockhash / getbestblockhash with HEIGHT / HASH,
+// singly or in a batch, and counts requests.
+async fn mock_zcashd() -> (SocketAddr, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let answer = |req: &Value| {
+        let result = match req["method"].as_str() {
+            Some("getblockcount") => json!(HEIGHT),
+            Some("getblockhash" | "getbestblockhash") => json!(HASH),
+            _ => Value::Null,
+        };
+        json!({"jsonrpc": "2.0", "id": req["id"], "result": result})
+    };
+    let app = Router::new().route(
+        "/",
+        post(move |Json(req): Json<Value>| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                match req.as_array() {
+                    Some(batch) => Json(Value::Array(batch.iter().map(answer).collect())),
+                    None => Json(answer(&req)),
+                }
+            }
+        }),
+    );
+    serve(app, hits).await
+}
+
+// Answers every request with a 307 to `location`.
+async fn mock_redirect(location: String) -> (SocketAddr, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let app = Router::new().route(
+        "/",
+        post(move || {
+            let counter = counter.clone();
+            let location = location.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                (StatusCode::TEMPORARY_REDIRECT, [(header::LOCATION, location)]).into_response()
+            }
+        }),
+    );
+    serve(app, hits).await
+}
+
+async fn serve(app: Router, hits: Arc<AtomicUsize>) -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (addr, hits)
+}
+
+// ---- fixture builders -------------------------------------------------------
+
+fn cfg(trusted_rpcs: Vec<String>, allow_private_endpoints: bool) -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs,
+        rpc_timeout: Duration::from_secs(2),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: Some(Duration::from_secs(60)),
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: Some(Duration::from_secs(3600)),
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: Duration::from_secs(15 * 60),
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: None,
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+// The trusted quorum is server config, not operator input, so it's reached
+// through `call_single` and never meets the guard.
+async fn build_state(resolver: Arc<StubResolver>, allow_private: bool) -> AppState {
+    let (trusted, _) = mock_zcashd().await;
+    let config = cfg(vec![format!("http://{trusted}")], allow_private);
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let rpc = ZcashRpcQuorum::new(config.trusted_rpcs.clone(), Duration::from_secs(2));
+    let egress = EgressGuard::with_resolver(resolver, allow_private);
+    AppState::with_egress(config, Arc::new(store), rpc, egress)
+}
+
+async fn insert_node(state: &AppState, rpc_endpoint: Option<String>, p2p_address: Option<String>) -> Node {
+    let node = Node {
+        id: Uuid::new_v4(),
+        wallet: "WalletEgress".into(),
+        kind: NodeKind::ZebraFull,
+        label: Some(format!("egress-{}", Uuid::new_v4())),
+        rpc_endpoint,
+        p2p_address,
+        network: "mainnet".into(),
+        status: NodeStatus::Registered,
+        last_height: None,
+        last_block_hash: None,
+        last_proof_at: None,
+        registered_at: Utc::now(),
+        points: 0,
+        uptime_seconds: 0,
+    };
+    state
+        .store()
+        .insert_node(&node, &format!("token-{}", node.id))
+        .await
+        .unwrap();
+    node
+}
+
+// ---- tests ------------------------------------------------------------------
+
+#[tokio::test]
+async fn hostname_resolving_to_loopback_is_refused_before_dialling() {
+    let (operator, hits) = mock_zcashd().await;
+    let resolver = Arc::new(StubResolver::default().with("rpc.operator.test", &["127.0.0.1"]));
+    let state = build_state(resolver.clone(), false).await;
+    let node = insert_node(&state, Some(format!("http://rpc.operator.test:{}", operator.port())), None).await;
+
+    let err = scheduler::poll_one_node(&state, &node, Some(HEIGHT))
+        .await
+        .expect_err("a name resolving to loopback must not be polled");
+    assert!(err.to_string().contains("127.0.0.1"), "{err}");
+    assert_eq!(hits.load(Ordering::SeqCst), 0, "operator mock must not be contacted");
+    assert_eq!(resolver.lookups.load(Ordering::SeqCst), 1);
+    assert!(state.store().list_proofs_by_node(node.id, 10).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn metadata_and_mixed_answers_are_refused() {
+    let resolver = Arc::new(
+        StubResolver::default()
+            .with("meta.operator.test", &["169.254.169.254"])
+            .with("meta6.operator.test", &["fd00:ec2::254"])
+            .with("mixed.operator.test", &["203.0.113.10", "10.0.0.7"]),
+    );
+    let state = build_state(resolver, false).await;
+    for (host, bad) in [
+        ("meta.operator.test", "169.254.169.254"),
+        ("meta6.operator.test", "fd00:ec2::254"),
+        ("mixed.operator.test", "10.0.0.7"),
+    ] {
+        let node = insert_node(&state, Some(format!("http://{host}:8232")), None).await;
+        let err = scheduler::poll_one_node(&state, &node, Some(HEIGHT))
+            .await
+            .expect_err(host);
+        assert!(err.to_string().contains(bad), "{host}: {err}");
+    }
+}
+
+#[tokio::test]
+async fn refused_endpoint_is_recorded_as_unreachable() {
+    let resolver = Arc::new(StubResolver::default().with("rpc.operator.test", &["192.168.1.20"]));
+    let state = build_state(resolver, false).await;
+    let node = insert_node(&state, Some("https://rpc.operator.test".into()), None).await;
+
+    let sum = scheduler::run_exposed_rpc_polls(&state).await.unwrap();
+    assert_eq!((sum.polled, sum.credited, sum.unreachable), (1, 0, 1));
+    let stats = state.store().get_rpc_poll_stats(node.id).await.unwrap().unwrap();
+    assert_eq!(stats.last_outcome, RpcPollOutcome::Unreachable);
+    assert!(stats.last_error.as_deref().unwrap_or("").contains("192.168.1.20"));
+}
+
+#[tokio::test]
+async fn connection_is_pinned_to_the_checked_address() {
+    // `.test` never resolves in real DNS, so a credit proves the request went
+    // to the address the stub handed the guard.
+    let (operator, hits) = mock_zcashd().await;
+    let resolver = Arc::new(StubResolver::default().with("pinned.operator.test", &["127.0.0.1"]));
+    let state = build_state(resolver.clone(), true).await;
+    let node = insert_node(&state, Some(format!("http://pinned.operator.test:{}", operator.port())), None).await;
+
+    let outcome = scheduler::poll_one_node(&state, &node, Some(HEIGHT)).await.unwrap();
+    assert_eq!(outcome, Some(RpcPollOutcome::Credited));
+    assert_eq!(hits.load(Ordering::SeqCst), 2, "tip batch, then the hash at that height");
+    assert_eq!(resolver.lookups.load(Ordering::SeqCst), 1, "one lookup per poll");
+}
+
+#[tokio::test]
+async fn redirects_are_not_followed() {
+    let (target, target_hits) = mock_zcashd().await;
+    let (operator, operator_hits) = mock_redirect(format!("http://{target}/")).await;
+    let state = build_state(Arc::new(StubResolver::default()), true).await;
+    let node = insert_node(&state, Some(format!("http://{operator}")), None).await;
+
+    let err = scheduler::poll_one_node(&state, &node, Some(HEIGHT))
+        .await
+        .expect_err("a redirect is a failed poll");
+    assert!(err.to_string().contains("307"), "{err}");
+    assert_eq!(operator_hits.load(Ordering::SeqCst), 1);
+    assert_eq!(target_hits.load(Ordering::SeqCst), 0, "redirect target must not be contacted");
+}
+
+#[tokio::test]
+async fn p2p_probe_refuses_private_resolution() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let counter = accepted.clone();
+    tokio::spawn(async move {
+        while listener.accept().await.is_ok() {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    let resolver = Arc::new(StubResolver::default().with("peer.operator.test", &["127.0.0.1"]));
+    let state = build_state(resolver, false).await;
+    let node = insert_node(&state, None, Some(format!("peer.operator.test:{port}"))).await;
+
+    let probe = scheduler::probe_one_node(&state, &node).await.unwrap().unwrap();
+    assert!(!probe.reachable);
+    assert_eq!(probe.points_awarded, 0);
+    assert!(probe.error.as_deref().unwrap_or("").contains("127.0.0.1"), "{:?}", probe.error);
+    assert_eq!(accepted.load(Ordering::SeqCst), 0, "no TCP connection attempted");
+}
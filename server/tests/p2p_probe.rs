@@ -0,0 +1,360 @@
+// Integration tests for the P2P reachability probe.
+//
+// A local TCP listener stands in for the operator's full node and speaks just
+// enough of the Zcash peer protocol for a version/verack handshake. We call
+// scheduler::probe_one_node / run_p2p_probes directly. No live network. The
+// stand-in reports start height 3_350_000.
+
+use chrono::Utc;
+use depinzcash_server::{
+    config::{Config, ZcashNetwork},
+    p2p::{self, VersionMessage},
+    rpc::ZcashRpcQuorum,
+    scheduler,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::{ChallengeKind, LedgerSource, Node, NodeKind, NodeStatus},
+};
+use std::{net::SocketAddr, time::Duration};
+use tokio::{net::TcpListener, task::JoinHandle};
+use uuid::Uuid;
+
+// ---- stand-in peer ----------------------------------------------------------
+
+#[derive(Clone, Copy)]
+enum Behaviour {
+    // Chatter first, then version; verack once ours arrives.
+    Honest,
+    // Speaks testnet magic to a mainnet prober.
+    WrongNetwork,
+    // Accepts and never says anything.
+    Silent,
+}
+
+struct StandInPeer {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl StandInPeer {
+    async fn start(behaviour: Behaviour) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            loop {
+                let Ok((mut sock, _)) = listener.accept().await else { return };
+                tokio::spawn(async move {
+                    let magic = match behaviour {
+                        Behaviour::WrongNetwork => p2p::magic(ZcashNetwork::Testnet),
+                        _ => p2p::magic(ZcashNetwork::Mainnet),
+                    };
+                    if let Behaviour::Silent = behaviour {
+                        tokio::time::sleep(Duration::from_secs(30)).await;
+                        return;
+                    }
+                    let theirs = VersionMessage {
+                        version: p2p::PROTOCOL_VERSION,
+                        services: 1,
+                        timestamp: Utc::now().timestamp(),
+                        nonce: 7,
+                        user_agent: "/Zebra:2.4.0/".into(),
+                        start_height: 3_350_000,
+                        relay: true,
+                    };
+                    let _ = p2p::write_message(&mut sock, magic, "sendaddrv2", &[]).await;
+                    let _ = p2p::write_message(&mut sock, magic, "version", &theirs.encode()).await;
+                    let mut sent_verack = false;
+                    while let Ok((command, payload)) =
+                        p2p::read_message(&mut sock, p2p::magic(ZcashNetwork::Mainnet)).await
+                    {
+                        match command.as_str() {
+                            "version" => {
+                                let ours = VersionMessage::decode(&payload).unwrap();
+                                assert!(ours.user_agent.starts_with("/DePINZcash-probe:"));
+                            }
+                            "verack" if !sent_verack => {
+                                sent_verack = true;
+                                let _ = p2p::write_message(&mut sock, magic, "verack", &[]).await;
+                            }
+                            _ => {}
+                        }
+                    }
+                });
+            }
+        });
+        StandInPeer { addr, handle }
+    }
+
+    fn address(&self) -> String {
+        self.addr.to_string()
+    }
+
+    fn shutdown(self) {
+        self.handle.abort();
+    }
+}
+
+// Bound then dropped — connections get refused.
+async fn dead_address() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    addr.to_string()
+}
+
+// ---- fixture builders -----------------------------------------------------
+
+fn cfg() -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs: vec![],
+        rpc_timeout: Duration::from_millis(500),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: Some(Duration::from_secs(3600)),
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: true,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: Duration::from_secs(15 * 60),
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: None,
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+async fn build_state() -> AppState {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let rpc = ZcashRpcQuorum::new(vec![], Duration::from_secs(2));
+    AppState::new(cfg(), store, rpc)
+}
+
+fn make_node(p2p_address: Option<String>) -> Node {
+    Node {
+        id: Uuid::new_v4(),
+        wallet: "WalletABC".into(),
+        kind: NodeKind::ZebraFull,
+        label: Some("p2p-node".into()),
+        rpc_endpoint: None,
+        p2p_address,
+        network: "mainnet".into(),
+        status: NodeStatus::Active,
+        last_height: None,
+        last_block_hash: None,
+        last_proof_at: None,
+        registered_at: Utc::now(),
+        points: 0,
+        uptime_seconds: 0,
+    }
+}
+
+// ---- tests ----------------------------------------------------------------
+
+#[tokio::test]
+async fn reachable_node_records_peer_version_and_earns_bonus() {
+    let peer = StandInPeer::start(Behaviour::Honest).await;
+    let state = build_state().await;
+    let node = make_node(Some(peer.address()));
+    state.store().insert_node(&node, "tok-1").await.unwrap();
+
+    let probe = scheduler::probe_one_node(&state, &node)
+        .await
+        .unwrap()
+        .expect("node has a p2p address");
+    assert!(probe.reachable, "handshake should complete: {:?}", probe.error);
+    assert_eq!(probe.user_agent.as_deref(), Some("/Zebra:2.4.0/"));
+    assert_eq!(probe.protocol_version, Some(p2p::PROTOCOL_VERSION));
+    assert_eq!(probe.start_height, Some(3_350_000));
+    assert_eq!(probe.points_awarded, 10, "zebra-full reward tier");
+
+    let stored = state.store().list_p2p_probes_for_node(node.id, 10).await.unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].user_agent.as_deref(), Some("/Zebra:2.4.0/"));
+
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.points, 10);
+    let ledger = state.store().ledger_for_node(node.id, 10).await.unwrap();
+    assert_eq!(ledger.len(), 1);
+    assert_eq!(ledger[0].source, LedgerSource::P2pReachability);
+    assert_eq!(ledger[0].ref_id.as_deref(), Some(probe.id.to_string().as_str()));
+    assert!(state.store().points_mismatches().await.unwrap().is_empty());
+
+    peer.shutdown();
+}
+
+#[tokio::test]
+async fn unproven_address_is_paid_once() {
+    let peer = StandInPeer::start(Behaviour::Honest).await;
+    let state = build_state().await;
+    let node = make_node(Some(peer.address()));
+    state.store().insert_node(&node, "tok-once").await.unwrap();
+
+    let first = scheduler::probe_one_node(&state, &node).await.unwrap().unwrap();
+    let second = scheduler::probe_one_node(&state, &node).await.unwrap().unwrap();
+    assert!(first.reachable && second.reachable);
+    assert_eq!((first.points_awarded, second.points_awarded), (10, 0));
+    assert_eq!(state.store().get_node(node.id).await.unwrap().unwrap().points, 10);
+
+    peer.shutdown();
+}
+
+#[tokio::test]
+async fn proven_address_is_paid_every_probe() {
+    let peer = StandInPeer::start(Behaviour::Honest).await;
+    let state = build_state().await;
+    // Same host as the rpc_endpoint we already poll...
+    let mut same_host = make_node(Some(peer.address()));
+    same_host.rpc_endpoint = Some("http://127.0.0.1:8232".into());
+    // ...or an accepted proof within the stale window.
+    let mut proving = make_node(Some(format!("localhost:{}", peer.addr.port())));
+    proving.label = Some("proving".into());
+    proving.last_proof_at = Some(Utc::now());
+    for (i, node) in [&same_host, &proving].into_iter().enumerate() {
+        state.store().insert_node(node, &format!("tok-proven-{i}")).await.unwrap();
+        for _ in 0..2 {
+            let probe = scheduler::probe_one_node(&state, node).await.unwrap().unwrap();
+            assert_eq!(probe.points_awarded, 10, "{:?}", node.label);
+        }
+        assert_eq!(state.store().get_node(node.id).await.unwrap().unwrap().points, 20);
+    }
+
+    peer.shutdown();
+}
+
+#[tokio::test]
+async fn start_height_is_judged_against_the_trusted_tip() {
+    let peer = StandInPeer::start(Behaviour::Honest).await;
+    let state = build_state().await;
+    let node = make_node(Some(peer.address()));
+    state.store().insert_node(&node, "tok-tip").await.unwrap();
+
+    // max_height_drift is 8 in cfg().
+    for (tip, why) in [(3_350_100, "behind"), (3_349_000, "ahead")] {
+        state.set_trusted_tip(tip).await;
+        let probe = scheduler::probe_one_node(&state, &node).await.unwrap().unwrap();
+        assert!(probe.reachable);
+        assert_eq!(probe.points_awarded, 0);
+        assert!(probe.error.as_deref().unwrap().contains(why), "{:?}", probe.error);
+    }
+
+    state.set_trusted_tip(3_350_005).await;
+    let probe = scheduler::probe_one_node(&state, &node).await.unwrap().unwrap();
+    assert!(probe.error.is_none());
+    assert_eq!(probe.points_awarded, 10);
+
+    peer.shutdown();
+}
+
+#[tokio::test]
+async fn refused_connection_is_recorded_unreachable_without_bonus() {
+    let state = build_state().await;
+    let node = make_node(Some(dead_address().await));
+    state.store().insert_node(&node, "tok-2").await.unwrap();
+
+    let probe = scheduler::probe_one_node(&state, &node).await.unwrap().unwrap();
+    assert!(!probe.reachable);
+    assert!(probe.error.as_deref().unwrap().contains("connecting"));
+    assert_eq!(probe.points_awarded, 0);
+    assert!(probe.user_agent.is_none());
+
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.points, 0);
+    assert_eq!(state.store().list_p2p_probes_for_node(node.id, 10).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn peer_on_other_network_is_unreachable() {
+    let peer = StandInPeer::start(Behaviour::WrongNetwork).await;
+    let state = build_state().await;
+    let node = make_node(Some(peer.address()));
+    state.store().insert_node(&node, "tok-3").await.unwrap();
+
+    let probe = scheduler::probe_one_node(&state, &node).await.unwrap().unwrap();
+    assert!(!probe.reachable);
+    assert!(probe.error.as_deref().unwrap().contains("magic"));
+    assert_eq!(probe.points_awarded, 0);
+
+    peer.shutdown();
+}
+
+#[tokio::test]
+async fn silent_peer_times_out() {
+    let peer = StandInPeer::start(Behaviour::Silent).await;
+    let state = build_state().await;
+    let node = make_node(Some(peer.address()));
+    state.store().insert_node(&node, "tok-4").await.unwrap();
+
+    let probe = scheduler::probe_one_node(&state, &node).await.unwrap().unwrap();
+    assert!(!probe.reachable);
+    assert!(probe.error.as_deref().unwrap().contains("timed out"));
+
+    peer.shutdown();
+}
+
+#[tokio::test]
+async fn run_probes_only_targets_unsuspended_nodes_with_an_address() {
+    let peer = StandInPeer::start(Behaviour::Honest).await;
+    let state = build_state().await;
+    let reachable = make_node(Some(peer.address()));
+    let mut dead = make_node(Some(dead_address().await));
+    dead.label = Some("dead".into());
+    let mut no_address = make_node(None);
+    no_address.label = Some("rpc-only".into());
+    let mut suspended = make_node(Some(dead_address().await));
+    suspended.label = Some("suspended".into());
+    suspended.status = NodeStatus::Suspended;
+    for (i, n) in [&reachable, &dead, &no_address, &suspended].into_iter().enumerate() {
+        state.store().insert_node(n, &format!("tok-run-{i}")).await.unwrap();
+    }
+
+    let sum = scheduler::run_p2p_probes(&state).await.unwrap();
+    assert_eq!(
+        sum,
+        scheduler::P2pProbeSummary {
+            probed: 2,
+            reachable: 1,
+            unreachable: 1,
+            out_of_sync: 0,
+        }
+    );
+    assert!(state.store().list_p2p_probes_for_node(no_address.id, 10).await.unwrap().is_empty());
+    assert!(state.store().list_p2p_probes_for_node(suspended.id, 10).await.unwrap().is_empty());
+
+    peer.shutdown();
+}
@@ -0,0 +1,311 @@
+// Integration tests for the pending-proof re-check worker.
+//
+// Proofs are inserted directly as Pending (what `submit` writes when the
+// trusted quorum errors), then `scheduler::recheck_pending_proofs` is run
+// against a mock trusted RPC. No live network, no scheduler timing.
+
+use axum::{routing::post, Json, Router};
+use chrono::{Duration as ChronoDuration, Utc};
+use depinzcash_server::{
+    config::{Config, ZcashNetwork},
+    rpc::ZcashRpcQuorum,
+    scheduler,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::{ChallengeKind, Node, NodeKind, NodeStatus, Proof, ProofVerdict},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{net::TcpListener, task::JoinHandle};
+use uuid::Uuid;
+
+// ---- mock trusted zcashd ---------------------------------------------------
+
+#[derive(Deserialize)]
+struct JsonRpcReq {
+    method: String,
+    #[serde(default, rename = "params")]
+    _params: Value,
+}
+
+#[derive(Serialize)]
+struct JsonRpcResp {
+    jsonrpc: &'static str,
+    id: u32,
+    result: Value,
+}
+
+struct MockNode {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl MockNode {
+    async fn start(block_hash: &str) -> Self {
+        let hash = Arc::new(block_hash.to_string());
+        let app = Router::new().route(
+            "/",
+            post(move |Json(req): Json<JsonRpcReq>| {
+                let hash = hash.clone();
+                async move {
+                    let result = match req.method.as_str() {
+                        "getblockhash" => json!(hash.as_str()),
+                        _ => Value::Null,
+                    };
+                    Json(JsonRpcResp {
+                        jsonrpc: "2.0",
+                        id: 1,
+                        result,
+                    })
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        MockNode { addr, handle }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    fn shutdown(self) {
+        self.handle.abort();
+    }
+}
+
+// Bound then dropped — connections get refused, so the quorum errors.
+async fn dead_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{addr}")
+}
+
+// ---- fixture builders -----------------------------------------------------
+
+fn cfg(trusted_rpcs: Vec<String>) -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs,
+        rpc_timeout: Duration::from_secs(2),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: Some(Duration::from_secs(60)),
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(60 * 60),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: Duration::from_secs(15 * 60),
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: None,
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+async fn build_state(trusted_rpcs: Vec<String>) -> AppState {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let rpc = ZcashRpcQuorum::new(trusted_rpcs.clone(), Duration::from_secs(2));
+    AppState::new(cfg(trusted_rpcs), store, rpc)
+}
+
+fn make_node() -> Node {
+    Node {
+        id: Uuid::new_v4(),
+        wallet: "WalletABC".into(),
+        kind: NodeKind::ZebraFull,
+        label: Some("test-node".into()),
+        rpc_endpoint: None,
+        p2p_address: None,
+        network: "mainnet".into(),
+        status: NodeStatus::Registered,
+        last_height: None,
+        last_block_hash: None,
+        last_proof_at: None,
+        registered_at: Utc::now(),
+        points: 0,
+        uptime_seconds: 0,
+    }
+}
+
+fn pending_proof(node: &Node, height: u64, hash: &str, age: ChronoDuration) -> Proof {
+    let received_at = Utc::now() - age;
+    Proof {
+        id: Uuid::new_v4(),
+        node_id: node.id,
+        wallet: node.wallet.clone(),
+        claimed_height: height,
+        claimed_block_hash: hash.into(),
+        proof_timestamp: received_at,
+        binary_hash: None,
+        uptime_seconds: Some(12 * 3600),
+        peers: Some(16),
+        verdict: ProofVerdict::Pending,
+        reject_reason: Some("trusted-rpc-error: timeout".into()),
+        points_awarded: 0,
+        received_at,
+        trusted_tip_height: Some(height),
+        finalized_at: None,
+    }
+}
+
+const HEIGHT: u64 = 3_350_000;
+const HASH: &str = "0000000000abcdef1234567890abcdef1234567890abcdef1234567890abcd01";
+
+// ---- tests ----------------------------------------------------------------
+
+#[tokio::test]
+async fn pending_proof_accepted_and_credited_once_quorum_recovers() {
+    let trusted = MockNode::start(HASH).await;
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node();
+    state.store().insert_node(&node, "tok").await.unwrap();
+    let proof = pending_proof(&node, HEIGHT, HASH, ChronoDuration::minutes(5));
+    state.store().insert_proof(&proof).await.unwrap();
+
+    let sum = scheduler::recheck_pending_proofs(&state).await.unwrap();
+    assert_eq!(sum.accepted, 1);
+
+    let p = &state.store().list_proofs_by_node(node.id, 10).await.unwrap()[0];
+    assert_eq!(p.verdict, ProofVerdict::Accepted);
+    assert!(p.reject_reason.is_none());
+    // Same formula as submit: 10 * (1 + 5) + 12 uptime + 3 peers.
+    assert_eq!(p.points_awarded, 75);
+
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.status, NodeStatus::Active);
+    assert_eq!(n.points, 75);
+    assert_eq!(n.last_height, Some(HEIGHT));
+
+    // A second pass finds nothing to do — no double credit.
+    let sum = scheduler::recheck_pending_proofs(&state).await.unwrap();
+    assert_eq!(sum, scheduler::PendingRecheckSummary::default());
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.points, 75);
+
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn pending_proof_with_wrong_hash_is_rejected() {
+    let trusted = MockNode::start(HASH).await;
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node();
+    state.store().insert_node(&node, "tok").await.unwrap();
+    let bad = "deadbeef".repeat(8);
+    let proof = pending_proof(&node, HEIGHT, &bad, ChronoDuration::minutes(5));
+    state.store().insert_proof(&proof).await.unwrap();
+
+    let sum = scheduler::recheck_pending_proofs(&state).await.unwrap();
+    assert_eq!(sum.rejected, 1);
+
+    let p = &state.store().list_proofs_by_node(node.id, 10).await.unwrap()[0];
+    assert_eq!(p.verdict, ProofVerdict::Rejected);
+    assert!(p.reject_reason.as_deref().unwrap().contains("mismatch"));
+    assert_eq!(p.points_awarded, 0);
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.points, 0);
+
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn pending_proof_stays_pending_while_quorum_errors() {
+    let state = build_state(vec![dead_url().await]).await;
+    let node = make_node();
+    state.store().insert_node(&node, "tok").await.unwrap();
+    let proof = pending_proof(&node, HEIGHT, HASH, ChronoDuration::minutes(5));
+    state.store().insert_proof(&proof).await.unwrap();
+
+    let sum = scheduler::recheck_pending_proofs(&state).await.unwrap();
+    assert_eq!(sum.still_pending, 1);
+    let p = &state.store().list_proofs_by_node(node.id, 10).await.unwrap()[0];
+    assert_eq!(p.verdict, ProofVerdict::Pending);
+}
+
+#[tokio::test]
+async fn pending_proof_past_max_age_is_rejected_without_credit() {
+    // Quorum would confirm it — expiry wins regardless.
+    let trusted = MockNode::start(HASH).await;
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node();
+    state.store().insert_node(&node, "tok").await.unwrap();
+    let proof = pending_proof(&node, HEIGHT, HASH, ChronoDuration::hours(2));
+    state.store().insert_proof(&proof).await.unwrap();
+
+    let sum = scheduler::recheck_pending_proofs(&state).await.unwrap();
+    assert_eq!(sum.expired, 1);
+    let p = &state.store().list_proofs_by_node(node.id, 10).await.unwrap()[0];
+    assert_eq!(p.verdict, ProofVerdict::Rejected);
+    assert!(p.reject_reason.as_deref().unwrap().starts_with("pending-expired"));
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.points, 0);
+
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn late_acceptance_does_not_move_tip_backwards() {
+    let trusted = MockNode::start(HASH).await;
+    let state = build_state(vec![trusted.url()]).await;
+    let node = make_node();
+    state.store().insert_node(&node, "tok").await.unwrap();
+    // The node has since proven a later height.
+    let newer_hash = "ff".repeat(32);
+    state
+        .store()
+        .apply_proof_acceptance(node.id, Uuid::new_v4(), HEIGHT + 5, &newer_hash, 10, Utc::now())
+        .await
+        .unwrap();
+    let proof = pending_proof(&node, HEIGHT, HASH, ChronoDuration::minutes(5));
+    state.store().insert_proof(&proof).await.unwrap();
+
+    let sum = scheduler::recheck_pending_proofs(&state).await.unwrap();
+    assert_eq!(sum.accepted, 1);
+    let n = state.store().get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(n.last_height, Some(HEIGHT + 5));
+    assert_eq!(n.last_block_hash.as_deref(), Some(newer_hash.as_str()));
+    assert_eq!(n.points, 10 + 75);
+
+    trusted.shutdown();
+}
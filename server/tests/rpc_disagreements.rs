@@ -0,0 +1,305 @@
+// Integration tests for trusted-rpc disagreement forensics.
+//
+// Mock trusted rpcs answer getblockhash with a fixed hash (some of them
+// late); the quorum's disagreements are stored with
+// `scheduler::flush_rpc_disagreements`, read back through
+// /api/admin/rpc/disagreements and counted by `scheduler::check_rpc_dissent`.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use chrono::{Duration as ChronoDuration, Utc};
+use depinzcash_server::{
+    api,
+    config::{Config, ZcashNetwork},
+    rpc::{QuorumPolicy, RpcError, ZcashRpcQuorum},
+    scheduler,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::{ChallengeKind, RpcAnswer, RpcDisagreement},
+};
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{net::TcpListener, task::JoinHandle};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+// ---- mock trusted zcashd ---------------------------------------------------
+
+#[derive(Deserialize)]
+struct JsonRpcReq {
+    #[serde(rename = "method")]
+    _method: String,
+}
+
+#[derive(Serialize)]
+struct JsonRpcResp {
+    jsonrpc: &'static str,
+    id: u32,
+    result: Value,
+}
+
+struct MockNode {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl MockNode {
+    async fn start(block_hash: &str, delay: Duration) -> Self {
+        let hash = Arc::new(block_hash.to_string());
+        let app = Router::new().route(
+            "/",
+            post(move |Json(_req): Json<JsonRpcReq>| {
+                let hash = hash.clone();
+                async move {
+                    tokio::time::sleep(delay).await;
+                    Json(JsonRpcResp {
+                        jsonrpc: "2.0",
+                        id: 1,
+                        result: json!(hash.as_str()),
+                    })
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        MockNode { addr, handle }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for MockNode {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+// ---- fixture builders -----------------------------------------------------
+
+fn cfg(trusted_rpcs: Vec<String>) -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs,
+        rpc_timeout: Duration::from_secs(2),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 3,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: Duration::from_secs(15 * 60),
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: None,
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+async fn build_state(trusted_rpcs: Vec<String>, threshold: Option<u32>) -> AppState {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let rpc = ZcashRpcQuorum::new(trusted_rpcs.clone(), Duration::from_secs(2)).with_policy(QuorumPolicy {
+        threshold,
+        ..QuorumPolicy::default()
+    });
+    AppState::new(cfg(trusted_rpcs), store, rpc)
+}
+
+async fn get_disagreements(state: &AppState, query: &str, key: Option<&str>) -> (StatusCode, Value) {
+    let mut req = Request::get(format!("/api/admin/rpc/disagreements{query}"));
+    if let Some(k) = key {
+        req = req.header("x-admin-key", k);
+    }
+    let resp = api::router(state.clone())
+        .oneshot(req.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+// Straggler disagreements are recorded in the background after the round
+// has answered; waits until the quorum has one to hand over.
+async fn flush_within(state: &AppState, within: Duration) -> usize {
+    let deadline = tokio::time::Instant::now() + within;
+    loop {
+        let n = scheduler::flush_rpc_disagreements(state).await.unwrap();
+        if n > 0 || tokio::time::Instant::now() >= deadline {
+            return n;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+fn host(node: &MockNode) -> String {
+    format!("http://{}", node.addr)
+}
+
+// ---- tests ------------------------------------------------------------------
+
+#[tokio::test]
+async fn no_quorum_round_is_stored_with_every_answer() {
+    let a = MockNode::start("aa", Duration::ZERO).await;
+    let b = MockNode::start("aa", Duration::ZERO).await;
+    let c = MockNode::start("cc", Duration::ZERO).await;
+    let state = build_state(vec![a.url(), b.url(), c.url()], Some(3)).await;
+
+    assert!(matches!(state.rpc().get_block_hash(10).await, Err(RpcError::NoQuorum)));
+
+    let (status, body) = get_disagreements(&state, "", Some("admin-key")).await;
+    assert_eq!(status, StatusCode::OK);
+    let records = body["disagreements"].as_array().unwrap();
+    assert_eq!(records.len(), 1);
+    let d = &records[0];
+    assert_eq!(d["method"], "getblockhash");
+    assert_eq!(d["params"], json!([10]));
+    assert!(d["agreed"].is_null());
+    assert_eq!(d["answers"].as_array().unwrap().len(), 3);
+    assert_eq!(d["dissenters"], json!([host(&c)]));
+    assert_eq!(body["endpoints"][0]["endpoint"], host(&c));
+    assert_eq!(body["endpoints"][0]["dissents"], 1);
+    assert_eq!(body["endpoints"][0]["alerting"], false);
+    assert_eq!((body["alert_threshold"].as_u64(), body["window_secs"].as_u64()), (Some(3), Some(3600)));
+}
+
+#[tokio::test]
+async fn late_dissenter_is_recorded_after_the_round_is_won() {
+    let a = MockNode::start("aa", Duration::ZERO).await;
+    let b = MockNode::start("aa", Duration::ZERO).await;
+    let c = MockNode::start("cc", Duration::from_millis(200)).await;
+    let state = build_state(vec![a.url(), b.url(), c.url()], None).await;
+
+    assert_eq!(state.rpc().get_block_hash(10).await.unwrap(), "aa");
+    assert_eq!(flush_within(&state, Duration::from_secs(3)).await, 1);
+
+    let records = state.store().list_rpc_disagreements(Utc::now() - ChronoDuration::hours(1), 10).await.unwrap();
+    assert_eq!(records[0].agreed, Some(json!("aa")));
+    assert_eq!(records[0].dissenters, vec![host(&c)]);
+    let late = records[0].answers.iter().find(|a| a.endpoint == host(&c)).unwrap();
+    assert_eq!(late.result, Some(json!("cc")));
+}
+
+#[tokio::test]
+async fn unanimous_rounds_and_even_splits_name_no_dissenter() {
+    let a = MockNode::start("aa", Duration::ZERO).await;
+    let b = MockNode::start("bb", Duration::ZERO).await;
+    let state = build_state(vec![a.url(), b.url()], None).await;
+    assert!(matches!(state.rpc().get_block_hash(10).await, Err(RpcError::NoQuorum)));
+
+    let agreeing = build_state(vec![a.url()], None).await;
+    agreeing.rpc().get_block_hash(10).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(scheduler::flush_rpc_disagreements(&agreeing).await.unwrap(), 0);
+
+    let (_, body) = get_disagreements(&state, "", Some("admin-key")).await;
+    assert_eq!(body["disagreements"][0]["dissenters"], json!([]));
+    assert_eq!(body["endpoints"], json!([]));
+}
+
+#[tokio::test]
+async fn repeated_dissent_raises_an_alert() {
+    let a = MockNode::start("aa", Duration::ZERO).await;
+    let b = MockNode::start("aa", Duration::ZERO).await;
+    let c = MockNode::start("cc", Duration::ZERO).await;
+    let state = build_state(vec![a.url(), b.url(), c.url()], Some(3)).await;
+
+    for h in 1..=2 {
+        assert!(state.rpc().get_block_hash(h).await.is_err());
+    }
+    scheduler::flush_rpc_disagreements(&state).await.unwrap();
+    assert!(scheduler::check_rpc_dissent(&state).await.unwrap().is_empty());
+
+    assert!(state.rpc().get_block_hash(3).await.is_err());
+    scheduler::flush_rpc_disagreements(&state).await.unwrap();
+    let flagged = scheduler::check_rpc_dissent(&state).await.unwrap();
+    assert_eq!(flagged.len(), 1);
+    assert_eq!((flagged[0].endpoint.as_str(), flagged[0].dissents), (host(&c).as_str(), 3));
+    assert!(flagged[0].alerting);
+}
+
+#[tokio::test]
+async fn old_records_leave_the_window_and_are_pruned() {
+    let state = build_state(vec![], None).await;
+    let record = |days: i64| RpcDisagreement {
+        id: Uuid::new_v4(),
+        method: "getblockhash".into(),
+        params: json!([days]),
+        agreed: None,
+        answers: vec![RpcAnswer {
+            endpoint: "https://rpc.example".into(),
+            weight: 1,
+            result: Some(json!("cc")),
+            error: None,
+        }],
+        dissenters: vec!["https://rpc.example".into()],
+        observed_at: Utc::now() - ChronoDuration::days(days),
+    };
+    for days in [0, 2, 40] {
+        state.store().insert_rpc_disagreement(&record(days)).await.unwrap();
+    }
+
+    scheduler::check_rpc_dissent(&state).await.unwrap();
+    let (_, body) = get_disagreements(&state, "", Some("admin-key")).await;
+    let params: Vec<&Value> = body["disagreements"].as_array().unwrap().iter().map(|d| &d["params"]).collect();
+    assert_eq!(params, vec![&json!([0]), &json!([2])]);
+    // Only the one inside the hour-long window counts toward an alert.
+    assert_eq!(body["endpoints"][0]["dissents"], 1);
+
+    let since = (Utc::now() - ChronoDuration::days(1)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let (_, body) = get_disagreements(&state, &format!("?since={since}&limit=5"), Some("admin-key")).await;
+    assert_eq!(body["disagreements"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn disagreements_endpoint_needs_the_admin_key() {
+    let state = build_state(vec![], None).await;
+    assert_eq!(get_disagreements(&state, "", None).await.0, StatusCode::UNAUTHORIZED);
+    assert_eq!(get_disagreements(&state, "", Some("wrong")).await.0, StatusCode::UNAUTHORIZED);
+}
@@ -3,10 +3,17 @@
 // the quorum client against them.
 
 use axum::{routing::post, Json, Router};
-use depinzcash_server::rpc::{RpcError, ZcashRpcQuorum};
+use depinzcash_server::rpc::{BreakerState, QuorumPolicy, RpcError, ZcashRpcQuorum};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 use tokio::{net::TcpListener, sync::Mutex, task::JoinHandle};
 
 #[derive(Deserialize)]
@@ -34,21 +41,30 @@ enum MockBehavior {
     },
     // Always 500s.
     Error,
+    // Answers like `Fixed`, but only after the delay.
+    Slow(Value, Duration),
 }
 
 struct MockServer {
     addr: SocketAddr,
     handle: JoinHandle<()>,
+    // Requests received.
+    hits: Arc<AtomicUsize>,
+    behavior: Arc<Mutex<MockBehavior>>,
 }
 
 impl MockServer {
     async fn start(behavior: MockBehavior) -> Self {
         let state = Arc::new(Mutex::new(behavior));
+        let behavior = state.clone();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
         let app = Router::new()
             .route(
                 "/",
                 post(move |Json(req): Json<JsonRpcReq>| {
                     let state = state.clone();
+                    counter.fetch_add(1, Ordering::SeqCst);
                     async move {
                         let b = state.lock().await.clone();
                         match b {
@@ -66,6 +82,14 @@ impl MockServer {
                                 }))
                             }
                             MockBehavior::Error => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+                            MockBehavior::Slow(v, delay) => {
+                                tokio::time::sleep(delay).await;
+                                Ok(Json(JsonRpcResp {
+                                    jsonrpc: "2.0",
+                                    id: 1,
+                                    result: v,
+                                }))
+                            }
                         }
                     }
                 }),
@@ -76,12 +100,25 @@ impl MockServer {
         let handle = tokio::spawn(async move {
             axum::serve(listener, app.into_make_service()).await.unwrap();
         });
-        Self { addr, handle }
+        Self {
+            addr,
+            handle,
+            hits,
+            behavior,
+        }
     }
 
     fn url(&self) -> String {
         format!("http://{}/", self.addr)
     }
+
+    fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+
+    async fn set(&self, behavior: MockBehavior) {
+        *self.behavior.lock().await = behavior;
+    }
 }
 
 impl Drop for MockServer {
@@ -208,3 +245,363 @@ async fn type_mismatch_returns_other_error() {
     let r = q.get_block_count().await;
     assert!(matches!(r, Err(RpcError::Other(_))), "got: {:?}", r);
 }
+
+#[tokio::test]
+async fn projected_quorum_votes_on_projection_only() {
+    // Raw responses all differ (confirmations), projections agree.
+    let s1 = MockServer::start(MockBehavior::Fixed(json!({"hash": "ab", "confirmations": 1}))).await;
+    let s2 = MockServer::start(MockBehavior::Fixed(json!({"hash": "ab", "confirmations": 2}))).await;
+    let s3 = MockServer::start(MockBehavior::Fixed(json!({"confirmations": 3}))).await;
+    let q = quorum_for(&[&s1, &s2, &s3]);
+    assert!(matches!(
+        q.quorum_call("getblock", json!(["1", 1])).await,
+        Err(RpcError::NoQuorum)
+    ));
+    // s3 has no hash — counts as a failed endpoint, 2/3 still agree.
+    let v = q
+        .quorum_call_projected("getblock", json!(["1", 1]), |v| v.get("hash").cloned())
+        .await
+        .unwrap();
+    assert_eq!(v, json!("ab"));
+}
+
+// ---- weights, threshold, circuit breaker -------------------------------
+
+#[tokio::test]
+async fn heavier_endpoint_outvotes_two_light_ones() {
+    let s1 = MockServer::start(MockBehavior::Fixed(json!(100))).await;
+    let s2 = MockServer::start(MockBehavior::Fixed(json!(999))).await;
+    let s3 = MockServer::start(MockBehavior::Fixed(json!(999))).await;
+    // Total 5, majority 3: the weight-3 endpoint alone carries it.
+    let q = quorum_for(&[&s1, &s2, &s3]).with_weights(vec![3, 1, 1]);
+    assert_eq!(q.get_block_count().await.unwrap(), 100);
+}
+
+#[tokio::test]
+async fn configured_threshold_can_demand_every_endpoint() {
+    let s1 = MockServer::start(MockBehavior::Fixed(json!(100))).await;
+    let s2 = MockServer::start(MockBehavior::Fixed(json!(100))).await;
+    let s3 = MockServer::start(MockBehavior::Fixed(json!(999))).await;
+    let q = quorum_for(&[&s1, &s2, &s3]).with_policy(QuorumPolicy {
+        threshold: Some(3),
+        ..QuorumPolicy::default()
+    });
+    assert!(matches!(q.get_block_count().await, Err(RpcError::NoQuorum)));
+}
+
+#[tokio::test]
+async fn quorum_answers_without_waiting_for_a_slow_endpoint() {
+    let s1 = MockServer::start(MockBehavior::Fixed(json!(100))).await;
+    let s2 = MockServer::start(MockBehavior::Fixed(json!(100))).await;
+    let s3 = MockServer::start(MockBehavior::Slow(json!(100), Duration::from_secs(5))).await;
+    let q = ZcashRpcQuorum::new(vec![s1.url(), s2.url(), s3.url()], Duration::from_secs(10));
+    let started = Instant::now();
+    assert_eq!(q.get_block_count().await.unwrap(), 100);
+    assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn breaker_skips_a_failing_endpoint_until_its_cooldown_ends() {
+    let s1 = MockServer::start(MockBehavior::Fixed(json!(100))).await;
+    let s2 = MockServer::start(MockBehavior::Fixed(json!(100))).await;
+    let bad = MockServer::start(MockBehavior::Error).await;
+    let q = quorum_for(&[&s1, &s2, &bad]).with_policy(QuorumPolicy {
+        threshold: None,
+        breaker_failures: 2,
+        breaker_cooldown: Duration::from_millis(300),
+    });
+    let settle = || tokio::time::sleep(Duration::from_millis(50));
+
+    for _ in 0..2 {
+        assert_eq!(q.get_block_count().await.unwrap(), 100);
+        settle().await;
+    }
+    assert_eq!(bad.hits(), 2);
+    let health = q.health();
+    assert_eq!(health.endpoints[2].state, BreakerState::Open);
+    assert_eq!(health.endpoints[2].consecutive_failures, 2);
+    assert!(health.endpoints[2].score < health.endpoints[0].score);
+    assert_eq!(health.available_weight, 2);
+    assert!(health.ready, "2 of 3 still reach the majority");
+
+    // Open: not called at all.
+    assert_eq!(q.get_block_count().await.unwrap(), 100);
+    settle().await;
+    assert_eq!(bad.hits(), 2);
+
+    // Cooldown over: one trial call, which fails and re-opens the breaker.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(q.get_block_count().await.unwrap(), 100);
+    settle().await;
+    assert_eq!(bad.hits(), 3);
+    assert_eq!(q.health().endpoints[2].state, BreakerState::Open);
+}
+
+#[tokio::test]
+async fn too_little_healthy_weight_fails_fast_without_calling() {
+    let s1 = MockServer::start(MockBehavior::Error).await;
+    let s2 = MockServer::start(MockBehavior::Error).await;
+    let q = quorum_for(&[&s1, &s2]).with_policy(QuorumPolicy {
+        threshold: None,
+        breaker_failures: 1,
+        breaker_cooldown: Duration::from_secs(60),
+    });
+    assert!(matches!(q.get_block_count().await, Err(RpcError::AllFailed)));
+    let r = q.get_block_count().await;
+    assert!(
+        matches!(r, Err(RpcError::Unhealthy { available: 0, threshold: 2 })),
+        "got: {:?}",
+        r
+    );
+    assert_eq!((s1.hits(), s2.hits()), (1, 1));
+    assert!(!q.health().ready);
+}
+
+#[tokio::test]
+async fn breaker_closes_when_the_trial_call_succeeds() {
+    let s1 = MockServer::start(MockBehavior::Error).await;
+    let q = quorum_for(&[&s1]).with_policy(QuorumPolicy {
+        threshold: None,
+        breaker_failures: 1,
+        breaker_cooldown: Duration::ZERO,
+    });
+    assert!(q.get_block_count().await.is_err());
+    assert_eq!(q.health().endpoints[0].state, BreakerState::Open);
+
+    s1.set(MockBehavior::Fixed(json!(7))).await;
+    assert_eq!(q.get_block_count().await.unwrap(), 7);
+    let h = &q.health().endpoints[0];
+    assert_eq!(h.state, BreakerState::Closed);
+    assert_eq!((h.successes, h.failures, h.consecutive_failures), (1, 1, 0));
+    assert!(h.latency_ms.is_some());
+}
+
+// ---- block-hash cache ----------------------------------------------------
+
+// Tells the quorum the tip is at `tip`, then answers everything with `hash`.
+async fn at_tip(s: &MockServer, q: &ZcashRpcQuorum, tip: u64, hash: &str) {
+    s.set(MockBehavior::Fixed(json!(tip))).await;
+    assert_eq!(q.get_block_count().await.unwrap(), tip);
+    s.set(MockBehavior::Fixed(json!(hash))).await;
+}
+
+#[tokio::test]
+async fn deep_block_hashes_are_served_from_the_cache() {
+    let s1 = MockServer::start(MockBehavior::Error).await;
+    let q = quorum_for(&[&s1]).with_hash_cache(16, 100);
+    at_tip(&s1, &q, 1_000, "00ab").await;
+
+    for _ in 0..5 {
+        assert_eq!(q.get_block_hash(900).await.unwrap(), "00ab");
+    }
+    // One getblockcount plus one getblockhash.
+    assert_eq!(s1.hits(), 2);
+    let c = q.health().hash_cache;
+    assert_eq!((c.entries, c.hits, c.misses), (1, 4, 1));
+}
+
+#[tokio::test]
+async fn hashes_near_the_tip_are_asked_for_every_time() {
+    let s1 = MockServer::start(MockBehavior::Error).await;
+    let q = quorum_for(&[&s1]).with_hash_cache(16, 100);
+    at_tip(&s1, &q, 1_000, "00ab").await;
+
+    q.get_block_hash(901).await.unwrap();
+    q.get_block_hash(901).await.unwrap();
+    assert_eq!(s1.hits(), 3);
+    assert_eq!(q.health().hash_cache.entries, 0);
+}
+
+#[tokio::test]
+async fn nothing_is_cached_before_the_tip_is_known() {
+    let s1 = MockServer::start(MockBehavior::Fixed(json!("00ab"))).await;
+    let q = quorum_for(&[&s1]).with_hash_cache(16, 100);
+    q.get_block_hash(5).await.unwrap();
+    q.get_block_hash(5).await.unwrap();
+    assert_eq!(s1.hits(), 2);
+}
+
+#[tokio::test]
+async fn failed_rounds_are_not_cached() {
+    let s1 = MockServer::start(MockBehavior::Error).await;
+    let q = quorum_for(&[&s1])
+        .with_hash_cache(16, 100)
+        .with_policy(QuorumPolicy {
+            breaker_failures: 0,
+            ..QuorumPolicy::default()
+        });
+    at_tip(&s1, &q, 1_000, "00ab").await;
+    s1.set(MockBehavior::Error).await;
+    assert!(matches!(q.get_block_hash(10).await, Err(RpcError::AllFailed)));
+
+    s1.set(MockBehavior::Fixed(json!("00ab"))).await;
+    assert_eq!(q.get_block_hash(10).await.unwrap(), "00ab");
+    assert_eq!(q.health().hash_cache.entries, 1);
+}
+
+#[tokio::test]
+async fn cache_keeps_the_newest_heights_when_full() {
+    let s1 = MockServer::start(MockBehavior::Error).await;
+    let q = quorum_for(&[&s1]).with_hash_cache(2, 10);
+    at_tip(&s1, &q, 1_000, "00ab").await;
+
+    for h in [100, 200, 300] {
+        q.get_block_hash(h).await.unwrap();
+    }
+    assert_eq!(q.health().hash_cache.entries, 2);
+    let before = s1.hits();
+    q.get_block_hash(300).await.unwrap();
+    q.get_block_hash(200).await.unwrap();
+    assert_eq!(s1.hits(), before);
+    q.get_block_hash(100).await.unwrap();
+    assert_eq!(s1.hits(), before + 1);
+}
+
+#[tokio::test]
+async fn size_zero_disables_the_cache() {
+    let s1 = MockServer::start(MockBehavior::Error).await;
+    let q = quorum_for(&[&s1]).with_hash_cache(0, 10);
+    at_tip(&s1, &q, 1_000, "00ab").await;
+    q.get_block_hash(100).await.unwrap();
+    q.get_block_hash(100).await.unwrap();
+    assert_eq!(s1.hits(), 3);
+    assert_eq!(q.health().hash_cache.entries, 0);
+}
+
+#[tokio::test]
+async fn concurrent_lookups_near_the_tip_share_one_round() {
+    let s1 = MockServer::start(MockBehavior::Slow(json!("00ab"), Duration::from_millis(200))).await;
+    let s2 = MockServer::start(MockBehavior::Slow(json!("00ab"), Duration::from_millis(200))).await;
+    let q = quorum_for(&[&s1, &s2]);
+
+    let lookups = (0..50).map(|_| {
+        let q = q.clone();
+        tokio::spawn(async move { q.get_block_hash(990).await })
+    });
+    for r in futures::future::join_all(lookups).await {
+        assert_eq!(r.unwrap().unwrap(), "00ab");
+    }
+    assert_eq!((s1.hits(), s2.hits()), (1, 1));
+    let c = q.health().hash_cache;
+    assert_eq!((c.misses, c.shared, c.entries), (1, 49, 0));
+
+    // The round is over: the next lookup asks again.
+    q.get_block_hash(990).await.unwrap();
+    assert_eq!((s1.hits(), s2.hits()), (2, 2));
+}
+
+// ---- disagreements ---------------------------------------------------------
+
+#[tokio::test]
+async fn failed_endpoints_are_not_recorded_as_disagreeing() {
+    let s1 = MockServer::start(MockBehavior::Fixed(json!("00ab"))).await;
+    let s2 = MockServer::start(MockBehavior::Fixed(json!("00ab"))).await;
+    let s3 = MockServer::start(MockBehavior::Error).await;
+    let q = quorum_for(&[&s1, &s2, &s3]);
+    assert_eq!(q.get_block_hash(5).await.unwrap(), "00ab");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(q.take_disagreements().is_empty());
+}
+
+#[tokio::test]
+async fn tip_answers_apart_name_no_dissenter() {
+    let s1 = MockServer::start(MockBehavior::Fixed(json!(100))).await;
+    let s2 = MockServer::start(MockBehavior::Fixed(json!(100))).await;
+    let s3 = MockServer::start(MockBehavior::Fixed(json!(99))).await;
+    let q = quorum_for(&[&s1, &s2, &s3]);
+    assert_eq!(q.get_block_count().await.unwrap(), 100);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(q.take_disagreements().is_empty(), "a won tip round isn't kept");
+
+    let q = quorum_for(&[&s1, &s2, &s3]).with_policy(QuorumPolicy {
+        threshold: Some(3),
+        ..QuorumPolicy::default()
+    });
+    assert!(matches!(q.get_block_count().await, Err(RpcError::NoQuorum)));
+    let kept = q.take_disagreements();
+    assert_eq!(kept.len(), 1);
+    assert!(kept[0].dissenters.is_empty());
+}
+
+// ---- batches ---------------------------------------------------------------
+
+// Answers getblockcount / getblockhash, with an rpc error for anything else.
+// With `batches` it answers arrays, replies reversed to make the client match
+// ids; without, it refuses them the way a server with no batch support does.
+async fn batch_server(batches: bool) -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let answer = |req: &Value| match req["method"].as_str() {
+        Some("getblockcount") => json!({"jsonrpc": "2.0", "id": req["id"], "result": 1_000}),
+        Some("getblockhash") => json!({"jsonrpc": "2.0", "id": req["id"], "result": format!("hash-{}", req["params"][0])}),
+        _ => json!({"jsonrpc": "2.0", "id": req["id"], "error": {"code": -32601, "message": "Method not found"}}),
+    };
+    let app = Router::new().route(
+        "/",
+        post(move |Json(req): Json<Value>| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match req.as_array() {
+                    Some(calls) if batches => Json(Value::Array(calls.iter().rev().map(answer).collect())),
+                    Some(_) => Json(json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32600, "message": "Invalid request"}})),
+                    None => Json(answer(&req)),
+                }
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service()).await.unwrap();
+    });
+    (format!("http://{addr}/"), hits)
+}
+
+fn tip_and_hash() -> [(&'static str, Value); 2] {
+    [("getblockcount", json!([])), ("getblockhash", json!([990]))]
+}
+
+#[tokio::test]
+async fn quorum_batch_is_one_request_per_endpoint() {
+    let (a, a_hits) = batch_server(true).await;
+    let (b, b_hits) = batch_server(true).await;
+    let q = ZcashRpcQuorum::new(vec![a, b], Duration::from_secs(2));
+    assert_eq!(q.quorum_batch(&tip_and_hash()).await.unwrap(), vec![json!(1_000), json!("hash-990")]);
+    assert_eq!((a_hits.load(Ordering::SeqCst), b_hits.load(Ordering::SeqCst)), (1, 1));
+}
+
+#[tokio::test]
+async fn endpoint_without_batches_is_called_one_at_a_time_from_then_on() {
+    let (a, hits) = batch_server(false).await;
+    let q = ZcashRpcQuorum::new(vec![a], Duration::from_secs(2));
+    assert_eq!(q.quorum_batch(&tip_and_hash()).await.unwrap(), vec![json!(1_000), json!("hash-990")]);
+    assert_eq!(hits.load(Ordering::SeqCst), 3, "refused batch, then each call");
+    q.quorum_batch(&tip_and_hash()).await.unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 5, "no second batch attempt");
+}
+
+#[tokio::test]
+async fn batch_with_a_failed_call_fails_its_endpoint() {
+    let (a, _) = batch_server(true).await;
+    let q = ZcashRpcQuorum::new(vec![a], Duration::from_secs(2));
+    let calls = [("getblockcount", json!([])), ("getblockheader", json!(["00ab"]))];
+    assert!(matches!(q.quorum_batch(&calls).await, Err(RpcError::AllFailed)));
+    assert_eq!(q.health().endpoints[0].failures, 1);
+}
+
+#[tokio::test]
+async fn pinned_batch_keeps_one_result_per_call() {
+    let (a, hits) = batch_server(true).await;
+    let addr: std::net::SocketAddr = a.trim_start_matches("http://").trim_end_matches('/').parse().unwrap();
+    let q = ZcashRpcQuorum::new(vec![], Duration::from_secs(2));
+    let calls = [
+        ("getblockhash", json!([7])),
+        ("getblockheader", json!(["00ab"])),
+        ("getblockcount", json!([])),
+    ];
+    let results = q.call_pinned_batch(&a, addr, None, &calls).await.unwrap();
+    assert_eq!(results[0].as_ref().unwrap(), &json!("hash-7"));
+    assert!(results[1].as_ref().unwrap_err().to_string().contains("Method not found"));
+    assert_eq!(results[2].as_ref().unwrap(), &json!(1_000));
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
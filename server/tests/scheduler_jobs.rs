@@ -0,0 +1,272 @@
+// Integration tests for scheduler job leases and the persisted job schedule.
+//
+// Two AppStates over one store stand in for two server instances sharing a
+// database, or for one instance before and after a restart. Short TTLs and
+// intervals keep the tests fast. No live network.
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use chrono::Utc;
+use depinzcash_server::{
+    api,
+    config::{Config, ZcashNetwork},
+    jobs::{JobLease, ScheduledJob},
+    rpc::ZcashRpcQuorum,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::ChallengeKind,
+};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::ServiceExt;
+
+fn cfg() -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs: vec![],
+        rpc_timeout: Duration::from_millis(500),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: Duration::from_secs(15 * 60),
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: None,
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+// Two "instances" over the same database.
+async fn two_instances() -> (AppState, AppState) {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let shared: Arc<dyn Store> = Arc::new(store);
+    let a = AppState::with_store(cfg(), shared.clone(), ZcashRpcQuorum::new(vec![], Duration::from_secs(2)));
+    let b = AppState::with_store(cfg(), shared, ZcashRpcQuorum::new(vec![], Duration::from_secs(2)));
+    (a, b)
+}
+
+#[tokio::test]
+async fn instances_get_distinct_ids() {
+    let (a, b) = two_instances().await;
+    assert_ne!(a.instance_id(), b.instance_id());
+}
+
+#[tokio::test]
+async fn only_one_instance_runs_a_job() {
+    let (a, b) = two_instances().await;
+    let lease_a = JobLease::new(&a, "uptime", Duration::from_secs(90));
+    let lease_b = JobLease::new(&b, "uptime", Duration::from_secs(90));
+
+    assert!(lease_a.acquire().await);
+    assert!(!lease_b.acquire().await);
+    assert!(lease_a.acquire().await, "holder renews");
+    assert!(!lease_b.acquire().await);
+
+    // Other jobs are free to land on the other instance.
+    assert!(JobLease::new(&b, "snapshot", Duration::from_secs(90)).acquire().await);
+}
+
+#[tokio::test]
+async fn keep_alive_holds_the_lease_past_its_ttl() {
+    let (a, b) = two_instances().await;
+    let ttl = Duration::from_millis(300);
+    let lease_a = JobLease::new(&a, "snapshot", ttl);
+    let lease_b = JobLease::new(&b, "snapshot", ttl);
+    assert!(lease_a.acquire().await);
+    let keeper = lease_a.keep_alive();
+
+    tokio::time::sleep(ttl * 3).await;
+    assert!(!lease_b.acquire().await, "renewals keep the rival out");
+    keeper.abort();
+}
+
+#[tokio::test]
+async fn follower_takes_over_once_the_holder_stops_renewing() {
+    let (a, b) = two_instances().await;
+    let ttl = Duration::from_millis(300);
+    let lease_a = JobLease::new(&a, "exposed_rpc", ttl);
+    let lease_b = JobLease::new(&b, "exposed_rpc", ttl);
+    let keeper_a = lease_a.keep_alive();
+    let keeper_b = lease_b.keep_alive();
+    tokio::time::sleep(ttl / 2).await;
+
+    // Whoever won, the other one is shut out.
+    let (holder, follower, holder_keeper) = if lease_a.acquire().await {
+        (lease_a, lease_b, keeper_a)
+    } else {
+        (lease_b, lease_a, keeper_b)
+    };
+    assert!(!follower.acquire().await);
+
+    // The holder "crashes".
+    holder_keeper.abort();
+    tokio::time::sleep(ttl * 2).await;
+    assert!(follower.acquire().await, "follower should have taken over");
+    assert!(!holder.acquire().await, "old holder is now locked out");
+}
+
+// ---- schedule ---------------------------------------------------------------
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+// True if `due` returns within `within`.
+async fn runs_within(job: &mut ScheduledJob, within: Duration) -> bool {
+    tokio::time::timeout(within, job.due()).await.is_ok()
+}
+
+#[tokio::test]
+async fn new_job_waits_for_its_first_run() {
+    let (a, _) = two_instances().await;
+    let mut job = ScheduledJob::start(&a, "snapshot", HOUR, HOUR);
+    assert!(!runs_within(&mut job, Duration::from_millis(200)).await);
+
+    let run = a.store().get_job_run("snapshot").await.unwrap().unwrap();
+    assert!(run.last_run_at.is_none());
+    assert!(run.next_run_at > Utc::now() + chrono::Duration::minutes(59));
+}
+
+#[tokio::test]
+async fn restart_keeps_the_cadence_instead_of_resetting_it() {
+    let (a, b) = two_instances().await;
+    let mut job = ScheduledJob::start(&a, "snapshot", HOUR, Duration::ZERO);
+    assert!(runs_within(&mut job, Duration::from_secs(1)).await);
+    job.finished(None::<&anyhow::Error>).await;
+    let first = a.store().get_job_run("snapshot").await.unwrap().unwrap();
+    let ran = first.last_run_at.expect("run recorded");
+    assert_eq!(first.next_run_at, ran + chrono::Duration::hours(1));
+
+    // "Restart": a fresh instance with a zero first-run delay still waits for
+    // the recorded next run rather than firing or starting a new week.
+    let mut restarted = ScheduledJob::start(&b, "snapshot", HOUR, Duration::ZERO);
+    assert!(!runs_within(&mut restarted, Duration::from_millis(300)).await);
+    let after = b.store().get_job_run("snapshot").await.unwrap().unwrap();
+    assert_eq!(after.next_run_at, first.next_run_at);
+}
+
+#[tokio::test]
+async fn overdue_job_runs_right_after_restart() {
+    let (a, _) = two_instances().await;
+    let overdue = Utc::now() - chrono::Duration::hours(3);
+    a.store().schedule_job("snapshot", overdue).await.unwrap();
+
+    let mut job = ScheduledJob::start(&a, "snapshot", HOUR, HOUR);
+    assert!(runs_within(&mut job, Duration::from_secs(1)).await);
+    job.finished(None::<&anyhow::Error>).await;
+    let run = a.store().get_job_run("snapshot").await.unwrap().unwrap();
+    assert!(run.next_run_at > Utc::now() + chrono::Duration::minutes(59), "next run counts from now");
+}
+
+#[tokio::test]
+async fn shorter_interval_takes_effect_on_restart() {
+    let (a, _) = two_instances().await;
+    let mut weekly = ScheduledJob::start(&a, "uptime", HOUR * 24 * 7, Duration::ZERO);
+    assert!(runs_within(&mut weekly, Duration::from_secs(1)).await);
+    weekly.finished(None::<&anyhow::Error>).await;
+
+    // Same instance, new config: due one short interval after the last run,
+    // not at the week-out time recorded under the old one.
+    let mut fast = ScheduledJob::start(&a, "uptime", Duration::from_millis(100), Duration::ZERO);
+    assert!(runs_within(&mut fast, Duration::from_secs(1)).await);
+}
+
+#[tokio::test]
+async fn errors_are_recorded_and_survive_later_successes() {
+    let (a, _) = two_instances().await;
+    let mut job = ScheduledJob::start(&a, "finality", Duration::from_millis(50), Duration::ZERO);
+    assert!(runs_within(&mut job, Duration::from_secs(1)).await);
+    let err = anyhow::anyhow!("quorum unreachable").context("confirming finality");
+    job.finished(Some(&err)).await;
+
+    let run = a.store().get_job_run("finality").await.unwrap().unwrap();
+    assert_eq!(run.last_error.as_deref(), Some("confirming finality: quorum unreachable"));
+    assert_eq!(run.last_error_at, run.last_finished_at);
+
+    assert!(runs_within(&mut job, Duration::from_secs(1)).await);
+    job.finished(None::<&anyhow::Error>).await;
+    let run = a.store().get_job_run("finality").await.unwrap().unwrap();
+    assert_eq!(run.last_error.as_deref(), Some("confirming finality: quorum unreachable"));
+    assert!(run.last_error_at < run.last_run_at);
+}
+
+async fn get_jobs(state: &AppState, key: Option<&str>) -> (StatusCode, Value) {
+    let mut req = Request::builder().uri("/api/admin/jobs");
+    if let Some(key) = key {
+        req = req.header("x-admin-key", key);
+    }
+    let resp = api::router(state.clone())
+        .oneshot(req.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn admin_jobs_endpoint_lists_schedule_errors_and_holder() {
+    let (a, _) = two_instances().await;
+    let mut failing = ScheduledJob::start(&a, "exposed_rpc", HOUR, Duration::ZERO);
+    assert!(runs_within(&mut failing, Duration::from_secs(1)).await);
+    failing.finished(Some(&anyhow::anyhow!("listing nodes"))).await;
+    a.store().schedule_job("snapshot", Utc::now() + chrono::Duration::hours(1)).await.unwrap();
+
+    let (status, _) = get_jobs(&a, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = get_jobs(&a, Some("admin-key")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["instance_id"], a.instance_id());
+    let jobs = body["jobs"].as_array().unwrap();
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[0]["job"], "exposed_rpc");
+    assert_eq!(jobs[0]["last_error"], "listing nodes");
+    assert_eq!(jobs[0]["last_run_failed"], true);
+    assert_eq!(jobs[0]["lease_holder"], a.instance_id());
+    assert!(jobs[0]["next_run_at"].is_string());
+    assert_eq!(jobs[1]["job"], "snapshot");
+    assert!(jobs[1]["last_run_at"].is_null());
+    assert_eq!(jobs[1]["last_run_failed"], false);
+}
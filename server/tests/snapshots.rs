@@ -11,7 +11,8 @@ use depinzcash_server::{
     config::{Config, ZcashNetwork},
     rpc::ZcashRpcQuorum,
     state::AppState,
-    store::SqliteStore,
+    store::{SqliteStore, Store},
+    types::ChallengeKind,
 };
 use ed25519_dalek::{Signer, SigningKey};
 use http_body_util::BodyExt;
@@ -26,6 +27,14 @@ fn test_config() -> Config {
         database_url: "sqlite::memory:".into(),
         trusted_rpcs: vec![],
         rpc_timeout: Duration::from_secs(1),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
         admin_api_key: Some("admin-key".into()),
         cors_allowed_origins: vec![],
         scheduler_enabled: false,
@@ -34,6 +43,22 @@ fn test_config() -> Config {
         uptime_reward_interval: Duration::from_secs(60),
         snapshot_interval: None,
         exposed_rpc_poll_interval: None,
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: None,
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: false,
+        rpc_credentials_key: None,
         max_height_drift: 8,
         max_clock_skew: Duration::from_secs(15 * 60),
         rate_limit_enabled: false,
@@ -46,6 +71,7 @@ fn test_config() -> Config {
         spl_mint: Some("So11111111111111111111111111111111111111112".into()),
         solana_cluster: "devnet".into(),
         network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
     }
 }
 
@@ -230,6 +256,100 @@ async fn claim_payload_has_valid_shape() {
     assert!(body["proof"]["leaf_index"].as_u64().is_some());
 }
 
+// ---- delta cycles -------------------------------------------------------
+
+#[tokio::test]
+async fn second_cycle_pays_only_points_earned_since_first() {
+    let state = build_state().await;
+    let wallet = register_and_submit(state.clone(), 100).await;
+    let (s, b1) = publish_snapshot(state.clone()).await;
+    assert_eq!(s, StatusCode::OK);
+    let first = b1["total_points"].as_u64().unwrap();
+    assert!(first > 0);
+
+    let node = &state.store().list_nodes_by_wallet(&wallet).await.unwrap()[0];
+    state.store().add_uptime_and_points(node.id, 300, 7).await.unwrap();
+
+    let (s, b2) = publish_snapshot(state.clone()).await;
+    assert_eq!(s, StatusCode::OK, "publish 2: {b2}");
+    assert_eq!(b2["cycle"], 2);
+    assert_eq!(b2["total_points"], 7, "cycle 2 must not re-pay cycle 1");
+
+    let (s, claim) = get_json(
+        api::router(state),
+        &format!("/api/wallet/{wallet}/claim/latest"),
+    )
+    .await;
+    assert_eq!(s, StatusCode::OK);
+    assert_eq!(claim["cycle"], 2);
+    assert_eq!(claim["points"], 7);
+    assert_eq!(claim["points_from"], first);
+    assert_eq!(claim["points_to"], first + 7);
+}
+
+#[tokio::test]
+async fn wallet_with_no_new_points_keeps_its_last_claim() {
+    let state = build_state().await;
+    let a = register_and_submit(state.clone(), 100).await;
+    let (s, _) = publish_snapshot(state.clone()).await;
+    assert_eq!(s, StatusCode::OK);
+
+    // Nothing earned since — an empty delta cycle is refused.
+    let (s, _) = publish_snapshot(state.clone()).await;
+    assert_eq!(s, StatusCode::INTERNAL_SERVER_ERROR);
+
+    // Only B earns in cycle 2; A's claim still points at cycle 1.
+    let _b = register_and_submit(state.clone(), 101).await;
+    let (s, b2) = publish_snapshot(state.clone()).await;
+    assert_eq!(s, StatusCode::OK);
+    assert_eq!(b2["cycle"], 2);
+    assert_eq!(b2["leaves"], 1);
+
+    let (s, claim) = get_json(api::router(state), &format!("/api/wallet/{a}/claim/latest")).await;
+    assert_eq!(s, StatusCode::OK);
+    assert_eq!(claim["cycle"], 1);
+    assert_eq!(claim["points_from"], 0);
+}
+
+#[tokio::test]
+async fn every_unclaimed_cycle_stays_listed() {
+    let state = build_state().await;
+    let wallet = register_and_submit(state.clone(), 100).await;
+    let (s, b1) = publish_snapshot(state.clone()).await;
+    assert_eq!(s, StatusCode::OK);
+    let first = b1["total_points"].as_u64().unwrap();
+
+    let node = &state.store().list_nodes_by_wallet(&wallet).await.unwrap()[0];
+    state.store().add_uptime_and_points(node.id, 300, 7).await.unwrap();
+    let (s, b2) = publish_snapshot(state.clone()).await;
+    assert_eq!(s, StatusCode::OK);
+
+    // Neither cycle claimed: both proofs are still served, oldest first,
+    // each against its own root.
+    let (s, claims) = get_json(api::router(state.clone()), &format!("/api/wallet/{wallet}/claims")).await;
+    assert_eq!(s, StatusCode::OK);
+    let claims = claims.as_array().unwrap();
+    assert_eq!(claims.len(), 2);
+    assert_eq!((claims[0]["cycle"].clone(), claims[1]["cycle"].clone()), (json!(1), json!(2)));
+    assert_eq!(claims[0]["points"], first);
+    assert_eq!(claims[0]["merkle_root"], b1["merkle_root"]);
+    assert_eq!(claims[1]["points"], 7);
+    assert_eq!(claims[1]["merkle_root"], b2["merkle_root"]);
+    assert!(claims.iter().all(|c| c["proof"]["siblings"].is_array() && c["wallet"] == wallet));
+
+    let (s, later) = get_json(api::router(state.clone()), &format!("/api/wallet/{wallet}/claims?after_cycle=1")).await;
+    assert_eq!(s, StatusCode::OK);
+    assert_eq!(later.as_array().unwrap().len(), 1);
+    assert_eq!(later[0]["cycle"], 2);
+
+    let (other, _) = fresh_kp();
+    let (s, none) = get_json(api::router(state.clone()), &format!("/api/wallet/{other}/claims")).await;
+    assert_eq!(s, StatusCode::OK);
+    assert_eq!(none, json!([]));
+    let (s, _) = get_json(api::router(state), "/api/wallet/not-a-pubkey/claims").await;
+    assert_eq!(s, StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn snapshot_carries_spl_mint_through() {
     let state = build_state().await;
@@ -1,19 +1,175 @@
-// Direct store-level tests against the SQLite backend. These bypass the HTTP
-// layer and exercise the persistence guarantees the store promises to the rest
-// of the system: idempotent migrations, uniqueness, monotonic point accrual,
-// nonce single-use, snapshot insertion, etc.
+// Direct store-level tests. These bypass the HTTP layer and exercise the
+// persistence guarantees every `Store` backend promises to the rest of the
+// system: idempotent migrations, uniqueness, monotonic point accrual, nonce
+// single-use, snapshot insertion, etc.
+//
+// Each test runs once per backend (`sqlite::*`, `postgres::*`). The Postgres
+// copies are #[ignore]'d so `cargo test` needs no database server; point them
+// at a throwaway local instance and opt in:
+//
+//   TEST_POSTGRES_URL=postgres://postgres@127.0.0.1:5432/postgres \
+//     cargo test --test store_conformance -- --include-ignored
+//
+// Every Postgres test creates its own `depinzcash_test_<uuid>` database there
+// and leaves it behind. Skipped (printed and returned ok) when the env var is
+// unset.
+
+use std::ops::Deref;
 
 use chrono::Utc;
 use depinzcash_server::{
-    store::SqliteStore,
-    types::{Challenge, ChallengeKind, ChallengeStatus, Node, NodeKind, NodeStatus, Proof, ProofVerdict},
+    store::{PgStore, SqliteStore, Store},
+    types::{
+        Challenge, ChallengeKind, ChallengeOrigin, ChallengeStatus, DelegateScope, Delegation,
+        ChainInfo, ChainInfoSource, LedgerSource, NetworkUpgrade, NewSnapshotLeaf, Node, NodeChainInfo, NodeKind, NodeStatus, P2pProbe, Proof, ProofVerdict, RpcAnswer, RpcDisagreement, RpcPollOutcome, RpcPollStats, SnapshotLeaf,
+    },
 };
+use sqlx::{PgPool, SqlitePool};
 use uuid::Uuid;
 
-async fn fresh_store() -> SqliteStore {
-    let s = SqliteStore::connect("sqlite::memory:").await.unwrap();
-    s.migrate().await.unwrap();
-    s
+const ENV_POSTGRES: &str = "TEST_POSTGRES_URL";
+
+enum Backend {
+    Sqlite,
+    // Server URL; each store gets a fresh database on it.
+    Postgres(String),
+}
+
+// A migrated store plus a raw handle on its pool, for the odd test that has
+// to write behind the store's back.
+struct TestStore {
+    store: Box<dyn Store>,
+    raw: Raw,
+}
+
+enum Raw {
+    Sqlite(SqlitePool),
+    Postgres(PgPool),
+}
+
+impl Backend {
+    async fn fresh_store(&self) -> TestStore {
+        let (store, raw): (Box<dyn Store>, Raw) = match self {
+            Backend::Sqlite => {
+                let s = SqliteStore::connect("sqlite::memory:").await.unwrap();
+                let raw = Raw::Sqlite(s.pool().clone());
+                (Box::new(s), raw)
+            }
+            Backend::Postgres(server) => {
+                let name = format!("depinzcash_test_{}", Uuid::new_v4().simple());
+                let admin = PgPool::connect(server).await.unwrap();
+                sqlx::query(&format!("CREATE DATABASE {name}"))
+                    .execute(&admin)
+                    .await
+                    .unwrap();
+                admin.close().await;
+                let mut url = url::Url::parse(server).unwrap();
+                url.set_path(&name);
+                let s = PgStore::connect(url.as_str()).await.unwrap();
+                let raw = Raw::Postgres(s.pool().clone());
+                (Box::new(s), raw)
+            }
+        };
+        store.migrate().await.unwrap();
+        TestStore { store, raw }
+    }
+}
+
+impl TestStore {
+    async fn bump_points_bypassing_ledger(&self, node_id: Uuid, delta: i64) {
+        match &self.raw {
+            Raw::Sqlite(pool) => {
+                sqlx::query("UPDATE nodes SET points = points + ?1 WHERE id = ?2")
+                    .bind(delta)
+                    .bind(node_id.to_string())
+                    .execute(pool)
+                    .await
+                    .unwrap();
+            }
+            Raw::Postgres(pool) => {
+                sqlx::query("UPDATE nodes SET points = points + $1 WHERE id = $2")
+                    .bind(delta)
+                    .bind(node_id.to_string())
+                    .execute(pool)
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+}
+
+impl TestStore {
+    // Every later points_ledger insert errors, as a dropped connection or a
+    // full disk would halfway through a credit.
+    async fn fail_ledger_writes(&self) {
+        match &self.raw {
+            Raw::Sqlite(pool) => {
+                sqlx::query(
+                    "CREATE TRIGGER fail_ledger BEFORE INSERT ON points_ledger
+                     BEGIN SELECT RAISE(ABORT, 'ledger unavailable'); END",
+                )
+                .execute(pool)
+                .await
+                .unwrap();
+            }
+            Raw::Postgres(pool) => {
+                sqlx::query(
+                    "CREATE FUNCTION fail_ledger() RETURNS trigger LANGUAGE plpgsql
+                     AS $$ BEGIN RAISE EXCEPTION 'ledger unavailable'; END $$",
+                )
+                .execute(pool)
+                .await
+                .unwrap();
+                sqlx::query(
+                    "CREATE TRIGGER fail_ledger BEFORE INSERT ON points_ledger
+                     FOR EACH ROW EXECUTE FUNCTION fail_ledger()",
+                )
+                .execute(pool)
+                .await
+                .unwrap();
+            }
+        }
+    }
+}
+
+impl Deref for TestStore {
+    type Target = dyn Store;
+
+    fn deref(&self) -> &Self::Target {
+        self.store.as_ref()
+    }
+}
+
+fn postgres_url() -> Option<String> {
+    std::env::var(ENV_POSTGRES).ok().filter(|s| !s.is_empty())
+}
+
+// One #[tokio::test] per backend for each conformance fn.
+macro_rules! conformance {
+    ($($name:ident),* $(,)?) => {
+        mod sqlite {
+            $(
+                #[tokio::test]
+                async fn $name() {
+                    super::$name(super::Backend::Sqlite).await;
+                }
+            )*
+        }
+
+        mod postgres {
+            $(
+                #[tokio::test]
+                #[ignore = "needs TEST_POSTGRES_URL env var pointing at a local Postgres server"]
+                async fn $name() {
+                    let Some(url) = super::postgres_url() else {
+                        eprintln!("skipping: {} unset", super::ENV_POSTGRES);
+                        return;
+                    };
+                    super::$name(super::Backend::Postgres(url)).await;
+                }
+            )*
+        }
+    };
 }
 
 fn sample_node(wallet: &str, label: Option<&str>) -> Node {
@@ -23,6 +179,7 @@ fn sample_node(wallet: &str, label: Option<&str>) -> Node {
         kind: NodeKind::ZebraFull,
         label: label.map(String::from),
         rpc_endpoint: None,
+        p2p_address: None,
         network: "mainnet".into(),
         status: NodeStatus::Registered,
         last_height: None,
@@ -34,6 +191,17 @@ fn sample_node(wallet: &str, label: Option<&str>) -> Node {
     }
 }
 
+fn leaf(wallet: &str, from: u64, to: u64, hash: &str) -> NewSnapshotLeaf {
+    NewSnapshotLeaf {
+        wallet: wallet.to_string(),
+        points: to - from,
+        points_from: from,
+        points_to: to,
+        leaf_hash: hash.to_string(),
+        proof_json: r#"{"siblings":[],"leaf_index":0}"#.to_string(),
+    }
+}
+
 fn sample_proof(node_id: Uuid, wallet: &str, height: u64, hash: &str, verdict: ProofVerdict, pts: u64) -> Proof {
     Proof {
         id: Uuid::new_v4(),
@@ -49,14 +217,15 @@ fn sample_proof(node_id: Uuid, wallet: &str, height: u64, hash: &str, verdict: P
         reject_reason: None,
         points_awarded: pts,
         received_at: Utc::now(),
+        trusted_tip_height: None,
+        finalized_at: None,
     }
 }
 
 // ---- migrations + connection -------------------------------------------
 
-#[tokio::test]
-async fn migrate_is_idempotent() {
-    let store = fresh_store().await;
+async fn migrate_is_idempotent(backend: Backend) {
+    let store = backend.fresh_store().await;
     // Running again must not error.
     store.migrate().await.unwrap();
     store.migrate().await.unwrap();
@@ -64,9 +233,8 @@ async fn migrate_is_idempotent() {
 
 // ---- node CRUD ---------------------------------------------------------
 
-#[tokio::test]
-async fn insert_then_fetch_node() {
-    let store = fresh_store().await;
+async fn insert_then_fetch_node(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", Some("primary"));
     store.insert_node(&node, "tok-123").await.unwrap();
     let got = store.get_node(node.id).await.unwrap().unwrap();
@@ -75,26 +243,29 @@ async fn insert_then_fetch_node() {
     assert_eq!(got.status, NodeStatus::Registered);
 }
 
-#[tokio::test]
-async fn get_node_returns_none_for_unknown_id() {
-    let store = fresh_store().await;
+async fn get_node_returns_none_for_unknown_id(backend: Backend) {
+    let store = backend.fresh_store().await;
     let result = store.get_node(Uuid::new_v4()).await.unwrap();
     assert!(result.is_none());
 }
 
-#[tokio::test]
-async fn auth_token_lookup_returns_node() {
-    let store = fresh_store().await;
+async fn auth_token_lookup_returns_node(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", None);
-    store.insert_node(&node, "secret-token-xyz").await.unwrap();
-    let by_tok = store.get_node_by_auth_token("secret-token-xyz").await.unwrap().unwrap();
+    store.insert_node(&node, "hash-xyz").await.unwrap();
+    let by_tok = store.get_node_by_auth_token_hash("hash-xyz").await.unwrap().unwrap();
     assert_eq!(by_tok.id, node.id);
-    assert!(store.get_node_by_auth_token("not-a-real-token").await.unwrap().is_none());
+    assert!(store.get_node_by_auth_token_hash("not-a-real-hash").await.unwrap().is_none());
+
+    // Rotation only swaps out the hash it was given.
+    assert!(!store.replace_auth_token_hash(node.id, "stale", "hash-new").await.unwrap());
+    assert!(store.replace_auth_token_hash(node.id, "hash-xyz", "hash-new").await.unwrap());
+    assert!(store.get_node_by_auth_token_hash("hash-xyz").await.unwrap().is_none());
+    assert_eq!(store.get_node_by_auth_token_hash("hash-new").await.unwrap().unwrap().id, node.id);
 }
 
-#[tokio::test]
-async fn list_nodes_by_wallet_orders_by_registered_at() {
-    let store = fresh_store().await;
+async fn list_nodes_by_wallet_orders_by_registered_at(backend: Backend) {
+    let store = backend.fresh_store().await;
     let n1 = sample_node("multi", Some("first"));
     let n2 = sample_node("multi", Some("second"));
     let n3 = sample_node("other", None);
@@ -114,9 +285,8 @@ async fn list_nodes_by_wallet_orders_by_registered_at() {
     assert!(absent.is_empty());
 }
 
-#[tokio::test]
-async fn update_node_status_persists() {
-    let store = fresh_store().await;
+async fn update_node_status_persists(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", None);
     store.insert_node(&node, "tok").await.unwrap();
     store.update_node_status(node.id, NodeStatus::Stale).await.unwrap();
@@ -124,15 +294,74 @@ async fn update_node_status_persists() {
     assert_eq!(updated.status, NodeStatus::Stale);
 }
 
-#[tokio::test]
-async fn apply_proof_acceptance_updates_node_state() {
-    let store = fresh_store().await;
+async fn paused_nodes_leave_the_work_lists(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let mut node = sample_node("walletA", None);
+    node.rpc_endpoint = Some("https://rpc.example.com".into());
+    node.p2p_address = Some("203.0.113.7:8233".into());
+    store.insert_node(&node, "tok").await.unwrap();
+
+    // Compare-and-set: only from the expected status.
+    assert!(!store.transition_node_status(node.id, NodeStatus::Active, NodeStatus::Paused).await.unwrap());
+    assert!(store.transition_node_status(node.id, NodeStatus::Registered, NodeStatus::Paused).await.unwrap());
+    assert!(store.list_nodes_with_rpc("mainnet").await.unwrap().is_empty());
+    assert!(store.list_nodes_with_p2p("mainnet").await.unwrap().is_empty());
+
+    // A late pending proof still counts but doesn't un-pause the node.
+    store
+        .apply_proof_acceptance(node.id, Uuid::new_v4(), 3_000_000, "hh", 5, Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(store.get_node(node.id).await.unwrap().unwrap().status, NodeStatus::Paused);
+
+    assert!(store.transition_node_status(node.id, NodeStatus::Paused, NodeStatus::Stale).await.unwrap());
+    assert_eq!(store.list_nodes_with_rpc("mainnet").await.unwrap().len(), 1);
+}
+
+async fn late_proofs_do_not_lift_a_suspension(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let mut node = sample_node("walletA", None);
+    node.rpc_endpoint = Some("https://rpc.example.com".into());
+    store.insert_node(&node, "tok").await.unwrap();
+    store.update_node_status(node.id, NodeStatus::Suspended).await.unwrap();
+
+    // A pending proof resolved after the suspension still counts, but the
+    // node stays suspended and off the work lists.
+    store
+        .apply_proof_acceptance(node.id, Uuid::new_v4(), 3_000_000, "hh", 5, Utc::now())
+        .await
+        .unwrap();
+    let got = store.get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(got.status, NodeStatus::Suspended);
+    assert_eq!(got.points, 5);
+    assert!(store.list_nodes_with_rpc("mainnet").await.unwrap().is_empty());
+}
+
+async fn update_node_details_replaces_label_and_endpoint(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let mut node = sample_node("walletA", Some("old"));
+    node.rpc_endpoint = Some("https://old.example.com".into());
+    store.insert_node(&node, "tok").await.unwrap();
+
+    assert!(store.update_node_details(node.id, Some("new"), Some("https://new.example.com")).await.unwrap());
+    let got = store.get_node(node.id).await.unwrap().unwrap();
+    assert_eq!(got.label.as_deref(), Some("new"));
+    assert_eq!(got.rpc_endpoint.as_deref(), Some("https://new.example.com"));
+
+    assert!(store.update_node_details(node.id, None, None).await.unwrap());
+    let got = store.get_node(node.id).await.unwrap().unwrap();
+    assert!(got.label.is_none() && got.rpc_endpoint.is_none());
+    assert!(!store.update_node_details(Uuid::new_v4(), None, None).await.unwrap());
+}
+
+async fn apply_proof_acceptance_updates_node_state(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", None);
     store.insert_node(&node, "tok").await.unwrap();
 
     let when = Utc::now();
     store
-        .apply_proof_acceptance(node.id, 12345, "blockhash-abc", 50, when)
+        .apply_proof_acceptance(node.id, Uuid::new_v4(), 12345, "blockhash-abc", 50, when)
         .await
         .unwrap();
     let n = store.get_node(node.id).await.unwrap().unwrap();
@@ -143,9 +372,8 @@ async fn apply_proof_acceptance_updates_node_state() {
     assert!(n.last_proof_at.is_some());
 }
 
-#[tokio::test]
-async fn add_uptime_and_points_is_additive() {
-    let store = fresh_store().await;
+async fn add_uptime_and_points_is_additive(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", None);
     store.insert_node(&node, "tok").await.unwrap();
 
@@ -159,9 +387,8 @@ async fn add_uptime_and_points_is_additive() {
 
 // ---- proof storage ------------------------------------------------------
 
-#[tokio::test]
-async fn insert_proof_and_list_for_wallet() {
-    let store = fresh_store().await;
+async fn insert_proof_and_list_for_wallet(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", None);
     store.insert_node(&node, "tok").await.unwrap();
 
@@ -181,9 +408,8 @@ async fn insert_proof_and_list_for_wallet() {
     assert_eq!(count_absent, 0);
 }
 
-#[tokio::test]
-async fn duplicate_proof_height_hash_for_same_node_conflicts() {
-    let store = fresh_store().await;
+async fn duplicate_proof_height_hash_for_same_node_conflicts(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", None);
     store.insert_node(&node, "tok").await.unwrap();
 
@@ -195,9 +421,8 @@ async fn duplicate_proof_height_hash_for_same_node_conflicts() {
     assert!(err.is_err(), "expected UNIQUE constraint violation");
 }
 
-#[tokio::test]
-async fn last_accepted_proof_for_node_returns_most_recent() {
-    let store = fresh_store().await;
+async fn last_accepted_proof_for_node_returns_most_recent(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", None);
     store.insert_node(&node, "tok").await.unwrap();
 
@@ -220,30 +445,40 @@ async fn last_accepted_proof_for_node_returns_most_recent() {
 
 // ---- nonce single-use ---------------------------------------------------
 
-#[tokio::test]
-async fn nonce_is_single_use_per_wallet() {
-    let store = fresh_store().await;
-    let ok = store.try_use_nonce("nonce-abc", "walletA").await.unwrap();
+async fn nonce_is_single_use_per_wallet(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let ok = store.try_use_nonce("nonce-abc", "walletA", Utc::now()).await.unwrap();
     assert!(ok);
-    let again = store.try_use_nonce("nonce-abc", "walletA").await.unwrap();
+    let again = store.try_use_nonce("nonce-abc", "walletA", Utc::now()).await.unwrap();
     assert!(!again, "second attempt must return false");
 }
 
-#[tokio::test]
-async fn nonce_collisions_across_wallets_still_global() {
-    // Our schema makes nonce globally unique; same nonce across wallets must collide.
-    let store = fresh_store().await;
-    let first = store.try_use_nonce("shared-nonce", "walletA").await.unwrap();
-    let second = store.try_use_nonce("shared-nonce", "walletB").await.unwrap();
+async fn nonce_is_scoped_to_its_wallet(backend: Backend) {
+    // A nonce only means something under its wallet's signature, so another
+    // wallet picking the same one is not a replay.
+    let store = backend.fresh_store().await;
+    let first = store.try_use_nonce("shared-nonce", "walletA", Utc::now()).await.unwrap();
+    let second = store.try_use_nonce("shared-nonce", "walletB", Utc::now()).await.unwrap();
     assert!(first);
-    assert!(!second);
+    assert!(second);
+}
+
+async fn nonces_prune_by_signed_time(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let now = Utc::now();
+    assert!(store.try_use_nonce("old-nonce", "walletA", now - chrono::Duration::hours(2)).await.unwrap());
+    assert!(store.try_use_nonce("new-nonce", "walletA", now).await.unwrap());
+
+    assert_eq!(store.prune_nonces(now - chrono::Duration::hours(1)).await.unwrap(), 1);
+    assert_eq!(store.prune_nonces(now - chrono::Duration::hours(1)).await.unwrap(), 0);
+    assert!(!store.try_use_nonce("new-nonce", "walletA", now).await.unwrap(), "kept nonce still single-use");
+    assert!(store.try_use_nonce("old-nonce", "walletA", now - chrono::Duration::hours(2)).await.unwrap());
 }
 
 // ---- challenges ---------------------------------------------------------
 
-#[tokio::test]
-async fn challenge_insert_then_get() {
-    let store = fresh_store().await;
+async fn challenge_insert_then_get(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", None);
     store.insert_node(&node, "tok").await.unwrap();
     let ch = Challenge {
@@ -257,6 +492,10 @@ async fn challenge_insert_then_get() {
         status: ChallengeStatus::Open,
         answered_at: None,
         passed: None,
+        origin: ChallengeOrigin::Operator,
+        subject: None,
+        answer_hash: None,
+        fail_reason: None,
     };
     store.insert_challenge(&ch).await.unwrap();
 
@@ -268,9 +507,8 @@ async fn challenge_insert_then_get() {
     assert_eq!(open.id, ch.id);
 }
 
-#[tokio::test]
-async fn mark_challenge_answered_persists() {
-    let store = fresh_store().await;
+async fn mark_challenge_answered_persists(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", None);
     store.insert_node(&node, "tok").await.unwrap();
     let ch = Challenge {
@@ -284,22 +522,26 @@ async fn mark_challenge_answered_persists() {
         status: ChallengeStatus::Open,
         answered_at: None,
         passed: None,
+        origin: ChallengeOrigin::Operator,
+        subject: None,
+        answer_hash: None,
+        fail_reason: None,
     };
     store.insert_challenge(&ch).await.unwrap();
     store
-        .mark_challenge_answered(ch.id, true, Utc::now())
+        .mark_challenge_answered(ch.id, true, Some("expect"), Utc::now())
         .await
         .unwrap();
 
     let got = store.get_challenge(ch.id).await.unwrap().unwrap();
     assert_eq!(got.status, ChallengeStatus::Answered);
     assert_eq!(got.passed, Some(true));
+    assert_eq!(got.answer_hash.as_deref(), Some("expect"));
     assert!(got.answered_at.is_some());
 }
 
-#[tokio::test]
-async fn expire_old_challenges_only_touches_open() {
-    let store = fresh_store().await;
+async fn expire_old_challenges_only_touches_open(backend: Backend) {
+    let store = backend.fresh_store().await;
     let node = sample_node("walletA", None);
     store.insert_node(&node, "tok").await.unwrap();
 
@@ -314,6 +556,10 @@ async fn expire_old_challenges_only_touches_open() {
         status: ChallengeStatus::Open,
         answered_at: None,
         passed: None,
+        origin: ChallengeOrigin::Operator,
+        subject: None,
+        answer_hash: None,
+        fail_reason: None,
     };
     let fresh_open = Challenge {
         id: Uuid::new_v4(),
@@ -326,6 +572,10 @@ async fn expire_old_challenges_only_touches_open() {
         status: ChallengeStatus::Open,
         answered_at: None,
         passed: None,
+        origin: ChallengeOrigin::Operator,
+        subject: None,
+        answer_hash: None,
+        fail_reason: None,
     };
     store.insert_challenge(&old_expired).await.unwrap();
     store.insert_challenge(&fresh_open).await.unwrap();
@@ -341,9 +591,8 @@ async fn expire_old_challenges_only_touches_open() {
 
 // ---- stats --------------------------------------------------------------
 
-#[tokio::test]
-async fn network_stats_count_correctly() {
-    let store = fresh_store().await;
+async fn network_stats_count_correctly(backend: Backend) {
+    let store = backend.fresh_store().await;
     // network_stats only counts nodes that have produced an accepted proof
     // (last_proof_at IS NOT NULL) — registration-only spam doesn't inflate
     // the public counter.
@@ -363,9 +612,8 @@ async fn network_stats_count_correctly() {
     assert_eq!(stats.network, "mainnet");
 }
 
-#[tokio::test]
-async fn leaderboard_orders_by_total_points_desc() {
-    let store = fresh_store().await;
+async fn leaderboard_orders_by_total_points_desc(backend: Backend) {
+    let store = backend.fresh_store().await;
     for (i, (wallet, pts)) in [("low", 5u64), ("mid", 50), ("hi", 500)].iter().enumerate() {
         let mut n = sample_node(wallet, None);
         // leaderboard now filters to nodes with last_proof_at set + last_height
@@ -382,9 +630,8 @@ async fn leaderboard_orders_by_total_points_desc() {
     assert_eq!(board[2].wallet, "low");
 }
 
-#[tokio::test]
-async fn wallet_stats_aggregates_across_nodes() {
-    let store = fresh_store().await;
+async fn wallet_stats_aggregates_across_nodes(backend: Backend) {
+    let store = backend.fresh_store().await;
     let a = sample_node("walletA", Some("a1"));
     let b = sample_node("walletA", Some("a2"));
     store.insert_node(&a, "t1").await.unwrap();
@@ -400,15 +647,14 @@ async fn wallet_stats_aggregates_across_nodes() {
 
 // ---- snapshots ----------------------------------------------------------
 
-#[tokio::test]
-async fn snapshot_cycle_increments() {
-    let store = fresh_store().await;
+async fn snapshot_cycle_increments(backend: Backend) {
+    let store = backend.fresh_store().await;
     let n = sample_node("walletA", None);
     store.insert_node(&n, "tok").await.unwrap();
     store.add_uptime_and_points(n.id, 0, 100).await.unwrap();
 
-    let id1 = store.insert_snapshot(1, "root-1", 100, Some("mint")).await.unwrap();
-    let id2 = store.insert_snapshot(2, "root-2", 200, Some("mint")).await.unwrap();
+    let id1 = store.insert_snapshot_with_leaves(1, "root-1", 100, Some("mint"), &[]).await.unwrap();
+    let id2 = store.insert_snapshot_with_leaves(2, "root-2", 200, Some("mint"), &[]).await.unwrap();
     assert_ne!(id1, id2);
 
     let latest = store.latest_snapshot().await.unwrap().unwrap();
@@ -416,12 +662,10 @@ async fn snapshot_cycle_increments() {
     assert_eq!(latest.2, "root-2");
 }
 
-#[tokio::test]
-async fn snapshot_leaf_round_trip() {
-    let store = fresh_store().await;
-    let sid = store.insert_snapshot(1, "root-x", 100, None).await.unwrap();
-    store
-        .insert_snapshot_leaf(sid, "walletA", 100, "leaf-hash-A", r#"{"siblings":[],"leaf_index":0}"#)
+async fn snapshot_leaf_round_trip(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let sid = store
+        .insert_snapshot_with_leaves(1, "root-x", 100, None, &[leaf("walletA", 0, 100, "leaf-hash-A")])
         .await
         .unwrap();
     let leaf = store.snapshot_leaf_for_wallet(sid, "walletA").await.unwrap().unwrap();
@@ -433,31 +677,886 @@ async fn snapshot_leaf_round_trip() {
     assert!(missing.is_none());
 }
 
-#[tokio::test]
-async fn total_points_per_wallet_filters_zeroes() {
-    let store = fresh_store().await;
+async fn snapshot_leaf_advances_wallet_baseline_monotonically(backend: Backend) {
+    let store = backend.fresh_store().await;
+    store.insert_snapshot_with_leaves(1, "r1", 100, None, &[leaf("walletA", 0, 100, "h1")]).await.unwrap();
+    assert_eq!(store.snapshot_baselines().await.unwrap()["walletA"], 100);
+
+    store.insert_snapshot_with_leaves(2, "r2", 40, None, &[leaf("walletA", 100, 140, "h2")]).await.unwrap();
+    assert_eq!(store.snapshot_baselines().await.unwrap()["walletA"], 140);
+
+    // A stale write can't pull the baseline back down.
+    store.insert_snapshot_with_leaves(3, "r3", 10, None, &[leaf("walletA", 0, 10, "h3")]).await.unwrap();
+    assert_eq!(store.snapshot_baselines().await.unwrap()["walletA"], 140);
+
+    let latest = store.latest_snapshot_leaf_for_wallet("walletA").await.unwrap().unwrap();
+    assert_eq!(latest.cycle, 3);
+    assert!(store.latest_snapshot_leaf_for_wallet("ghost").await.unwrap().is_none());
+
+    let cycles = |leaves: Vec<SnapshotLeaf>| leaves.iter().map(|l| l.cycle).collect::<Vec<_>>();
+    assert_eq!(cycles(store.snapshot_leaves_for_wallet("walletA", 0, 10).await.unwrap()), vec![1, 2, 3]);
+    assert_eq!(cycles(store.snapshot_leaves_for_wallet("walletA", 1, 1).await.unwrap()), vec![2]);
+    assert!(store.snapshot_leaves_for_wallet("ghost", 0, 10).await.unwrap().is_empty());
+}
+
+async fn failed_snapshot_leaf_rolls_back_the_whole_cycle(backend: Backend) {
+    let store = backend.fresh_store().await;
+    store.insert_snapshot_with_leaves(1, "r1", 100, None, &[leaf("walletA", 0, 100, "h1")]).await.unwrap();
+
+    // The second walletB leaf breaks the (snapshot_id, wallet) key after the
+    // snapshot row, walletA's leaf and both baselines were already written.
+    let leaves = [leaf("walletA", 100, 150, "a2"), leaf("walletB", 0, 30, "b2"), leaf("walletB", 0, 30, "b2")];
+    assert!(store.insert_snapshot_with_leaves(2, "r2", 80, None, &leaves).await.is_err());
+
+    assert_eq!(store.latest_snapshot().await.unwrap().unwrap().1, 1, "no cycle 2 root");
+    let baselines = store.snapshot_baselines().await.unwrap();
+    assert_eq!(baselines["walletA"], 100);
+    assert!(!baselines.contains_key("walletB"));
+    assert!(store.latest_snapshot_leaf_for_wallet("walletB").await.unwrap().is_none());
+}
+
+async fn total_points_per_wallet_filters_zeroes(backend: Backend) {
+    let store = backend.fresh_store().await;
     let pay = sample_node("paid", None);
     let zero = sample_node("zero", None);
     store.insert_node(&pay, "t1").await.unwrap();
     store.insert_node(&zero, "t2").await.unwrap();
     store.add_uptime_and_points(pay.id, 0, 42).await.unwrap();
 
-    let rows = store.total_points_per_wallet("mainnet").await.unwrap();
+    let rows = store.total_points_per_wallet("mainnet", false).await.unwrap();
     assert_eq!(rows.len(), 1);
     assert_eq!(rows[0].0, "paid");
     assert_eq!(rows[0].1, 42);
 }
 
-#[tokio::test]
-async fn snapshots_belong_to_their_app_only() {
-    // Two separate in-memory stores must not see each other's data.
-    let s1 = fresh_store().await;
-    let s2 = fresh_store().await;
+async fn total_points_per_wallet_finalized_only_holds_back_provisional_points(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let n = sample_node("w", None);
+    store.insert_node(&n, "t").await.unwrap();
+    let early = sample_proof(n.id, "w", 100, "aa", ProofVerdict::Accepted, 30);
+    let late = sample_proof(n.id, "w", 200, "bb", ProofVerdict::Accepted, 20);
+    store.insert_proof(&early).await.unwrap();
+    store.insert_proof(&late).await.unwrap();
+    store.apply_proof_acceptance(n.id, early.id, 100, "aa", 30, Utc::now()).await.unwrap();
+    store.apply_proof_acceptance(n.id, late.id, 200, "bb", 20, Utc::now()).await.unwrap();
+    // Uptime credit isn't tied to a proof — always counts.
+    store.add_uptime_and_points(n.id, 60, 5).await.unwrap();
+
+    assert_eq!(store.total_points_per_wallet("mainnet", false).await.unwrap()[0].1, 55);
+    assert_eq!(store.total_points_per_wallet("mainnet", true).await.unwrap()[0].1, 5);
+
+    assert!(store.finalize_proof(early.id, Utc::now()).await.unwrap());
+    assert!(!store.finalize_proof(early.id, Utc::now()).await.unwrap(), "finalize is one-shot");
+    assert_eq!(store.total_points_per_wallet("mainnet", true).await.unwrap()[0].1, 35);
+
+    let unfinalized = store.list_unfinalized_proofs(1_000, 10).await.unwrap();
+    assert_eq!(unfinalized.len(), 1);
+    assert_eq!(unfinalized[0].id, late.id);
+    assert!(store.list_unfinalized_proofs(199, 10).await.unwrap().is_empty());
+}
+
+async fn mark_proof_reorged_claws_back_points_exactly_once(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let n = sample_node("w", None);
+    store.insert_node(&n, "t").await.unwrap();
+    let p = sample_proof(n.id, "w", 100, "aa", ProofVerdict::Accepted, 30);
+    store.insert_proof(&p).await.unwrap();
+    store.apply_proof_acceptance(n.id, p.id, 100, "aa", 30, Utc::now()).await.unwrap();
+    store.add_uptime_and_points(n.id, 60, 5).await.unwrap();
+
+    assert!(store.mark_proof_reorged(p.id, "reorged: test", Utc::now()).await.unwrap());
+    assert!(!store.mark_proof_reorged(p.id, "reorged: test", Utc::now()).await.unwrap());
+
+    let node = store.get_node(n.id).await.unwrap().unwrap();
+    assert_eq!(node.points, 5);
+    let row = &store.list_proofs_by_node(n.id, 10).await.unwrap()[0];
+    assert_eq!(row.verdict, ProofVerdict::Reorged);
+    assert_eq!(row.reject_reason.as_deref(), Some("reorged: test"));
+    assert_eq!(row.points_awarded, 30, "revoked amount stays on the row");
+    assert!(row.finalized_at.is_some());
+}
+
+async fn mark_proof_reorged_never_drives_points_negative(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let n = sample_node("w", None);
+    store.insert_node(&n, "t").await.unwrap();
+    // Proof row claims more than the node currently holds.
+    let p = sample_proof(n.id, "w", 100, "aa", ProofVerdict::Accepted, 30);
+    store.insert_proof(&p).await.unwrap();
+    store.add_uptime_and_points(n.id, 0, 10).await.unwrap();
+
+    assert!(store.mark_proof_reorged(p.id, "reorged", Utc::now()).await.unwrap());
+    assert_eq!(store.get_node(n.id).await.unwrap().unwrap().points, 0);
+}
 
-    s1.insert_snapshot(1, "root", 100, None).await.unwrap();
+async fn snapshots_belong_to_their_app_only(backend: Backend) {
+    // Two separate stores must not see each other's data.
+    let s1 = backend.fresh_store().await;
+    let s2 = backend.fresh_store().await;
+
+    s1.insert_snapshot_with_leaves(1, "root", 100, None, &[]).await.unwrap();
     let s1_latest = s1.latest_snapshot().await.unwrap();
     let s2_latest = s2.latest_snapshot().await.unwrap();
 
     assert!(s1_latest.is_some());
     assert!(s2_latest.is_none());
 }
+
+async fn points_ledger_records_every_mutation_and_balances(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let n = sample_node("w", None);
+    store.insert_node(&n, "t").await.unwrap();
+    let p = sample_proof(n.id, "w", 100, "aa", ProofVerdict::Accepted, 30);
+    store.insert_proof(&p).await.unwrap();
+
+    store.apply_proof_acceptance(n.id, p.id, 100, "aa", 30, Utc::now()).await.unwrap();
+    store.add_uptime_and_points(n.id, 60, 5).await.unwrap();
+    store.add_uptime_and_points(n.id, 60, 0).await.unwrap();
+    store
+        .adjust_points(n.id, 3, LedgerSource::ChallengeBonus, Some("chal-1"))
+        .await
+        .unwrap();
+    assert!(store.mark_proof_reorged(p.id, "reorged", Utc::now()).await.unwrap());
+
+    let ledger = store.ledger_for_node(n.id, 100).await.unwrap();
+    let sources: Vec<_> = ledger.iter().map(|e| e.source).collect();
+    // Newest first; the zero-point uptime tick leaves no row.
+    assert_eq!(
+        sources,
+        vec![
+            LedgerSource::Revocation,
+            LedgerSource::ChallengeBonus,
+            LedgerSource::Uptime,
+            LedgerSource::Proof,
+        ]
+    );
+    assert_eq!(ledger[0].delta, -30);
+    assert_eq!(ledger[0].ref_id.as_deref(), Some(p.id.to_string().as_str()));
+    assert_eq!(ledger[3].ref_id.as_deref(), Some(p.id.to_string().as_str()));
+
+    let node = store.get_node(n.id).await.unwrap().unwrap();
+    assert_eq!(node.points, 8);
+    assert_eq!(ledger.iter().map(|e| e.delta).sum::<i64>(), 8);
+    assert!(store.points_mismatches().await.unwrap().is_empty());
+}
+
+async fn points_ledger_debits_record_only_what_was_taken(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let n = sample_node("w", None);
+    store.insert_node(&n, "t").await.unwrap();
+    store.add_uptime_and_points(n.id, 0, 10).await.unwrap();
+
+    let applied = store
+        .adjust_points(n.id, -25, LedgerSource::Admin, Some("farm cleanup"))
+        .await
+        .unwrap();
+    assert_eq!(applied, -10);
+    assert_eq!(store.get_node(n.id).await.unwrap().unwrap().points, 0);
+    let ledger = store.ledger_for_node(n.id, 10).await.unwrap();
+    assert_eq!(ledger[0].delta, -10);
+    assert_eq!(ledger[0].ref_id.as_deref(), Some("farm cleanup"));
+    assert!(store.points_mismatches().await.unwrap().is_empty());
+}
+
+async fn concurrent_debits_never_take_more_than_the_balance(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let n = sample_node("w", None);
+    store.insert_node(&n, "t").await.unwrap();
+    store.add_uptime_and_points(n.id, 0, 10).await.unwrap();
+
+    let debits = (0..5).map(|_| store.adjust_points(n.id, -4, LedgerSource::Admin, Some("cleanup")));
+    let applied: Vec<i64> = futures::future::join_all(debits)
+        .await
+        .into_iter()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(applied.iter().sum::<i64>(), -10);
+    assert_eq!(store.get_node(n.id).await.unwrap().unwrap().points, 0);
+    assert!(store.points_mismatches().await.unwrap().is_empty());
+
+    // An empty balance takes nothing and writes no row; an unknown node errors.
+    assert_eq!(store.adjust_points(n.id, -4, LedgerSource::Admin, None).await.unwrap(), 0);
+    assert_eq!(store.ledger_for_node(n.id, 100).await.unwrap().len(), 4);
+    assert!(store
+        .adjust_points(Uuid::new_v4(), 5, LedgerSource::Admin, None)
+        .await
+        .is_err());
+}
+
+async fn concurrent_reorgs_and_debits_never_take_more_than_the_balance(backend: Backend) {
+    let store = backend.fresh_store().await;
+    // A few rounds: the race only shows when a debit commits between a
+    // revocation's read of the balance and its write.
+    for round in 0..10 {
+        let n = sample_node("w", Some(&format!("r{round}")));
+        store.insert_node(&n, "t").await.unwrap();
+        let mut proofs = Vec::new();
+        for h in 0..3 {
+            let p = sample_proof(n.id, "w", 100 + h, &format!("h{h}"), ProofVerdict::Accepted, 10);
+            store.insert_proof(&p).await.unwrap();
+            store.apply_proof_acceptance(n.id, p.id, p.claimed_height, &p.claimed_block_hash, 10, Utc::now()).await.unwrap();
+            proofs.push(p);
+        }
+
+        // 30 reorged + 20 debited against a balance of 30.
+        let reorgs = proofs.iter().map(|p| store.mark_proof_reorged(p.id, "reorged", Utc::now()));
+        let debits = (0..5).map(|_| store.adjust_points(n.id, -4, LedgerSource::Admin, None));
+        let (reorged, debited) = futures::future::join(
+            futures::future::join_all(reorgs),
+            futures::future::join_all(debits),
+        )
+        .await;
+        assert!(reorged.into_iter().all(|r| r.unwrap()));
+        debited.into_iter().for_each(|r| {
+            r.unwrap();
+        });
+
+        assert_eq!(store.get_node(n.id).await.unwrap().unwrap().points, 0, "round {round}");
+        let ledger = store.ledger_for_node(n.id, 100).await.unwrap();
+        assert_eq!(ledger.iter().filter(|e| e.delta < 0).map(|e| e.delta).sum::<i64>(), -30);
+    }
+    assert!(store.points_mismatches().await.unwrap().is_empty());
+}
+
+async fn points_mismatches_flags_writes_that_bypass_the_ledger(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let good = sample_node("good", None);
+    let bad = sample_node("bad", None);
+    store.insert_node(&good, "t1").await.unwrap();
+    store.insert_node(&bad, "t2").await.unwrap();
+    store.add_uptime_and_points(good.id, 0, 10).await.unwrap();
+    store.add_uptime_and_points(bad.id, 0, 10).await.unwrap();
+
+    store.bump_points_bypassing_ledger(bad.id, 500).await;
+
+    let mismatches = store.points_mismatches().await.unwrap();
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].node_id, bad.id);
+    assert_eq!(mismatches[0].wallet, "bad");
+    assert_eq!(mismatches[0].cached_points, 510);
+    assert_eq!(mismatches[0].ledger_points, 10);
+    assert_eq!(mismatches[0].difference, 500);
+}
+
+async fn consecutive_challenge_failures_resets_on_pass(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let n = sample_node("w", None);
+    store.insert_node(&n, "t").await.unwrap();
+    let base = Utc::now() - chrono::Duration::hours(1);
+    for (i, passed) in [false, true, false, false].into_iter().enumerate() {
+        let at = base + chrono::Duration::minutes(i as i64);
+        store
+            .insert_challenge(&Challenge {
+                id: Uuid::new_v4(),
+                node_id: n.id,
+                kind: ChallengeKind::BlockHash,
+                target_height: 100,
+                expected_hash: "h".into(),
+                issued_at: at,
+                expires_at: at,
+                status: ChallengeStatus::Answered,
+                answered_at: Some(at),
+                passed: Some(passed),
+                origin: ChallengeOrigin::Auto,
+                subject: None,
+                answer_hash: None,
+                fail_reason: None,
+            })
+            .await
+            .unwrap();
+    }
+    assert_eq!(store.consecutive_challenge_failures(n.id).await.unwrap(), 2);
+    assert_eq!(store.list_challenges_for_node(n.id, 10).await.unwrap().len(), 4);
+}
+
+async fn active_nodes_are_those_that_proved_within_the_hour(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let mut fresh = sample_node("wA", None);
+    fresh.last_proof_at = Some(Utc::now() - chrono::Duration::minutes(5));
+    fresh.last_height = Some(100);
+    let mut quiet = sample_node("wB", None);
+    quiet.last_proof_at = Some(Utc::now() - chrono::Duration::minutes(90));
+    quiet.last_height = Some(100);
+    store.insert_node(&fresh, "t1").await.unwrap();
+    store.insert_node(&quiet, "t2").await.unwrap();
+
+    let active = store.list_active_nodes("mainnet", 0, 10).await.unwrap();
+    assert_eq!(active.iter().map(|n| n.id).collect::<Vec<_>>(), vec![fresh.id]);
+    let stats = store.network_stats("mainnet", 0).await.unwrap();
+    assert_eq!(stats.total_nodes, 2);
+    assert_eq!(stats.active_nodes, 1);
+}
+
+async fn recent_proofs_filters_combine(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let a = sample_node("wA", None);
+    let b = sample_node("wB", None);
+    store.insert_node(&a, "t1").await.unwrap();
+    store.insert_node(&b, "t2").await.unwrap();
+    store.insert_proof(&sample_proof(a.id, "wA", 1, "h1", ProofVerdict::Accepted, 5)).await.unwrap();
+    store.insert_proof(&sample_proof(a.id, "wA", 2, "h2", ProofVerdict::Rejected, 0)).await.unwrap();
+    store.insert_proof(&sample_proof(b.id, "wB", 3, "h3", ProofVerdict::Accepted, 5)).await.unwrap();
+
+    assert_eq!(store.list_recent_proofs("mainnet", 10, None, None).await.unwrap().len(), 3);
+    assert_eq!(store.list_recent_proofs("mainnet", 2, None, None).await.unwrap().len(), 2);
+    assert_eq!(
+        store.list_recent_proofs("mainnet", 10, Some("accepted"), None).await.unwrap().len(),
+        2
+    );
+    assert_eq!(store.list_recent_proofs("mainnet", 10, None, Some("wA")).await.unwrap().len(), 2);
+    let both = store
+        .list_recent_proofs("mainnet", 10, Some("accepted"), Some("wA"))
+        .await
+        .unwrap();
+    assert_eq!(both.len(), 1);
+    assert_eq!(both[0].claimed_height, 1);
+    assert!(store.list_recent_proofs("testnet", 10, None, None).await.unwrap().is_empty());
+}
+
+async fn try_insert_proof_ignores_duplicates_and_pending_resolves_once(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let n = sample_node("w", None);
+    store.insert_node(&n, "t").await.unwrap();
+    let p = sample_proof(n.id, "w", 100, "h", ProofVerdict::Pending, 0);
+    assert!(store.try_insert_proof(&p).await.unwrap());
+    let dup = sample_proof(n.id, "w", 100, "h", ProofVerdict::Pending, 0);
+    assert!(!store.try_insert_proof(&dup).await.unwrap());
+
+    assert_eq!(store.list_pending_proofs(10).await.unwrap().len(), 1);
+    assert!(store
+        .resolve_pending_proof(p.id, ProofVerdict::Accepted, None, 40)
+        .await
+        .unwrap());
+    assert!(!store
+        .resolve_pending_proof(p.id, ProofVerdict::Rejected, Some("late"), 0)
+        .await
+        .unwrap());
+    assert!(store.list_pending_proofs(10).await.unwrap().is_empty());
+    let row = store.last_accepted_proof_for_node(n.id).await.unwrap().unwrap();
+    assert_eq!(row.points_awarded, 40);
+}
+
+async fn accept_pending_proof_credits_in_the_same_transaction(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let n = sample_node("w", None);
+    store.insert_node(&n, "t").await.unwrap();
+    let p = sample_proof(n.id, "w", 100, "h", ProofVerdict::Pending, 0);
+    store.insert_proof(&p).await.unwrap();
+
+    assert!(store.accept_pending_proof(&p, 40).await.unwrap());
+    assert!(!store.accept_pending_proof(&p, 40).await.unwrap(), "credited once");
+    let got = store.get_node(n.id).await.unwrap().unwrap();
+    assert_eq!((got.points, got.last_height, got.status), (40, Some(100), NodeStatus::Active));
+    assert_eq!(store.last_accepted_proof_for_node(n.id).await.unwrap().unwrap().points_awarded, 40);
+    assert_eq!(store.ledger_for_node(n.id, 10).await.unwrap().len(), 1);
+
+    // The credit fails after the verdict was written: both roll back and the
+    // proof stays pending for the next re-check.
+    let q = sample_proof(n.id, "w", 101, "i", ProofVerdict::Pending, 0);
+    store.insert_proof(&q).await.unwrap();
+    store.fail_ledger_writes().await;
+    assert!(store.accept_pending_proof(&q, 40).await.is_err());
+    let pending = store.list_pending_proofs(10).await.unwrap();
+    assert_eq!(pending.iter().map(|p| p.id).collect::<Vec<_>>(), vec![q.id]);
+    assert_eq!(pending[0].points_awarded, 0);
+    let got = store.get_node(n.id).await.unwrap().unwrap();
+    assert_eq!((got.points, got.last_height), (40, Some(100)));
+}
+
+async fn node_daily_series_buckets_by_day(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let n = sample_node("w", None);
+    store.insert_node(&n, "t").await.unwrap();
+    let now = Utc::now();
+    for (i, (days_ago, verdict, pts)) in [
+        (0, ProofVerdict::Accepted, 10),
+        (0, ProofVerdict::Rejected, 0),
+        (2, ProofVerdict::Accepted, 7),
+        (30, ProofVerdict::Accepted, 99),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut p = sample_proof(n.id, "w", i as u64, "h", verdict, pts);
+        p.received_at = now - chrono::Duration::days(days_ago);
+        store.insert_proof(&p).await.unwrap();
+    }
+
+    let series = store.node_daily_series(n.id, 7).await.unwrap();
+    assert_eq!(series.len(), 2, "the 30-day-old proof is outside the window");
+    assert_eq!(series[0].day, (now - chrono::Duration::days(2)).format("%Y-%m-%d").to_string());
+    assert_eq!((series[0].proofs, series[0].accepted, series[0].points), (1, 1, 7));
+    assert_eq!(series[1].day, now.format("%Y-%m-%d").to_string());
+    assert_eq!((series[1].proofs, series[1].accepted, series[1].points), (2, 1, 10));
+}
+
+async fn admin_cleanup_counts_and_deletes_in_batches(backend: Backend) {
+    let store = backend.fresh_store().await;
+    // Three nodes on one wallet (one over a limit of 2), two at fake heights.
+    let base = Utc::now() - chrono::Duration::hours(1);
+    for i in 0..3 {
+        let mut n = sample_node("farm", Some(&format!("n{i}")));
+        n.registered_at = base + chrono::Duration::minutes(i);
+        store.insert_node(&n, &format!("f{i}")).await.unwrap();
+    }
+    for i in 0..2 {
+        let mut n = sample_node(&format!("fake{i}"), None);
+        n.last_height = Some(5);
+        store.insert_node(&n, &format!("k{i}")).await.unwrap();
+    }
+
+    assert_eq!(store.count_fake_height_nodes(1_000).await.unwrap(), 2);
+    assert_eq!(store.count_excess_wallet_nodes(2).await.unwrap(), 1);
+    assert_eq!(store.delete_fake_height_nodes(1_000, 1).await.unwrap(), 1);
+    assert_eq!(store.count_fake_height_nodes(1_000).await.unwrap(), 1);
+    assert_eq!(store.delete_excess_wallet_nodes(2, 10).await.unwrap(), 1);
+
+    let farm = store.list_nodes_by_wallet("farm").await.unwrap();
+    let labels: Vec<_> = farm.iter().filter_map(|n| n.label.as_deref()).collect();
+    assert_eq!(labels, vec!["n0", "n1"], "oldest registrations are kept");
+
+    let victim = farm[0].id;
+    assert!(store.delete_node(victim).await.unwrap());
+    assert!(!store.delete_node(victim).await.unwrap());
+    assert!(store.get_node(victim).await.unwrap().is_none());
+    store.ping().await.unwrap();
+}
+
+async fn deregister_keeps_history_and_drops_the_node_everywhere_else(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let base = Utc::now() - chrono::Duration::hours(1);
+    let mut n = sample_node("w", Some("main"));
+    n.registered_at = base;
+    n.rpc_endpoint = Some("http://10.0.0.1:8232".into());
+    n.p2p_address = Some("10.0.0.1:8233".into());
+    store.insert_node(&n, "tok").await.unwrap();
+    let mut second = sample_node("w", Some("second"));
+    second.registered_at = base + chrono::Duration::minutes(1);
+    store.insert_node(&second, "tok2").await.unwrap();
+    store.put_rpc_credential(n.id, "v1:aa", Utc::now()).await.unwrap();
+    store.add_uptime_and_points(n.id, 60, 25).await.unwrap();
+
+    assert!(store.deregister_node(n.id).await.unwrap());
+    assert!(!store.deregister_node(n.id).await.unwrap());
+    assert!(!store.deregister_node(Uuid::new_v4()).await.unwrap());
+
+    let kept = store.get_node(n.id).await.unwrap().unwrap();
+    assert_eq!(kept.status, NodeStatus::Deregistered);
+    assert_eq!((kept.label, kept.rpc_endpoint, kept.p2p_address), (None, None, None));
+    assert_eq!(kept.points, 25);
+    assert_eq!(store.ledger_for_node(n.id, 10).await.unwrap().len(), 1);
+    assert!(store.points_mismatches().await.unwrap().is_empty());
+    assert!(store.get_node_by_auth_token_hash("tok").await.unwrap().is_none());
+    assert!(store.get_rpc_credential(n.id).await.unwrap().is_none());
+    assert!(store.list_nodes_with_rpc("mainnet").await.unwrap().is_empty());
+    assert!(store.list_nodes_with_p2p("mainnet").await.unwrap().is_empty());
+
+    // Doesn't hold a per-wallet slot, and a late proof doesn't revive it.
+    assert_eq!(store.count_excess_wallet_nodes(1).await.unwrap(), 0);
+    let p = sample_proof(n.id, "w", 100, "aa", ProofVerdict::Accepted, 5);
+    store.insert_proof(&p).await.unwrap();
+    store.apply_proof_acceptance(n.id, p.id, 100, "aa", 5, Utc::now()).await.unwrap();
+    assert_eq!(store.get_node(n.id).await.unwrap().unwrap().status, NodeStatus::Deregistered);
+
+    // The label is free again.
+    store.insert_node(&sample_node("w", Some("main")), "tok3").await.unwrap();
+}
+
+async fn p2p_probes_round_trip_and_credit_ledger(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let mut n = sample_node("w", None);
+    n.p2p_address = Some("203.0.113.7:8233".into());
+    let bare = sample_node("w2", None);
+    store.insert_node(&n, "t").await.unwrap();
+    store.insert_node(&bare, "t2").await.unwrap();
+    assert_eq!(
+        store.list_nodes_with_p2p("mainnet").await.unwrap().iter().map(|n| n.id).collect::<Vec<_>>(),
+        vec![n.id]
+    );
+
+    let at = Utc::now();
+    let up = P2pProbe {
+        id: Uuid::new_v4(),
+        node_id: n.id,
+        address: "203.0.113.7:8233".into(),
+        reachable: true,
+        user_agent: Some("/Zebra:2.4.0/".into()),
+        protocol_version: Some(170_140),
+        start_height: Some(3_000_000),
+        error: None,
+        points_awarded: 10,
+        probed_at: at - chrono::Duration::minutes(1),
+    };
+    let down = P2pProbe {
+        id: Uuid::new_v4(),
+        reachable: false,
+        user_agent: None,
+        protocol_version: None,
+        start_height: None,
+        error: Some("connecting: refused".into()),
+        points_awarded: 0,
+        probed_at: at,
+        ..up.clone()
+    };
+    store.record_p2p_probe(&up).await.unwrap();
+    store.record_p2p_probe(&down).await.unwrap();
+
+    let probes = store.list_p2p_probes_for_node(n.id, 10).await.unwrap();
+    assert_eq!(probes.len(), 2);
+    assert!(!probes[0].reachable, "newest first");
+    assert_eq!(probes[1].protocol_version, Some(170_140));
+    assert_eq!(probes[1].start_height, Some(3_000_000));
+    assert_eq!(store.get_node(n.id).await.unwrap().unwrap().points, 10);
+    let ledger = store.ledger_for_node(n.id, 10).await.unwrap();
+    assert_eq!(ledger.len(), 1, "unreachable probe earns nothing");
+    assert_eq!(ledger[0].source, LedgerSource::P2pReachability);
+    assert!(store.points_mismatches().await.unwrap().is_empty());
+}
+
+async fn p2p_address_is_unique_and_first_payment_is_tracked(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let mut n = sample_node("w", None);
+    n.p2p_address = Some("203.0.113.7:8233".into());
+    store.insert_node(&n, "t").await.unwrap();
+    assert!(store.p2p_address_in_use("203.0.113.7:8233").await.unwrap());
+    assert!(!store.p2p_address_in_use("203.0.113.8:8233").await.unwrap());
+
+    let mut copy = sample_node("w2", None);
+    copy.p2p_address = n.p2p_address.clone();
+    assert!(store.insert_node(&copy, "t2").await.is_err(), "unique index");
+
+    let probe = P2pProbe {
+        id: Uuid::new_v4(),
+        node_id: n.id,
+        address: "203.0.113.7:8233".into(),
+        reachable: true,
+        user_agent: Some("/Zebra:2.4.0/".into()),
+        protocol_version: Some(170_140),
+        start_height: Some(3_000_000),
+        error: Some("start height 2999000 too far behind trusted tip 3000000".into()),
+        points_awarded: 0,
+        probed_at: Utc::now(),
+    };
+    store.record_p2p_probe(&probe).await.unwrap();
+    assert!(!store.p2p_probe_paid(n.id).await.unwrap(), "unpaid probes do not count");
+    store
+        .record_p2p_probe(&P2pProbe { id: Uuid::new_v4(), error: None, points_awarded: 10, ..probe })
+        .await
+        .unwrap();
+    assert!(store.p2p_probe_paid(n.id).await.unwrap());
+
+    // Deregistering frees the address for someone else.
+    assert!(store.deregister_node(n.id).await.unwrap());
+    assert!(!store.p2p_address_in_use("203.0.113.7:8233").await.unwrap());
+    store.insert_node(&copy, "t2").await.unwrap();
+}
+
+async fn job_lease_has_one_holder_until_it_expires(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let now = Utc::now();
+    let ttl = chrono::Duration::seconds(90);
+    assert!(store.acquire_lease("uptime", "a", now, now + ttl).await.unwrap());
+    assert!(!store.acquire_lease("uptime", "b", now, now + ttl).await.unwrap());
+    assert!(store.acquire_lease("snapshot", "b", now, now + ttl).await.unwrap(), "leases are per job");
+
+    // The holder renews; a rival still can't get in before the renewed expiry.
+    let later = now + chrono::Duration::seconds(60);
+    assert!(store.acquire_lease("uptime", "a", later, later + ttl).await.unwrap());
+    assert!(!store.acquire_lease("uptime", "b", now + ttl, now + ttl + ttl).await.unwrap());
+
+    // Once it lapses, anyone can take it, and the old holder is locked out.
+    let lapsed = later + ttl;
+    assert!(store.acquire_lease("uptime", "b", lapsed, lapsed + ttl).await.unwrap());
+    assert!(!store.acquire_lease("uptime", "a", lapsed, lapsed + ttl).await.unwrap());
+}
+
+async fn job_schedule_keeps_first_time_and_last_error(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let now = Utc::now();
+    let hour = chrono::Duration::hours(1);
+    store.schedule_job("snapshot", now + hour).await.unwrap();
+    store.schedule_job("snapshot", now).await.unwrap();
+    let run = store.get_job_run("snapshot").await.unwrap().unwrap();
+    assert_eq!(run.next_run_at.timestamp(), (now + hour).timestamp(), "first schedule wins");
+    assert!(run.last_run_at.is_none() && run.last_error.is_none());
+    assert!(store.get_job_run("uptime").await.unwrap().is_none());
+
+    let done = now + chrono::Duration::seconds(5);
+    store.finish_job_run("snapshot", now, done, now + hour, Some("boom")).await.unwrap();
+    let later = now + hour;
+    store.finish_job_run("snapshot", later, later, later + hour, None).await.unwrap();
+    // A job whose first recorded event is a run needs no schedule_job.
+    store.finish_job_run("uptime", now, now, now + hour, None).await.unwrap();
+    assert!(store.acquire_lease("snapshot", "inst-1", now, now + hour).await.unwrap());
+
+    let runs = store.list_job_runs().await.unwrap();
+    assert_eq!(runs.iter().map(|r| r.job.as_str()).collect::<Vec<_>>(), vec!["snapshot", "uptime"]);
+    let snap = &runs[0];
+    assert_eq!(snap.last_run_at.map(|t| t.timestamp()), Some(later.timestamp()));
+    assert_eq!(snap.next_run_at.timestamp(), (later + hour).timestamp());
+    assert_eq!(snap.last_error.as_deref(), Some("boom"), "a clean run keeps the last error");
+    assert_eq!(snap.last_error_at.map(|t| t.timestamp()), Some(done.timestamp()));
+    assert_eq!(snap.lease_holder.as_deref(), Some("inst-1"));
+    assert!(runs[1].lease_holder.is_none() && runs[1].last_error_at.is_none());
+}
+
+async fn rpc_poll_stats_upsert_filter_by_network_and_cascade(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let main = sample_node("w", None);
+    let mut test = sample_node("w2", None);
+    test.network = "testnet".into();
+    store.insert_node(&main, "t").await.unwrap();
+    store.insert_node(&test, "t2").await.unwrap();
+    assert!(store.get_rpc_poll_stats(main.id).await.unwrap().is_none());
+
+    let at = Utc::now();
+    let mut stats = RpcPollStats {
+        node_id: main.id,
+        last_polled_at: at,
+        last_outcome: RpcPollOutcome::Unreachable,
+        last_error: Some("connection refused".into()),
+        last_latency_ms: Some(12),
+        last_success_at: None,
+        consecutive_failures: 3,
+        next_poll_at: at + chrono::Duration::minutes(15),
+    };
+    store.upsert_rpc_poll_stats(&stats).await.unwrap();
+    store
+        .upsert_rpc_poll_stats(&RpcPollStats { node_id: test.id, ..stats.clone() })
+        .await
+        .unwrap();
+    stats.last_outcome = RpcPollOutcome::Credited;
+    stats.last_error = None;
+    stats.last_success_at = Some(at);
+    stats.consecutive_failures = 0;
+    store.upsert_rpc_poll_stats(&stats).await.unwrap();
+
+    let got = store.get_rpc_poll_stats(main.id).await.unwrap().unwrap();
+    assert_eq!(got.last_outcome, RpcPollOutcome::Credited);
+    assert_eq!(got.consecutive_failures, 0);
+    assert!(got.last_error.is_none());
+    assert_eq!(got.last_latency_ms, Some(12));
+    assert_eq!(got.last_success_at.map(|t| t.timestamp()), Some(at.timestamp()));
+    let mainnet = store.list_rpc_poll_stats("mainnet").await.unwrap();
+    assert_eq!(mainnet.iter().map(|s| s.node_id).collect::<Vec<_>>(), vec![main.id]);
+
+    assert!(store.delete_node(main.id).await.unwrap());
+    assert!(store.get_rpc_poll_stats(main.id).await.unwrap().is_none(), "deleted with its node");
+}
+
+async fn rpc_credential_upsert_and_cascade(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let node = sample_node("w", None);
+    store.insert_node(&node, "t").await.unwrap();
+    assert!(store.get_rpc_credential(node.id).await.unwrap().is_none());
+
+    store.put_rpc_credential(node.id, "v1:aa", Utc::now()).await.unwrap();
+    store.put_rpc_credential(node.id, "v1:bb", Utc::now()).await.unwrap();
+    assert_eq!(store.get_rpc_credential(node.id).await.unwrap().as_deref(), Some("v1:bb"));
+    store.delete_rpc_credential(node.id).await.unwrap();
+    assert!(store.get_rpc_credential(node.id).await.unwrap().is_none());
+    store.put_rpc_credential(node.id, "v1:cc", Utc::now()).await.unwrap();
+
+    assert!(store.delete_node(node.id).await.unwrap());
+    assert!(store.get_rpc_credential(node.id).await.unwrap().is_none(), "deleted with its node");
+}
+
+async fn delegations_upsert_revoke_and_cascade(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let node = sample_node("w", None);
+    store.insert_node(&node, "t").await.unwrap();
+    let now = Utc::now();
+    let mut d = Delegation {
+        node_id: node.id,
+        delegate: "hotkey".into(),
+        scopes: vec![DelegateScope::Proofs, DelegateScope::Challenges],
+        expires_at: now + chrono::Duration::days(30),
+        created_at: now,
+        revoked_at: None,
+    };
+    store.upsert_delegation(&d).await.unwrap();
+    let got = store.get_delegation(node.id, "hotkey").await.unwrap().unwrap();
+    assert_eq!(got.scopes, d.scopes);
+    assert!(got.allows(DelegateScope::Challenges, now));
+    assert!(store.get_delegation(node.id, "other").await.unwrap().is_none());
+
+    assert!(store.revoke_delegation(node.id, "hotkey", now).await.unwrap());
+    assert!(!store.revoke_delegation(node.id, "hotkey", now).await.unwrap(), "already revoked");
+    assert!(!store.revoke_delegation(node.id, "other", now).await.unwrap());
+    assert!(!store.get_delegation(node.id, "hotkey").await.unwrap().unwrap().is_active(now));
+
+    // Delegating the same key again replaces the revoked row.
+    d.scopes = vec![DelegateScope::Proofs];
+    d.created_at = now + chrono::Duration::seconds(1);
+    store.upsert_delegation(&d).await.unwrap();
+    let list = store.list_delegations(node.id).await.unwrap();
+    assert_eq!(list.len(), 1);
+    assert!(list[0].revoked_at.is_none());
+    assert!(!list[0].allows(DelegateScope::Challenges, now));
+
+    assert!(store.delete_node(node.id).await.unwrap());
+    assert!(store.list_delegations(node.id).await.unwrap().is_empty(), "deleted with its node");
+}
+
+async fn rpc_disagreements_round_trip_newest_first_and_prune(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let now = Utc::now();
+    let record = |hours: i64, agreed: Option<serde_json::Value>| RpcDisagreement {
+        id: Uuid::new_v4(),
+        method: "getblockhash".into(),
+        params: serde_json::json!([hours]),
+        agreed,
+        answers: vec![
+            RpcAnswer {
+                endpoint: "https://a.example".into(),
+                weight: 2,
+                result: Some(serde_json::json!("aa")),
+                error: None,
+            },
+            RpcAnswer {
+                endpoint: "https://b.example:8232".into(),
+                weight: 1,
+                result: None,
+                error: Some("timed out".into()),
+            },
+        ],
+        dissenters: vec!["https://a.example".into()],
+        observed_at: now - chrono::Duration::hours(hours),
+    };
+    let recent = record(1, Some(serde_json::json!("aa")));
+    store.insert_rpc_disagreement(&record(48, None)).await.unwrap();
+    store.insert_rpc_disagreement(&recent).await.unwrap();
+
+    let all = store.list_rpc_disagreements(now - chrono::Duration::days(7), 10).await.unwrap();
+    assert_eq!(all.len(), 2);
+    let got = &all[0];
+    assert_eq!((got.id, got.params.clone(), got.agreed.clone()), (recent.id, serde_json::json!([1]), Some(serde_json::json!("aa"))));
+    assert_eq!(got.answers.len(), 2);
+    assert_eq!((got.answers[0].weight, got.answers[1].error.as_deref()), (2, Some("timed out")));
+    assert_eq!(got.dissenters, vec!["https://a.example".to_string()]);
+    assert!(all[1].agreed.is_none());
+    assert_eq!(store.list_rpc_disagreements(now - chrono::Duration::hours(2), 10).await.unwrap().len(), 1);
+    assert_eq!(store.list_rpc_disagreements(now - chrono::Duration::days(7), 1).await.unwrap()[0].id, recent.id);
+
+    assert_eq!(store.prune_rpc_disagreements(now - chrono::Duration::days(1)).await.unwrap(), 1);
+    assert_eq!(store.list_rpc_disagreements(now - chrono::Duration::days(7), 10).await.unwrap().len(), 1);
+}
+
+async fn node_chain_info_upsert_filter_and_readiness_paid_once(backend: Backend) {
+    let store = backend.fresh_store().await;
+    let main = sample_node("w", None);
+    let mut test = sample_node("w2", None);
+    test.network = "testnet".into();
+    store.insert_node(&main, "t").await.unwrap();
+    store.insert_node(&test, "t2").await.unwrap();
+    assert!(store.get_node_chain_info(main.id).await.unwrap().is_none());
+
+    let now = Utc::now();
+    let mut record = NodeChainInfo {
+        node_id: main.id,
+        source: ChainInfoSource::Relay,
+        info: ChainInfo {
+            chain: "main".into(),
+            consensus_branch_id: "c2d6d0b4".into(),
+            next_branch_id: None,
+            upgrades: vec![],
+        },
+        quorum_match: None,
+        mismatch: None,
+        observed_at: now - chrono::Duration::days(2),
+    };
+    store.upsert_node_chain_info(&record).await.unwrap();
+    store
+        .upsert_node_chain_info(&NodeChainInfo { node_id: test.id, observed_at: now, ..record.clone() })
+        .await
+        .unwrap();
+    assert!(store.list_node_chain_info("mainnet", now - chrono::Duration::days(1)).await.unwrap().is_empty());
+
+    record.source = ChainInfoSource::ExposedRpc;
+    record.info.consensus_branch_id = "c8e71055".into();
+    record.info.next_branch_id = Some("c8e71055".into());
+    record.info.upgrades = vec![NetworkUpgrade {
+        branch_id: "77190ad8".into(),
+        name: "NU7".into(),
+        activation_height: 3_500_000,
+        status: "pending".into(),
+    }];
+    record.quorum_match = Some(false);
+    record.mismatch = Some("chain tip branch".into());
+    record.observed_at = now;
+    store.upsert_node_chain_info(&record).await.unwrap();
+
+    let got = store.get_node_chain_info(main.id).await.unwrap().unwrap();
+    assert_eq!(got.source, ChainInfoSource::ExposedRpc);
+    assert_eq!(got.info, record.info);
+    assert_eq!((got.quorum_match, got.mismatch.as_deref()), (Some(false), Some("chain tip branch")));
+    let listed = store.list_node_chain_info("mainnet", now - chrono::Duration::days(1)).await.unwrap();
+    assert_eq!(listed.iter().map(|r| r.node_id).collect::<Vec<_>>(), vec![main.id]);
+
+    assert!(store.record_upgrade_readiness(main.id, "77190ad8", 100, now).await.unwrap());
+    assert!(!store.record_upgrade_readiness(main.id, "77190ad8", 100, now).await.unwrap(), "paid once per upgrade");
+    assert_eq!(store.get_node(main.id).await.unwrap().unwrap().points, 100);
+    let ledger = store.ledger_for_node(main.id, 10).await.unwrap();
+    assert_eq!(ledger.len(), 1);
+    assert_eq!((ledger[0].source, ledger[0].delta, ledger[0].ref_id.as_deref()), (LedgerSource::UpgradeReadiness, 100, Some("77190ad8")));
+
+    assert!(store.delete_node(main.id).await.unwrap());
+    assert!(store.get_node_chain_info(main.id).await.unwrap().is_none(), "deleted with its node");
+}
+
+conformance!(
+    migrate_is_idempotent,
+    insert_then_fetch_node,
+    get_node_returns_none_for_unknown_id,
+    auth_token_lookup_returns_node,
+    list_nodes_by_wallet_orders_by_registered_at,
+    update_node_status_persists,
+    paused_nodes_leave_the_work_lists,
+    late_proofs_do_not_lift_a_suspension,
+    update_node_details_replaces_label_and_endpoint,
+    apply_proof_acceptance_updates_node_state,
+    add_uptime_and_points_is_additive,
+    insert_proof_and_list_for_wallet,
+    duplicate_proof_height_hash_for_same_node_conflicts,
+    last_accepted_proof_for_node_returns_most_recent,
+    nonce_is_single_use_per_wallet,
+    nonce_is_scoped_to_its_wallet,
+    nonces_prune_by_signed_time,
+    challenge_insert_then_get,
+    mark_challenge_answered_persists,
+    expire_old_challenges_only_touches_open,
+    network_stats_count_correctly,
+    leaderboard_orders_by_total_points_desc,
+    wallet_stats_aggregates_across_nodes,
+    snapshot_cycle_increments,
+    snapshot_leaf_round_trip,
+    snapshot_leaf_advances_wallet_baseline_monotonically,
+    failed_snapshot_leaf_rolls_back_the_whole_cycle,
+    total_points_per_wallet_filters_zeroes,
+    total_points_per_wallet_finalized_only_holds_back_provisional_points,
+    mark_proof_reorged_claws_back_points_exactly_once,
+    mark_proof_reorged_never_drives_points_negative,
+    snapshots_belong_to_their_app_only,
+    points_ledger_records_every_mutation_and_balances,
+    points_ledger_debits_record_only_what_was_taken,
+    concurrent_debits_never_take_more_than_the_balance,
+    concurrent_reorgs_and_debits_never_take_more_than_the_balance,
+    points_mismatches_flags_writes_that_bypass_the_ledger,
+    consecutive_challenge_failures_resets_on_pass,
+    active_nodes_are_those_that_proved_within_the_hour,
+    recent_proofs_filters_combine,
+    try_insert_proof_ignores_duplicates_and_pending_resolves_once,
+    accept_pending_proof_credits_in_the_same_transaction,
+    node_daily_series_buckets_by_day,
+    admin_cleanup_counts_and_deletes_in_batches,
+    deregister_keeps_history_and_drops_the_node_everywhere_else,
+    p2p_probes_round_trip_and_credit_ledger,
+    p2p_address_is_unique_and_first_payment_is_tracked,
+    job_lease_has_one_holder_until_it_expires,
+    job_schedule_keeps_first_time_and_last_error,
+    rpc_poll_stats_upsert_filter_by_network_and_cascade,
+    rpc_credential_upsert_and_cascade,
+    delegations_upsert_revoke_and_cascade,
+    rpc_disagreements_round_trip_newest_first_and_prune,
+    node_chain_info_upsert_filter_and_readiness_paid_once,
+);
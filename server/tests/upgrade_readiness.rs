@@ -0,0 +1,414 @@
+// Integration tests for consensus-branch / network-upgrade readiness.
+//
+// Mock JSON-RPC nodes (batch-capable) answer getblockchaininfo for both the
+// trusted quorum and the operator; relay submissions carry a signed
+// chain_info. Checks what gets recorded, when the readiness bonus is paid,
+// and what /api/stats/upgrades reports.
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use chrono::Utc;
+use depinzcash_server::{
+    api,
+    auth::proof_message_v3,
+    config::{Config, ZcashNetwork},
+    rpc::ZcashRpcQuorum,
+    scheduler,
+    state::AppState,
+    store::{SqliteStore, Store},
+    types::{
+        AnnouncedUpgrade, ChainInfo, ChallengeKind, LedgerSource, Node, NodeKind, NodeStatus, RpcPollOutcome,
+    },
+};
+use ed25519_dalek::{Signer, SigningKey};
+use http_body_util::BodyExt;
+use rand::RngCore;
+use serde_json::{json, Value};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{net::TcpListener, task::JoinHandle};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+const HEIGHT: u64 = 3_350_000;
+const HASH: &str = "0000000000abcdef1234567890abcdef1234567890abcdef1234567890ab0025";
+const NU6: &str = "c8e71055";
+const NU7: &str = "77190ad8";
+
+// ---- mock zebrad ----------------------------------------------------------
+
+struct MockNode {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl MockNode {
+    async fn start(responses: HashMap<String, Value>) -> Self {
+        let responses = Arc::new(responses);
+        let app = Router::new().route(
+            "/",
+            post(move |Json(body): Json<Value>| {
+                let responses = responses.clone();
+                async move {
+                    let answer = |call: &Value| {
+                        let result = call["method"]
+                            .as_str()
+                            .and_then(|m| responses.get(m))
+                            .cloned()
+                            .unwrap_or(Value::Null);
+                        json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })
+                    };
+                    match body {
+                        Value::Array(calls) => Json(Value::Array(calls.iter().map(answer).collect())),
+                        call => Json(answer(&call)),
+                    }
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        MockNode { addr, handle }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    fn shutdown(self) {
+        self.handle.abort();
+    }
+}
+
+// getblockchaininfo as zebrad returns it, with NU7 listed as pending when
+// the node has the upgrade's consensus rules.
+fn blockchain_info(chaintip: &str, with_nu7: bool) -> Value {
+    let mut upgrades = json!({
+        "c2d6d0b4": { "name": "NU5", "activationheight": 1_687_104, "status": "active" },
+        "c8e71055": { "name": "NU6", "activationheight": 2_726_400, "status": "active" },
+    });
+    if with_nu7 {
+        upgrades[NU7] = json!({ "name": "NU7", "activationheight": 3_500_000, "status": "pending" });
+    }
+    json!({
+        "chain": "main",
+        "blocks": HEIGHT,
+        "upgrades": upgrades,
+        "consensus": { "chaintip": chaintip, "nextblock": chaintip },
+    })
+}
+
+fn responses(info: Value) -> HashMap<String, Value> {
+    let mut m = HashMap::new();
+    m.insert("getblockcount".into(), json!(HEIGHT));
+    m.insert("getblockhash".into(), json!(HASH));
+    m.insert("getbestblockhash".into(), json!(HASH));
+    m.insert("getblockchaininfo".into(), info);
+    m
+}
+
+// ---- fixture builders -----------------------------------------------------
+
+fn cfg(trusted_rpcs: Vec<String>) -> Config {
+    Config {
+        bind_addr: "127.0.0.1:0".into(),
+        database_url: "sqlite::memory:".into(),
+        trusted_rpcs,
+        rpc_timeout: Duration::from_secs(2),
+        trusted_rpc_weights: vec![],
+        rpc_quorum_threshold: None,
+        rpc_breaker_failures: 3,
+        rpc_breaker_cooldown: Duration::from_secs(60),
+        block_hash_cache_size: 50_000,
+        block_hash_cache_depth: 100,
+        rpc_dissent_alert_threshold: 5,
+        rpc_dissent_window: Duration::from_secs(3600),
+        admin_api_key: Some("admin-key".into()),
+        cors_allowed_origins: vec![],
+        scheduler_enabled: false,
+        heartbeat_interval: Duration::from_secs(60),
+        challenge_check_interval: Duration::from_secs(60),
+        uptime_reward_interval: Duration::from_secs(60),
+        snapshot_interval: None,
+        exposed_rpc_poll_interval: Some(Duration::from_secs(60)),
+        exposed_rpc_poll_concurrency: 16,
+        exposed_rpc_max_backoff: Duration::from_secs(6 * 60 * 60),
+        pending_recheck_interval: None,
+        pending_recheck_batch: 100,
+        pending_proof_max_age: Duration::from_secs(86_400),
+        finality_depth: 0,
+        finality_check_interval: Duration::from_secs(300),
+        auto_challenge_interval: None,
+        auto_challenge_kinds: ChallengeKind::ALL.to_vec(),
+        challenge_failure_threshold: 3,
+        p2p_probe_interval: None,
+        p2p_probe_concurrency: 16,
+        announced_upgrade: Some(AnnouncedUpgrade {
+            name: "NU7".into(),
+            branch_id: NU7.into(),
+            activation_height: 3_500_000,
+        }),
+        upgrade_readiness_bonus: 100,
+        allow_private_endpoints: true,
+        rpc_credentials_key: None,
+        max_height_drift: 8,
+        max_clock_skew: Duration::from_secs(15 * 60),
+        rate_limit_enabled: false,
+        rate_limit_per_second: 1000,
+        rate_limit_burst: 5000,
+        registration_enabled: true,
+        proof_submission_enabled: true,
+        max_nodes_per_wallet: 5,
+        min_real_height: 0,
+        spl_mint: None,
+        solana_cluster: "devnet".into(),
+        network: ZcashNetwork::Mainnet,
+        proof_v1_cutoff: None,
+    }
+}
+
+async fn build_state(config: Config, tip: u64) -> AppState {
+    let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+    store.migrate().await.unwrap();
+    let rpc = ZcashRpcQuorum::new(config.trusted_rpcs.clone(), Duration::from_secs(2));
+    let state = AppState::new(config, store, rpc);
+    state.set_trusted_tip(tip).await;
+    state
+}
+
+fn make_node(wallet: &str, rpc_endpoint: Option<String>) -> Node {
+    Node {
+        id: Uuid::new_v4(),
+        wallet: wallet.into(),
+        kind: NodeKind::ZebraFull,
+        label: Some("upgrade-node".into()),
+        rpc_endpoint,
+        p2p_address: None,
+        network: "mainnet".into(),
+        status: NodeStatus::Registered,
+        last_height: None,
+        last_block_hash: None,
+        last_proof_at: None,
+        registered_at: Utc::now(),
+        points: 0,
+        uptime_seconds: 0,
+    }
+}
+
+async fn readiness_bonuses(state: &AppState, node_id: Uuid) -> Vec<i64> {
+    state
+        .store()
+        .ledger_for_node(node_id, 100)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|e| e.source == LedgerSource::UpgradeReadiness)
+        .map(|e| e.delta)
+        .collect()
+}
+
+async fn json_request(app: Router, method: Method, path: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let req = Request::builder()
+        .method(method)
+        .uri(path)
+        .header("content-type", "application/json")
+        .body(body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty))
+        .unwrap();
+    let resp = app.oneshot(req).await.unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+fn fresh_keypair() -> (String, SigningKey) {
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let sk = SigningKey::from_bytes(&secret);
+    (bs58::encode(sk.verifying_key().to_bytes()).into_string(), sk)
+}
+
+// A relay proof for `node`, signed as message_version `version`.
+fn relay_submission(node: &Node, sk: &SigningKey, chain_info: &ChainInfo, version: u32) -> Value {
+    let ts = Utc::now().to_rfc3339();
+    let nonce = format!("nonce-{}", Uuid::new_v4());
+    let msg = proof_message_v3(
+        &node.wallet,
+        &node.id.to_string(),
+        HEIGHT,
+        HASH,
+        &ts,
+        &nonce,
+        Some(3600),
+        Some(8),
+        None,
+        Some(chain_info),
+    );
+    json!({
+        "wallet": node.wallet,
+        "node_id": node.id,
+        "signature": bs58::encode(sk.sign(&msg).to_bytes()).into_string(),
+        "nonce": nonce,
+        "claimed_height": HEIGHT,
+        "claimed_block_hash": HASH,
+        "proof_timestamp": ts,
+        "uptime_seconds": 3600,
+        "peers": 8,
+        "message_version": version,
+        "chain_info": chain_info,
+    })
+}
+
+fn reported(chaintip: &str, with_nu7: bool) -> ChainInfo {
+    depinzcash_server::upgrades::parse_blockchain_info(&blockchain_info(chaintip, with_nu7)).unwrap()
+}
+
+// ---- tests ----------------------------------------------------------------
+
+#[tokio::test]
+async fn exposed_poll_records_chain_info_and_pays_the_bonus_once() {
+    let trusted = MockNode::start(responses(blockchain_info(NU6, false))).await;
+    let operator = MockNode::start(responses(blockchain_info(NU6, true))).await;
+    let state = build_state(cfg(vec![trusted.url()]), HEIGHT).await;
+    let node = make_node("WalletUpgrade", Some(operator.url()));
+    state.store().insert_node(&node, "auth-upgrade").await.unwrap();
+
+    let outcome = scheduler::poll_one_node(&state, &node, Some(HEIGHT)).await.unwrap();
+    assert_eq!(outcome, Some(RpcPollOutcome::Credited));
+    let second = scheduler::poll_one_node(&state, &node, Some(HEIGHT)).await.unwrap();
+    assert_eq!(second, Some(RpcPollOutcome::Unchanged));
+
+    let record = state.store().get_node_chain_info(node.id).await.unwrap().expect("chain info recorded");
+    assert_eq!(record.quorum_match, Some(true), "mismatch: {:?}", record.mismatch);
+    assert_eq!(record.info.consensus_branch_id, NU6);
+    assert_eq!(readiness_bonuses(&state, node.id).await, vec![100], "bonus paid exactly once");
+
+    let (status, body) =
+        json_request(api::router(state.clone()), Method::GET, &format!("/api/nodes/{}/chain-info", node.id), None)
+            .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["source"], "exposed_rpc");
+    assert_eq!(body["info"]["upgrades"].as_array().unwrap().len(), 3);
+
+    operator.shutdown();
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn wrong_branch_is_recorded_as_mismatch_without_bonus() {
+    let trusted = MockNode::start(responses(blockchain_info(NU6, false))).await;
+    // Still on NU5's branch although NU6 is active on the quorum.
+    let operator = MockNode::start(responses(blockchain_info("c2d6d0b4", true))).await;
+    let state = build_state(cfg(vec![trusted.url()]), HEIGHT).await;
+    let node = make_node("WalletStale", Some(operator.url()));
+    state.store().insert_node(&node, "auth-stale").await.unwrap();
+
+    scheduler::poll_one_node(&state, &node, Some(HEIGHT)).await.unwrap();
+
+    let record = state.store().get_node_chain_info(node.id).await.unwrap().unwrap();
+    assert_eq!(record.quorum_match, Some(false));
+    assert!(record.mismatch.as_deref().unwrap().contains("branch"), "{:?}", record.mismatch);
+    assert!(readiness_bonuses(&state, node.id).await.is_empty());
+
+    operator.shutdown();
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn no_bonus_once_the_upgrade_has_activated() {
+    let trusted = MockNode::start(responses(blockchain_info(NU6, false))).await;
+    let state = build_state(cfg(vec![trusted.url()]), 3_500_000).await;
+    let (wallet, sk) = fresh_keypair();
+    let node = make_node(&wallet, None);
+    state.store().insert_node(&node, "auth-late").await.unwrap();
+
+    let (status, body) = json_request(
+        api::router(state.clone()),
+        Method::POST,
+        "/api/proofs/submit",
+        Some(relay_submission(&node, &sk, &reported(NU6, true), 3)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["readiness_bonus"], 0);
+    assert!(readiness_bonuses(&state, node.id).await.is_empty());
+
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn relay_chain_info_must_be_signed_with_v3() {
+    let trusted = MockNode::start(responses(blockchain_info(NU6, false))).await;
+    let state = build_state(cfg(vec![trusted.url()]), HEIGHT).await;
+    let (wallet, sk) = fresh_keypair();
+    let node = make_node(&wallet, None);
+    state.store().insert_node(&node, "auth-relay").await.unwrap();
+    let info = reported(NU6, true);
+
+    let (status, body) = json_request(
+        api::router(state.clone()),
+        Method::POST,
+        "/api/proofs/submit",
+        Some(relay_submission(&node, &sk, &info, 2)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    // Signed, but the body's chain info was swapped after signing.
+    let mut tampered = relay_submission(&node, &sk, &info, 3);
+    tampered["chain_info"] = serde_json::to_value(reported(NU6, false)).unwrap();
+    let (status, _) =
+        json_request(api::router(state.clone()), Method::POST, "/api/proofs/submit", Some(tampered)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "signature no longer covers the body");
+
+    let (status, body) = json_request(
+        api::router(state.clone()),
+        Method::POST,
+        "/api/proofs/submit",
+        Some(relay_submission(&node, &sk, &info, 3)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["verdict"], "accepted", "{body}");
+    assert_eq!(body["readiness_bonus"], 100);
+    assert!(body["chain_info_mismatch"].is_null());
+    assert_eq!(readiness_bonuses(&state, node.id).await, vec![100]);
+
+    trusted.shutdown();
+}
+
+#[tokio::test]
+async fn stats_endpoint_reports_adoption() {
+    let trusted = MockNode::start(responses(blockchain_info(NU6, false))).await;
+    let state = build_state(cfg(vec![trusted.url()]), HEIGHT).await;
+    for (i, (chaintip, with_nu7)) in [(NU6, true), (NU6, false), ("c2d6d0b4", false)].into_iter().enumerate() {
+        let (wallet, sk) = fresh_keypair();
+        let node = make_node(&wallet, None);
+        state.store().insert_node(&node, &format!("auth-{i}")).await.unwrap();
+        let (status, body) = json_request(
+            api::router(state.clone()),
+            Method::POST,
+            "/api/proofs/submit",
+            Some(relay_submission(&node, &sk, &reported(chaintip, with_nu7), 3)),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+    }
+
+    let (status, body) = json_request(api::router(state.clone()), Method::GET, "/api/stats/upgrades", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["announced"]["name"], "NU7");
+    assert_eq!(body["blocks_until_activation"], 150_000);
+    assert_eq!(body["quorum"]["consensus_branch_id"], NU6);
+    assert_eq!(body["reporting_nodes"], 3);
+    assert_eq!(body["matching_quorum"], 2);
+    assert_eq!(body["mismatched"], 1);
+    assert_eq!(body["ready"], 1);
+    assert_eq!(body["by_branch"][0], json!({ "branch_id": NU6, "nodes": 2 }));
+
+    trusted.shutdown();
+}
@@ -28,6 +28,7 @@ export interface PublicNode {
   kind: string;
   label: string | null;
   rpc_endpoint: string | null;
+  p2p_address: string | null;
   network: string;
   status: string;
   last_height: number | null;
@@ -38,11 +39,41 @@ export interface PublicNode {
   uptime_seconds: number;
 }
 
+// Trusted-RPC quorum health, as in /readyz. `endpoint` is scheme://host:port.
+export interface QuorumHealth {
+  threshold: number;
+  total_weight: number;
+  available_weight: number;
+  ready: boolean;
+  endpoints: {
+    endpoint: string;
+    weight: number;
+    state: "closed" | "open" | "half_open";
+    score: number;
+    latency_ms: number | null;
+    consecutive_failures: number;
+    successes: number;
+    failures: number;
+    open_until: string | null;
+    last_success_at: string | null;
+    last_failure_at: string | null;
+  }[];
+  hash_cache: {
+    size: number;
+    depth: number;
+    entries: number;
+    hits: number;
+    misses: number;
+    shared: number;
+  };
+}
+
 export interface ServerInfo {
   name: string;
   version: string;
   network: string;
   rpc_endpoints: number;
+  rpc_quorum: QuorumHealth;
   trusted_tip_height: number | null;
   spl_mint: string | null;
   solana_cluster: string;
@@ -50,6 +81,8 @@ export interface ServerInfo {
   exposed_rpc_enabled: boolean;
   exposed_rpc_poll_seconds: number | null;
   registration_message_v1: string;
+  registration_message_v2: string;
+  rpc_credentials_enabled: boolean;
   rewards_note: string;
 }
 
@@ -61,11 +94,63 @@ export interface RegisterRequest {
   kind: string;
   label?: string | null;
   rpc_endpoint?: string | null;
+  p2p_address?: string | null;
+  // Needs message_version 2 (see registrationMessageV2).
+  rpc_auth?: RpcAuth | null;
+  message_version?: 1 | 2;
 }
 
+export type RpcAuth =
+  | { scheme: "basic"; username: string; password: string }
+  | { scheme: "bearer"; token: string };
+
 export interface RegisterResponse {
   node: PublicNode;
   auth_token: string;
+  rpc_auth: "basic" | "bearer" | null;
+}
+
+// Operator-side edits, authorized by the node's auth_token. Absent fields are
+// left alone; "" clears.
+export interface UpdateNodeRequest {
+  label?: string;
+  rpc_endpoint?: string;
+  rpc_auth?: RpcAuth;
+}
+
+export interface RotateTokenResponse {
+  node_id: string;
+  auth_token: string;
+}
+
+// A hot key the wallet has authorised to sign proofs and/or challenges.
+export interface Delegation {
+  node_id: string;
+  delegate: string;
+  scopes: ("proofs" | "challenges")[];
+  expires_at: string;
+  created_at: string;
+  revoked_at: string | null;
+}
+
+// Wallet-signed (see delegationMessage). scopes and expires_at are sent
+// exactly as signed.
+export interface DelegateRequest {
+  wallet: string;
+  delegate: string;
+  scopes: string;
+  expires_at: string;
+  nonce: string;
+  timestamp: string;
+  signature: string;
+}
+
+export interface RevokeDelegateRequest {
+  wallet: string;
+  delegate: string;
+  nonce: string;
+  timestamp: string;
+  signature: string;
 }
 
 export interface ProofRecord {
@@ -84,6 +169,93 @@ export interface ProofRecord {
   received_at: string;
 }
 
+export interface ChallengeRecord {
+  id: string;
+  kind: string;
+  origin: "operator" | "auto";
+  target_height: number;
+  subject: string | null;
+  status: "open" | "answered" | "expired";
+  passed: boolean | null;
+  expected_hash: string | null;
+  answer_hash: string | null;
+  fail_reason: string | null;
+  issued_at: string;
+  answered_at: string | null;
+}
+
+export interface P2pProbeRecord {
+  id: string;
+  node_id: string;
+  address: string;
+  reachable: boolean;
+  user_agent: string | null;
+  protocol_version: number | null;
+  start_height: number | null;
+  error: string | null;
+  points_awarded: number;
+  probed_at: string;
+}
+
+export type RpcPollOutcome =
+  | "credited"
+  | "unchanged"
+  | "hash_mismatch"
+  | "out_of_drift"
+  | "quorum_unavailable"
+  | "unreachable"
+  | "error";
+
+export interface RpcPollStats {
+  node_id: string;
+  last_polled_at: string;
+  last_outcome: RpcPollOutcome;
+  last_error: string | null;
+  last_latency_ms: number | null;
+  last_success_at: string | null;
+  consecutive_failures: number;
+  next_poll_at: string;
+}
+
+export interface NetworkUpgrade {
+  branch_id: string;
+  name: string;
+  activation_height: number;
+  status: string;
+}
+
+export interface ChainInfo {
+  chain: string;
+  consensus_branch_id: string;
+  next_branch_id: string | null;
+  upgrades: NetworkUpgrade[];
+}
+
+export interface NodeChainInfo {
+  node_id: string;
+  source: "exposed_rpc" | "relay";
+  info: ChainInfo;
+  // null when the trusted quorum had no answer.
+  quorum_match: boolean | null;
+  mismatch: string | null;
+  observed_at: string;
+}
+
+export interface UpgradeReadiness {
+  network: string;
+  announced: { name: string; branch_id: string; activation_height: number } | null;
+  trusted_tip_height: number | null;
+  blocks_until_activation: number | null;
+  quorum: ChainInfo | null;
+  since: string;
+  reporting_nodes: number;
+  matching_quorum: number;
+  mismatched: number;
+  ready: number;
+  ready_share: number;
+  by_branch: { branch_id: string; nodes: number }[];
+}
+
 export interface NodeDailyBucket {
   day: string;
   proofs: number;
@@ -96,6 +268,9 @@ export interface ClaimPayload {
   cycle: number;
   merkle_root: string;
   points: number;
+  // Cumulative range this cycle's claim covers; points = points_to - points_from.
+  points_from: number;
+  points_to: number;
   leaf_hash: string;
   proof: { siblings: string[]; leaf_index: number };
   spl_mint: string | null;
@@ -118,6 +293,79 @@ export function registrationMessage(args: {
   return new TextEncoder().encode(text);
 }
 
+// v2 also signs the endpoints, and the credentials as a SHA-256 digest so the
+// wallet's signing prompt never shows the secret.
+export async function registrationMessageV2(args: {
+  wallet: string;
+  nonce: string;
+  timestamp: string;
+  kind: string;
+  network: string;
+  label: string;
+  rpcEndpoint?: string | null;
+  p2pAddress?: string | null;
+  rpcAuth?: RpcAuth | null;
+}): Promise<Uint8Array> {
+  const authDigest = args.rpcAuth ? await rpcAuthDigest(args.rpcAuth) : "";
+  const text =
+    `depinzcash:register:v2\n${args.wallet}\n${args.nonce}\n${args.timestamp}\n${args.kind}\n${args.network}\n${args.label}\n` +
+    `${args.rpcEndpoint ?? ""}\n${args.p2pAddress ?? ""}\n${authDigest}\n`;
+  return new TextEncoder().encode(text);
+}
+
+export function delegationMessage(args: {
+  wallet: string;
+  nodeId: string;
+  delegate: string;
+  scopes: string;
+  expiresAt: string;
+  nonce: string;
+  timestamp: string;
+}): Uint8Array {
+  const text =
+    `depinzcash:delegate:v1\n${args.wallet}\n${args.nodeId}\n${args.delegate}\n${args.scopes}\n${args.expiresAt}\n${args.nonce}\n${args.timestamp}\n`;
+  return new TextEncoder().encode(text);
+}
+
+export function revocationMessage(args: {
+  wallet: string;
+  nodeId: string;
+  delegate: string;
+  nonce: string;
+  timestamp: string;
+}): Uint8Array {
+  const text =
+    `depinzcash:delegate:revoke:v1\n${args.wallet}\n${args.nodeId}\n${args.delegate}\n${args.nonce}\n${args.timestamp}\n`;
+  return new TextEncoder().encode(text);
+}
+
+// Solana off-chain message envelope (version 0) around one of the messages
+// above, for wallets that refuse to sign arbitrary bytes (Ledger). The server
+// accepts a signature over either form. Mirrors auth::offchain_message.
+export function offchainMessage(message: Uint8Array): Uint8Array {
+  if (message.length === 0 || message.length > 65535 - 20) {
+    throw new Error("message can't be wrapped as an off-chain message");
+  }
+  const ascii = message.every((c) => c >= 0x20 && c <= 0x7e);
+  const format = message.length <= 1212 ? (ascii ? 0 : 1) : 2;
+  const out = new Uint8Array(20 + message.length);
+  out.set(new TextEncoder().encode("solana offchain"), 1);
+  out[0] = 0xff;
+  out[16] = 0;
+  out[17] = format;
+  out[18] = message.length & 0xff;
+  out[19] = message.length >> 8;
+  out.set(message, 20);
+  return out;
+}
+
+async function rpcAuthDigest(auth: RpcAuth): Promise<string> {
+  const canonical =
+    auth.scheme === "basic" ? `basic\n${auth.username}\n${auth.password}` : `bearer\n${auth.token}`;
+  const digest = await crypto.subtle.digest("SHA-256", new TextEncoder().encode(canonical));
+  return Array.from(new Uint8Array(digest), (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
 // ---- fetch helpers ----------------------------------------------------------
 
 class ApiError extends Error {
@@ -157,6 +405,7 @@ export const api = {
   networkStats: () => request<NetworkStats>("/api/stats/network"),
   leaderboard: (limit = 100) =>
     request<WalletStats[]>(`/api/stats/leaderboard?limit=${limit}`),
+  upgradeReadiness: () => request<UpgradeReadiness>("/api/stats/upgrades"),
   walletStats: (wallet: string) =>
     request<WalletStats>(`/api/wallet/${encodeURIComponent(wallet)}/stats`),
   walletNodes: (wallet: string) =>
@@ -168,9 +417,22 @@ export const api = {
     }),
   latestClaim: (wallet: string) =>
     request<ClaimPayload>(`/api/wallet/${encodeURIComponent(wallet)}/claim/latest`),
+  // Every cycle's claim, oldest first; each delta cycle is claimed separately.
+  claims: (wallet: string, afterCycle = 0) =>
+    request<ClaimPayload[]>(
+      `/api/wallet/${encodeURIComponent(wallet)}/claims?after_cycle=${afterCycle}`,
+    ),
   node: (id: string) => request<PublicNode>(`/api/nodes/${encodeURIComponent(id)}`),
   nodeProofs: (id: string, limit = 100) =>
     request<ProofRecord[]>(`/api/nodes/${encodeURIComponent(id)}/proofs?limit=${limit}`),
+  nodeChallenges: (id: string, limit = 100) =>
+    request<ChallengeRecord[]>(`/api/nodes/${encodeURIComponent(id)}/challenges?limit=${limit}`),
+  nodeP2pProbes: (id: string, limit = 100) =>
+    request<P2pProbeRecord[]>(`/api/nodes/${encodeURIComponent(id)}/p2p-probes?limit=${limit}`),
+  nodeRpcPoll: (id: string) =>
+    request<RpcPollStats | null>(`/api/nodes/${encodeURIComponent(id)}/rpc-poll`),
+  nodeChainInfo: (id: string) =>
+    request<NodeChainInfo | null>(`/api/nodes/${encodeURIComponent(id)}/chain-info`),
   nodeSeries: (id: string, days = 14) =>
     request<NodeDailyBucket[]>(`/api/nodes/${encodeURIComponent(id)}/series?days=${days}`),
   activeNodes: (limit = 200) => request<PublicNode[]>(`/api/nodes?limit=${limit}`),
@@ -181,8 +443,36 @@ export const api = {
     if (opts?.wallet) p.set("wallet", opts.wallet);
     return request<ProofRecord[]>(`/api/proofs/recent?${p.toString()}`);
   },
+  updateNode: (id: string, token: string, req: UpdateNodeRequest) =>
+    manage<PublicNode>(id, "update", token, req),
+  pauseNode: (id: string, token: string) => manage<PublicNode>(id, "pause", token),
+  resumeNode: (id: string, token: string) => manage<PublicNode>(id, "resume", token),
+  rotateNodeToken: (id: string, token: string) =>
+    manage<RotateTokenResponse>(id, "rotate-token", token),
+  deregisterNode: (id: string, token: string) =>
+    manage<{ deregistered: string; ok: boolean }>(id, "deregister", token),
+  nodeDelegates: (id: string) =>
+    request<Delegation[]>(`/api/nodes/${encodeURIComponent(id)}/delegates`),
+  delegate: (id: string, req: DelegateRequest) =>
+    request<Delegation>(`/api/nodes/${encodeURIComponent(id)}/delegates`, {
+      method: "POST",
+      body: JSON.stringify(req),
+    }),
+  revokeDelegate: (id: string, req: RevokeDelegateRequest) =>
+    request<Delegation>(`/api/nodes/${encodeURIComponent(id)}/delegates/revoke`, {
+      method: "POST",
+      body: JSON.stringify(req),
+    }),
 };
 
+function manage<T>(id: string, action: string, token: string, body: object = {}): Promise<T> {
+  return request<T>(`/api/nodes/${encodeURIComponent(id)}/${action}`, {
+    method: "POST",
+    headers: { authorization: `Bearer ${token}` },
+    body: JSON.stringify(body),
+  });
+}
+
 // Block explorer link for a Zcash block hash. Blockchair confirmed working
 // against mainnet block hashes (cross-checked against the server's proofs).
 export function zcashExplorerUrl(blockHash: string): string {
@@ -4,7 +4,6 @@ import { useConnection, useWallet } from "@solana/wallet-adapter-react";
 import { WalletMultiButton } from "@solana/wallet-adapter-react-ui";
 
 import {
-  ApiError,
   api,
   type ClaimPayload,
   type PublicNode,
@@ -70,7 +69,7 @@ export function Dashboard() {
 function WalletDashboard({ wallet }: { wallet: string }) {
   const [stats, setStats] = useState<WalletStats | null>(null);
   const [nodes, setNodes] = useState<PublicNode[] | null>(null);
-  const [claim, setClaim] = useState<ClaimPayload | null>(null);
+  const [claims, setClaims] = useState<ClaimPayload[] | null>(null);
   const [claimError, setClaimError] = useState<string | null>(null);
   const [error, setError] = useState<string | null>(null);
 
@@ -78,7 +77,7 @@ function WalletDashboard({ wallet }: { wallet: string }) {
     let cancelled = false;
     setStats(null);
     setNodes(null);
-    setClaim(null);
+    setClaims(null);
     setClaimError(null);
     setError(null);
 
@@ -93,16 +92,12 @@ function WalletDashboard({ wallet }: { wallet: string }) {
         setError(e instanceof Error ? e.message : String(e));
       }
       try {
-        const c = await api.latestClaim(wallet);
+        const c = await api.claims(wallet);
         if (cancelled) return;
-        setClaim(c);
+        setClaims(c.reverse()); // newest cycle on top
       } catch (e: unknown) {
         if (cancelled) return;
-        if (e instanceof ApiError && e.status === 404) {
-          setClaimError(null); // expected — no snapshot for this wallet yet
-        } else {
-          setClaimError(e instanceof Error ? e.message : String(e));
-        }
+        setClaimError(e instanceof Error ? e.message : String(e));
       }
     }
     load();
@@ -175,16 +170,22 @@ function WalletDashboard({ wallet }: { wallet: string }) {
       </section>
 
       <section className="flex flex-col gap-3">
-        <h2 className="text-lg font-semibold">Latest claim</h2>
-        {!claim && !claimError && (
+        <h2 className="text-lg font-semibold">Claims</h2>
+        {claims?.length === 0 && (
           <div className="card text-sm text-zcash-subtle">
             No published snapshot includes this wallet yet — claims appear after the next snapshot cycle
             (default cadence: weekly).
           </div>
         )}
         {claimError && <ErrorBanner message={claimError} />}
-        {claim && (
-          <div className="card flex flex-col gap-3 text-sm">
+        {claims && claims.length > 1 && (
+          <p className="text-xs text-zcash-subtle">
+            Every snapshot cycle pays only what was earned since the one before, so each cycle is claimed
+            on its own. Cycles you already claimed are refused on-chain.
+          </p>
+        )}
+        {claims?.map((claim) => (
+          <div key={claim.cycle} className="card flex flex-col gap-3 text-sm">
             <div className="grid gap-3 md:grid-cols-3">
               <Kv label="Cycle" value={`#${claim.cycle}`} />
               <Kv label="Points credited" value={formatNumber(claim.points)} accent />
@@ -201,7 +202,7 @@ function WalletDashboard({ wallet }: { wallet: string }) {
               <pre className="mt-2 overflow-x-auto font-mono leading-5">{JSON.stringify(claim, null, 2)}</pre>
             </details>
           </div>
-        )}
+        ))}
       </section>
     </div>
   );